@@ -2,14 +2,16 @@
 use axum::{
     extract::{
         ws::{WebSocketUpgrade, WebSocket},
+        ConnectInfo,
         State,
         Query,
     },
+    http::HeaderMap,
     response::Response,
     routing::get,
     Router,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, net::{IpAddr, SocketAddr}};
 use serde::Deserialize;
 
 use crate::{
@@ -17,6 +19,7 @@ use crate::{
         websocket::{SubscriptionTopic, WsMessage},
         ApiError, ApiResult,
     },
+    services::ws_codec::{WsCodec, WsCompression, WsEncoding, SUPPORTED_SUBPROTOCOLS},
     AppState,
 };
 
@@ -27,6 +30,29 @@ pub struct WsQuery {
     pub topics: Option<String>, // Comma-separated list
     /// Client metadata
     pub client_id: Option<String>,
+    /// Frame encoding fallback when no subprotocol is negotiated (`json` | `msgpack`)
+    pub encoding: Option<String>,
+    /// Frame compression fallback when no subprotocol is negotiated (`none` | `message-deflate`)
+    pub compression: Option<String>,
+}
+
+impl WsQuery {
+    /// Codec requested through query parameters
+    fn codec(&self) -> Result<WsCodec, ApiError> {
+        let encoding = match self.encoding.as_deref() {
+            Some(value) => WsEncoding::parse(value).ok_or_else(|| {
+                ApiError::ValidationError(format!("Unsupported encoding '{}'", value))
+            })?,
+            None => WsEncoding::default(),
+        };
+        let compression = match self.compression.as_deref() {
+            Some(value) => WsCompression::parse(value).ok_or_else(|| {
+                ApiError::ValidationError(format!("Unsupported compression '{}'", value))
+            })?,
+            None => WsCompression::default(),
+        };
+        Ok(WsCodec { encoding, compression })
+    }
 }
 
 /// Create WebSocket router
@@ -35,6 +61,7 @@ pub fn websocket_routes() -> Router<AppState> {
         .route("/ws", get(websocket_handler))
         .route("/ws/broadcast", get(broadcast_test_handler))
         .route("/ws/stats", get(websocket_stats_handler))
+        .route("/ws/cluster", get(websocket_cluster_handler))
 }
 
 /// Handle WebSocket upgrade requests
@@ -42,8 +69,25 @@ pub fn websocket_routes() -> Router<AppState> {
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Query(params): Query<WsQuery>,  // Make params mutable
 ) -> Result<Response, ApiError> {
+    // Behind a trusted proxy the client address comes from X-Forwarded-For
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(",");
+    let remote_ip = state
+        .websocket_service
+        .config()
+        .client_ip(peer.ip(), Some(forwarded_for.as_str()));
+
+    // Reject over-limit clients with an HTTP status before upgrading
+    state.websocket_service.check_admission(Some(remote_ip)).await?;
+
     // Clone topics before moving params into closure
     let topics_clone = params.topics.clone().unwrap_or_default();
     
@@ -54,6 +98,9 @@ pub async fn websocket_handler(
         .map(|s| s.trim().into())
         .collect();
 
+    // Validate the query fallback before upgrading so bad values get a 400
+    let query_codec = params.codec()?;
+
     // Log connection attempt
     tracing::info!(
         "WebSocket upgrade request - Client: {:?}, Topics: {:?}",
@@ -61,10 +108,22 @@ pub async fn websocket_handler(
         topics
     );
 
-    // Upgrade the connection
-    Ok(ws.on_upgrade(move |socket| {
-        handle_websocket(socket, state, topics, params)  // Now params can be moved
-    }))
+    // Upgrade the connection, negotiating the frame codec via subprotocol
+    // Hard frame cap at the protocol layer; the service enforces the configured
+    // `max_frame_size` itself so clients get an Error message first
+    let hard_limit = state.websocket_service.config().max_frame_size.saturating_mul(4);
+
+    Ok(ws
+        .max_message_size(hard_limit)
+        .protocols(SUPPORTED_SUBPROTOCOLS)
+        .on_upgrade(move |socket| {
+            let codec = socket
+                .protocol()
+                .and_then(|p| p.to_str().ok())
+                .and_then(WsCodec::from_subprotocol)
+                .unwrap_or(query_codec);
+            handle_websocket(socket, state, topics, params, codec, remote_ip)
+        }))
 }
 /// Handle the actual WebSocket connection
 async fn handle_websocket(
@@ -72,8 +131,12 @@ async fn handle_websocket(
     state: AppState,
     _topics: Vec<SubscriptionTopic>,
     _params: WsQuery,
+    codec: WsCodec,
+    remote_ip: IpAddr,
 ) {
-    if let Err(e) = state.websocket_service.handle_connection(socket).await {
+    tracing::debug!(encoding = %codec.subprotocol(), "Negotiated WebSocket frame codec");
+
+    if let Err(e) = state.websocket_service.handle_connection(socket, codec, Some(remote_ip)).await {
         tracing::error!("WebSocket connection failed: {}", e);
     }
 }
@@ -123,4 +186,19 @@ pub async fn websocket_stats_handler(
     Ok(axum::Json(stats))
 }
 
+/// Get connection registries across all backend nodes sharing the message bus
+pub async fn websocket_cluster_handler(
+    State(state): State<AppState>,
+) -> ApiResult<axum::Json<serde_json::Value>> {
+    let nodes = state.websocket_service.get_cluster_registry().await;
+    let total_connections: usize = nodes.iter().map(|node| node.connections.len()).sum();
+
+    let cluster = serde_json::json!({
+        "node_id": state.websocket_service.node_id(),
+        "node_count": nodes.len(),
+        "total_connections": total_connections,
+        "nodes": nodes
+    });
 
+    Ok(axum::Json(cluster))
+}
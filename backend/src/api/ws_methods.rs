@@ -0,0 +1,635 @@
+// backend/src/api/ws_methods.rs
+
+//! WebSocket RPC methods
+//!
+//! Registers the request/response methods the SPA can call over the socket.
+//! Each method mirrors an existing REST endpoint so both transports return
+//! the same payloads.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    models::{
+        backups::BackupRequest,
+        credentials::CredentialProfileInput,
+        devices::{DeviceGroupInput, DeviceInput},
+        reports::{DeviceRunStatus, ParameterValues},
+        ApiError,
+    },
+    routes::{
+        reports::{list_reports, load_reports, ReportListQuery},
+        results::{diff_stored_results, DiffQuery},
+    },
+    services::{
+        config_backups::VersionQuery, credential_vault::AssignmentTarget, device_inventory::DeviceQuery, inventory_import::{self, ImportOptions}, report_runs::RunRequest, results_store::ResultQuery,
+        rpc_registry::parse_params,
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+struct YamlGetParams {
+    schema: String,
+    file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportGetParams {
+    report_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportWriteParams {
+    report_id: String,
+    report: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRunParams {
+    report_id: String,
+    device: String,
+    #[serde(default)]
+    parameters: ParameterValues,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportStartRunParams {
+    report_id: String,
+    #[serde(flatten)]
+    request: RunRequest,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRunGetParams {
+    run_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultGetParams {
+    run_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultsLatestParams {
+    report_id: String,
+    status: Option<DeviceRunStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleParams {
+    schedule_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceGetParams {
+    device: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceWriteParams {
+    device: String,
+    fields: DeviceInput,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupGetParams {
+    group: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupWriteParams {
+    group: String,
+    fields: DeviceGroupInput,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileGetParams {
+    profile: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileWriteParams {
+    profile: String,
+    fields: CredentialProfileInput,
+}
+
+#[derive(Debug, Deserialize)]
+struct CredentialTargetParams {
+    target: AssignmentTarget,
+    /// Device or group, by ID or name
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CredentialAssignParams {
+    target: AssignmentTarget,
+    name: String,
+    /// `None` clears the assignment
+    profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BackupJobParams {
+    job_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct BackupVersionParams {
+    version_id: uuid::Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceImportParams {
+    /// The CSV, YAML or Ansible inventory source
+    text: String,
+    #[serde(flatten)]
+    options: ImportOptions,
+}
+
+/// Register all built-in RPC methods on the WebSocket service
+pub fn register_methods(state: &AppState) {
+    let rpc = state.websocket_service.rpc();
+
+    // Introspection: list the available methods
+    let registry = rpc.clone();
+    rpc.register("rpc.methods", move |_ctx, _params| {
+        let registry = registry.clone();
+        async move { Ok(serde_json::json!(registry.method_names())) }
+    });
+
+    let s = state.clone();
+    rpc.register("yaml.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: YamlGetParams = parse_params(params)?;
+            s.yaml_service
+                .get_yaml_data(&params.schema, params.file.as_deref())
+                .await
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("yaml.schemas", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let schemas = s.yaml_service.list_available_schemas().await?;
+            Ok(serde_json::json!(schemas))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.list", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let query: ReportListQuery = parse_params(params)?;
+            let reports = load_reports(&s.yaml_service).await?;
+            let page = list_reports(reports, &query)?;
+            serde_json::to_value(page).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportGetParams = parse_params(params)?;
+            let reports = load_reports(&s.yaml_service).await?;
+            let report = reports.get(&params.report_id).ok_or_else(|| {
+                ApiError::NotFound(format!("Report '{}' not found", params.report_id))
+            })?;
+            serde_json::to_value(report).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.create", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportWriteParams = parse_params(params)?;
+            let report = s.report_catalog.create(&params.report_id, params.report).await?;
+            serde_json::to_value(report).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.update", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportWriteParams = parse_params(params)?;
+            let report = s.report_catalog.update(&params.report_id, params.report).await?;
+            serde_json::to_value(report).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.delete", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportGetParams = parse_params(params)?;
+            s.report_catalog.delete(&params.report_id).await?;
+            Ok(serde_json::json!({ "deleted": params.report_id }))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.run", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportRunParams = parse_params(params)?;
+            let reports = load_reports(&s.yaml_service).await?;
+            let report = reports.get(&params.report_id).ok_or_else(|| {
+                ApiError::NotFound(format!("Report '{}' not found", params.report_id))
+            })?;
+            let result = s
+                .report_runner
+                .run_single(&params.report_id, report, &params.device, &params.parameters)
+                .await?;
+            serde_json::to_value(result).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.start_run", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportStartRunParams = parse_params(params)?;
+            let reports = load_reports(&s.yaml_service).await?;
+            let report = reports.get(&params.report_id).cloned().ok_or_else(|| {
+                ApiError::NotFound(format!("Report '{}' not found", params.report_id))
+            })?;
+            let run = s.report_runner.start(&params.report_id, report, params.request).await?;
+            serde_json::to_value(run).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("reports.get_run", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ReportRunGetParams = parse_params(params)?;
+            let run = s.report_runner.get(params.run_id).await?;
+            serde_json::to_value(run).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("results.list", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let query: ResultQuery = parse_params(params)?;
+            let page = s.results_store.list(query).await?;
+            serde_json::to_value(page).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("results.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ResultGetParams = parse_params(params)?;
+            let result = s.results_store.get(params.run_id).await?;
+            serde_json::to_value(result).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("results.latest", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ResultsLatestParams = parse_params(params)?;
+            let results = s
+                .results_store
+                .latest_per_device(&params.report_id, params.status)
+                .await?;
+            serde_json::to_value(results).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("results.diff", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let query: DiffQuery = parse_params(params)?;
+            let diff = diff_stored_results(&s, query).await?;
+            serde_json::to_value(diff).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("schedules.list", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let schedules = s.scheduler.list().await?;
+            serde_json::to_value(schedules).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("schedules.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ScheduleParams = parse_params(params)?;
+            let schedule = s.scheduler.get(&params.schedule_id).await?;
+            serde_json::to_value(schedule).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("schedules.pause", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ScheduleParams = parse_params(params)?;
+            let schedule = s.scheduler.pause(&params.schedule_id).await?;
+            serde_json::to_value(schedule).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("schedules.resume", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ScheduleParams = parse_params(params)?;
+            let schedule = s.scheduler.resume(&params.schedule_id).await?;
+            serde_json::to_value(schedule).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("schedules.trigger", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ScheduleParams = parse_params(params)?;
+            let run = s.scheduler.trigger(&params.schedule_id).await?;
+            serde_json::to_value(run).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.list", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let query: DeviceQuery = parse_params(params)?;
+            let page = s.device_inventory.list(query).await?;
+            serde_json::to_value(page).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: DeviceGetParams = parse_params(params)?;
+            let device = s.device_inventory.get(&params.device).await?;
+            serde_json::to_value(device).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.create", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let input: DeviceInput = parse_params(params)?;
+            let device = s.device_inventory.create(input).await?;
+            serde_json::to_value(device).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.update", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: DeviceWriteParams = parse_params(params)?;
+            let device = s.device_inventory.update(&params.device, params.fields).await?;
+            serde_json::to_value(device).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.delete", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: DeviceGetParams = parse_params(params)?;
+            let device = s.device_inventory.delete(&params.device).await?;
+            Ok(serde_json::json!({ "deleted": device.id }))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.discover", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: DeviceGetParams = parse_params(params)?;
+            let device = s.device_discovery.discover(&params.device).await?;
+            serde_json::to_value(device).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("devices.import", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: DeviceImportParams = parse_params(params)?;
+            let report = inventory_import::import(&s.device_inventory, &params.text, &params.options).await?;
+            serde_json::to_value(report).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.list", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let groups = s.device_groups.list().await?;
+            serde_json::to_value(groups).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: GroupGetParams = parse_params(params)?;
+            let group = s.device_groups.get(&params.group).await?;
+            serde_json::to_value(group).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.devices", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: GroupGetParams = parse_params(params)?;
+            let members = s.device_groups.members(&params.group).await?;
+            serde_json::to_value(members).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.create", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let input: DeviceGroupInput = parse_params(params)?;
+            let group = s.device_groups.create(input).await?;
+            serde_json::to_value(group).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.update", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: GroupWriteParams = parse_params(params)?;
+            let group = s.device_groups.update(&params.group, params.fields).await?;
+            serde_json::to_value(group).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("device_groups.delete", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: GroupGetParams = parse_params(params)?;
+            let group = s.device_groups.delete(&params.group).await?;
+            Ok(serde_json::json!({ "deleted": group.id }))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.list", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let profiles = s.credential_vault.list().await?;
+            serde_json::to_value(profiles).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.get", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ProfileGetParams = parse_params(params)?;
+            let profile = s.credential_vault.get(&params.profile).await?;
+            serde_json::to_value(profile).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.create", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let input: CredentialProfileInput = parse_params(params)?;
+            let profile = s.credential_vault.create(input).await?;
+            serde_json::to_value(profile).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.update", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ProfileWriteParams = parse_params(params)?;
+            let profile = s.credential_vault.update(&params.profile, params.fields).await?;
+            serde_json::to_value(profile).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.delete", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: ProfileGetParams = parse_params(params)?;
+            let profile = s.credential_vault.delete(&params.profile).await?;
+            Ok(serde_json::json!({ "deleted": profile.id }))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.assign", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: CredentialAssignParams = parse_params(params)?;
+            let effective = s
+                .credential_vault
+                .assign(params.target, &params.name, params.profile.as_deref())
+                .await?;
+            serde_json::to_value(effective).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.effective", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: CredentialTargetParams = parse_params(params)?;
+            let effective = s.credential_vault.effective(params.target, &params.name).await?;
+            serde_json::to_value(effective).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.vault", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let status = s.credential_vault.status().await?;
+            serde_json::to_value(status).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("credentials.rotate", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let report = s.credential_vault.rotate().await?;
+            serde_json::to_value(report).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("backups.start", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let request: BackupRequest = parse_params(params)?;
+            let job = s.config_backups.start(request).await?;
+            serde_json::to_value(job).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("backups.jobs", move |_ctx, _params| {
+        let s = s.clone();
+        async move {
+            let jobs = s.config_backups.jobs().await;
+            serde_json::to_value(jobs).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("backups.job", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: BackupJobParams = parse_params(params)?;
+            let job = s.config_backups.job(params.job_id).await?;
+            serde_json::to_value(job).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("backups.versions", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let query: VersionQuery = parse_params(params)?;
+            let page = s.config_backups.versions(query).await?;
+            serde_json::to_value(page).map_err(|e| ApiError::SerializationError(e.to_string()))
+        }
+    });
+
+    let s = state.clone();
+    rpc.register("backups.content", move |_ctx, params| {
+        let s = s.clone();
+        async move {
+            let params: BackupVersionParams = parse_params(params)?;
+            let (version, content) = s.config_backups.content(params.version_id).await?;
+            Ok(serde_json::json!({ "version": version, "content": content }))
+        }
+    });
+}
@@ -26,9 +26,38 @@
 //! - GET /api/schemas - List available schemas
 //! - GET /api/navigation - Get navigation config
 //! - GET /api/navigation/yaml - Get raw navigation YAML
+//! - GET /api/reports?q=&category=&sort=&limit=&cursor= - Search and page report definitions
+//! - POST/PUT/DELETE /api/reports/:report_id - Create, replace or delete a report definition
+//! - POST /api/reports/:report_id/run - Run a report against a device
+//! - POST /api/reports/:report_id/runs - Run a report across devices (progress over /ws)
+//! - GET /api/report-runs/:run_id - State of a multi-device run
+//! - GET /api/reports/:report_id/results/latest - Latest stored result per device
+//! - GET /api/results - Stored report results (filters, paging)
+//! - GET /api/results/diff?from=&to=&key= - Rows added, removed and changed between two results
+//! - GET /api/results/:run_id - One stored result with its table
+//!   (run endpoints also export `?format=csv|xlsx|jsonl|html`)
+//! - GET /api/schedules - Scheduled report runs with their state
+//! - GET/POST /api/devices?q=&type=&site=&tag=&status=&reachability= - List and add inventory devices
+//! - GET/PUT/DELETE /api/devices/:device - One device, by ID or name
+//! - POST /api/devices/import?format=&map=&commit=&skip_invalid= - Bulk import (dry run by default)
+//! - POST /api/devices/:device/discover - Probe reachability and read facts now
+//! - GET/POST /api/device-groups - List and add device groups (static or filter-based)
+//! - GET/PUT/DELETE /api/device-groups/:group - One group, by ID or name
+//! - GET /api/device-groups/:group/devices - Current members of a group
+//! - GET/POST /api/credentials - List and add credential profiles (secrets redacted)
+//! - GET/PUT/DELETE /api/credentials/:profile - One profile, by ID or name
+//! - GET /api/credentials/vault - Master key state; POST /api/credentials/rotate - Re-encrypt
+//! - GET/PUT/DELETE /api/{devices/:device,device-groups/:group}/credentials - Assigned profile
+//! - POST /api/backups - Back up device configurations (completion over /ws)
+//! - GET /api/backups/jobs, /api/backups/jobs/:job_id - Backup jobs and their progress
+//! - GET /api/backups/versions?device=&format=&job_id= - Stored configuration versions
+//! - GET /api/backups/versions/:version_id[/download] - One version, or its configuration file
+//! - GET /api/schedules/:schedule_id - One schedule
+//! - POST /api/schedules/:schedule_id/{pause,resume,trigger} - Control a schedule
 //! - GET /api/reload - Reload schemas (dev)
 //! - GET /ws - WebSocket connection
 //! - GET /ws/stats - WebSocket statistics
+//! - GET /ws/cluster - Connection registries of every backend node
 
 // =============================================================================
 // IMPORTS AND MODULES
@@ -39,7 +68,7 @@ use std::{net::SocketAddr, sync::Arc};
 
 // External crate imports
 use tower_http::cors::CorsLayer;
-use tracing::{info, Level};
+use tracing::{info, warn, Level};
 
 // Internal module declarations
 mod models;
@@ -48,7 +77,21 @@ mod api;
 mod routes;
 
 // Internal imports
-use services::{YamlService, WebSocketService};
+use models::{reports::MissReason, websocket::WsConfig};
+use services::{
+    message_bus::{RedisBus, DEFAULT_BUS_CHANNEL},
+    netconf::{standin, NetconfConfig, NetconfTransport},
+    report_engine::{CatalogueCheck, StaticTransport},
+    report_replay::{CaptureTransport, ReplayTransport},
+    inventory_import::{self, ImportOptions},
+    credential_vault::{self, KeyRing},
+    device_discovery::{self, DiscoveryConfig},
+    config_backups::BackupConfig,
+    report_runs::RunnerConfig,
+    database::DatabaseConfig,
+    results_store::StoreConfig,
+    ConfigBackups, CredentialVault, Database, DeviceDiscovery, DeviceGroups, DeviceInventory, ReportCatalog, ReportEngine, ReportRunner, ResultsStore, Scheduler, YamlService, WebSocketService,
+};
 
 // =============================================================================
 // APPLICATION STATE
@@ -63,6 +106,36 @@ pub struct AppState {
     
     /// WebSocket service for real-time communication
     pub websocket_service: Arc<WebSocketService>,
+
+    /// Report engine executing report definitions against devices
+    pub report_engine: Arc<ReportEngine>,
+
+    /// Multi-device report runs streaming progress over the WebSocket
+    pub report_runner: Arc<ReportRunner>,
+
+    /// History of report executions
+    pub results_store: Arc<ResultsStore>,
+
+    /// Cron-scheduled report runs from schedules.yaml
+    pub scheduler: Arc<Scheduler>,
+
+    /// Writes report definitions back to reports.yaml
+    pub report_catalog: Arc<ReportCatalog>,
+
+    /// Devices reports run against, stored next to the results
+    pub device_inventory: Arc<DeviceInventory>,
+
+    /// Static and filter-based sets of inventory devices
+    pub device_groups: Arc<DeviceGroups>,
+
+    /// Encrypted device credentials, assigned per device or group
+    pub credential_vault: Arc<CredentialVault>,
+
+    /// Reachability and facts probing of inventory devices
+    pub device_discovery: Arc<DeviceDiscovery>,
+
+    /// Versioned running configurations pulled from devices
+    pub config_backups: Arc<ConfigBackups>,
 }
 
 // =============================================================================
@@ -75,6 +148,29 @@ pub struct AppState {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize structured logging with info level
     tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    // Subcommands (development tools) run instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("netconf-standin") {
+        return run_netconf_standin(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("check-reports") {
+        return run_check_reports(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("import-devices") {
+        return run_import_devices(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("probe-device") {
+        return run_probe_device(&args[1..]).await;
+    }
+    if args.first().map(String::as_str) == Some("vault-keygen") {
+        println!("{}", credential_vault::generate_key());
+        return Ok(());
+    }
+    if args.first().map(String::as_str) == Some("vault-rotate") {
+        return run_vault_rotate().await;
+    }
+
     info!("Starting Thalyx Backend Server...");
 
     // =========================================================================
@@ -85,18 +181,116 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let yaml_service = Arc::new(YamlService::new("../shared/schemas").await?);
     
     info!("Initializing WebSocket service...");
-    let websocket_service = Arc::new(WebSocketService::new(None));
+    let mut websocket_service = WebSocketService::new(Some(WsConfig::from_env()));
+
+    // Multi-node deployments share broadcasts through Redis pub/sub
+    if let Ok(bus_url) = std::env::var("THALYX_BUS_URL") {
+        let channel = std::env::var("THALYX_BUS_CHANNEL")
+            .unwrap_or_else(|_| DEFAULT_BUS_CHANNEL.to_string());
+        info!("Connecting WebSocket message bus at {}...", bus_url);
+        let bus = RedisBus::connect(&bus_url, &channel).await?;
+        websocket_service = websocket_service.with_message_bus(Arc::new(bus));
+    }
+    let websocket_service = Arc::new(websocket_service);
     
     // Start WebSocket background tasks for connection monitoring and pinging
     websocket_service.start_background_tasks().await;
     info!("WebSocket background tasks started");
 
+    info!("Opening database...");
+    let database_config = DatabaseConfig::from_env();
+    let data_dir = database_config.data_dir.clone();
+    let database = Arc::new(Database::open(database_config)?);
+    let results_store = Arc::new(ResultsStore::new(database.clone(), StoreConfig::from_env()));
+    results_store.start_retention();
+
+    let device_inventory = Arc::new(DeviceInventory::new(database.clone(), websocket_service.clone()));
+    let device_groups = Arc::new(DeviceGroups::new(
+        database.clone(),
+        device_inventory.clone(),
+        websocket_service.clone(),
+    ));
+
+    // A configured but unusable master key stops startup rather than locking the vault
+    let credential_vault = Arc::new(CredentialVault::new(
+        database.clone(),
+        device_inventory.clone(),
+        device_groups.clone(),
+        KeyRing::from_env()?,
+        websocket_service.clone(),
+    ));
+
+    let netconf_config = NetconfConfig::from_env();
+    if netconf_config.known_hosts.is_none() {
+        if netconf_config.insecure_skip_host_key {
+            warn!("NETCONF_INSECURE_SKIP_HOST_KEY is set: SSH host keys of devices are NOT verified");
+        } else {
+            warn!("NETCONF_KNOWN_HOSTS is not set: SSH connections to devices will be refused");
+        }
+    }
+
+    info!("Initializing report engine...");
+    let report_engine = Arc::new(build_report_engine(credential_vault.clone())?);
+    info!(transport = report_engine.transport_name(), "Report engine ready");
+
+    // Discovery always talks NETCONF, whichever transport reports use
+    info!("Starting device discovery...");
+    let discovery_transport = NetconfTransport::new(NetconfConfig::from_env()).with_credentials(credential_vault.clone());
+    let device_discovery = Arc::new(DeviceDiscovery::new(
+        device_inventory.clone(),
+        Arc::new(discovery_transport),
+        websocket_service.clone(),
+        DiscoveryConfig::from_env(),
+    ));
+    device_discovery.start_background_tasks();
+
+    let report_runner = Arc::new(ReportRunner::new(
+        report_engine.clone(),
+        websocket_service.clone(),
+        results_store.clone(),
+        device_groups.clone(),
+        RunnerConfig::from_env(),
+    ));
+
+    info!("Starting report scheduler...");
+    let scheduler = Arc::new(Scheduler::new(yaml_service.clone(), report_runner.clone(), &data_dir).await?);
+    scheduler.start_background_tasks();
+
+    let report_catalog = Arc::new(ReportCatalog::new(yaml_service.clone(), websocket_service.clone()));
+
+    // Backups go through the report transport, so static and replay modes serve recorded configurations
+    let config_backups = Arc::new(ConfigBackups::new(
+        report_engine.clone(),
+        database,
+        device_inventory.clone(),
+        device_groups.clone(),
+        websocket_service.clone(),
+        BackupConfig::from_env(),
+    ));
+
     // Create application state with shared services
     let state = AppState { 
         yaml_service,
-        websocket_service,
+        websocket_service: websocket_service.clone(),
+        report_engine: report_engine.clone(),
+        report_runner,
+        results_store,
+        scheduler,
+        report_catalog,
+        device_inventory,
+        device_groups,
+        credential_vault,
+        device_discovery,
+        config_backups,
     };
 
+    // Register request/response methods callable over the WebSocket
+    api::ws_methods::register_methods(&state);
+    info!(
+        methods = ?state.websocket_service.rpc().method_names(),
+        "WebSocket RPC methods registered"
+    );
+
     // =========================================================================
     // APPLICATION CONFIGURATION
     // =========================================================================
@@ -110,14 +304,302 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // SERVER STARTUP
     // =========================================================================
     
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3001));
+    // PORT lets several instances share a host (e.g. behind a local load balancer)
+    let port = std::env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3001);
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
     info!("Server listening on {}", addr);
     info!("WebSocket endpoint available at ws://{}/ws", addr);
     info!("API documentation available at http://{}/health", addr);
     
     // Bind to address and start serving requests
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Peer addresses are needed for the per-IP WebSocket connection cap
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal(websocket_service, report_engine))
+        .await?;
     
     Ok(())
 }
+
+/// Select the transport used to execute report RPCs
+/// `THALYX_REPORT_TRANSPORT`: `netconf` (default, live devices), `static` (one set of
+/// recorded replies for every device), `replay` (captured replies per device) or
+/// `capture` (NETCONF, recording every reply for later replay)
+/// NETCONF sessions log in with the vault's credentials for devices that have a profile
+fn build_report_engine(vault: Arc<CredentialVault>) -> Result<ReportEngine, Box<dyn std::error::Error>> {
+    let mode = std::env::var("THALYX_REPORT_TRANSPORT").unwrap_or_else(|_| "netconf".to_string());
+    let netconf = || {
+        let config = NetconfConfig::from_env();
+        info!(user = %config.credentials.username, "Report RPCs use NETCONF over SSH");
+        let transport = Arc::new(NetconfTransport::new(config).with_credentials(vault.clone()));
+        transport.start_maintenance();
+        transport
+    };
+    match mode.as_str() {
+        "netconf" => Ok(ReportEngine::new(netconf())),
+        "static" => {
+            let fixture_dir = std::env::var("THALYX_REPORT_FIXTURES")
+                .unwrap_or_else(|_| "../shared/fixtures/rpc-replies".to_string());
+            let transport = StaticTransport::from_dir(&fixture_dir)?;
+            info!(rpcs = ?transport.rpc_names(), "Serving report RPCs from recorded replies in {}", fixture_dir);
+            Ok(ReportEngine::new(Arc::new(transport)))
+        }
+        "replay" => {
+            let replay_dir = std::env::var("THALYX_REPORT_REPLAY_DIR")
+                .unwrap_or_else(|_| "../shared/fixtures/rpc-replies".to_string());
+            Ok(ReportEngine::new(Arc::new(ReplayTransport::new(replay_dir)?)))
+        }
+        "capture" => {
+            let capture_dir = std::env::var("THALYX_REPORT_CAPTURE_DIR").unwrap_or_else(|_| "captures".to_string());
+            Ok(ReportEngine::new(Arc::new(CaptureTransport::new(netconf(), capture_dir))))
+        }
+        other => Err(format!(
+            "Unknown THALYX_REPORT_TRANSPORT '{}' (expected netconf, static, replay or capture)",
+            other
+        )
+        .into()),
+    }
+}
+
+/// `netconf-standin [--listen ADDR] [--replies DIR] [--base-1.0]`
+/// Serves canned NETCONF replies over plain TCP for local development
+async fn run_netconf_standin(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut listen = "127.0.0.1:8300".to_string();
+    let mut replies = "../shared/fixtures/rpc-replies".to_string();
+    let mut chunked = true;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--listen" => listen = args.next().ok_or("--listen requires an address")?.clone(),
+            "--replies" => replies = args.next().ok_or("--replies requires a directory")?.clone(),
+            "--base-1.0" => chunked = false,
+            other => return Err(format!("Unknown netconf-standin argument '{}'", other).into()),
+        }
+    }
+
+    standin::run(&listen, StaticTransport::from_dir(&replies)?, chunked).await?;
+    Ok(())
+}
+
+/// `check-reports [--replies DIR] [--device NAME]... [--strict]`
+/// Runs every report in reports.yaml against recorded replies and prints rows and field misses;
+/// fails on compile or extraction errors, and on misses with `--strict`
+/// Replies are looked up like `THALYX_REPORT_TRANSPORT=replay`; without `--device` every
+/// device directory under DIR is checked, or the shared top-level replies if there is none
+async fn run_check_reports(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut replies = "../shared/fixtures/rpc-replies".to_string();
+    let mut devices = Vec::new();
+    let mut strict = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replies" => replies = args.next().ok_or("--replies requires a directory")?.clone(),
+            "--device" => devices.push(args.next().ok_or("--device requires a device name")?.clone()),
+            "--strict" => strict = true,
+            other => return Err(format!("Unknown check-reports argument '{}'", other).into()),
+        }
+    }
+
+    let yaml_service = YamlService::new("../shared/schemas").await?;
+    let reports = routes::reports::load_reports(&yaml_service).await?;
+    let transport = ReplayTransport::new(&replies)?;
+    if devices.is_empty() {
+        devices = transport.devices()?;
+    }
+    if devices.is_empty() {
+        devices.push("fixtures".to_string());
+    }
+    let engine = ReportEngine::new(Arc::new(transport));
+
+    let mut checks = Vec::new();
+    for device in &devices {
+        if devices.len() > 1 {
+            println!("== {}", device);
+        }
+        let device_checks = engine.check_catalogue(&reports, device).await;
+        print_checks(&device_checks);
+        checks.extend(device_checks);
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|check| check.error.is_some() || (strict && !check.misses.is_empty()))
+        .count();
+    if failures > 0 {
+        return Err(format!("{} of {} reports failed the check", failures, checks.len()).into());
+    }
+    Ok(())
+}
+
+/// `import-devices FILE [--format FORMAT] [--map FIELD:COLUMN]... [--commit] [--skip-invalid]`
+/// Imports devices from CSV, YAML or an Ansible inventory into the inventory of
+/// `THALYX_DATA_DIR`; prints the planned action of every record and writes nothing
+/// without `--commit`. Fails when records are conflicts or errors
+async fn run_import_devices(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = None;
+    let mut options = ImportOptions::default();
+    let mut mapping = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let format = args.next().ok_or("--format requires csv, yaml, ansible_ini or ansible_yaml")?;
+                options.format = Some(serde_json::from_value(serde_json::Value::String(format.clone()))
+                    .map_err(|_| format!("Unknown import format '{}'", format))?);
+            }
+            "--map" => mapping.push(args.next().ok_or("--map requires FIELD:COLUMN")?.clone()),
+            "--commit" => options.commit = true,
+            "--skip-invalid" => options.skip_invalid = true,
+            other if other.starts_with("--") => return Err(format!("Unknown import-devices argument '{}'", other).into()),
+            other => file = Some(other.to_string()),
+        }
+    }
+    let file = file.ok_or("import-devices requires a file")?;
+    options.mapping = inventory_import::parse_mapping(&mapping.join(","))?;
+    if options.format.is_none() {
+        options.format = match std::path::Path::new(&file).extension().and_then(|e| e.to_str()) {
+            Some("csv") => Some(models::devices::ImportFormat::Csv),
+            Some("ini") => Some(models::devices::ImportFormat::AnsibleIni),
+            _ => None,
+        };
+    }
+
+    let text = tokio::fs::read_to_string(&file).await?;
+    let database = Arc::new(Database::open(DatabaseConfig::from_env())?);
+    let inventory = DeviceInventory::new(database, Arc::new(WebSocketService::new(None)));
+    let report = inventory_import::import(&inventory, &text, &options).await?;
+
+    for row in &report.rows {
+        let action = serde_json::to_value(row.action)?;
+        let detail = match (&row.message, row.changes.is_empty()) {
+            (Some(message), _) => format!(": {}", message),
+            (None, false) => format!(" ({})", row.changes.join(", ")),
+            (None, true) => String::new(),
+        };
+        println!(
+            "{:>5}  {:<9} {}{}",
+            row.line,
+            action.as_str().unwrap_or_default(),
+            row.name.as_deref().unwrap_or("-"),
+            detail
+        );
+    }
+    let summary = report.summary;
+    println!(
+        "{}: {} create, {} update, {} unchanged, {} conflict, {} error{}",
+        report.format.as_str(),
+        summary.create,
+        summary.update,
+        summary.unchanged,
+        summary.conflict,
+        summary.error,
+        if report.committed { " (committed)" } else { " (dry run)" }
+    );
+    if let Some(message) = &report.message {
+        println!("{}", message);
+    }
+
+    if summary.conflict + summary.error > 0 && !(report.committed && options.skip_invalid) {
+        return Err(format!("{} records are conflicts or errors", summary.conflict + summary.error).into());
+    }
+    Ok(())
+}
+
+/// `probe-device ADDRESS [--reachability]`
+/// Probes one address the way discovery does and prints the outcome as JSON; without
+/// `--reachability` it opens a NETCONF session and reads facts, with the `NETCONF_*`
+/// credentials. Try it against `netconf-standin` at `tcp://127.0.0.1:8300`
+async fn run_probe_device(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut address = None;
+    let mut full = true;
+    for arg in args {
+        match arg.as_str() {
+            "--reachability" => full = false,
+            other if other.starts_with("--") => return Err(format!("Unknown probe-device argument '{}'", other).into()),
+            other => address = Some(other.to_string()),
+        }
+    }
+    let address = address.ok_or("probe-device requires an address")?;
+
+    let transport = NetconfTransport::new(NetconfConfig::from_env());
+    let config = DiscoveryConfig::from_env();
+    let (probe, facts) = device_discovery::probe(&transport, &address, full, config.timeout).await;
+    println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "probe": &probe, "facts": facts }))?);
+    if !probe.tcp.ok {
+        return Err(format!("{} is unreachable", address).into());
+    }
+    Ok(())
+}
+
+/// `vault-rotate`
+/// Re-encrypts every credential profile in `THALYX_DATA_DIR` with the current master key;
+/// the keys it was encrypted with must still be configured as previous keys
+async fn run_vault_rotate() -> Result<(), Box<dyn std::error::Error>> {
+    let database = Arc::new(Database::open(DatabaseConfig::from_env())?);
+    let websocket = Arc::new(WebSocketService::new(None));
+    let inventory = Arc::new(DeviceInventory::new(database.clone(), websocket.clone()));
+    let groups = Arc::new(DeviceGroups::new(database.clone(), inventory.clone(), websocket.clone()));
+    let vault = CredentialVault::new(database, inventory, groups, KeyRing::from_env()?, websocket);
+
+    let report = vault.rotate().await?;
+    println!(
+        "key {}: {} rotated, {} unchanged",
+        report.key_id, report.rotated, report.unchanged
+    );
+    if !report.unreadable.is_empty() {
+        return Err(format!(
+            "{} profiles use a key that is not configured: {}",
+            report.unreadable.len(),
+            report.unreadable.join(", ")
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// One line per report, then its field misses and assertion verdict
+fn print_checks(checks: &[CatalogueCheck]) {
+    for check in checks {
+        match &check.error {
+            Some(error) => println!("FAIL  {} ({}): {}", check.report_id, check.rpc, error),
+            None if check.misses.is_empty() => println!("ok    {} ({}): {} rows", check.report_id, check.rpc, check.rows),
+            None => println!("MISS  {} ({}): {} rows", check.report_id, check.rpc, check.rows),
+        }
+        for miss in &check.misses {
+            let problem = match miss.reason {
+                MissReason::NoMatch => "matched nothing".to_string(),
+                MissReason::Unparsed => format!("did not parse (e.g. '{}')", miss.values.first().map_or("", String::as_str)),
+            };
+            println!(
+                "        {} ('{}') {} in {} of {} rows",
+                miss.column,
+                miss.field,
+                problem,
+                miss.rows.len(),
+                check.rows
+            );
+        }
+        // Informational: recorded replies are not expected to pass live thresholds
+        if let Some(verdict) = check.verdict {
+            println!("        assertions: {}", verdict.as_str());
+        }
+    }
+}
+
+/// Resolves on Ctrl+C, after telling peer nodes this instance is leaving
+/// and closing pooled device sessions
+async fn shutdown_signal(websocket_service: Arc<WebSocketService>, report_engine: Arc<ReportEngine>) {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::error!("Failed to listen for shutdown signal: {}", e);
+        std::future::pending::<()>().await;
+    }
+    info!("Shutdown signal received");
+    websocket_service.leave_cluster().await;
+    report_engine.shutdown().await;
+}
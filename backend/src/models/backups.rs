@@ -0,0 +1,239 @@
+// backend/src/models/backups.rs
+
+//! # Configuration Backup Models
+//!
+//! ## Description
+//! Running configurations pulled from devices by the backup engine
+//! (`services::config_backups`). A `BackupJob` fetches the configuration of a list of
+//! devices in one or more `ConfigFormat`s; every fetched document that differs from the
+//! device's previous one becomes a new `ConfigVersion`. Contents are stored once per
+//! SHA-256 hash, so identical configurations across devices or time share storage.
+//!
+//! ## How to Use
+//! 1. Submit a `BackupRequest` to start a job
+//! 2. Follow the `BackupJob` until its status is `completed`
+//! 3. List `ConfigVersion`s of a device and download their content
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// FORMATS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Representation a configuration is retrieved in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigFormat {
+    /// Curly-brace hierarchy, as `show configuration` prints it
+    Text,
+    /// Flat `set` commands
+    Set,
+    /// The `<configuration>` element
+    Xml,
+}
+
+impl ConfigFormat {
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Text, ConfigFormat::Set, ConfigFormat::Xml];
+
+    /// Name used in the API and as the `format` attribute of `get-configuration`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConfigFormat::Text => "text",
+            ConfigFormat::Set => "set",
+            ConfigFormat::Xml => "xml",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == name)
+    }
+
+    /// File extension of a downloaded version
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Text => "conf",
+            ConfigFormat::Set => "set",
+            ConfigFormat::Xml => "xml",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ConfigFormat::Text | ConfigFormat::Set => "text/plain; charset=utf-8",
+            ConfigFormat::Xml => "application/xml",
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// JOBS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// What to back up and how
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackupRequest {
+    /// Inventory device names, addresses, or `@group` for the members of a device group;
+    /// duplicates are dropped
+    pub devices: Vec<String>,
+    /// Formats to retrieve; all of them when empty
+    #[serde(default)]
+    pub formats: Vec<ConfigFormat>,
+    /// Devices backed up at once (1..=256)
+    pub concurrency: Option<usize>,
+    /// Seconds allowed per device for all its formats (1..=3600)
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupJobStatus {
+    Running,
+    Completed,
+}
+
+/// State of one device within a backup job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceBackupStatus {
+    Pending,
+    Running,
+    /// Every requested format was stored
+    Succeeded,
+    /// At least one format could not be retrieved; the others may have been stored
+    Failed,
+    TimedOut,
+}
+
+/// One format of a device's configuration as stored by a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatBackup {
+    pub format: ConfigFormat,
+    /// Version holding the content: a new one, or the latest when nothing changed
+    pub version_id: Uuid,
+    pub hash: String,
+    pub size: usize,
+    /// Whether the content differed from the previous version
+    pub changed: bool,
+}
+
+/// Outcome of a backup job on one device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceBackup {
+    /// Inventory name when the target is a known device, the target itself otherwise
+    pub device: String,
+    /// Address the configuration was retrieved from
+    pub address: String,
+    pub status: DeviceBackupStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Formats stored, in request order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backups: Vec<FormatBackup>,
+    /// Failure or timeout reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DeviceBackup {
+    pub fn pending(device: String, address: String) -> Self {
+        Self {
+            device,
+            address,
+            status: DeviceBackupStatus::Pending,
+            started_at: None,
+            duration_ms: None,
+            backups: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// Counts of a backup job's devices by status, and of the versions it created
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupJobSummary {
+    pub total: usize,
+    pub pending: usize,
+    pub running: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    /// Configurations that differed from their previous version
+    pub changed: usize,
+    /// Configurations identical to their previous version
+    pub unchanged: usize,
+}
+
+impl BackupJobSummary {
+    pub fn of(devices: &[DeviceBackup]) -> Self {
+        let mut summary = Self {
+            total: devices.len(),
+            ..Self::default()
+        };
+        for device in devices {
+            match device.status {
+                DeviceBackupStatus::Pending => summary.pending += 1,
+                DeviceBackupStatus::Running => summary.running += 1,
+                DeviceBackupStatus::Succeeded => summary.succeeded += 1,
+                DeviceBackupStatus::Failed => summary.failed += 1,
+                DeviceBackupStatus::TimedOut => summary.timed_out += 1,
+            }
+            let changed = device.backups.iter().filter(|b| b.changed).count();
+            summary.changed += changed;
+            summary.unchanged += device.backups.len() - changed;
+        }
+        summary
+    }
+}
+
+/// Configurations of several devices being backed up, aggregated per device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupJob {
+    pub job_id: Uuid,
+    pub status: BackupJobStatus,
+    pub formats: Vec<ConfigFormat>,
+    /// Devices backed up at the same time
+    pub concurrency: usize,
+    /// Time allowed per device before it is marked `timed_out`
+    pub device_timeout_secs: u64,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub summary: BackupJobSummary,
+    /// One entry per device, in request order
+    pub devices: Vec<DeviceBackup>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// VERSIONS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A stored configuration of one device in one format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigVersion {
+    pub version_id: Uuid,
+    pub device: String,
+    pub format: ConfigFormat,
+    /// Hex SHA-256 of the content
+    pub hash: String,
+    /// Content length in bytes
+    pub size: usize,
+    /// Job that first retrieved this content
+    pub job_id: Uuid,
+    /// When the content was first retrieved
+    pub taken_at: chrono::DateTime<chrono::Utc>,
+    /// Latest backup that found the device still running this content
+    pub confirmed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One page of versions, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigVersionPage {
+    /// Versions matching the filters, across all pages
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    pub versions: Vec<ConfigVersion>,
+}
@@ -0,0 +1,147 @@
+// backend/src/models/credentials.rs
+
+//! # Credential Models
+//!
+//! ## Description
+//! Credential profiles of the vault (`services::credential_vault`): the username and
+//! secrets used to log in to devices. Secrets travel inward only: they are accepted as
+//! `Secret` values, stored encrypted, and never serialized or logged in clear;
+//! responses list which secrets a profile has, not their values.
+//!
+//! ## How to Use
+//! 1. Submit a `CredentialProfileInput` to create or replace a profile
+//! 2. Assign it to a device or group with a `CredentialAssignment`
+//! 3. Read `CredentialProfile` records and `EffectiveCredentials` back
+
+use serde::{Deserialize, Serialize, Serializer};
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// Text shown instead of a secret
+pub const REDACTED: &str = "<redacted>";
+
+/// A secret value: redacted when printed or serialized, wiped from memory when dropped
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// The clear text, for the few places that must use it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Kinds of secret a profile can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    Password,
+    /// SSH private key, PEM or OpenSSH text
+    PrivateKey,
+    /// Passphrase of the private key
+    Passphrase,
+}
+
+/// Fields of a profile supplied by clients, when creating or replacing it
+/// On replace, an omitted secret keeps its stored value and an empty one removes it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialProfileInput {
+    /// Unique name, e.g. `lab-readonly`
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<Secret>,
+    #[serde(default)]
+    pub private_key: Option<Secret>,
+    #[serde(default)]
+    pub passphrase: Option<Secret>,
+}
+
+/// A credential profile as shown to clients, without its secrets
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialProfile {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub username: String,
+    /// Secrets the profile holds
+    pub secrets: Vec<SecretKind>,
+    /// Master key the secrets are encrypted with
+    pub key_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Body assigning a profile to a device or group
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialAssignment {
+    /// Profile ID or name
+    pub profile: String,
+}
+
+/// The profile a device or group logs in with, and where it comes from
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveCredentials {
+    /// `None` when the configured `NETCONF_*` defaults apply
+    pub profile: Option<CredentialProfile>,
+    /// `device`, `group:<name>` or `default`
+    pub source: String,
+}
+
+/// State of the vault's master keys
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    /// Whether a master key is configured; without one secrets cannot be read or written
+    pub unlocked: bool,
+    /// Key new secrets are encrypted with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// Older keys still accepted for reading, until a rotation re-encrypts their secrets
+    pub previous_key_ids: Vec<String>,
+    /// Profiles per key they are encrypted with
+    pub profiles_by_key: indexmap::IndexMap<String, usize>,
+}
+
+/// Outcome of re-encrypting every profile with the current master key
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationReport {
+    pub key_id: String,
+    /// Profiles re-encrypted
+    pub rotated: usize,
+    /// Profiles already on the current key
+    pub unchanged: usize,
+    /// Profiles encrypted with a key that is no longer configured
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unreadable: Vec<String>,
+}
@@ -0,0 +1,359 @@
+// backend/src/models/devices.rs
+
+//! # Device Models
+//!
+//! ## Description
+//! Network devices of the inventory: how to reach them, what they are and where
+//! they stand. Devices are stored in the embedded database next to report results
+//! (`services::device_inventory`).
+//!
+//! ## How to Use
+//! 1. Submit a `DeviceInput` to create or replace a device
+//! 2. Read `Device` records and `DevicePage` listings back
+//! 3. Bulk imports report an `ImportReport` with one `ImportRow` per source record
+//! 4. Group devices with a `DeviceGroupInput`: a list of devices or a filter
+//! 5. Read what probing found in `Device.discovery` (`DiscoveryState`)
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// DEVICES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Role of a device in the network; matches the `/devices?type=` navigation entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceType {
+    Router,
+    Switch,
+    Firewall,
+    Server,
+    #[default]
+    Other,
+}
+
+impl DeviceType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeviceType::Router => "router",
+            DeviceType::Switch => "switch",
+            DeviceType::Firewall => "firewall",
+            DeviceType::Server => "server",
+            DeviceType::Other => "other",
+        }
+    }
+}
+
+/// Administrative state of a device
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceStatus {
+    /// In service
+    #[default]
+    Active,
+    /// Not deployed yet
+    Planned,
+    /// Temporarily out of service
+    Maintenance,
+    /// Kept for the record, no longer in the network
+    Decommissioned,
+}
+
+impl DeviceStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeviceStatus::Active => "active",
+            DeviceStatus::Planned => "planned",
+            DeviceStatus::Maintenance => "maintenance",
+            DeviceStatus::Decommissioned => "decommissioned",
+        }
+    }
+}
+
+/// Fields of a device supplied by clients, when creating or replacing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceInput {
+    /// Unique name, e.g. `r1.lab`; what report runs and the UI refer to
+    pub name: String,
+    /// Management address reports connect to: `host`, `host:port`, `[v6]:port`
+    pub address: String,
+    #[serde(default)]
+    pub vendor: String,
+    /// Model or OS family, e.g. `mx204`
+    #[serde(default)]
+    pub platform: String,
+    #[serde(rename = "type", default)]
+    pub kind: DeviceType,
+    #[serde(default)]
+    pub site: String,
+    #[serde(default)]
+    pub rack: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub status: DeviceStatus,
+}
+
+/// A device of the inventory
+#[derive(Debug, Clone, Serialize)]
+pub struct Device {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub fields: DeviceInput,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Reachability and facts found by probing the device (`services::device_discovery`)
+    pub discovery: DiscoveryState,
+}
+
+/// One page of devices, ordered by name
+#[derive(Debug, Clone, Serialize)]
+pub struct DevicePage {
+    /// Devices matching the filters, across all pages
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    pub devices: Vec<Device>,
+}
+
+/// What happened to a device or group, as announced on the `data:devices` and
+/// `data:device-groups` topics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// DISCOVERY
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Whether a device answers, as of its last probe
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    /// Not probed yet
+    #[default]
+    Unknown,
+    /// Every probe step succeeded
+    Reachable,
+    /// The port answers, but SSH or NETCONF does not
+    Degraded,
+    /// The management port does not accept connections
+    Unreachable,
+}
+
+impl Reachability {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Reachability::Unknown => "unknown",
+            Reachability::Reachable => "reachable",
+            Reachability::Degraded => "degraded",
+            Reachability::Unreachable => "unreachable",
+        }
+    }
+}
+
+/// Outcome of one probe step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeStep {
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// SSH banner on success, the error otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Outcome of probing a device's management address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeReport {
+    /// Address probed, so a changed address is probed again
+    pub address: String,
+    /// TCP connect to the management port
+    pub tcp: ProbeStep,
+    /// SSH banner; `None` for plain TCP stand-ins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<ProbeStep>,
+    /// NETCONF session and hello; `None` until a full probe got that far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netconf: Option<ProbeStep>,
+}
+
+/// Facts read from a device over NETCONF; `None` where the device did not say
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceFacts {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    /// Uptime when the facts were collected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uptime_seconds: Option<u64>,
+    pub collected_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// What probing found out about a device, kept on its inventory record
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryState {
+    #[serde(default)]
+    pub reachability: Reachability,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last probe the management port answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe: Option<ProbeReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facts: Option<DeviceFacts>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// IMPORTS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Source format of a bulk import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// Header row, then one device per row
+    Csv,
+    /// A list of devices, or a mapping of names to devices
+    Yaml,
+    /// Ansible INI inventory (`hosts` file)
+    AnsibleIni,
+    /// Ansible YAML inventory (`all: { hosts, children, vars }`)
+    AnsibleYaml,
+}
+
+impl ImportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Yaml => "yaml",
+            ImportFormat::AnsibleIni => "ansible_ini",
+            ImportFormat::AnsibleYaml => "ansible_yaml",
+        }
+    }
+}
+
+/// What an import does with one source record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    /// A new device
+    Create,
+    /// An existing device of the same name, with different fields
+    Update,
+    /// An existing device of the same name, identical already
+    Unchanged,
+    /// Valid, but clashes with another record or device (name or address)
+    Conflict,
+    /// The record cannot be turned into a device
+    Error,
+}
+
+/// One source record and its outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRow {
+    /// 1-based line of the record in the source
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub action: ImportAction,
+    /// The device as it would be stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<DeviceInput>,
+    /// Existing device the record updates or matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<Uuid>,
+    /// Fields an update changes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+    /// Why the record is a conflict or an error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Rows per action
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ImportSummary {
+    pub create: usize,
+    pub update: usize,
+    pub unchanged: usize,
+    pub conflict: usize,
+    pub error: usize,
+}
+
+/// Outcome of a bulk import, planned or committed
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub format: ImportFormat,
+    /// Whether the creates and updates were written
+    pub committed: bool,
+    /// Why a requested commit did not happen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub summary: ImportSummary,
+    pub rows: Vec<ImportRow>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// GROUPS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// How a group's members are determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    /// Listed devices
+    Static,
+    /// Devices matching a filter, evaluated whenever the group is used
+    Dynamic,
+}
+
+/// Fields of a group supplied by clients, when creating or replacing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceGroupInput {
+    /// Unique name, referred to as `@name` in device lists
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Filter expression of a dynamic group, e.g. `type == router and site == LON`
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Members of a static group, by ID or name
+    #[serde(default)]
+    pub devices: Vec<String>,
+}
+
+/// A named set of devices
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub kind: GroupKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// IDs of the members of a static group
+    pub devices: Vec<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A group with its current members, ordered by name
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupMembers {
+    pub group: DeviceGroup,
+    pub devices: Vec<Device>,
+}
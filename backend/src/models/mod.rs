@@ -6,6 +6,11 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 pub mod websocket;
+pub mod reports;
+pub mod schedules;
+pub mod devices;
+pub mod credentials;
+pub mod backups;
 
 pub type ApiResult<T> = Result<T, ApiError>;
 
@@ -37,21 +42,74 @@ pub enum ApiError {
     
     #[error("Internal server error: {0}")]
     InternalError(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("Device error: {0}")]
+    DeviceError(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
+
+impl ApiError {
+    /// HTTP status code associated with this error
+    /// Also used as the error code for WebSocket RPC replies
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::YamlParseError(_) => StatusCode::BAD_REQUEST,
+            ApiError::FileNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::IoError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::SerializationError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::DeserializationError(_) => StatusCode::BAD_REQUEST,
+            ApiError::WebSocketError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::LimitExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::DeviceError(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+        }
+    }
+
+    /// Whether this error is a client exceeding a configured limit
+    pub fn is_limit_violation(&self) -> bool {
+        matches!(
+            self,
+            ApiError::RateLimited(_) | ApiError::LimitExceeded(_) | ApiError::PayloadTooLarge(_)
+        )
+    }
+
+    /// Client-facing error message (internal details are not exposed)
+    pub fn public_message(&self) -> String {
+        match self {
+            ApiError::IoError(_) => "Internal server error".to_string(),
+            ApiError::SerializationError(_) => "Serialization failed".to_string(),
+            ApiError::DeserializationError(_) => "Invalid request format".to_string(),
+            ApiError::WebSocketError(_) => "WebSocket error".to_string(),
+            ApiError::InternalError(_) => "Internal server error".to_string(),
+            _ => self.to_string(),
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match &self {
-            ApiError::YamlParseError(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            ApiError::FileNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),  // Add this match
-            ApiError::IoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
-            ApiError::SerializationError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Serialization failed".to_string()),
-            ApiError::DeserializationError(_) => (StatusCode::BAD_REQUEST, "Invalid request format".to_string()),
-            ApiError::WebSocketError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WebSocket error".to_string()),
-            ApiError::ValidationError(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            ApiError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
-        };
+        let status = self.status_code();
+        let error_message = self.public_message();
 
         let body = serde_json::json!({
             "error": error_message,
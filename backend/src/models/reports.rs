@@ -0,0 +1,727 @@
+// backend/src/models/reports.rs
+
+//! # Report Models
+//!
+//! ## Description
+//! Report definitions as declared in `shared/data/reports.yaml` and the tabular
+//! results produced when a report is run against a device.
+//!
+//! ## How to Use
+//! 1. Load definitions with `routes::reports::load_reports`
+//! 2. Run them through `services::report_engine::ReportEngine::run`
+//! 3. Serialize the returned `ReportResult` straight to the client
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REPORT DEFINITIONS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Individual report configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    /// Display title for the report
+    pub title: String,
+    /// Category grouping (e.g., "Routing", "Interfaces", "MPLS", "System")
+    pub category: String,
+    /// RPC method to call; empty for composite reports
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub rpc: String,
+    /// XPath 1.0 expression selecting one node per row; empty for composite reports
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub xpath: String,
+    /// Field mappings for display (display name -> XPath relative to the row, optionally
+    /// typed), in column order; empty for composite reports
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: IndexMap<String, FieldSpec>,
+    /// Reports whose rows are joined into this one instead of running an RPC
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ReportSource>,
+    /// Columns calculated from the other columns, appended in order
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub computed: IndexMap<String, FieldSpec>,
+    /// Optional RPC arguments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_args: Option<IndexMap<String, serde_json::Value>>,
+    /// Column identifying a row across runs (display name or field), used to diff results
+    /// Defaults to the first column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Checks applied to the extracted table, e.g. `State == Established`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionSpec>,
+    /// Values supplied per run and substituted for `$name` in `rpc_args`, `xpath` and
+    /// field paths, in form order
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub parameters: IndexMap<String, ParameterSpec>,
+}
+
+impl Report {
+    /// Whether the rows come from `sources` rather than an RPC
+    pub fn is_composite(&self) -> bool {
+        !self.sources.is_empty()
+    }
+
+    /// RPC the report calls, or the RPCs of its sources joined with `, `
+    pub fn rpc_names(&self) -> String {
+        if !self.is_composite() {
+            return self.rpc.clone();
+        }
+        self.sources
+            .iter()
+            .map(|source| source.definition.as_ref().map_or(source.report.as_str(), |d| d.rpc.as_str()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// How a source's rows are matched against the first source's
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinKind {
+    /// Keep rows without a match, with `null` cells for this source
+    #[default]
+    Left,
+    /// Drop rows without a match
+    Inner,
+}
+
+/// One report feeding a composite report
+///
+/// ```yaml
+/// sources:
+///   - { report: test_interfaces, key: "Interface Name" }
+///   - { report: test_lldp_neighbors, key: "Local Interface", columns: ["Neighbor"] }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSource {
+    /// ID of a non-composite report in the same catalogue
+    pub report: String,
+    /// Column of that report the rows are joined on (display name or field)
+    pub key: String,
+    /// Ignored for the first source, whose rows drive the result
+    #[serde(default)]
+    pub join: JoinKind,
+    /// Columns taken from the source, in order; by default all of them, minus the key
+    /// after the first source
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    /// Values for a templated source; `"$name"` passes on one of the composite's parameters
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: ParameterValues,
+    /// Definition of `report`, linked when the catalogue is loaded
+    #[serde(skip)]
+    pub definition: Option<Box<Report>>,
+}
+
+/// Values bound to a report's parameters for one run, by parameter name
+pub type ParameterValues = serde_json::Map<String, serde_json::Value>;
+
+/// JSON type of a report parameter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterType {
+    #[default]
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+/// A report parameter, written with JSON Schema keywords so the list endpoint hands the
+/// frontend a ready-made form schema
+///
+/// ```yaml
+/// parameters:
+///   interface:
+///     type: string
+///     description: "Interface name, e.g. ge-0/0/0"
+///   detail:
+///     type: boolean
+///     default: false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSpec {
+    #[serde(rename = "type", default)]
+    pub kind: ParameterType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Used when a run does not supply the parameter; without one it is required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    /// Allowed values
+    #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<serde_json::Value>,
+    /// Bounds of `integer` and `number` parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+    /// Regular expression `string` values must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+/// Value type of a report column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    /// Text as found in the reply (numbers and booleans from XPath expressions stay as they are)
+    #[default]
+    String,
+    Integer,
+    Float,
+    /// `true`/`false`, `yes`/`no`, `up`/`down`, `enabled`/`disabled`, `1`/`0`
+    Boolean,
+    /// Whole seconds, from `3w2d 04:12:11`, `1d 2h 3m`, `04:12:11`, `12:11` or `3600`
+    Duration,
+    /// Normalized IPv4/IPv6 address or prefix; a Junos `+port` suffix is dropped
+    Ip,
+    /// Raw values translated through `map`; unmapped values are kept
+    Enum,
+}
+
+impl FieldType {
+    pub fn is_numeric(self) -> bool {
+        matches!(self, FieldType::Integer | FieldType::Float)
+    }
+}
+
+/// Unit conversion applied to a numeric field, e.g. `bytes` to `mbps`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitConversion {
+    pub from: String,
+    pub to: String,
+}
+
+/// A field as written in `reports.yaml`: a bare XPath or a typed declaration
+///
+/// ```yaml
+/// "Up/Down Time": "elapsed-time"        # text
+/// "Input Rate":
+///   path: "input-bps"
+///   type: float
+///   unit: { from: bps, to: mbps }
+///   precision: 2
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldSpec {
+    Path(String),
+    Typed {
+        /// XPath relative to the row
+        path: String,
+        #[serde(rename = "type", default)]
+        kind: FieldType,
+        /// `enum` only: raw value -> displayed value
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        map: IndexMap<String, serde_json::Value>,
+        /// Numeric types only
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<UnitConversion>,
+        /// Decimal places kept by `float` fields
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        precision: Option<u32>,
+    },
+}
+
+impl FieldSpec {
+    pub fn path(&self) -> &str {
+        match self {
+            FieldSpec::Path(path) | FieldSpec::Typed { path, .. } => path,
+        }
+    }
+
+    pub fn kind(&self) -> FieldType {
+        match self {
+            FieldSpec::Path(_) => FieldType::String,
+            FieldSpec::Typed { kind, .. } => *kind,
+        }
+    }
+}
+
+/// How much a failed assertion matters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    Fail,
+    Warn,
+}
+
+/// An assertion as written in `reports.yaml`: a bare check or a check with options
+///
+/// A check is `<column> <op> <value>` or `count(rows) <op> <number>`, where `column`
+/// is a display name or field and `op` one of `==`, `!=`, `<`, `<=`, `>`, `>=`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AssertionSpec {
+    Check(String),
+    Detailed {
+        check: String,
+        #[serde(default)]
+        severity: Severity,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+}
+
+impl AssertionSpec {
+    pub fn check(&self) -> &str {
+        match self {
+            AssertionSpec::Check(check) | AssertionSpec::Detailed { check, .. } => check,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            AssertionSpec::Check(_) => Severity::Fail,
+            AssertionSpec::Detailed { severity, .. } => *severity,
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            AssertionSpec::Check(_) => None,
+            AssertionSpec::Detailed { description, .. } => description.as_deref(),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REPORT RESULTS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Column of a report result table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportColumn {
+    /// Display name from `Report.fields`
+    pub name: String,
+    /// XPath the column is extracted from
+    pub field: String,
+    /// Type of the cells, for sorting and filtering
+    #[serde(rename = "type", default)]
+    pub kind: FieldType,
+    /// Unit of numeric cells after conversion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// Index of the column a key refers to, by display name or else by field
+pub fn find_column(columns: &[ReportColumn], key: &str) -> Option<usize> {
+    columns
+        .iter()
+        .position(|c| c.name == key)
+        .or_else(|| columns.iter().position(|c| c.field == key))
+}
+
+/// One row of a report result; cells line up with `ReportResult.columns`
+/// Node-set fields give the trimmed text of the first node, number and boolean
+/// expressions (`count(...)`, `flap-count > 0`) give JSON numbers and booleans,
+/// typed fields give their `FieldType`, and fields that matched nothing or did not
+/// parse are `null`
+pub type ReportRow = Vec<serde_json::Value>;
+
+/// Why a field produced `null` cells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissReason {
+    /// The XPath matched nothing
+    #[default]
+    NoMatch,
+    /// The value did not parse as the field's type
+    Unparsed,
+}
+
+/// A field that produced `null` cells in some rows of a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldMiss {
+    /// Column display name
+    pub column: String,
+    pub field: String,
+    #[serde(default)]
+    pub reason: MissReason,
+    /// Zero-based indexes of the rows with a `null` cell
+    pub rows: Vec<usize>,
+    /// `unparsed` only: the raw values, lined up with `rows`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+}
+
+/// Outcome of assertions, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Warn => "warn",
+            Verdict::Fail => "fail",
+        }
+    }
+}
+
+/// A row (or the table, for `count(rows)`) that did not satisfy a check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckFailure {
+    /// Zero-based row index; absent for table-level checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+    /// Value the check saw (`null` when the field matched nothing)
+    pub actual: serde_json::Value,
+}
+
+/// Result of one assertion over the whole table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckOutcome {
+    pub check: String,
+    pub severity: Severity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub verdict: Verdict,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<CheckFailure>,
+}
+
+/// Verdicts of a report's assertions on one result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResults {
+    /// Worst verdict over all checks
+    pub verdict: Verdict,
+    /// Verdict per row, lined up with `rows`
+    pub rows: Vec<Verdict>,
+    pub checks: Vec<CheckOutcome>,
+}
+
+/// Outcome of running a report against a single device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportResult {
+    /// Unique ID of this run
+    pub run_id: Uuid,
+    pub report_id: String,
+    pub title: String,
+    /// Device the RPC was sent to
+    pub device: String,
+    pub rpc: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+    /// Columns in the order declared in `Report.fields`
+    pub columns: Vec<ReportColumn>,
+    pub rows: Vec<ReportRow>,
+    /// Fields that matched nothing, so `null` cells can be told apart from a broken path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub misses: Vec<FieldMiss>,
+    /// Present when the report declares assertions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assertions: Option<AssertionResults>,
+    /// Parameter values the report ran with, defaults included
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: ParameterValues,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// MULTI-DEVICE RUNS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Progress of a multi-device run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRunStatus {
+    Running,
+    Completed,
+}
+
+/// State of one device within a multi-device run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceRunStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+/// Outcome of a report on one device of a multi-device run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceRun {
+    pub device: String,
+    pub status: DeviceRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Extracted table once the device succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ReportResult>,
+    /// Failure or timeout reason
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DeviceRun {
+    pub fn pending(device: String) -> Self {
+        Self {
+            device,
+            status: DeviceRunStatus::Pending,
+            started_at: None,
+            duration_ms: None,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+/// Device counts of a multi-device run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportRunSummary {
+    pub total: usize,
+    pub pending: usize,
+    pub running: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    /// Rows extracted across all succeeded devices
+    pub rows: usize,
+    /// Worst assertion verdict across devices, devices that failed or timed out counting
+    /// as `fail`; absent until a device returns assertion results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+}
+
+impl ReportRunSummary {
+    pub fn of(devices: &[DeviceRun]) -> Self {
+        let mut summary = Self {
+            total: devices.len(),
+            ..Self::default()
+        };
+        for device in devices {
+            match device.status {
+                DeviceRunStatus::Pending => summary.pending += 1,
+                DeviceRunStatus::Running => summary.running += 1,
+                DeviceRunStatus::Succeeded => summary.succeeded += 1,
+                DeviceRunStatus::Failed => summary.failed += 1,
+                DeviceRunStatus::TimedOut => summary.timed_out += 1,
+            }
+            summary.rows += device.result.as_ref().map_or(0, |r| r.rows.len());
+            let verdict = device.result.as_ref().and_then(|r| r.assertions.as_ref()).map(|a| a.verdict);
+            summary.verdict = summary.verdict.max(verdict);
+        }
+        if summary.verdict.is_some() && summary.failed + summary.timed_out > 0 {
+            summary.verdict = Some(Verdict::Fail);
+        }
+        summary
+    }
+}
+
+/// A report fanned out across several devices, aggregated per device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportRun {
+    pub run_id: Uuid,
+    pub report_id: String,
+    pub title: String,
+    pub status: ReportRunStatus,
+    /// WebSocket topic streaming this run's progress (`data:report-run:<run_id>`)
+    pub topic: String,
+    /// Devices queried at the same time
+    pub concurrency: usize,
+    /// Time allowed per device before it is marked `timed_out`
+    pub device_timeout_secs: u64,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub summary: ReportRunSummary,
+    /// Parameter values every device runs with, defaults included
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: ParameterValues,
+    /// One entry per device, in request order
+    pub devices: Vec<DeviceRun>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// STORED RESULTS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A recorded report execution on one device, as listed in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResultSummary {
+    /// `ReportResult.run_id` of a successful run, or a fresh ID for a failed one
+    pub run_id: Uuid,
+    /// Multi-device run the execution belonged to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_run_id: Option<Uuid>,
+    pub report_id: String,
+    pub title: String,
+    pub device: String,
+    pub rpc: String,
+    /// `succeeded`, `failed` or `timed_out`
+    pub status: DeviceRunStatus,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+    pub row_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Overall assertion verdict, when the report declares assertions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    /// Parameter values of a parameterized report
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: ParameterValues,
+}
+
+/// A recorded report execution including its table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResult {
+    #[serde(flatten)]
+    pub summary: StoredResultSummary,
+    pub columns: Vec<ReportColumn>,
+    pub rows: Vec<ReportRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub misses: Vec<FieldMiss>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assertions: Option<AssertionResults>,
+}
+
+impl StoredResult {
+    /// Record of a successful run
+    pub fn succeeded(result: ReportResult, batch_run_id: Option<Uuid>) -> Self {
+        Self {
+            summary: StoredResultSummary {
+                run_id: result.run_id,
+                batch_run_id,
+                report_id: result.report_id,
+                title: result.title,
+                device: result.device,
+                rpc: result.rpc,
+                status: DeviceRunStatus::Succeeded,
+                started_at: result.started_at,
+                duration_ms: result.duration_ms,
+                row_count: result.rows.len(),
+                error: None,
+                verdict: result.assertions.as_ref().map(|a| a.verdict),
+                parameters: result.parameters,
+            },
+            columns: result.columns,
+            rows: result.rows,
+            misses: result.misses,
+            assertions: result.assertions,
+        }
+    }
+
+    /// Record of a run that failed or timed out on a device
+    pub fn unsuccessful(
+        report_id: &str,
+        report: &Report,
+        parameters: &ParameterValues,
+        entry: &DeviceRun,
+        batch_run_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            summary: StoredResultSummary {
+                run_id: Uuid::new_v4(),
+                batch_run_id,
+                report_id: report_id.to_string(),
+                title: report.title.clone(),
+                device: entry.device.clone(),
+                rpc: report.rpc_names(),
+                status: entry.status,
+                started_at: entry.started_at.unwrap_or_else(chrono::Utc::now),
+                duration_ms: entry.duration_ms.unwrap_or_default(),
+                row_count: 0,
+                error: entry.error.clone(),
+                verdict: None,
+                parameters: parameters.clone(),
+            },
+            columns: Vec::new(),
+            rows: Vec::new(),
+            misses: Vec::new(),
+            assertions: None,
+        }
+    }
+}
+
+/// One page of the result history
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredResultPage {
+    /// Results matching the filters, across all pages
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    /// Newest first
+    pub results: Vec<StoredResultSummary>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// RESULT DIFFS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A row present in only one of the compared results
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffRow {
+    /// Value of the key column
+    pub key: serde_json::Value,
+    /// Cells by column display name
+    pub cells: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A cell whose value differs between the compared results
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub column: String,
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// A row present in both results with at least one changed cell
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedRow {
+    pub key: serde_json::Value,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Row counts of a diff
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+/// Differences between two stored results of the same report
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultDiff {
+    pub report_id: String,
+    /// Column rows are matched on
+    pub key: ReportColumn,
+    /// Older side of the comparison
+    pub from: StoredResultSummary,
+    /// Newer side of the comparison
+    pub to: StoredResultSummary,
+    pub summary: DiffSummary,
+    /// Rows only in `to`
+    pub added: Vec<DiffRow>,
+    /// Rows only in `from`
+    pub removed: Vec<DiffRow>,
+    pub changed: Vec<ChangedRow>,
+    /// Columns only in `to`, when the report definition changed between the runs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns_added: Vec<String>,
+    /// Columns only in `from`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns_removed: Vec<String>,
+    /// Key values shared by several rows of one result; such rows are paired in order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_keys: Vec<serde_json::Value>,
+}
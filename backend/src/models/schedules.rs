@@ -0,0 +1,101 @@
+// backend/src/models/schedules.rs
+
+//! # Schedule Models
+//!
+//! ## Description
+//! Scheduled report runs as declared in `shared/data/schedules.yaml`, and the
+//! runtime status the scheduler reports for each of them.
+//!
+//! ## How to Use
+//! 1. Load definitions with `services::scheduler::load_schedules`
+//! 2. Read live status through `services::scheduler::Scheduler::list`
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::reports::ParameterValues;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SCHEDULE DEFINITIONS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// What to do with fire times that passed while the backend was down or busy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Drop missed fire times and wait for the next one
+    #[default]
+    Skip,
+    /// Run once as soon as possible, however many fire times were missed
+    RunOnce,
+}
+
+/// A report run on a cron schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    /// Report ID from `reports.yaml`
+    pub report: String,
+    /// Devices the report runs against; `@group` for the members of a device group
+    pub targets: Vec<String>,
+    /// Cron expression: `minute hour day-of-month month day-of-week`,
+    /// with an optional leading seconds field
+    pub cron: String,
+    /// IANA time zone the expression is evaluated in
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    #[serde(default)]
+    pub missed: MissedRunPolicy,
+    /// Disabled schedules are listed but never fire on their own
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Devices queried at once; defaults to the runner's configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Seconds allowed per device; defaults to the runner's configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Values for the report's parameters; omitted ones take their defaults
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: ParameterValues,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SCHEDULE STATUS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A schedule with its live state
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub id: String,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// Paused through the API; survives restarts
+    pub paused: bool,
+    /// Next automatic fire time; absent when paused, disabled or invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last time a run was started, automatically or on demand
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Multi-device run started last (`GET /api/report-runs/:run_id`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run_id: Option<Uuid>,
+    /// Whether the last run is still in progress
+    pub running: bool,
+    /// Fire times skipped since startup because the previous run was still going
+    pub skipped_overlaps: u64,
+    /// Why the schedule cannot fire (bad cron expression, unknown report, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -26,6 +26,7 @@
 //! - Real-time data updates
 //! - Error handling
 //! - Custom events
+//! - Request/response RPC (Request, Response, RequestFailed)
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -77,6 +78,16 @@ pub enum WsMessage {
     
     // Custom events (extensible)
     Custom { event: String, data: serde_json::Value },
+
+    // Request/response RPC - the reply carries the `id` of the originating request
+    Request {
+        id: String,
+        method: String,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+    Response { id: String, result: serde_json::Value },
+    RequestFailed { id: String, code: u16, message: String },
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════════
@@ -92,6 +103,8 @@ pub struct ConnectionInfo {
     pub last_ping: Option<chrono::DateTime<chrono::Utc>>,
     pub subscriptions: Vec<String>, // Topics the client is subscribed to
     pub metadata: HashMap<String, String>, // Additional client info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_ip: Option<std::net::IpAddr>, // Peer address, used for per-IP limits
 }
 
 impl ConnectionInfo {
@@ -103,6 +116,7 @@ impl ConnectionInfo {
             last_ping: None,
             subscriptions: Vec::new(),
             metadata: HashMap::new(),
+            remote_ip: None,
         }
     }
 }
@@ -129,14 +143,14 @@ pub enum SubscriptionTopic {
 // Implementations for converting between SubscriptionTopic and String
 // Enables topic-based message routing and filtering
 
-impl ToString for SubscriptionTopic {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for SubscriptionTopic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Navigation => "navigation".to_string(),
-            Self::FileSystem => "filesystem".to_string(),
-            Self::DataUpdates(source) => format!("data:{}", source),
-            Self::All => "all".to_string(),
-            Self::Direct(conn_id) => format!("direct:{}", conn_id),
+            Self::Navigation => write!(f, "navigation"),
+            Self::FileSystem => write!(f, "filesystem"),
+            Self::DataUpdates(source) => write!(f, "data:{}", source),
+            Self::All => write!(f, "all"),
+            Self::Direct(conn_id) => write!(f, "direct:{}", conn_id),
         }
     }
 }
@@ -175,6 +189,18 @@ pub struct WsConfig {
     pub connection_timeout: std::time::Duration,
     pub max_connections: usize,
     pub buffer_size: Option<usize>, // Optional buffer size with default
+
+    // Per-connection input limits
+    pub max_connections_per_ip: usize, // 0 disables the per-IP cap
+    pub trusted_proxies: Vec<std::net::IpAddr>, // Peers whose `X-Forwarded-For` is honoured
+    pub max_frame_size: usize,      // Largest accepted inbound message, in bytes (after inflate)
+    pub max_subscriptions: usize,   // Topics a single connection may subscribe to
+    pub rate_limit_per_second: f64, // Token bucket refill rate for inbound messages
+    pub rate_limit_burst: u32,      // Token bucket capacity
+    pub max_violations: u32,        // Limit violations tolerated before disconnecting
+
+    // Multi-node fan-out
+    pub cluster_heartbeat_interval: std::time::Duration, // Registry snapshot publish period
 }
 
 impl Default for WsConfig {
@@ -184,6 +210,64 @@ impl Default for WsConfig {
             connection_timeout: std::time::Duration::from_secs(300), // 5 minutes
             max_connections: 1000,
             buffer_size: Some(1024 * 64), // 64KB default buffer
+            max_connections_per_ip: 50,
+            trusted_proxies: Vec::new(),
+            max_frame_size: 1024 * 1024, // 1MB
+            max_subscriptions: 64,
+            rate_limit_per_second: 20.0,
+            rate_limit_burst: 40,
+            max_violations: 5,
+            cluster_heartbeat_interval: std::time::Duration::from_secs(10),
+        }
+    }
+}
+
+impl WsConfig {
+    /// Defaults overridden by `WS_*` environment variables, e.g. `WS_MAX_CONNECTIONS=200`
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|v| v.parse().ok())
+        }
+
+        let defaults = Self::default();
+        Self {
+            max_connections: env("WS_MAX_CONNECTIONS").unwrap_or(defaults.max_connections),
+            max_connections_per_ip: env("WS_MAX_CONNECTIONS_PER_IP")
+                .unwrap_or(defaults.max_connections_per_ip),
+            trusted_proxies: std::env::var("WS_TRUSTED_PROXIES")
+                .map(|v| v.split(',').filter_map(|ip| ip.trim().parse().ok()).collect())
+                .unwrap_or(defaults.trusted_proxies),
+            max_frame_size: env("WS_MAX_FRAME_SIZE").unwrap_or(defaults.max_frame_size),
+            max_subscriptions: env("WS_MAX_SUBSCRIPTIONS").unwrap_or(defaults.max_subscriptions),
+            rate_limit_per_second: env("WS_RATE_LIMIT_PER_SECOND")
+                .unwrap_or(defaults.rate_limit_per_second),
+            rate_limit_burst: env("WS_RATE_LIMIT_BURST").unwrap_or(defaults.rate_limit_burst),
+            max_violations: env("WS_MAX_VIOLATIONS").unwrap_or(defaults.max_violations),
+            ..defaults
+        }
+    }
+
+    /// Client address used for per-IP limits
+    ///
+    /// `X-Forwarded-For` is only consulted when the direct peer is a trusted proxy;
+    /// the rightmost entry that is not itself a trusted proxy is taken as the client.
+    pub fn client_ip(
+        &self,
+        peer: std::net::IpAddr,
+        forwarded_for: Option<&str>,
+    ) -> std::net::IpAddr {
+        if !self.trusted_proxies.contains(&peer) {
+            return peer;
+        }
+
+        let mut client = peer;
+        for entry in forwarded_for.unwrap_or_default().rsplit(',') {
+            match entry.trim().parse() {
+                Ok(ip) if self.trusted_proxies.contains(&ip) => client = ip,
+                Ok(ip) => return ip,
+                Err(_) => break,
+            }
         }
+        client
     }
 }
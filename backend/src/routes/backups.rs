@@ -0,0 +1,92 @@
+//! Configuration Backup Routes
+//!
+//! Handles starting configuration backups, following their jobs, and browsing and
+//! downloading the stored versions
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use uuid::Uuid;
+use crate::{
+    models,
+    models::backups::{BackupJob, BackupRequest, ConfigVersion, ConfigVersionPage},
+    services::{config_backups::VersionQuery, report_export::sanitize_filename},
+    AppState,
+};
+
+/// Start a backup of the listed devices
+/// Returns `202` with the job snapshot; progress streams on `data:backups`
+pub async fn start_backup(
+    State(state): State<AppState>,
+    Json(request): Json<BackupRequest>,
+) -> models::ApiResult<(StatusCode, Json<BackupJob>)> {
+    let job = state.config_backups.start(request).await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Backup jobs still in memory, newest first
+pub async fn list_jobs(State(state): State<AppState>) -> Json<Vec<BackupJob>> {
+    Json(state.config_backups.jobs().await)
+}
+
+/// Current state of one backup job
+pub async fn get_job(
+    Path(job_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<BackupJob>> {
+    Ok(Json(state.config_backups.job(job_id).await?))
+}
+
+/// Newest-first page of stored versions
+/// Supports `?device=`, `?format=`, `?job_id=`, `?limit=` and `?offset=`
+pub async fn list_versions(
+    Query(query): Query<VersionQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ConfigVersionPage>> {
+    Ok(Json(state.config_backups.versions(query).await?))
+}
+
+/// Metadata of one version
+pub async fn get_version(
+    Path(version_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ConfigVersion>> {
+    Ok(Json(state.config_backups.version(version_id).await?))
+}
+
+/// Configuration of one version as a file named after the device and backup time
+pub async fn download_version(
+    Path(version_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Response> {
+    let (version, content) = state.config_backups.content(version_id).await?;
+    let filename = format!(
+        "{}-{}.{}",
+        sanitize_filename(&version.device),
+        version.taken_at.format("%Y%m%dT%H%M%SZ"),
+        version.format.extension()
+    );
+
+    let mut response = content.into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(version.format.content_type()));
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+/// Creates configuration backup routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/backups", post(start_backup))
+        .route("/api/backups/jobs", get(list_jobs))
+        .route("/api/backups/jobs/:job_id", get(get_job))
+        .route("/api/backups/versions", get(list_versions))
+        .route("/api/backups/versions/:version_id", get(get_version))
+        .route("/api/backups/versions/:version_id/download", get(download_version))
+}
@@ -0,0 +1,144 @@
+//! Credential Vault Routes
+//!
+//! Handles credential profiles, their assignment to devices and device groups, and the
+//! vault's master keys. Secrets are accepted on write and never returned
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use crate::{
+    models,
+    models::credentials::{
+        CredentialAssignment, CredentialProfile, CredentialProfileInput, EffectiveCredentials, RotationReport,
+        VaultStatus,
+    },
+    services::credential_vault::AssignmentTarget,
+    AppState,
+};
+
+/// List credential profiles, ordered by name
+pub async fn list_profiles(State(state): State<AppState>) -> models::ApiResult<Json<Vec<CredentialProfile>>> {
+    Ok(Json(state.credential_vault.list().await?))
+}
+
+/// Get one profile by ID or name
+pub async fn get_profile(
+    Path(profile): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<CredentialProfile>> {
+    Ok(Json(state.credential_vault.get(&profile).await?))
+}
+
+/// Add a profile
+/// Returns `201`; `409` if the name is taken, `503` while the vault is locked
+pub async fn create_profile(
+    State(state): State<AppState>,
+    Json(input): Json<CredentialProfileInput>,
+) -> models::ApiResult<(StatusCode, Json<CredentialProfile>)> {
+    let profile = state.credential_vault.create(input).await?;
+    Ok((StatusCode::CREATED, Json(profile)))
+}
+
+/// Replace a profile; omitted secrets are kept, empty ones removed
+pub async fn update_profile(
+    Path(profile): Path<String>,
+    State(state): State<AppState>,
+    Json(input): Json<CredentialProfileInput>,
+) -> models::ApiResult<Json<CredentialProfile>> {
+    Ok(Json(state.credential_vault.update(&profile, input).await?))
+}
+
+/// Remove a profile; `409` while it is assigned
+pub async fn delete_profile(
+    Path(profile): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<StatusCode> {
+    state.credential_vault.delete(&profile).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Master key IDs and the profiles encrypted with each
+pub async fn vault_status(State(state): State<AppState>) -> models::ApiResult<Json<VaultStatus>> {
+    Ok(Json(state.credential_vault.status().await?))
+}
+
+/// Re-encrypt every profile with the current master key
+pub async fn rotate(State(state): State<AppState>) -> models::ApiResult<Json<RotationReport>> {
+    Ok(Json(state.credential_vault.rotate().await?))
+}
+
+/// Profile a device logs in with: its own, a group's, or the defaults
+pub async fn device_credentials(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    Ok(Json(state.credential_vault.effective(AssignmentTarget::Device, &device).await?))
+}
+
+/// Assign a profile to a device
+pub async fn assign_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+    Json(assignment): Json<CredentialAssignment>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    let vault = &state.credential_vault;
+    Ok(Json(vault.assign(AssignmentTarget::Device, &device, Some(&assignment.profile)).await?))
+}
+
+/// Clear a device's own assignment; it falls back to its groups' profiles
+pub async fn unassign_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    Ok(Json(state.credential_vault.assign(AssignmentTarget::Device, &device, None).await?))
+}
+
+/// Profile assigned to a group
+pub async fn group_credentials(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    Ok(Json(state.credential_vault.effective(AssignmentTarget::Group, &group).await?))
+}
+
+/// Assign a profile to a group, for members without their own
+pub async fn assign_group(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+    Json(assignment): Json<CredentialAssignment>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    let vault = &state.credential_vault;
+    Ok(Json(vault.assign(AssignmentTarget::Group, &group, Some(&assignment.profile)).await?))
+}
+
+/// Clear a group's assignment
+pub async fn unassign_group(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<EffectiveCredentials>> {
+    Ok(Json(state.credential_vault.assign(AssignmentTarget::Group, &group, None).await?))
+}
+
+/// Creates credential vault routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/credentials", get(list_profiles).post(create_profile))
+        .route("/api/credentials/vault", get(vault_status))
+        .route("/api/credentials/rotate", post(rotate))
+        .route(
+            "/api/credentials/:profile",
+            get(get_profile).put(update_profile).delete(delete_profile),
+        )
+        .route(
+            "/api/devices/:device/credentials",
+            get(device_credentials).put(assign_device).delete(unassign_device),
+        )
+        .route(
+            "/api/device-groups/:group/credentials",
+            get(group_credentials).put(assign_group).delete(unassign_group),
+        )
+}
@@ -0,0 +1,179 @@
+//! Device Inventory Routes
+//!
+//! Handles listing, creating, replacing, deleting, probing and bulk-importing inventory
+//! devices, and the device groups built from them
+
+use axum::{
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use crate::{
+    models,
+    models::devices::{
+        Device, DeviceGroup, DeviceGroupInput, DeviceInput, DevicePage, GroupMembers, ImportFormat, ImportReport,
+    },
+    services::{
+        device_inventory::DeviceQuery,
+        inventory_import::{self, ImportOptions},
+    },
+    AppState,
+};
+
+/// Query of an import; the source is the request body
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<ImportFormat>,
+    /// `field:column,...`
+    pub map: Option<String>,
+    #[serde(default)]
+    pub commit: bool,
+    #[serde(default)]
+    pub skip_invalid: bool,
+}
+
+/// List devices
+/// Filters: `q` text search, `type`, `site`, `tag`, `status`, `reachability` (comma-separated, any of);
+/// ordered by name and paged with `limit`/`offset`
+pub async fn list_devices(
+    Query(query): Query<DeviceQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<DevicePage>> {
+    Ok(Json(state.device_inventory.list(query).await?))
+}
+
+/// Get one device by ID or name
+pub async fn get_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<Device>> {
+    Ok(Json(state.device_inventory.get(&device).await?))
+}
+
+/// Add a device to the inventory
+/// Returns `201`; `409` if the name is taken, `400` if a field is invalid
+pub async fn create_device(
+    State(state): State<AppState>,
+    Json(input): Json<DeviceInput>,
+) -> models::ApiResult<(StatusCode, Json<Device>)> {
+    let device = state.device_inventory.create(input).await?;
+    Ok((StatusCode::CREATED, Json(device)))
+}
+
+/// Replace a device, addressed by ID or name
+pub async fn update_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+    Json(input): Json<DeviceInput>,
+) -> models::ApiResult<Json<Device>> {
+    Ok(Json(state.device_inventory.update(&device, input).await?))
+}
+
+/// Remove a device, addressed by ID or name
+pub async fn delete_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<StatusCode> {
+    state.device_inventory.delete(&device).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Probe a device now: reachability over TCP, SSH and NETCONF, then its facts
+/// Returns the device with the new `discovery` state
+pub async fn discover_device(
+    Path(device): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<Device>> {
+    Ok(Json(state.device_discovery.discover(&device).await?))
+}
+
+/// Import devices from CSV, YAML or an Ansible inventory in the body
+/// A dry run unless `commit=true`; the report lists every record with its line and
+/// action, and says why a requested commit was refused; bodies over
+/// `inventory_import::MAX_IMPORT_BYTES` are refused with `413`
+pub async fn import_devices(
+    Query(query): Query<ImportQuery>,
+    State(state): State<AppState>,
+    body: String,
+) -> models::ApiResult<Json<ImportReport>> {
+    let options = ImportOptions {
+        format: query.format,
+        mapping: inventory_import::parse_mapping(query.map.as_deref().unwrap_or_default())?,
+        commit: query.commit,
+        skip_invalid: query.skip_invalid,
+    };
+    Ok(Json(inventory_import::import(&state.device_inventory, &body, &options).await?))
+}
+
+/// List device groups, ordered by name
+pub async fn list_groups(State(state): State<AppState>) -> models::ApiResult<Json<Vec<DeviceGroup>>> {
+    Ok(Json(state.device_groups.list().await?))
+}
+
+/// Get one group by ID or name
+pub async fn get_group(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<DeviceGroup>> {
+    Ok(Json(state.device_groups.get(&group).await?))
+}
+
+/// Current members of a group; dynamic groups are evaluated now
+pub async fn group_devices(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<GroupMembers>> {
+    Ok(Json(state.device_groups.members(&group).await?))
+}
+
+/// Add a group: `devices` for a static group, `filter` for a dynamic one
+/// Returns `201`; `409` if the name is taken, `400` for unknown devices or a bad filter
+pub async fn create_group(
+    State(state): State<AppState>,
+    Json(input): Json<DeviceGroupInput>,
+) -> models::ApiResult<(StatusCode, Json<DeviceGroup>)> {
+    let group = state.device_groups.create(input).await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// Replace a group, addressed by ID or name
+pub async fn update_group(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+    Json(input): Json<DeviceGroupInput>,
+) -> models::ApiResult<Json<DeviceGroup>> {
+    Ok(Json(state.device_groups.update(&group, input).await?))
+}
+
+/// Remove a group; its devices stay in the inventory
+pub async fn delete_group(
+    Path(group): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<StatusCode> {
+    state.device_groups.delete(&group).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Creates device inventory routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/devices", get(list_devices).post(create_device))
+        .route(
+            "/api/devices/import",
+            post(import_devices).layer(DefaultBodyLimit::max(inventory_import::MAX_IMPORT_BYTES)),
+        )
+        .route(
+            "/api/devices/:device",
+            get(get_device).put(update_device).delete(delete_device),
+        )
+        .route("/api/devices/:device/discover", post(discover_device))
+        .route("/api/device-groups", get(list_groups).post(create_group))
+        .route(
+            "/api/device-groups/:group",
+            get(get_group).put(update_group).delete(delete_group),
+        )
+        .route("/api/device-groups/:group/devices", get(group_devices))
+}
@@ -11,7 +11,12 @@ mod health;
 mod yaml;
 mod navigation;
 mod websocket;
-mod reports;
+pub mod reports;
+pub mod results;
+mod schedules;
+mod devices;
+mod credentials;
+mod backups;
 
 /// Creates and configures all application routes
 /// 
@@ -33,6 +38,21 @@ pub fn create_routes() -> Router<AppState> {
         
         // Reports management routes
         .merge(reports::routes())
+
+        // Report result history routes
+        .merge(results::routes())
+
+        // Scheduled report run routes
+        .merge(schedules::routes())
+
+        // Device inventory routes
+        .merge(devices::routes())
+
+        // Credential vault routes
+        .merge(credentials::routes())
+
+        // Configuration backup routes
+        .merge(backups::routes())
         
         // WebSocket communication routes
         .merge(websocket::routes())
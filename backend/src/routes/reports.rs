@@ -1,44 +1,62 @@
 //! Reports Management Routes
 //! 
-//! Handles report configuration, retrieval, and filtering
+//! Handles report configuration, retrieval, filtering, and execution
 
 use axum::{
-    extract::{Path, State},
-    response::Json,
-    routing::get,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
     Router,
 };
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::{AppState, models};
-
-/// Individual report configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Report {
-    /// Display title for the report
-    pub title: String,
-    /// Category grouping (e.g., "Routing", "Interfaces", "MPLS", "System")
-    pub category: String,
-    /// RPC method to call
-    pub rpc: String,
-    /// XPath for data extraction
-    pub xpath: String,
-    /// Field mappings for display
-    pub fields: HashMap<String, String>,
-    /// Optional RPC arguments
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rpc_args: Option<HashMap<String, serde_json::Value>>,
+use uuid::Uuid;
+use crate::{
+    models,
+    models::reports::{ParameterValues, ReportRun, StoredResult},
+    services::{
+        report_export::{export_response, ExportFormat, ExportQuery, ExportTable},
+        report_join,
+        report_runs::RunRequest,
+        YamlService,
+    },
+    AppState,
+};
+
+pub use crate::models::reports::Report;
+
+/// Largest page `GET /api/reports` returns
+pub const MAX_REPORTS_PAGE_SIZE: usize = 500;
+
+/// Query parameters for listing reports
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportListQuery {
+    /// Words that must all appear in the ID, title, RPC or a field name or path (case-insensitive)
+    pub q: Option<String>,
+    /// Comma-separated categories, matched case-insensitively
+    pub category: Option<String>,
+    /// `id` (default), `title`, `category` or `rpc`; a leading `-` sorts descending
+    pub sort: Option<String>,
+    /// Page size; without it every matching report is returned
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
 }
 
 /// Response structure for listing all reports
 #[derive(Serialize)]
 pub struct ReportsListResponse {
-    /// Total number of reports
+    /// Number of reports matching the filters, across pages
     pub total: usize,
-    /// Available categories
+    /// Categories of the whole catalogue
     pub categories: Vec<String>,
-    /// All reports indexed by their ID
-    pub reports: HashMap<String, Report>,
+    /// Reports of this page indexed by their ID, in sort order
+    pub reports: IndexMap<String, Report>,
+    /// Cursor of the next page; absent on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Response structure for filtered reports
@@ -48,23 +66,109 @@ pub struct FilteredReportsResponse {
     pub category: String,
     /// Number of reports in this category
     pub count: usize,
-    /// Reports in the specified category
-    pub reports: HashMap<String, Report>,
+    /// Reports in the specified category, ordered by ID
+    pub reports: IndexMap<String, Report>,
 }
 
-/// Get all available reports
-/// Returns a comprehensive list of all reports with metadata
-pub async fn get_all_reports(
-    State(state): State<AppState>,
-) -> models::ApiResult<Json<ReportsListResponse>> {
-    // Load reports from YAML file
-    let reports_data = state.yaml_service.get_yaml_data("reports", None).await?;
-    
-    // Parse the YAML data into our Report structures
-    let reports: HashMap<String, Report> = serde_json::from_value(reports_data)
-        .map_err(|e| models::ApiError::ValidationError(format!("Failed to parse reports: {}", e)))?;
-    
-    // Extract unique categories
+/// Column reports are sorted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ReportSort {
+    Id,
+    Title,
+    Category,
+    Rpc,
+}
+
+impl ReportSort {
+    fn parse(spec: Option<&str>) -> models::ApiResult<(Self, bool)> {
+        let spec = spec.map(str::trim).filter(|s| !s.is_empty()).unwrap_or("id");
+        let (name, descending) = match spec.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (spec, false),
+        };
+        let sort = match name {
+            "id" => Self::Id,
+            "title" => Self::Title,
+            "category" => Self::Category,
+            "rpc" => Self::Rpc,
+            other => {
+                return Err(models::ApiError::ValidationError(format!(
+                    "Unknown sort '{}'; expected id, title, category or rpc",
+                    other
+                )))
+            }
+        };
+        Ok((sort, descending))
+    }
+
+    /// Case-insensitive sort key; ties are broken by ID
+    fn key(self, report_id: &str, report: &Report) -> String {
+        match self {
+            Self::Id => report_id,
+            Self::Title => &report.title,
+            Self::Category => &report.category,
+            Self::Rpc => return report.rpc_names().to_lowercase(),
+        }
+        .to_lowercase()
+    }
+}
+
+/// Position after the last report of a page
+/// Holds the sort key so the next page stays stable when that report is deleted meanwhile
+#[derive(Debug, Serialize, Deserialize)]
+struct ReportCursor {
+    sort: ReportSort,
+    descending: bool,
+    key: String,
+    id: String,
+}
+
+impl ReportCursor {
+    /// Opaque, URL-safe form: hex of the JSON
+    fn encode(&self) -> String {
+        serde_json::to_vec(self)
+            .unwrap_or_default()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn decode(cursor: &str) -> models::ApiResult<Self> {
+        let invalid = || models::ApiError::ValidationError(format!("Invalid cursor '{}'", cursor));
+        if !cursor.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| cursor.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+}
+
+/// Search, filter, sort and page the report catalogue
+/// Shared by the REST handler and the WebSocket RPC method
+pub fn list_reports(reports: HashMap<String, Report>, query: &ReportListQuery) -> models::ApiResult<ReportsListResponse> {
+    let (sort, descending) = ReportSort::parse(query.sort.as_deref())?;
+    if let Some(limit) = query.limit {
+        if limit == 0 || limit > MAX_REPORTS_PAGE_SIZE {
+            return Err(models::ApiError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_REPORTS_PAGE_SIZE
+            )));
+        }
+    }
+    let cursor = query.cursor.as_deref().map(ReportCursor::decode).transpose()?;
+    if let Some(cursor) = &cursor {
+        if cursor.sort != sort || cursor.descending != descending {
+            return Err(models::ApiError::ValidationError(
+                "cursor was issued for a different sort".to_string(),
+            ));
+        }
+    }
+
     let mut categories: Vec<String> = reports
         .values()
         .map(|report| report.category.clone())
@@ -72,14 +176,116 @@ pub async fn get_all_reports(
         .into_iter()
         .collect();
     categories.sort();
-    
-    let response = ReportsListResponse {
-        total: reports.len(),
+
+    let terms: Vec<String> = query
+        .q
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let wanted_categories: Vec<String> = query
+        .category
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    let mut matching: Vec<(String, String, Report)> = reports
+        .into_iter()
+        .filter(|(_, report)| {
+            wanted_categories.is_empty() || wanted_categories.contains(&report.category.to_lowercase())
+        })
+        .filter(|(report_id, report)| {
+            let haystack = search_text(report_id, report);
+            terms.iter().all(|term| haystack.contains(term.as_str()))
+        })
+        .map(|(report_id, report)| (sort.key(&report_id, &report), report_id, report))
+        .collect();
+    matching.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    if descending {
+        matching.reverse();
+    }
+
+    let total = matching.len();
+    let start = match &cursor {
+        Some(cursor) => matching
+            .iter()
+            .position(|(key, report_id, _)| {
+                let ordering = (key, report_id).cmp(&(&cursor.key, &cursor.id));
+                if descending {
+                    ordering.is_lt()
+                } else {
+                    ordering.is_gt()
+                }
+            })
+            .unwrap_or(total),
+        None => 0,
+    };
+    let end = query.limit.map_or(total, |limit| (start + limit).min(total));
+
+    let next_cursor = (end < total).then(|| {
+        let (key, report_id, _) = &matching[end - 1];
+        ReportCursor {
+            sort,
+            descending,
+            key: key.clone(),
+            id: report_id.clone(),
+        }
+        .encode()
+    });
+    let reports = matching
+        .drain(start..end)
+        .map(|(_, report_id, report)| (report_id, report))
+        .collect();
+
+    Ok(ReportsListResponse {
+        total,
         categories,
         reports,
-    };
-    
-    Ok(Json(response))
+        next_cursor,
+    })
+}
+
+/// Lowercased text `q` is matched against: ID, title, RPCs, source reports, field and
+/// computed column names and paths
+fn search_text(report_id: &str, report: &Report) -> String {
+    let mut text = format!("{} {} {}", report_id, report.title, report.rpc_names());
+    for source in &report.sources {
+        text.push(' ');
+        text.push_str(&source.report);
+    }
+    for (name, field) in report.fields.iter().chain(&report.computed) {
+        text.push(' ');
+        text.push_str(name);
+        text.push(' ');
+        text.push_str(field.path());
+    }
+    text.to_lowercase()
+}
+
+/// Load and parse all report definitions from reports.yaml
+/// Shared by the REST handlers and the WebSocket RPC methods
+pub async fn load_reports(yaml_service: &YamlService) -> models::ApiResult<HashMap<String, Report>> {
+    let reports_data = yaml_service.get_yaml_data("reports", None).await?;
+    let mut reports = serde_json::from_value(reports_data)
+        .map_err(|e| models::ApiError::ValidationError(format!("Failed to parse reports: {}", e)))?;
+    report_join::link_sources(&mut reports);
+    Ok(reports)
+}
+
+/// List reports
+/// Supports `q` text search, `category=a,b`, `sort=[-]id|title|category|rpc` and
+/// cursor pagination with `limit`/`cursor`; no matches is an empty page
+pub async fn get_all_reports(
+    Query(query): Query<ReportListQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ReportsListResponse>> {
+    // Load reports from YAML file
+    let reports = load_reports(&state.yaml_service).await?;
+    Ok(Json(list_reports(reports, &query)?))
 }
 
 /// Get a specific report by ID
@@ -89,9 +295,7 @@ pub async fn get_report_by_id(
     State(state): State<AppState>,
 ) -> models::ApiResult<Json<Report>> {
     // Load reports from YAML file
-    let reports_data = state.yaml_service.get_yaml_data("reports", None).await?;
-    let reports: HashMap<String, Report> = serde_json::from_value(reports_data)
-        .map_err(|e| models::ApiError::ValidationError(format!("Failed to parse reports: {}", e)))?;
+    let reports = load_reports(&state.yaml_service).await?;
     
     // Find the specific report
     match reports.get(&report_id) {
@@ -100,26 +304,51 @@ pub async fn get_report_by_id(
     }
 }
 
+/// Add a report definition to reports.yaml
+/// Returns `201`; `409` if the ID exists, `400` if the ID or definition is invalid
+pub async fn create_report(
+    Path(report_id): Path<String>,
+    State(state): State<AppState>,
+    Json(body): Json<serde_json::Value>,
+) -> models::ApiResult<(StatusCode, Json<Report>)> {
+    let report = state.report_catalog.create(&report_id, body).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Replace a report definition in reports.yaml
+/// Comments above the entry and every other entry are kept
+pub async fn update_report(
+    Path(report_id): Path<String>,
+    State(state): State<AppState>,
+    Json(body): Json<serde_json::Value>,
+) -> models::ApiResult<Json<Report>> {
+    Ok(Json(state.report_catalog.update(&report_id, body).await?))
+}
+
+/// Remove a report definition from reports.yaml
+pub async fn delete_report(
+    Path(report_id): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<StatusCode> {
+    state.report_catalog.delete(&report_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Filter reports by category
-/// Returns all reports that belong to the specified category
+/// Returns all reports that belong to the specified category; an unknown category is an empty list
 pub async fn filter_reports_by_category(
     Path(category): Path<String>,
     State(state): State<AppState>,
 ) -> models::ApiResult<Json<FilteredReportsResponse>> {
     // Load reports from YAML file
-    let reports_data = state.yaml_service.get_yaml_data("reports", None).await?;
-    let all_reports: HashMap<String, Report> = serde_json::from_value(reports_data)
-        .map_err(|e| models::ApiError::ValidationError(format!("Failed to parse reports: {}", e)))?;
+    let all_reports = load_reports(&state.yaml_service).await?;
     
     // Filter reports by category
-    let filtered_reports: HashMap<String, Report> = all_reports
+    let mut filtered_reports: IndexMap<String, Report> = all_reports
         .into_iter()
         .filter(|(_, report)| report.category.eq_ignore_ascii_case(&category))
         .collect();
-    
-    if filtered_reports.is_empty() {
-        return Err(models::ApiError::NotFound(format!("No reports found for category '{}'", category)));
-    }
+    filtered_reports.sort_keys();
     
     let response = FilteredReportsResponse {
         category: category.clone(),
@@ -130,10 +359,214 @@ pub async fn filter_reports_by_category(
     Ok(Json(response))
 }
 
+/// Request body for running a report
+#[derive(Debug, Deserialize)]
+pub struct RunReportRequest {
+    /// Device (hostname or management address) to run the report against
+    pub device: String,
+    /// Values for the report's parameters; omitted ones take their defaults
+    #[serde(default)]
+    pub parameters: ParameterValues,
+}
+
+/// Run a report against a device
+/// Invokes the report's RPC, applies its XPath and returns the extracted table
+/// The outcome is recorded in the result history; `?format=` exports it as a document
+pub async fn run_report(
+    Path(report_id): Path<String>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<RunReportRequest>,
+) -> models::ApiResult<Response> {
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let reports = load_reports(&state.yaml_service).await?;
+    let report = reports
+        .get(&report_id)
+        .ok_or_else(|| models::ApiError::NotFound(format!("Report '{}' not found", report_id)))?;
+
+    let result = state.report_runner.run_single(&report_id, report, &request.device, &request.parameters)
+        .await?;
+    if format == ExportFormat::Json {
+        return Ok(Json(result).into_response());
+    }
+
+    let table = ExportTable::from_result(&StoredResult::succeeded(result, None));
+    let body = table.render(format)?;
+    Ok(export_response(&table, format, body))
+}
+
+/// Run a report across several devices
+/// Returns `202` with the initial run state; progress streams on the run's WebSocket topic
+pub async fn start_report_run(
+    Path(report_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<RunRequest>,
+) -> models::ApiResult<(StatusCode, Json<ReportRun>)> {
+    let reports = load_reports(&state.yaml_service).await?;
+    let report = reports
+        .get(&report_id)
+        .cloned()
+        .ok_or_else(|| models::ApiError::NotFound(format!("Report '{}' not found", report_id)))?;
+
+    let run = state.report_runner.start(&report_id, report, request).await?;
+    Ok((StatusCode::ACCEPTED, Json(run)))
+}
+
+/// Get the aggregated state of a multi-device run
+/// `?format=` exports the rows of every device as one document with a `Device` column
+pub async fn get_report_run(
+    Path(run_id): Path<Uuid>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> models::ApiResult<Response> {
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let run = state.report_runner.get(run_id).await?;
+    if format == ExportFormat::Json {
+        return Ok(Json(run).into_response());
+    }
+
+    let table = ExportTable::from_run(&run);
+    let body = table.render(format)?;
+    Ok(export_response(&table, format, body))
+}
+
 /// Creates reports-related routes
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/api/reports", get(get_all_reports))
-        .route("/api/reports/:report_id", get(get_report_by_id))
+        .route(
+            "/api/reports/:report_id",
+            get(get_report_by_id).post(create_report).put(update_report).delete(delete_report),
+        )
+        .route("/api/reports/:report_id/run", post(run_report))
+        .route("/api/reports/:report_id/runs", post(start_report_run))
+        .route("/api/report-runs/:run_id", get(get_report_run))
         .route("/api/reports/filter/:category", get(filter_reports_by_category))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Seven reports; titles and categories repeat so sorts on them need the ID tiebreak
+    fn catalogue() -> HashMap<String, Report> {
+        [
+            ("test_bgp_summary", "BGP Neighbor", "Routing", "get-bgp-summary-information"),
+            ("test_ospf_neighbors", "OSPF Neighbor", "Routing", "get-ospf-neighbor-information"),
+            ("test_isis_adjacency", "IS-IS Adjacency", "Routing", "get-isis-adjacency-information"),
+            ("test_interfaces", "Interface Status", "Interfaces", "get-interface-information"),
+            ("test_interface_errors", "Interface Status", "Interfaces", "get-interface-information"),
+            ("test_chassis_alarms", "Alarms", "System", "get-alarm-information"),
+            ("test_ldp_sessions", "LDP Session", "MPLS", "get-ldp-session-information"),
+        ]
+        .into_iter()
+        .map(|(id, title, category, rpc)| {
+            let report = serde_json::from_value(json!({
+                "title": title,
+                "category": category,
+                "rpc": rpc,
+                "xpath": ".//row",
+                "fields": { "Name": "name" }
+            }))
+            .unwrap();
+            (id.to_string(), report)
+        })
+        .collect()
+    }
+
+    fn query(q: Option<&str>, sort: Option<&str>, limit: Option<usize>, cursor: Option<String>) -> ReportListQuery {
+        ReportListQuery {
+            q: q.map(str::to_string),
+            category: None,
+            sort: sort.map(str::to_string),
+            limit,
+            cursor,
+        }
+    }
+
+    /// IDs of every page of a listing, one page at a time
+    fn walk(reports: &HashMap<String, Report>, q: Option<&str>, sort: Option<&str>, limit: usize) -> Vec<Vec<String>> {
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = list_reports(reports.clone(), &query(q, sort, Some(limit), cursor)).unwrap();
+            pages.push(page.reports.keys().cloned().collect());
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return pages,
+            }
+        }
+    }
+
+    #[test]
+    fn walking_pages_returns_every_report_once() {
+        let reports = catalogue();
+        for sort in [None, Some("-id"), Some("title"), Some("-title"), Some("category"), Some("rpc")] {
+            let everything: Vec<String> = list_reports(reports.clone(), &query(None, sort, None, None))
+                .unwrap()
+                .reports
+                .into_keys()
+                .collect();
+            for limit in [1, 2, 3, 7] {
+                let pages = walk(&reports, None, sort, limit);
+                assert_eq!(pages.len(), everything.len().div_ceil(limit), "sort {:?} limit {}", sort, limit);
+                assert!(pages.iter().all(|page| page.len() <= limit));
+                assert_eq!(pages.concat(), everything, "sort {:?} limit {}", sort, limit);
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_survives_deletion_of_the_last_report_of_a_page() {
+        let mut reports = catalogue();
+        let first = list_reports(reports.clone(), &query(None, None, Some(3), None)).unwrap();
+        let last_of_page = first.reports.keys().last().unwrap().clone();
+
+        reports.remove(&last_of_page);
+        let second = list_reports(reports, &query(None, None, Some(3), first.next_cursor)).unwrap();
+        let ids: Vec<&String> = second.reports.keys().collect();
+        assert_eq!(ids, ["test_interfaces", "test_isis_adjacency", "test_ldp_sessions"]);
+    }
+
+    #[test]
+    fn tampered_cursors_are_rejected() {
+        let reports = catalogue();
+        let valid = list_reports(reports.clone(), &query(None, None, Some(2), None))
+            .unwrap()
+            .next_cursor
+            .unwrap();
+        let not_json: String = b"not json".iter().map(|b| format!("{:02x}", b)).collect();
+
+        for cursor in [
+            "zz".to_string(),
+            "abc".to_string(),
+            not_json,
+            valid[..valid.len() - 2].to_string(),
+            format!("{}é", &valid[..valid.len() - 1]),
+        ] {
+            let result = list_reports(reports.clone(), &query(None, None, Some(2), Some(cursor.clone())));
+            assert!(
+                matches!(result, Err(models::ApiError::ValidationError(_))),
+                "cursor {:?} accepted",
+                cursor
+            );
+        }
+
+        let result = list_reports(reports, &query(None, Some("-id"), Some(2), Some(valid)));
+        assert!(matches!(result, Err(models::ApiError::ValidationError(message)) if message.contains("different sort")));
+    }
+
+    #[test]
+    fn search_pages_only_through_matches() {
+        let reports = catalogue();
+        let pages = walk(&reports, Some("neighbor"), Some("title"), 1);
+
+        assert_eq!(pages, [vec!["test_bgp_summary".to_string()], vec!["test_ospf_neighbors".to_string()]]);
+        let first = list_reports(reports, &query(Some("neighbor"), Some("title"), Some(1), None)).unwrap();
+        assert_eq!(first.total, 2);
+        assert_eq!(first.categories.len(), 4);
+    }
+}
@@ -0,0 +1,113 @@
+//! Report Results Routes
+//!
+//! Handles browsing the stored history of report executions and diffing results
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use crate::{
+    models,
+    models::reports::{DeviceRunStatus, ResultDiff, StoredResult, StoredResultPage},
+    routes::reports::load_reports,
+    services::{
+        report_export::{export_response, ExportFormat, ExportQuery, ExportTable},
+        result_diff::diff_results,
+        results_store::ResultQuery,
+    },
+    AppState,
+};
+
+/// Query parameters for the latest results of a report
+#[derive(Debug, Default, Deserialize)]
+pub struct LatestResultsQuery {
+    /// Only consider results with this status, e.g. `succeeded`
+    pub status: Option<DeviceRunStatus>,
+}
+
+/// Query parameters for diffing two results
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Baseline result
+    pub from: Uuid,
+    /// Result compared against the baseline
+    pub to: Uuid,
+    /// Identity column (display name or field); defaults to the report's `key`
+    pub key: Option<String>,
+}
+
+/// Diff two stored results of the same report
+/// Shared by the REST handler and the WebSocket RPC method
+pub async fn diff_stored_results(state: &AppState, query: DiffQuery) -> models::ApiResult<ResultDiff> {
+    let from = state.results_store.get(query.from).await?;
+    let to = state.results_store.get(query.to).await?;
+
+    // The definition may have been edited or removed since the runs; only its key matters here
+    let key = match query.key {
+        Some(key) => Some(key),
+        None => load_reports(&state.yaml_service)
+            .await?
+            .get(&to.summary.report_id)
+            .and_then(|report| report.key.clone()),
+    };
+    diff_results(&from, &to, key.as_deref())
+}
+
+/// List stored results
+/// Filters: `report_id`, `device`, `status`, `verdict`, `batch_run_id`, `since`, `until`; paged with `limit`/`offset`
+pub async fn list_results(
+    Query(query): Query<ResultQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<StoredResultPage>> {
+    Ok(Json(state.results_store.list(query).await?))
+}
+
+/// Get one stored result including its table
+/// `?format=csv|xlsx|jsonl|html` (or a matching `Accept` header) downloads it as a document
+pub async fn get_result(
+    Path(run_id): Path<Uuid>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> models::ApiResult<Response> {
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let result = state.results_store.get(run_id).await?;
+    if format == ExportFormat::Json {
+        return Ok(Json(result).into_response());
+    }
+
+    let table = ExportTable::from_result(&result);
+    let body = table.render(format)?;
+    Ok(export_response(&table, format, body))
+}
+
+/// Diff two stored results: rows added, removed and changed between `from` and `to`
+pub async fn diff_results_handler(
+    Query(query): Query<DiffQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ResultDiff>> {
+    Ok(Json(diff_stored_results(&state, query).await?))
+}
+
+/// Get the most recent result of a report on every device it ran on
+pub async fn latest_results(
+    Path(report_id): Path<String>,
+    Query(query): Query<LatestResultsQuery>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<Vec<StoredResult>>> {
+    Ok(Json(state.results_store.latest_per_device(&report_id, query.status).await?))
+}
+
+/// Creates result history routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/results", get(list_results))
+        .route("/api/results/diff", get(diff_results_handler))
+        .route("/api/results/:run_id", get(get_result))
+        .route("/api/reports/:report_id/results/latest", get(latest_results))
+}
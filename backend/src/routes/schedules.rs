@@ -0,0 +1,67 @@
+//! Report Schedule Routes
+//!
+//! Handles listing scheduled report runs and pausing, resuming or triggering them
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use crate::{
+    models,
+    models::{reports::ReportRun, schedules::ScheduleStatus},
+    AppState,
+};
+
+/// List every schedule with its next and last run
+pub async fn list_schedules(
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<Vec<ScheduleStatus>>> {
+    Ok(Json(state.scheduler.list().await?))
+}
+
+/// Get one schedule with its next and last run
+pub async fn get_schedule(
+    Path(schedule_id): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ScheduleStatus>> {
+    Ok(Json(state.scheduler.get(&schedule_id).await?))
+}
+
+/// Pause a schedule until it is resumed
+pub async fn pause_schedule(
+    Path(schedule_id): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ScheduleStatus>> {
+    Ok(Json(state.scheduler.pause(&schedule_id).await?))
+}
+
+/// Resume a paused schedule from its next fire time
+pub async fn resume_schedule(
+    Path(schedule_id): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<Json<ScheduleStatus>> {
+    Ok(Json(state.scheduler.resume(&schedule_id).await?))
+}
+
+/// Run a schedule now
+/// Returns `202` with the started run, or `409` while its previous run is in progress
+pub async fn trigger_schedule(
+    Path(schedule_id): Path<String>,
+    State(state): State<AppState>,
+) -> models::ApiResult<(StatusCode, Json<ReportRun>)> {
+    let run = state.scheduler.trigger(&schedule_id).await?;
+    Ok((StatusCode::ACCEPTED, Json(run)))
+}
+
+/// Creates schedule routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/schedules", get(list_schedules))
+        .route("/api/schedules/:schedule_id", get(get_schedule))
+        .route("/api/schedules/:schedule_id/pause", post(pause_schedule))
+        .route("/api/schedules/:schedule_id/resume", post(resume_schedule))
+        .route("/api/schedules/:schedule_id/trigger", post(trigger_schedule))
+}
@@ -0,0 +1,411 @@
+// backend/src/services/assertions.rs
+
+//! # Report Assertions
+//!
+//! ## Description
+//! Threshold checks declared on a report (`Report.assertions`) and evaluated against
+//! every extracted table, turning reports into pre- and post-change validation tests.
+//! Each check yields a verdict per row; rows and the result get the worst verdict of
+//! the checks that apply to them.
+//!
+//! ## Syntax
+//! - `<column> <op> <value>` - checked on every row; `column` is a display name or
+//!   field from `Report.fields`: `State == Established`, `Flaps < 5`
+//! - `count(rows) <op> <number>` - checked once on the table: `count(rows) >= 2`
+//! - Operators: `==`, `!=`, `<`, `<=`, `>`, `>=`
+//! - Values may be quoted (`Description != ""`). When both sides are numbers they
+//!   compare numerically; `<`, `<=`, `>`, `>=` require numbers, `==`/`!=` otherwise
+//!   compare text. A field that matched nothing fails every check on it
+//!
+//! ## How to Use
+//! 1. `let checks = CompiledAssertions::compile(&report.assertions, &columns)?;`
+//! 2. `let results = checks.evaluate(&rows);` - `None` when the report has no assertions
+
+use serde_json::Value;
+
+use crate::models::{
+    reports::{
+        find_column, AssertionResults, AssertionSpec, CheckFailure, CheckOutcome, ReportColumn, ReportRow,
+        Severity, Verdict,
+    },
+    ApiError, ApiResult,
+};
+
+/// Table-level subject of a check
+const ROW_COUNT: &str = "count(rows)";
+
+/// Operators, longest first so `<=` is not read as `<`
+const OPERATORS: [(&str, Operator); 6] = [
+    ("==", Operator::Eq),
+    ("!=", Operator::Ne),
+    ("<=", Operator::Le),
+    (">=", Operator::Ge),
+    ("<", Operator::Lt),
+    (">", Operator::Gt),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    fn is_ordering(self) -> bool {
+        !matches!(self, Operator::Eq | Operator::Ne)
+    }
+
+    fn holds(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Operator::Eq => ordering == Equal,
+            Operator::Ne => ordering != Equal,
+            Operator::Lt => ordering == Less,
+            Operator::Le => ordering != Greater,
+            Operator::Gt => ordering == Greater,
+            Operator::Ge => ordering != Less,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Subject {
+    Column(usize),
+    RowCount,
+}
+
+/// One parsed check
+#[derive(Debug, Clone)]
+struct Assertion {
+    spec: AssertionSpec,
+    subject: Subject,
+    operator: Operator,
+    expected: String,
+    /// `expected` as a number, when it is one
+    expected_number: Option<f64>,
+}
+
+impl Assertion {
+    fn parse(spec: &AssertionSpec, columns: &[ReportColumn]) -> ApiResult<Self> {
+        let check = spec.check();
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid assertion '{}': {}", check, reason));
+
+        let (position, symbol, operator) = OPERATORS
+            .iter()
+            .filter_map(|(symbol, operator)| check.find(symbol).map(|position| (position, *symbol, *operator)))
+            // Leftmost operator; at equal positions the longer symbol was listed first
+            .min_by_key(|(position, _, _)| *position)
+            .ok_or_else(|| invalid("expected one of ==, !=, <, <=, >, >=".to_string()))?;
+
+        let subject_name = check[..position].trim();
+        let expected = unquote(check[position + symbol.len()..].trim());
+        let expected_number = parse_number(expected);
+
+        let subject = if subject_name == ROW_COUNT {
+            Subject::RowCount
+        } else {
+            let column = find_column(columns, subject_name).ok_or_else(|| {
+                invalid(format!(
+                    "'{}' is not a column; available: {}",
+                    subject_name,
+                    columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                ))
+            })?;
+            Subject::Column(column)
+        };
+
+        if subject_name.is_empty() {
+            return Err(invalid("missing column before the operator".to_string()));
+        }
+        if (operator.is_ordering() || matches!(subject, Subject::RowCount)) && expected_number.is_none() {
+            return Err(invalid(format!("'{}' is not a number", expected)));
+        }
+
+        Ok(Self {
+            spec: spec.clone(),
+            subject,
+            operator,
+            expected: expected.to_string(),
+            expected_number,
+        })
+    }
+
+    /// Whether a cell satisfies the check
+    fn accepts(&self, actual: &Value) -> bool {
+        let text = match actual {
+            Value::Null => return false,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        match (parse_number(&text), self.expected_number) {
+            (Some(actual), Some(expected)) => actual
+                .partial_cmp(&expected)
+                .is_some_and(|ordering| self.operator.holds(ordering)),
+            // Ordering checks need numbers on both sides
+            _ if self.operator.is_ordering() => false,
+            _ => self.operator.holds(text.as_str().cmp(self.expected.as_str())),
+        }
+    }
+
+    fn failed_verdict(&self) -> Verdict {
+        match self.spec.severity() {
+            Severity::Fail => Verdict::Fail,
+            Severity::Warn => Verdict::Warn,
+        }
+    }
+}
+
+/// A report's assertions, parsed against its columns
+#[derive(Debug, Clone, Default)]
+pub struct CompiledAssertions {
+    assertions: Vec<Assertion>,
+}
+
+impl CompiledAssertions {
+    /// Parse every check, naming the first invalid one
+    pub fn compile(specs: &[AssertionSpec], columns: &[ReportColumn]) -> ApiResult<Self> {
+        let assertions = specs
+            .iter()
+            .map(|spec| Assertion::parse(spec, columns))
+            .collect::<ApiResult<Vec<_>>>()?;
+        Ok(Self { assertions })
+    }
+
+    /// Evaluate every check against a table; `None` without assertions
+    pub fn evaluate(&self, rows: &[ReportRow]) -> Option<AssertionResults> {
+        if self.assertions.is_empty() {
+            return None;
+        }
+
+        let mut row_verdicts = vec![Verdict::Pass; rows.len()];
+        let checks: Vec<CheckOutcome> = self
+            .assertions
+            .iter()
+            .map(|assertion| {
+                let failures: Vec<CheckFailure> = match assertion.subject {
+                    Subject::RowCount => {
+                        let count = Value::from(rows.len());
+                        (!assertion.accepts(&count))
+                            .then_some(CheckFailure { row: None, actual: count })
+                            .into_iter()
+                            .collect()
+                    }
+                    Subject::Column(column) => rows
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, row)| {
+                            let actual = row.get(column).cloned().unwrap_or(Value::Null);
+                            (!assertion.accepts(&actual)).then_some(CheckFailure { row: Some(index), actual })
+                        })
+                        .collect(),
+                };
+
+                let verdict = if failures.is_empty() {
+                    Verdict::Pass
+                } else {
+                    assertion.failed_verdict()
+                };
+                for row in failures.iter().filter_map(|f| f.row) {
+                    row_verdicts[row] = row_verdicts[row].max(verdict);
+                }
+
+                CheckOutcome {
+                    check: assertion.spec.check().to_string(),
+                    severity: assertion.spec.severity(),
+                    description: assertion.spec.description().map(str::to_string),
+                    verdict,
+                    failures,
+                }
+            })
+            .collect();
+
+        Some(AssertionResults {
+            verdict: checks.iter().map(|c| c.verdict).max().unwrap_or(Verdict::Pass),
+            rows: row_verdicts,
+            checks,
+        })
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reports::{DeviceRun, DeviceRunStatus, ReportResult, ReportRunSummary};
+    use serde_json::json;
+
+    /// Peer, State, Flaps, Description
+    fn columns() -> Vec<ReportColumn> {
+        [("Peer", "peer-address"), ("State", "peer-state"), ("Flaps", "flap-count"), ("Description", "description")]
+            .iter()
+            .map(|(name, field)| ReportColumn {
+                name: name.to_string(),
+                field: field.to_string(),
+                kind: Default::default(),
+                unit: None,
+            })
+            .collect()
+    }
+
+    fn rows() -> Vec<ReportRow> {
+        vec![
+            vec![json!("10.0.0.1"), json!("Established"), json!(0), json!("core")],
+            vec![json!("10.0.0.2"), json!("Established"), json!("7"), json!("")],
+            vec![json!("10.0.0.3"), json!("Active"), Value::Null, Value::Null],
+        ]
+    }
+
+    fn check(check: &str) -> AssertionSpec {
+        AssertionSpec::Check(check.to_string())
+    }
+
+    fn warning(check: &str) -> AssertionSpec {
+        AssertionSpec::Detailed {
+            check: check.to_string(),
+            severity: Severity::Warn,
+            description: Some("worth a look".to_string()),
+        }
+    }
+
+    fn evaluate(specs: &[AssertionSpec]) -> AssertionResults {
+        CompiledAssertions::compile(specs, &columns()).unwrap().evaluate(&rows()).unwrap()
+    }
+
+    #[test]
+    fn text_checks_pass_and_fail_per_row() {
+        let results = evaluate(&[check("State == Established")]);
+        assert_eq!(results.verdict, Verdict::Fail);
+        assert_eq!(results.rows, [Verdict::Pass, Verdict::Pass, Verdict::Fail]);
+        assert_eq!(results.checks[0].failures.len(), 1);
+        assert_eq!(results.checks[0].failures[0].row, Some(2));
+        assert_eq!(results.checks[0].failures[0].actual, json!("Active"));
+
+        // By field, and `!=` with a quoted empty value
+        let results = evaluate(&[check("peer-state != Idle"), check("Description != \"\"")]);
+        assert_eq!(results.checks[0].verdict, Verdict::Pass);
+        assert_eq!(results.checks[1].verdict, Verdict::Fail);
+        let failed_rows: Vec<Option<usize>> = results.checks[1].failures.iter().map(|f| f.row).collect();
+        assert_eq!(failed_rows, [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn numeric_checks_compare_numbers_and_fail_on_null() {
+        let results = evaluate(&[check("Flaps < 5")]);
+        // 0 passes, "7" compares as a number and fails, null fails
+        assert_eq!(results.rows, [Verdict::Pass, Verdict::Fail, Verdict::Fail]);
+
+        let results = evaluate(&[check("Flaps >= 0"), check("Flaps <= 7"), check("Flaps > -1")]);
+        assert!(results.checks.iter().all(|c| c.failures.len() == 1 && c.failures[0].row == Some(2)));
+
+        // Ordering a text column never holds
+        let results = evaluate(&[check("State > 1")]);
+        assert_eq!(results.rows, [Verdict::Fail; 3]);
+    }
+
+    #[test]
+    fn row_count_checks_apply_to_the_table() {
+        let results = evaluate(&[check("count(rows) >= 2")]);
+        assert_eq!(results.verdict, Verdict::Pass);
+        assert!(results.checks[0].failures.is_empty());
+
+        let results = evaluate(&[check("count(rows) == 5")]);
+        assert_eq!(results.verdict, Verdict::Fail);
+        assert_eq!(results.checks[0].failures[0].row, None);
+        assert_eq!(results.checks[0].failures[0].actual, json!(3));
+        // Table-level failures do not mark rows
+        assert_eq!(results.rows, [Verdict::Pass; 3]);
+    }
+
+    #[test]
+    fn rows_and_results_take_the_worst_verdict() {
+        let results = evaluate(&[warning("Flaps < 5"), check("State == Established")]);
+
+        assert_eq!(results.checks[0].verdict, Verdict::Warn);
+        assert_eq!(results.checks[0].severity, Severity::Warn);
+        assert_eq!(results.checks[0].description.as_deref(), Some("worth a look"));
+        assert_eq!(results.rows, [Verdict::Pass, Verdict::Warn, Verdict::Fail]);
+        assert_eq!(results.verdict, Verdict::Fail);
+
+        let results = evaluate(&[warning("Flaps < 5"), check("count(rows) > 0")]);
+        assert_eq!(results.verdict, Verdict::Warn);
+    }
+
+    #[test]
+    fn invalid_checks_are_errors() {
+        for (spec, reason) in [
+            ("State", "expected one of"),
+            ("Uptime == 0", "'Uptime' is not a column"),
+            ("== Established", "is not a column"),
+            ("Flaps < many", "'many' is not a number"),
+            ("count(rows) == some", "'some' is not a number"),
+        ] {
+            let error = CompiledAssertions::compile(&[check(spec)], &columns()).unwrap_err();
+            assert!(matches!(error, ApiError::ValidationError(_)), "{}", spec);
+            assert!(error.to_string().contains(reason), "{}: {}", spec, error);
+        }
+    }
+
+    #[test]
+    fn no_assertions_give_no_results() {
+        assert!(CompiledAssertions::compile(&[], &columns()).unwrap().evaluate(&rows()).is_none());
+    }
+
+    fn device(status: DeviceRunStatus, verdict: Option<Verdict>) -> DeviceRun {
+        let result = (status == DeviceRunStatus::Succeeded).then(|| {
+            let mut result: ReportResult = serde_json::from_value(json!({
+                "run_id": uuid::Uuid::new_v4(),
+                "report_id": "test_bgp_summary",
+                "title": "BGP Neighbor",
+                "device": "r1.lab",
+                "rpc": "get-bgp-summary-information",
+                "started_at": "2026-10-18T08:00:00Z",
+                "duration_ms": 5,
+                "columns": [],
+                "rows": []
+            }))
+            .unwrap();
+            result.assertions = verdict.map(|verdict| AssertionResults { verdict, rows: Vec::new(), checks: Vec::new() });
+            result
+        });
+        DeviceRun {
+            status,
+            result,
+            ..DeviceRun::pending("r1.lab".to_string())
+        }
+    }
+
+    #[test]
+    fn run_verdict_rolls_up_devices() {
+        use DeviceRunStatus::*;
+        let verdict = |devices: &[DeviceRun]| ReportRunSummary::of(devices).verdict;
+
+        assert_eq!(verdict(&[device(Succeeded, Some(Verdict::Pass))]), Some(Verdict::Pass));
+        assert_eq!(
+            verdict(&[device(Succeeded, Some(Verdict::Pass)), device(Succeeded, Some(Verdict::Warn))]),
+            Some(Verdict::Warn)
+        );
+        // A device that did not answer fails the run once any device has assertions
+        assert_eq!(
+            verdict(&[device(Succeeded, Some(Verdict::Pass)), device(TimedOut, None)]),
+            Some(Verdict::Fail)
+        );
+        assert_eq!(verdict(&[device(Succeeded, None), device(Failed, None)]), None);
+        assert_eq!(verdict(&[device(Running, None)]), None);
+    }
+}
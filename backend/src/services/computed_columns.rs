@@ -0,0 +1,354 @@
+// backend/src/services/computed_columns.rs
+
+//! # Computed Columns
+//!
+//! ## Description
+//! Columns calculated from the other cells of a row (`Report.computed`), appended after
+//! the extracted or joined columns. Expressions are XPath 1.0 (`services::xpath`) where
+//! `{Column}` stands for a cell of the row, by display name or field, and `$name` for a
+//! report parameter. Results are typed like fields (`services::field_types`):
+//!
+//! ```yaml
+//! computed:
+//!   "Total (Mbps)": "{Input (Mbps)} + {Output (Mbps)}"
+//!   "Utilization %":
+//!     path: "({Input (Mbps)} + {Output (Mbps)}) * 100 div {Speed (Mbps)}"
+//!     type: float
+//!     precision: 1
+//!   Label: "concat({Interface}, ' -> ', {Neighbor})"
+//! ```
+//!
+//! Arithmetic uses `div` and `mod` as XPath does. A computed column may refer to the
+//! computed columns declared before it. A `null` input turns arithmetic into `null`.
+//!
+//! ## How to Use
+//! 1. `let computed = ComputedColumns::compile(&report.computed, &columns, &report.parameters)?;`
+//! 2. `let misses = computed.apply(&mut rows, &parameters)?;` - one cell appended per column
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::{
+    models::{
+        reports::{find_column, FieldMiss, FieldSpec, MissReason, ParameterSpec, ParameterValues, ReportColumn, ReportRow},
+        ApiError, ApiResult,
+    },
+    services::{
+        field_types::FieldTransform,
+        report_engine::cell_value,
+        report_parameters,
+        xpath::{XNode, XPath, XPathOptions},
+    },
+};
+
+/// Prefix of the XPath variables standing for cells; parameter names cannot contain `.`
+const CELL_VARIABLE: &str = "column.";
+
+/// One computed column
+#[derive(Debug, Clone)]
+struct ComputedColumn {
+    column: ReportColumn,
+    expression: XPath,
+    /// Cells the expression reads: variable name -> column index in the row
+    references: Vec<(String, usize)>,
+    transform: FieldTransform,
+}
+
+/// A report's computed columns, checked against the columns before them
+#[derive(Debug, Clone, Default)]
+pub struct ComputedColumns {
+    columns: Vec<ComputedColumn>,
+}
+
+impl ComputedColumns {
+    /// Compile every expression against `columns` and the computed columns declared
+    /// before it, naming the first invalid one
+    pub fn compile(
+        specs: &IndexMap<String, FieldSpec>,
+        columns: &[ReportColumn],
+        parameters: &IndexMap<String, ParameterSpec>,
+    ) -> ApiResult<Self> {
+        let mut available = columns.to_vec();
+        let mut computed = Vec::with_capacity(specs.len());
+
+        for (name, spec) in specs {
+            let source = spec.path();
+            let invalid = |reason: String| {
+                ApiError::ValidationError(format!("Invalid computed column '{}' ('{}'): {}", name, source, reason))
+            };
+            if available.iter().any(|column| column.name == *name) {
+                return Err(invalid("a column with this name already exists".to_string()));
+            }
+
+            let (rewritten, references) = rewrite(source, &available).map_err(invalid)?;
+            let expression = XPath::compile(&rewritten).map_err(|e| invalid(e.to_string()))?;
+            for variable in expression.variables() {
+                if !variable.starts_with(CELL_VARIABLE) {
+                    report_parameters::check_reference(parameters, &format!("Computed column '{}'", name), variable)?;
+                }
+            }
+
+            let transform = FieldTransform::compile(name, spec)?;
+            let column = ReportColumn {
+                name: name.clone(),
+                field: source.to_string(),
+                kind: transform.kind(),
+                unit: transform.unit().map(str::to_string),
+            };
+            available.push(column.clone());
+            computed.push(ComputedColumn {
+                column,
+                expression,
+                references,
+                transform,
+            });
+        }
+        Ok(Self { columns: computed })
+    }
+
+    /// Columns appended to every row, in order
+    pub fn columns(&self) -> impl Iterator<Item = &ReportColumn> {
+        self.columns.iter().map(|c| &c.column)
+    }
+
+    /// Append the computed cells to every row; `null` results and values that do not
+    /// parse as the declared type are reported as misses
+    pub fn apply(&self, rows: &mut [ReportRow], parameters: &ParameterValues) -> ApiResult<Vec<FieldMiss>> {
+        if self.columns.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Expressions only read variables; the context node is a placeholder
+        let document = roxmltree::Document::parse("<row/>")
+            .map_err(|e| ApiError::InternalError(format!("Invalid computed column context: {}", e)))?;
+        let mut options = XPathOptions {
+            variables: parameters.iter().map(|(name, value)| (name.clone(), value.clone())).collect::<HashMap<_, _>>(),
+            ..XPathOptions::default()
+        };
+
+        let mut misses = Vec::new();
+        for computed in &self.columns {
+            let (mut missing, mut unparsed) = (Vec::new(), Vec::new());
+            for (index, row) in rows.iter_mut().enumerate() {
+                for (variable, column) in &computed.references {
+                    options
+                        .variables
+                        .insert(variable.clone(), row.get(*column).cloned().unwrap_or(Value::Null));
+                }
+                let value = computed
+                    .expression
+                    .evaluate(XNode::Tree(document.root()), &options)
+                    .map_err(|e| {
+                        ApiError::ValidationError(format!("Cannot evaluate '{}': {}", computed.column.field, e))
+                    })?;
+                let cell = cell_value(value);
+                if cell.is_null() {
+                    missing.push(index);
+                }
+                let cell = computed.transform.apply(cell).unwrap_or_else(|raw| {
+                    unparsed.push((index, raw));
+                    Value::Null
+                });
+                row.push(cell);
+            }
+
+            let column = &computed.column;
+            if !missing.is_empty() {
+                misses.push(FieldMiss {
+                    column: column.name.clone(),
+                    field: column.field.clone(),
+                    reason: MissReason::NoMatch,
+                    rows: missing,
+                    values: Vec::new(),
+                });
+            }
+            if !unparsed.is_empty() {
+                let (rows, values) = unparsed.into_iter().unzip();
+                misses.push(FieldMiss {
+                    column: column.name.clone(),
+                    field: column.field.clone(),
+                    reason: MissReason::Unparsed,
+                    rows,
+                    values,
+                });
+            }
+        }
+        Ok(misses)
+    }
+}
+
+/// Replace every `{Column}` outside string literals with a cell variable
+fn rewrite(source: &str, columns: &[ReportColumn]) -> Result<(String, Vec<(String, usize)>), String> {
+    let mut rewritten = String::with_capacity(source.len());
+    let mut references: Vec<(String, usize)> = Vec::new();
+    let mut chars = source.char_indices();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                rewritten.push(c);
+                for (_, inner) in chars.by_ref() {
+                    rewritten.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                let rest = &source[position + 1..];
+                let length = rest
+                    .find('}')
+                    .ok_or_else(|| format!("unclosed '{{' at offset {}", position))?;
+                let name = rest[..length].trim();
+                let column = find_column(columns, name).ok_or_else(|| {
+                    format!(
+                        "'{}' is not a column; available: {}",
+                        name,
+                        columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                    )
+                })?;
+                let variable = format!("{}{}", CELL_VARIABLE, column);
+                // The space keeps a following `-` or `.` out of the variable name
+                rewritten.push('$');
+                rewritten.push_str(&variable);
+                rewritten.push(' ');
+                if !references.iter().any(|(existing, _)| *existing == variable) {
+                    references.push((variable, column));
+                }
+                // Skip the name and the closing brace
+                for _ in 0..rest[..=length].chars().count() {
+                    chars.next();
+                }
+            }
+            _ => rewritten.push(c),
+        }
+    }
+    Ok((rewritten, references))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::reports::FieldType;
+    use serde_json::json;
+
+    fn columns(names: &[&str]) -> Vec<ReportColumn> {
+        names
+            .iter()
+            .map(|name| ReportColumn {
+                name: name.to_string(),
+                field: name.to_lowercase().replace(' ', "-"),
+                kind: Default::default(),
+                unit: None,
+            })
+            .collect()
+    }
+
+    fn specs(yaml: &str) -> IndexMap<String, FieldSpec> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn compile(yaml: &str, names: &[&str]) -> ApiResult<ComputedColumns> {
+        ComputedColumns::compile(&specs(yaml), &columns(names), &IndexMap::new())
+    }
+
+    #[test]
+    fn shipped_readiness_column() {
+        let computed = compile(
+            "Ready:\n  path: \"{Oper Status} = 'Up' and {State} = 'Full'\"\n  type: boolean\n",
+            &["Interface", "Oper Status", "State"],
+        )
+        .unwrap();
+        let mut rows = vec![
+            vec![json!("ge-0/0/0.0"), json!("Up"), json!("Full")],
+            vec![json!("ge-0/0/1.0"), json!("Up"), json!("Init")],
+            vec![json!("ge-0/0/2.0"), json!("Down"), json!("Full")],
+        ];
+
+        let misses = computed.apply(&mut rows, &ParameterValues::new()).unwrap();
+
+        let ready: Vec<&Value> = rows.iter().map(|row| &row[3]).collect();
+        assert_eq!(ready, [&json!(true), &json!(false), &json!(false)]);
+        assert!(misses.is_empty());
+    }
+
+    #[test]
+    fn arithmetic_and_earlier_computed_columns() {
+        let computed = compile(
+            "Total: \"{In} + {Out}\"\nDouble:\n  path: \"{Total} * 2\"\n  type: integer\n",
+            &["Name", "In", "Out"],
+        )
+        .unwrap();
+        let mut rows = vec![vec![json!("ge-0/0/0"), json!(3), json!(4)]];
+
+        computed.apply(&mut rows, &ParameterValues::new()).unwrap();
+        // XPath numbers are doubles until a type says otherwise
+        assert_eq!(rows[0][3..], [json!(7.0), json!(14)]);
+        let kinds: Vec<FieldType> = computed.columns().map(|c| c.kind).collect();
+        assert_eq!(kinds, [FieldType::String, FieldType::Integer]);
+    }
+
+    #[test]
+    fn null_inputs_are_reported_as_missing() {
+        let computed = compile("Total: \"{In} + {Out}\"\n", &["In", "Out"]).unwrap();
+        let mut rows = vec![vec![json!(1), Value::Null], vec![json!(1), json!(2)], vec![Value::Null, json!(5)]];
+
+        let misses = computed.apply(&mut rows, &ParameterValues::new()).unwrap();
+
+        assert_eq!(rows[0][2], Value::Null);
+        assert_eq!(rows[1][2], json!(3.0));
+        assert_eq!(rows[2][2], Value::Null);
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].reason, MissReason::NoMatch);
+        assert_eq!(misses[0].rows, [0, 2]);
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_are_unparsed_misses() {
+        let computed = compile("Port:\n  path: \"concat({Name}, '/x')\"\n  type: integer\n", &["Name"]).unwrap();
+        let mut rows = vec![vec![json!("ge-0/0/0")]];
+
+        let misses = computed.apply(&mut rows, &ParameterValues::new()).unwrap();
+
+        assert_eq!(rows[0][1], Value::Null);
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].reason, MissReason::Unparsed);
+        assert_eq!(misses[0].values, ["ge-0/0/0/x"]);
+    }
+
+    #[test]
+    fn missing_and_later_columns_are_rejected() {
+        let error = compile("Rate: \"{In} div {Speed}\"\n", &["In"]).unwrap_err().to_string();
+        assert!(error.contains("'Speed' is not a column; available: In"), "{}", error);
+
+        let error = compile("A: \"{B} + 1\"\nB: \"{In}\"\n", &["In"]).unwrap_err().to_string();
+        assert!(error.contains("Invalid computed column 'A'"), "{}", error);
+
+        let error = compile("In: \"{In} + 1\"\n", &["In"]).unwrap_err().to_string();
+        assert!(error.contains("already exists"), "{}", error);
+
+        let error = compile("Total: \"{In + 1\"\n", &["In"]).unwrap_err().to_string();
+        assert!(error.contains("unclosed"), "{}", error);
+
+        let error = compile("Total: \"{In} + $speed\"\n", &["In"]).unwrap_err();
+        assert!(matches!(error, ApiError::ValidationError(_)), "{}", error);
+    }
+
+    #[test]
+    fn braces_in_string_literals_are_kept() {
+        let computed = compile("Label: \"concat({Name}, ' {Name}')\"\n", &["Name"]).unwrap();
+        let mut rows = vec![vec![json!("ge-0/0/0")]];
+
+        computed.apply(&mut rows, &ParameterValues::new()).unwrap();
+        assert_eq!(rows[0][1], json!("ge-0/0/0 {Name}"));
+    }
+
+    #[test]
+    fn evaluation_errors_name_the_expression() {
+        let computed = compile("Child: \"count({Name}/child)\"\n", &["Name"]).unwrap();
+        let mut rows = vec![vec![json!("ge-0/0/0")]];
+
+        let error = computed.apply(&mut rows, &ParameterValues::new()).unwrap_err().to_string();
+        assert!(error.contains("Cannot evaluate"), "{}", error);
+    }
+}
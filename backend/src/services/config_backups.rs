@@ -0,0 +1,784 @@
+// backend/src/services/config_backups.rs
+
+//! # Configuration Backups
+//!
+//! ## Description
+//! Pulls running configurations from devices and keeps their version history.
+//! A backup job fetches each device's committed configuration with `get-configuration`
+//! in the requested formats (text, set, XML) through the report engine's transport, with
+//! bounded concurrency and a per-device timeout. Jobs are kept in memory like report
+//! runs (`services::report_runs`); the configurations themselves go to the results
+//! database.
+//!
+//! ## Storage
+//! - `config_blobs` holds each distinct content once, keyed by its SHA-256
+//! - `config_versions` lists, per device and format, every content the device ran
+//! - A fetched configuration identical to the device's latest version only refreshes
+//!   that version's `confirmed_at`; anything else becomes a new version, reusing the
+//!   stored blob when the content was seen before (a rollback, or identical devices)
+//! - Versions are kept until deleted with the database; result retention does not apply
+//!
+//! ## Devices
+//! Targets may be inventory names, addresses or `@group`. Known devices are fetched
+//! from their inventory address and recorded under their inventory name, so the
+//! history of a device does not depend on how a backup was requested.
+//!
+//! ## Events
+//! Every `DataUpdate` on `data:backups` carries `job_id` and an `event`:
+//! - `backup_started` - `job`: the full `BackupJob` with every device `pending`
+//! - `device_backed_up` - `index`, `device`: the `DeviceBackup`, `summary`
+//! - `backup_completed` - `summary`, `finished_at`
+//!
+//! ## Configuration
+//! - `THALYX_BACKUP_CONCURRENCY` - devices backed up at once by default (8)
+//! - `THALYX_BACKUP_DEVICE_TIMEOUT` - seconds allowed per device by default (300)
+//! - `THALYX_BACKUP_HISTORY` - finished jobs kept in memory (100)
+//!
+//! ## How to Use
+//! 1. `let backups = Arc::new(ConfigBackups::new(engine, database, inventory, groups, websocket, BackupConfig::from_env()));`
+//! 2. `backups.start(BackupRequest { devices: vec!["@core".into()], .. }).await?` returns the job
+//! 3. Page through `backups.versions(query)` and read one with `backups.content(version_id)`
+
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+use rusqlite::{params, params_from_iter, OptionalExtension, Row};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn, Instrument};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        backups::{
+            BackupJob, BackupJobStatus, BackupJobSummary, BackupRequest, ConfigFormat, ConfigVersion,
+            ConfigVersionPage, DeviceBackup, DeviceBackupStatus, FormatBackup,
+        },
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        report_engine::RpcCall,
+        report_runs::{MAX_CONCURRENCY, MAX_DEVICES_PER_RUN, MAX_DEVICE_TIMEOUT_SECS},
+        database::{database_error, parse_column, timestamp, Database, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        DeviceGroups, DeviceInventory, ReportEngine, WebSocketService,
+    },
+};
+
+/// `DataUpdate.source` of backup events
+const TOPIC_SOURCE: &str = "backups";
+
+/// RPC retrieving a configuration; the format goes in its `format` attribute
+const GET_CONFIGURATION: &str = "get-configuration";
+
+const VERSION_COLUMNS: &str = "id, device, format, hash, size, job_id, taken_at, confirmed_at";
+
+/// Defaults applied when a backup request leaves options out
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub concurrency: usize,
+    pub device_timeout: Duration,
+    /// Finished jobs retained for `job`; running jobs are never evicted
+    pub history: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            device_timeout: Duration::from_secs(300),
+            history: 100,
+        }
+    }
+}
+
+impl BackupConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|n| *n > 0);
+
+        Self {
+            concurrency: number("THALYX_BACKUP_CONCURRENCY")
+                .map_or(defaults.concurrency, |n| (n as usize).min(MAX_CONCURRENCY)),
+            device_timeout: number("THALYX_BACKUP_DEVICE_TIMEOUT")
+                .map_or(defaults.device_timeout, |n| Duration::from_secs(n.min(MAX_DEVICE_TIMEOUT_SECS))),
+            history: number("THALYX_BACKUP_HISTORY").map_or(defaults.history, |n| n as usize),
+        }
+    }
+}
+
+/// Filters for listing configuration versions
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VersionQuery {
+    pub device: Option<String>,
+    pub format: Option<ConfigFormat>,
+    pub job_id: Option<Uuid>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Runs backup jobs and serves the stored configuration history
+#[derive(Debug)]
+pub struct ConfigBackups {
+    engine: Arc<ReportEngine>,
+    database: Arc<Database>,
+    inventory: Arc<DeviceInventory>,
+    groups: Arc<DeviceGroups>,
+    websocket: Arc<WebSocketService>,
+    config: BackupConfig,
+    /// Jobs in start order, so the oldest finished ones are evicted first
+    jobs: RwLock<IndexMap<Uuid, BackupJob>>,
+}
+
+impl ConfigBackups {
+    pub fn new(
+        engine: Arc<ReportEngine>,
+        database: Arc<Database>,
+        inventory: Arc<DeviceInventory>,
+        groups: Arc<DeviceGroups>,
+        websocket: Arc<WebSocketService>,
+        config: BackupConfig,
+    ) -> Self {
+        Self {
+            engine,
+            database,
+            inventory,
+            groups,
+            websocket,
+            config,
+            jobs: RwLock::new(IndexMap::new()),
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // JOBS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    /// Validate a request and start the job in the background
+    /// Returns the initial snapshot; progress follows on `data:backups`
+    pub async fn start(self: &Arc<Self>, request: BackupRequest) -> ApiResult<BackupJob> {
+        let concurrency = match request.concurrency {
+            Some(n) if n == 0 || n > MAX_CONCURRENCY => {
+                return Err(ApiError::ValidationError(format!(
+                    "concurrency must be between 1 and {}",
+                    MAX_CONCURRENCY
+                )));
+            }
+            Some(n) => n,
+            None => self.config.concurrency,
+        };
+        let device_timeout = match request.timeout_secs {
+            Some(s) if s == 0 || s > MAX_DEVICE_TIMEOUT_SECS => {
+                return Err(ApiError::ValidationError(format!(
+                    "timeout_secs must be between 1 and {}",
+                    MAX_DEVICE_TIMEOUT_SECS
+                )));
+            }
+            Some(s) => Duration::from_secs(s),
+            None => self.config.device_timeout,
+        };
+        let mut formats = request.formats;
+        if formats.is_empty() {
+            formats = ConfigFormat::ALL.to_vec();
+        }
+        let mut seen = HashSet::new();
+        formats.retain(|f| seen.insert(*f));
+
+        let targets = self.groups.expand_targets(request.devices).await?;
+        let devices = self.resolve_targets(targets).await?;
+
+        let job_id = Uuid::new_v4();
+        let job = BackupJob {
+            job_id,
+            status: BackupJobStatus::Running,
+            formats,
+            concurrency: concurrency.min(devices.len()),
+            device_timeout_secs: device_timeout.as_secs(),
+            started_at: chrono::Utc::now(),
+            finished_at: None,
+            summary: BackupJobSummary::of(&devices),
+            devices,
+        };
+
+        {
+            let mut jobs = self.jobs.write().await;
+            jobs.insert(job_id, job.clone());
+            self.evict(&mut jobs);
+        }
+
+        info!(
+            job_id = %job_id,
+            devices = job.devices.len(),
+            formats = ?job.formats.iter().map(|f| f.as_str()).collect::<Vec<_>>(),
+            concurrency = job.concurrency,
+            device_timeout_secs = job.device_timeout_secs,
+            "Starting configuration backup"
+        );
+
+        let backups = self.clone();
+        let snapshot = job.clone();
+        tokio::spawn(
+            async move { backups.execute(job, device_timeout).await }
+                .instrument(tracing::info_span!("config_backup", job_id = %job_id)),
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Current state of a job
+    pub async fn job(&self, job_id: Uuid) -> ApiResult<BackupJob> {
+        self.jobs
+            .read()
+            .await
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound(format!("Backup job '{}' not found", job_id)))
+    }
+
+    /// Jobs still in memory, newest first
+    pub async fn jobs(&self) -> Vec<BackupJob> {
+        self.jobs.read().await.values().rev().cloned().collect()
+    }
+
+    async fn execute(self: Arc<Self>, job: BackupJob, device_timeout: Duration) {
+        let job_id = job.job_id;
+        let timer = Instant::now();
+        self.publish(job_id, json!({ "event": "backup_started", "job": &job })).await;
+
+        let devices: Vec<(usize, String)> = job.devices.iter().map(|d| d.address.clone()).enumerate().collect();
+        futures_util::stream::iter(devices)
+            .for_each_concurrent(job.concurrency, |(index, address)| {
+                let (backups, job) = (&self, &job);
+                async move {
+                    backups.backup_device(job, index, address, device_timeout).await;
+                }
+            })
+            .await;
+
+        let (summary, finished_at) = {
+            let mut jobs = self.jobs.write().await;
+            let Some(job) = jobs.get_mut(&job_id) else { return };
+            job.status = BackupJobStatus::Completed;
+            job.finished_at = Some(chrono::Utc::now());
+            (job.summary.clone(), job.finished_at)
+        };
+
+        info!(
+            succeeded = summary.succeeded,
+            failed = summary.failed,
+            timed_out = summary.timed_out,
+            changed = summary.changed,
+            unchanged = summary.unchanged,
+            duration_ms = timer.elapsed().as_millis() as u64,
+            "Configuration backup completed"
+        );
+        self.publish(
+            job_id,
+            json!({ "event": "backup_completed", "summary": summary, "finished_at": finished_at }),
+        )
+        .await;
+    }
+
+    /// Back up every format of one device of `job`, the snapshot taken at start
+    async fn backup_device(&self, job: &BackupJob, index: usize, address: String, device_timeout: Duration) {
+        let job_id = job.job_id;
+        let device = job.devices[index].device.clone();
+        self.update(job_id, index, |entry| {
+            entry.status = DeviceBackupStatus::Running;
+            entry.started_at = Some(chrono::Utc::now());
+        })
+        .await;
+
+        let timer = Instant::now();
+        let mut backups = Vec::with_capacity(job.formats.len());
+        let mut errors = Vec::new();
+        let fetch_all = async {
+            for format in &job.formats {
+                match self.backup_format(job_id, &device, &address, *format).await {
+                    Ok(backup) => backups.push(backup),
+                    Err(e) => {
+                        warn!(device = %device, format = format.as_str(), error = %e, "Configuration backup failed");
+                        errors.push(format!("{}: {}", format.as_str(), e));
+                    }
+                }
+            }
+        };
+        let timed_out = tokio::time::timeout(device_timeout, fetch_all).await.is_err();
+        let duration_ms = timer.elapsed().as_millis() as u64;
+
+        let finished = self
+            .update(job_id, index, |entry| {
+                entry.duration_ms = Some(duration_ms);
+                entry.backups = backups;
+                if timed_out {
+                    warn!(device = %device, timeout_secs = device_timeout.as_secs(), "Device backup timed out");
+                    entry.status = DeviceBackupStatus::TimedOut;
+                    errors.push(format!("Not finished within {}s", device_timeout.as_secs()));
+                } else if errors.is_empty() {
+                    entry.status = DeviceBackupStatus::Succeeded;
+                } else {
+                    entry.status = DeviceBackupStatus::Failed;
+                }
+                entry.error = (!errors.is_empty()).then(|| errors.join("; "));
+            })
+            .await;
+
+        if let Some((entry, summary)) = finished {
+            self.publish(
+                job_id,
+                json!({ "event": "device_backed_up", "index": index, "device": entry, "summary": summary }),
+            )
+            .await;
+        }
+    }
+
+    /// Fetch one format and record it in the history
+    async fn backup_format(&self, job_id: Uuid, device: &str, address: &str, format: ConfigFormat) -> ApiResult<FormatBackup> {
+        let mut call = RpcCall {
+            name: GET_CONFIGURATION.to_string(),
+            args: IndexMap::new(),
+        };
+        call.args.insert("@database".to_string(), json!("committed"));
+        call.args.insert("@format".to_string(), json!(format.as_str()));
+
+        let reply = self.engine.execute(address, &call).await?;
+        let content = extract_configuration(&reply, format)?;
+        let backup = self.record(job_id, device, format, content).await?;
+        debug!(
+            device = %device,
+            format = format.as_str(),
+            hash = %backup.hash,
+            size = backup.size,
+            changed = backup.changed,
+            "Configuration retrieved"
+        );
+        Ok(backup)
+    }
+
+    /// Apply a change to one device entry; returns the entry and refreshed summary
+    async fn update(
+        &self,
+        job_id: Uuid,
+        index: usize,
+        change: impl FnOnce(&mut DeviceBackup),
+    ) -> Option<(DeviceBackup, BackupJobSummary)> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.get_mut(&job_id)?;
+        let entry = job.devices.get_mut(index)?;
+        change(entry);
+        let entry = entry.clone();
+        job.summary = BackupJobSummary::of(&job.devices);
+        Some((entry, job.summary.clone()))
+    }
+
+    async fn publish(&self, job_id: Uuid, mut data: serde_json::Value) {
+        data["job_id"] = json!(job_id);
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data,
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(job_id = %job_id, error = %e, "Failed to publish backup progress");
+        }
+    }
+
+    /// Drop the oldest finished jobs beyond the history size
+    fn evict(&self, jobs: &mut IndexMap<Uuid, BackupJob>) {
+        let mut finished = jobs.values().filter(|j| j.status == BackupJobStatus::Completed).count();
+        while finished > self.config.history {
+            let Some(oldest) = jobs
+                .iter()
+                .find(|(_, j)| j.status == BackupJobStatus::Completed)
+                .map(|(id, _)| *id)
+            else {
+                break;
+            };
+            jobs.shift_remove(&oldest);
+            finished -= 1;
+        }
+    }
+
+    /// Pair each target with the name it is recorded under and the address it is fetched from
+    /// Trims, drops empties and duplicates while keeping request order
+    async fn resolve_targets(&self, targets: Vec<String>) -> ApiResult<Vec<DeviceBackup>> {
+        let mut known = HashMap::new();
+        for device in self.inventory.all().await? {
+            for key in [device.id.to_string(), device.fields.name.clone(), device.fields.address.clone()] {
+                known
+                    .entry(key)
+                    .or_insert_with(|| (device.fields.name.clone(), device.fields.address.clone()));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let devices: Vec<DeviceBackup> = targets
+            .into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .map(|t| known.get(&t).cloned().unwrap_or_else(|| (t.clone(), t)))
+            .filter(|(name, _)| seen.insert(name.clone()))
+            .map(|(name, address)| DeviceBackup::pending(name, address))
+            .collect();
+
+        if devices.is_empty() {
+            return Err(ApiError::ValidationError("At least one device is required".to_string()));
+        }
+        if devices.len() > MAX_DEVICES_PER_RUN {
+            return Err(ApiError::ValidationError(format!(
+                "A backup accepts at most {} devices, got {}",
+                MAX_DEVICES_PER_RUN,
+                devices.len()
+            )));
+        }
+        Ok(devices)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // VERSIONS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    /// Store a fetched configuration unless it matches the device's latest version
+    async fn record(&self, job_id: Uuid, device: &str, format: ConfigFormat, content: String) -> ApiResult<FormatBackup> {
+        let hash = format!("{:x}", Sha256::digest(content.as_bytes()));
+        let device = device.to_string();
+        self.database
+            .blocking(move |connection| {
+                let transaction = connection.transaction().map_err(database_error)?;
+                let now = timestamp(&chrono::Utc::now());
+                let latest: Option<(String, String)> = transaction
+                    .query_row(
+                        "SELECT id, hash FROM config_versions WHERE device = ?1 AND format = ?2 \
+                         ORDER BY taken_at DESC, rowid DESC LIMIT 1",
+                        params![device, format.as_str()],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()
+                    .map_err(database_error)?;
+
+                let (version_id, changed) = match latest {
+                    Some((id, latest_hash)) if latest_hash == hash => {
+                        transaction
+                            .execute("UPDATE config_versions SET confirmed_at = ?1 WHERE id = ?2", params![now, id])
+                            .map_err(database_error)?;
+                        (id, false)
+                    }
+                    _ => {
+                        transaction
+                            .execute(
+                                "INSERT OR IGNORE INTO config_blobs (hash, content, size, created_at) \
+                                 VALUES (?1, ?2, ?3, ?4)",
+                                params![hash, content, content.len() as i64, now],
+                            )
+                            .map_err(database_error)?;
+                        let id = Uuid::new_v4().to_string();
+                        transaction
+                            .execute(
+                                "INSERT INTO config_versions (id, device, format, hash, job_id, taken_at, confirmed_at) \
+                                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                                params![id, device, format.as_str(), hash, job_id.to_string(), now],
+                            )
+                            .map_err(database_error)?;
+                        (id, true)
+                    }
+                };
+                transaction.commit().map_err(database_error)?;
+
+                Ok(FormatBackup {
+                    format,
+                    version_id: Uuid::parse_str(&version_id).map_err(|e| ApiError::InternalError(e.to_string()))?,
+                    hash,
+                    size: content.len(),
+                    changed,
+                })
+            })
+            .await
+    }
+
+    /// Newest-first page of versions matching the filters
+    pub async fn versions(&self, query: VersionQuery) -> ApiResult<ConfigVersionPage> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(ApiError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+        let offset = query.offset.unwrap_or(0);
+
+        self.database
+            .blocking(move |connection| {
+                let mut conditions = Vec::new();
+                let mut values: Vec<String> = Vec::new();
+                let mut filter = |condition: &str, value: Option<String>| {
+                    if let Some(value) = value {
+                        values.push(value);
+                        conditions.push(format!("{} ?{}", condition, values.len()));
+                    }
+                };
+                filter("v.device =", query.device);
+                filter("v.format =", query.format.map(|f| f.as_str().to_string()));
+                filter("v.job_id =", query.job_id.map(|id| id.to_string()));
+
+                let clause = if conditions.is_empty() {
+                    String::new()
+                } else {
+                    format!("WHERE {}", conditions.join(" AND "))
+                };
+
+                let total: i64 = connection
+                    .query_row(
+                        &format!("SELECT COUNT(*) FROM config_versions v {}", clause),
+                        params_from_iter(values.iter()),
+                        |row| row.get(0),
+                    )
+                    .map_err(database_error)?;
+
+                let sql = format!(
+                    "SELECT {} FROM config_versions v JOIN config_blobs b USING (hash) {} \
+                     ORDER BY v.taken_at DESC, v.rowid DESC LIMIT {} OFFSET {}",
+                    VERSION_COLUMNS, clause, limit, offset
+                );
+                let mut statement = connection.prepare(&sql).map_err(database_error)?;
+                let versions = statement
+                    .query_map(params_from_iter(values.iter()), read_version)
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+
+                Ok(ConfigVersionPage {
+                    total: total as usize,
+                    limit,
+                    offset,
+                    versions,
+                })
+            })
+            .await
+    }
+
+    /// One version's metadata
+    pub async fn version(&self, version_id: Uuid) -> ApiResult<ConfigVersion> {
+        Ok(self.content(version_id).await?.0)
+    }
+
+    /// One version with its configuration
+    pub async fn content(&self, version_id: Uuid) -> ApiResult<(ConfigVersion, String)> {
+        self.database
+            .blocking(move |connection| {
+                connection
+                    .query_row(
+                        &format!(
+                            "SELECT {}, b.content FROM config_versions v JOIN config_blobs b USING (hash) WHERE v.id = ?1",
+                            VERSION_COLUMNS
+                        ),
+                        params![version_id.to_string()],
+                        |row| Ok((read_version(row)?, row.get(8)?)),
+                    )
+                    .optional()
+                    .map_err(database_error)?
+                    .ok_or_else(|| ApiError::NotFound(format!("Configuration version '{}' not found", version_id)))
+            })
+            .await
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+fn read_version(row: &Row) -> rusqlite::Result<ConfigVersion> {
+    let time = |v: String| {
+        chrono::DateTime::parse_from_rfc3339(&v)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+    Ok(ConfigVersion {
+        version_id: parse_column(row, 0, |v: String| Uuid::parse_str(&v).ok())?,
+        device: row.get(1)?,
+        format: parse_column(row, 2, |v: String| ConfigFormat::parse(&v))?,
+        hash: row.get(3)?,
+        size: row.get::<_, i64>(4)? as usize,
+        job_id: parse_column(row, 5, |v: String| Uuid::parse_str(&v).ok())?,
+        taken_at: parse_column(row, 6, time)?,
+        confirmed_at: parse_column(row, 7, time)?,
+    })
+}
+
+/// The configuration carried by a `get-configuration` reply
+/// Text and set configurations are trimmed to end with one newline; an XML
+/// configuration is the `<configuration>` element with the namespaces it inherits
+/// declared on it, so it stands alone
+pub fn extract_configuration(reply: &str, format: ConfigFormat) -> ApiResult<String> {
+    let document = roxmltree::Document::parse(reply)
+        .map_err(|e| ApiError::DeviceError(format!("Malformed configuration reply: {}", e)))?;
+    if let Some(error) = document.descendants().find(|n| n.has_tag_name("rpc-error")) {
+        let message = error
+            .children()
+            .find(|n| n.has_tag_name("error-message"))
+            .and_then(|n| n.text())
+            .unwrap_or("unknown error");
+        return Err(ApiError::DeviceError(format!("get-configuration failed: {}", message.trim())));
+    }
+
+    let element = match format {
+        ConfigFormat::Text => "configuration-text",
+        ConfigFormat::Set => "configuration-set",
+        ConfigFormat::Xml => "configuration",
+    };
+    let node = document
+        .descendants()
+        .find(|n| n.has_tag_name(element))
+        .ok_or_else(|| ApiError::DeviceError(format!("Reply has no <{}> element", element)))?;
+
+    if format != ConfigFormat::Xml {
+        let text: String = node.descendants().filter(|n| n.is_text()).filter_map(|n| n.text()).collect();
+        let text = text.trim();
+        return Ok(if text.is_empty() { String::new() } else { format!("{}\n", text) });
+    }
+
+    let source = &reply[node.range()];
+    let start_tag = source.split_once('>').map_or(source, |(tag, _)| tag);
+    let mut declarations = String::new();
+    for namespace in node.namespaces().filter(|n| n.name() != Some("xml")) {
+        let attribute = match namespace.name() {
+            Some(prefix) => format!("xmlns:{}=", prefix),
+            None => "xmlns=".to_string(),
+        };
+        if !start_tag.contains(&attribute) {
+            declarations.push_str(&format!(r#" {}"{}""#, attribute, namespace.uri()));
+        }
+    }
+    let name_end = source
+        .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .unwrap_or(source.len());
+    Ok(format!("{}{}{}\n", &source[..name_end], declarations, &source[name_end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{database::DatabaseConfig, report_engine::StaticTransport};
+
+    const CONFIG: &str = "system {\n    host-name r1;\n}\n";
+
+    async fn backups() -> (ConfigBackups, Arc<Database>, std::path::PathBuf) {
+        let data_dir = std::env::temp_dir().join(format!("thalyx-backups-{}", Uuid::new_v4()));
+        let database = Arc::new(Database::open(DatabaseConfig { data_dir: data_dir.clone() }).unwrap());
+        let websocket = Arc::new(WebSocketService::new(None));
+        let inventory = Arc::new(DeviceInventory::new(database.clone(), websocket.clone()));
+        let groups = Arc::new(DeviceGroups::new(database.clone(), inventory.clone(), websocket.clone()));
+        let backups = ConfigBackups::new(
+            Arc::new(ReportEngine::new(Arc::new(StaticTransport::new()))),
+            database.clone(),
+            inventory,
+            groups,
+            websocket,
+            BackupConfig::default(),
+        );
+        (backups, database, data_dir)
+    }
+
+    async fn count(database: &Database, table: &'static str) -> i64 {
+        database
+            .blocking(move |connection| {
+                connection
+                    .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+                    .map_err(database_error)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn identical_backup_only_confirms_the_latest_version() {
+        let (backups, database, data_dir) = backups().await;
+        let first = backups.record(Uuid::new_v4(), "r1.lab", ConfigFormat::Text, CONFIG.to_string()).await.unwrap();
+        assert!(first.changed);
+        assert_eq!(first.size, CONFIG.len());
+
+        // Backdate the version so the refresh is visible
+        let version_id = first.version_id.to_string();
+        database
+            .blocking(move |connection| {
+                connection
+                    .execute(
+                        "UPDATE config_versions SET confirmed_at = '2026-01-01T00:00:00.000000Z' WHERE id = ?1",
+                        params![version_id],
+                    )
+                    .map_err(database_error)
+            })
+            .await
+            .unwrap();
+
+        let second_job = Uuid::new_v4();
+        let second = backups.record(second_job, "r1.lab", ConfigFormat::Text, CONFIG.to_string()).await.unwrap();
+        assert!(!second.changed);
+        assert_eq!(second.version_id, first.version_id);
+        assert_eq!(second.hash, first.hash);
+
+        let (version, content) = backups.content(first.version_id).await.unwrap();
+        assert_eq!(content, CONFIG);
+        assert_ne!(version.job_id, second_job, "the version keeps the job that first saw it");
+        assert!(version.confirmed_at >= version.taken_at, "confirmed_at was not refreshed");
+        assert_eq!(count(&database, "config_versions").await, 1);
+        assert_eq!(count(&database, "config_blobs").await, 1);
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn changed_configuration_makes_a_new_version() {
+        let (backups, database, data_dir) = backups().await;
+        let job_id = Uuid::new_v4();
+        let original = backups.record(job_id, "r1.lab", ConfigFormat::Text, CONFIG.to_string()).await.unwrap();
+        let edited_config = CONFIG.replace("r1", "r1-renamed");
+        let edited = backups.record(job_id, "r1.lab", ConfigFormat::Text, edited_config.clone()).await.unwrap();
+
+        assert!(edited.changed);
+        assert_ne!(edited.version_id, original.version_id);
+        assert_ne!(edited.hash, original.hash);
+        assert_eq!(backups.content(edited.version_id).await.unwrap().1, edited_config);
+
+        // Rolling back is a change too, but the earlier content is stored only once
+        let rollback = backups.record(job_id, "r1.lab", ConfigFormat::Text, CONFIG.to_string()).await.unwrap();
+        assert!(rollback.changed);
+        assert_eq!(rollback.hash, original.hash);
+        assert_eq!(count(&database, "config_versions").await, 3);
+        assert_eq!(count(&database, "config_blobs").await, 2);
+
+        let page = backups
+            .versions(VersionQuery { device: Some("r1.lab".to_string()), ..VersionQuery::default() })
+            .await
+            .unwrap();
+        let newest_first: Vec<Uuid> = page.versions.iter().map(|v| v.version_id).collect();
+        assert_eq!(newest_first, [rollback.version_id, edited.version_id, original.version_id]);
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[tokio::test]
+    async fn devices_with_the_same_configuration_share_one_blob() {
+        let (backups, database, data_dir) = backups().await;
+        let job_id = Uuid::new_v4();
+        let r1 = backups.record(job_id, "r1.lab", ConfigFormat::Set, CONFIG.to_string()).await.unwrap();
+        let r2 = backups.record(job_id, "r2.lab", ConfigFormat::Set, CONFIG.to_string()).await.unwrap();
+        // Same device, other format: its own history
+        let r1_text = backups.record(job_id, "r1.lab", ConfigFormat::Text, CONFIG.to_string()).await.unwrap();
+
+        assert!(r1.changed && r2.changed && r1_text.changed);
+        assert_eq!(r1.hash, r2.hash);
+        assert_ne!(r1.version_id, r2.version_id);
+        assert_eq!(count(&database, "config_versions").await, 3);
+        assert_eq!(count(&database, "config_blobs").await, 1);
+        assert_eq!(backups.content(r2.version_id).await.unwrap().1, CONFIG);
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,900 @@
+// backend/src/services/credential_vault.rs
+
+//! # Credential Vault
+//!
+//! ## Description
+//! Usernames, passwords and SSH keys for logging in to devices, kept out of the YAML
+//! under `shared/data`. Profiles live in the `credential_profiles` table of the
+//! embedded database with their secrets encrypted (AES-256-GCM, a fresh nonce per
+//! write, the profile ID as associated data so ciphertexts cannot be swapped between
+//! rows). Profiles are assigned to devices or device groups, and the NETCONF transport
+//! asks the vault (`CredentialSource`) for a device's credentials whenever it opens a
+//! session. Secrets never leave the vault in clear except towards SSH: responses and
+//! change events list which secrets a profile has, and `Secret` prints as `<redacted>`.
+//!
+//! ## Resolution
+//! A target is matched to an inventory device by name or address. Its own assignment
+//! wins; otherwise the first group containing it (by group name) with an assignment;
+//! otherwise the `NETCONF_*` defaults apply.
+//!
+//! ## Master Keys
+//! Keys are 32 random bytes, base64 encoded (`vault-keygen` prints one). Each key has
+//! an ID (16 hex digits derived from the key, safe to show) recorded with every
+//! profile. The first key encrypts; further keys are only read, for rotation:
+//! 1. Generate a new key and configure it first, the old one after it
+//! 2. `POST /api/credentials/rotate` (or `vault-rotate`) re-encrypts every profile
+//! 3. Remove the old key
+//!
+//! Without a key the vault is locked: profiles can be listed, but not written or used.
+//!
+//! ## Configuration
+//! - `THALYX_VAULT_KEY` - current master key
+//! - `THALYX_VAULT_PREVIOUS_KEYS` - comma-separated keys still accepted for reading
+//! - `THALYX_VAULT_KEY_FILE` - instead of the two above: one key per line, current first
+//!   (`#` comments); should be readable by the backend user only
+//!
+//! ## How to Use
+//! 1. `let vault = Arc::new(CredentialVault::new(database, inventory, groups, KeyRing::from_env()?, websocket));`
+//! 2. `NetconfTransport::new(config).with_credentials(vault.clone())`
+//! 3. `vault.create(input).await?`, then `vault.assign(AssignmentTarget::Group, "lon-pe", Some("lab-ro")).await?`
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use indexmap::IndexMap;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, path::Path, sync::Arc};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    models::{
+        credentials::{
+            CredentialProfile, CredentialProfileInput, EffectiveCredentials, RotationReport, Secret, SecretKind,
+            VaultStatus,
+        },
+        devices::{Device, DeviceChange},
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        device_inventory,
+        netconf::{
+            ssh::SshCredentials,
+            transport::{CredentialSource, DeviceAddress},
+        },
+        database::{database_error, parse_column, timestamp, to_json, Database},
+        DeviceGroups, DeviceInventory, WebSocketService,
+    },
+};
+
+/// `DataUpdate.source` of profile change events
+const TOPIC_SOURCE: &str = "credentials";
+
+/// Bytes of a master key (AES-256)
+const KEY_LENGTH: usize = 32;
+
+/// Bytes of an AES-GCM nonce, stored in front of the ciphertext
+const NONCE_LENGTH: usize = 12;
+
+/// Associated data of the key check value a key ID is taken from
+const KEY_ID_LABEL: &[u8] = b"thalyx-vault-key-id";
+
+const PROFILE_COLUMNS: &str =
+    "id, name, description, username, secrets, key_id, ciphertext, created_at, updated_at";
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// MASTER KEYS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// One master key, kept only as its cipher
+struct MasterKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+impl MasterKey {
+    /// Decode a base64 key; the decoded bytes are wiped once the cipher is built
+    fn decode(encoded: &str) -> ApiResult<Self> {
+        let bytes = Zeroizing::new(
+            BASE64
+                .decode(encoded.trim())
+                .map_err(|_| ApiError::ValidationError("Vault master key is not valid base64".to_string()))?,
+        );
+        if bytes.len() != KEY_LENGTH {
+            return Err(ApiError::ValidationError(format!(
+                "Vault master key must be {} bytes, got {}",
+                KEY_LENGTH,
+                bytes.len()
+            )));
+        }
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes));
+
+        // Key check value: the tag of an empty message under a fixed nonce identifies the
+        // key without revealing it
+        let check = cipher
+            .encrypt(Nonce::from_slice(&[0; NONCE_LENGTH]), Payload { msg: &[], aad: KEY_ID_LABEL })
+            .map_err(|_| ApiError::InternalError("Failed to derive the vault key ID".to_string()))?;
+        let id = check[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        Ok(Self { id, cipher })
+    }
+}
+
+/// Master keys: the first encrypts, every key decrypts what it encrypted
+#[derive(Debug, Default)]
+pub struct KeyRing {
+    keys: Vec<MasterKey>,
+}
+
+impl KeyRing {
+    /// Keys from `THALYX_VAULT_KEY_FILE`, or `THALYX_VAULT_KEY` and `THALYX_VAULT_PREVIOUS_KEYS`
+    /// An empty ring (locked vault) when none is set
+    pub fn from_env() -> ApiResult<Self> {
+        let var = |name: &str| std::env::var(name).ok().map(Zeroizing::new).filter(|v| !v.trim().is_empty());
+        let key = var("THALYX_VAULT_KEY");
+        let previous = var("THALYX_VAULT_PREVIOUS_KEYS");
+
+        if let Some(path) = var("THALYX_VAULT_KEY_FILE") {
+            if key.is_some() || previous.is_some() {
+                return Err(ApiError::ValidationError(
+                    "Set THALYX_VAULT_KEY_FILE or THALYX_VAULT_KEY, not both".to_string(),
+                ));
+            }
+            return Self::from_file(Path::new(path.trim()));
+        }
+
+        let mut encoded: Vec<&str> = Vec::new();
+        if let Some(key) = &key {
+            encoded.push(key);
+        }
+        if let Some(previous) = &previous {
+            if key.is_none() {
+                return Err(ApiError::ValidationError(
+                    "THALYX_VAULT_PREVIOUS_KEYS requires THALYX_VAULT_KEY".to_string(),
+                ));
+            }
+            encoded.extend(previous.split(',').map(str::trim).filter(|k| !k.is_empty()));
+        }
+        Self::from_encoded(&encoded)
+    }
+
+    /// One base64 key per line, current first; blank lines and `#` comments are skipped
+    pub fn from_file(path: &Path) -> ApiResult<Self> {
+        let text = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+            ApiError::FileNotFound(format!("Cannot read vault key file '{}': {}", path.display(), e))
+        })?);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(metadata) = std::fs::metadata(path) {
+                if metadata.permissions().mode() & 0o077 != 0 {
+                    warn!(file = %path.display(), "Vault key file is readable by other users; chmod 600 it");
+                }
+            }
+        }
+        let encoded: Vec<&str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        if encoded.is_empty() {
+            return Err(ApiError::ValidationError(format!(
+                "Vault key file '{}' holds no key",
+                path.display()
+            )));
+        }
+        Self::from_encoded(&encoded)
+    }
+
+    pub fn from_encoded(encoded: &[&str]) -> ApiResult<Self> {
+        let mut keys: Vec<MasterKey> = Vec::with_capacity(encoded.len());
+        for key in encoded {
+            let key = MasterKey::decode(key)?;
+            if !keys.iter().any(|k| k.id == key.id) {
+                keys.push(key);
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// ID of the key new secrets are encrypted with
+    pub fn current_id(&self) -> Option<&str> {
+        self.keys.first().map(|k| k.id.as_str())
+    }
+
+    fn current(&self) -> ApiResult<&MasterKey> {
+        self.keys.first().ok_or_else(locked)
+    }
+
+    /// Encrypt with the current key; returns the key ID and base64 of nonce + ciphertext
+    fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> ApiResult<(String, String)> {
+        let key = self.current()?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = key
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad })
+            .map_err(|_| ApiError::InternalError("Failed to encrypt credentials".to_string()))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok((key.id.clone(), BASE64.encode(sealed)))
+    }
+
+    fn decrypt(&self, key_id: &str, sealed: &str, aad: &[u8]) -> ApiResult<Zeroizing<Vec<u8>>> {
+        if !self.is_unlocked() {
+            return Err(locked());
+        }
+        let key = self.keys.iter().find(|k| k.id == key_id).ok_or_else(|| {
+            ApiError::ServiceUnavailable(format!(
+                "Credentials are encrypted with vault key {}, which is not configured",
+                key_id
+            ))
+        })?;
+        let sealed = BASE64
+            .decode(sealed)
+            .map_err(|_| ApiError::InternalError("Stored credentials are not valid base64".to_string()))?;
+        if sealed.len() < NONCE_LENGTH {
+            return Err(ApiError::InternalError("Stored credentials are truncated".to_string()));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        key.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map(Zeroizing::new)
+            .map_err(|_| ApiError::InternalError("Stored credentials failed authentication".to_string()))
+    }
+}
+
+/// A new random master key, base64 encoded
+pub fn generate_key() -> String {
+    let key = Zeroizing::new(Aes256Gcm::generate_key(&mut OsRng).to_vec());
+    BASE64.encode(&*key)
+}
+
+fn locked() -> ApiError {
+    ApiError::ServiceUnavailable(
+        "Credential vault is locked: set THALYX_VAULT_KEY or THALYX_VAULT_KEY_FILE".to_string(),
+    )
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// VAULT
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// What a profile is assigned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssignmentTarget {
+    Device,
+    Group,
+}
+
+impl AssignmentTarget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AssignmentTarget::Device => "device",
+            AssignmentTarget::Group => "group",
+        }
+    }
+}
+
+/// Secrets of a profile, as encrypted; wiped when dropped
+#[derive(Default, Serialize, Deserialize)]
+struct SecretBundle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    passphrase: Option<String>,
+}
+
+impl Drop for SecretBundle {
+    fn drop(&mut self) {
+        self.password.zeroize();
+        self.private_key.zeroize();
+        self.passphrase.zeroize();
+    }
+}
+
+impl SecretBundle {
+    fn kinds(&self) -> Vec<SecretKind> {
+        [
+            (SecretKind::Password, &self.password),
+            (SecretKind::PrivateKey, &self.private_key),
+            (SecretKind::Passphrase, &self.passphrase),
+        ]
+        .into_iter()
+        .filter(|(_, value)| value.is_some())
+        .map(|(kind, _)| kind)
+        .collect()
+    }
+}
+
+/// A profile row with its sealed secrets
+struct StoredProfile {
+    profile: CredentialProfile,
+    ciphertext: String,
+}
+
+/// Credential profiles, their assignments and the keys protecting them
+#[derive(Debug)]
+pub struct CredentialVault {
+    database: Arc<Database>,
+    inventory: Arc<DeviceInventory>,
+    groups: Arc<DeviceGroups>,
+    keys: KeyRing,
+    websocket: Arc<WebSocketService>,
+}
+
+impl CredentialVault {
+    pub fn new(
+        database: Arc<Database>,
+        inventory: Arc<DeviceInventory>,
+        groups: Arc<DeviceGroups>,
+        keys: KeyRing,
+        websocket: Arc<WebSocketService>,
+    ) -> Self {
+        match keys.current_id() {
+            Some(key_id) => info!(key_id, keys = keys.keys.len(), "Credential vault unlocked"),
+            None => warn!("Credential vault is locked (no THALYX_VAULT_KEY); devices use NETCONF_* credentials"),
+        }
+        Self {
+            database,
+            inventory,
+            groups,
+            keys,
+            websocket,
+        }
+    }
+
+    /// Configured keys and the profiles encrypted with each
+    pub async fn status(&self) -> ApiResult<VaultStatus> {
+        let profiles_by_key = self
+            .database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare("SELECT key_id, COUNT(*) FROM credential_profiles GROUP BY key_id ORDER BY key_id")
+                    .map_err(database_error)?;
+                let counts = statement
+                    .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))
+                    .map_err(database_error)?
+                    .collect::<Result<IndexMap<_, _>, _>>()
+                    .map_err(database_error)?;
+                Ok(counts)
+            })
+            .await?;
+
+        Ok(VaultStatus {
+            unlocked: self.keys.is_unlocked(),
+            key_id: self.keys.current_id().map(str::to_string),
+            previous_key_ids: self.keys.keys.iter().skip(1).map(|k| k.id.clone()).collect(),
+            profiles_by_key,
+        })
+    }
+
+    /// Every profile, ordered by name
+    pub async fn list(&self) -> ApiResult<Vec<CredentialProfile>> {
+        self.database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare(&format!("SELECT {} FROM credential_profiles ORDER BY name", PROFILE_COLUMNS))
+                    .map_err(database_error)?;
+                let profiles = statement
+                    .query_map([], read_profile)
+                    .map_err(database_error)?
+                    .map(|row| row.map(|stored| stored.profile))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+                Ok(profiles)
+            })
+            .await
+    }
+
+    /// One profile by ID or name
+    pub async fn get(&self, profile: &str) -> ApiResult<CredentialProfile> {
+        Ok(self.find(profile).await?.profile)
+    }
+
+    /// Add a profile; fails with `Conflict` if the name is taken
+    pub async fn create(&self, input: CredentialProfileInput) -> ApiResult<CredentialProfile> {
+        let id = Uuid::new_v4();
+        let now = chrono::Utc::now();
+        let (profile, ciphertext) = self.seal(id, input, SecretBundle::default(), now, now)?;
+
+        let stored = profile.clone();
+        self.database
+            .blocking(move |connection| {
+                connection
+                    .execute(
+                        &format!(
+                            "INSERT INTO credential_profiles ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                            PROFILE_COLUMNS
+                        ),
+                        params![
+                            stored.id.to_string(),
+                            stored.name,
+                            stored.description,
+                            stored.username,
+                            to_json(&stored.secrets)?,
+                            stored.key_id,
+                            ciphertext,
+                            timestamp(&stored.created_at),
+                            timestamp(&stored.updated_at),
+                        ],
+                    )
+                    .map_err(|e| write_error(e, &stored.name))?;
+                Ok(())
+            })
+            .await?;
+
+        info!(profile_id = %profile.id, name = %profile.name, secrets = ?profile.secrets, "Credential profile created");
+        self.publish(DeviceChange::Created, &profile).await;
+        Ok(profile)
+    }
+
+    /// Replace a profile; omitted secrets keep their stored values, empty ones are removed
+    pub async fn update(&self, profile: &str, input: CredentialProfileInput) -> ApiResult<CredentialProfile> {
+        let existing = self.find(profile).await?;
+        let current = self.open(&existing)?;
+        let (updated, ciphertext) = self.seal(
+            existing.profile.id,
+            input,
+            current,
+            existing.profile.created_at,
+            chrono::Utc::now(),
+        )?;
+
+        let stored = updated.clone();
+        self.database
+            .blocking(move |connection| {
+                let changed = connection
+                    .execute(
+                        "UPDATE credential_profiles SET name = ?2, description = ?3, username = ?4, secrets = ?5, \
+                         key_id = ?6, ciphertext = ?7, updated_at = ?8 WHERE id = ?1",
+                        params![
+                            stored.id.to_string(),
+                            stored.name,
+                            stored.description,
+                            stored.username,
+                            to_json(&stored.secrets)?,
+                            stored.key_id,
+                            ciphertext,
+                            timestamp(&stored.updated_at),
+                        ],
+                    )
+                    .map_err(|e| write_error(e, &stored.name))?;
+                if changed == 0 {
+                    return Err(ApiError::NotFound(format!("Credential profile '{}' not found", stored.id)));
+                }
+                Ok(())
+            })
+            .await?;
+
+        info!(profile_id = %updated.id, name = %updated.name, secrets = ?updated.secrets, "Credential profile updated");
+        self.publish(DeviceChange::Updated, &updated).await;
+        Ok(updated)
+    }
+
+    /// Remove a profile; fails with `Conflict` while it is assigned
+    pub async fn delete(&self, profile: &str) -> ApiResult<CredentialProfile> {
+        let existing = self.find(profile).await?.profile;
+        let id = existing.id.to_string();
+        let name = existing.name.clone();
+        self.database
+            .blocking(move |connection| {
+                let mut statement = connection
+                    .prepare(
+                        "SELECT target_kind, COUNT(*) FROM credential_assignments WHERE profile_id = ?1 \
+                         GROUP BY target_kind ORDER BY target_kind",
+                    )
+                    .map_err(database_error)?;
+                let uses = statement
+                    .query_map(params![id], |row| {
+                        let count = row.get::<_, i64>(1)?;
+                        Ok(format!("{} {}{}", count, row.get::<_, String>(0)?, if count == 1 { "" } else { "s" }))
+                    })
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+                if !uses.is_empty() {
+                    return Err(ApiError::Conflict(format!(
+                        "Credential profile '{}' is assigned to {}; unassign it first",
+                        name,
+                        uses.join(" and ")
+                    )));
+                }
+                connection
+                    .execute("DELETE FROM credential_profiles WHERE id = ?1", params![id])
+                    .map_err(database_error)?;
+                Ok(())
+            })
+            .await?;
+
+        info!(profile_id = %existing.id, name = %existing.name, "Credential profile deleted");
+        self.publish(DeviceChange::Deleted, &existing).await;
+        Ok(existing)
+    }
+
+    /// Assign a profile to a device or group, or clear its assignment with `None`
+    /// Returns what the target now logs in with
+    pub async fn assign(
+        &self,
+        target: AssignmentTarget,
+        name: &str,
+        profile: Option<&str>,
+    ) -> ApiResult<EffectiveCredentials> {
+        let target_id = match target {
+            AssignmentTarget::Device => self.inventory.get(name).await?.id,
+            AssignmentTarget::Group => self.groups.get(name).await?.id,
+        };
+        let profile = match profile {
+            Some(profile) => Some(self.find(profile).await?.profile),
+            None => None,
+        };
+
+        let profile_id = profile.as_ref().map(|p| p.id.to_string());
+        self.database
+            .blocking(move |connection| {
+                match profile_id {
+                    Some(profile_id) => connection.execute(
+                        "INSERT INTO credential_assignments (target_kind, target_id, profile_id) VALUES (?1, ?2, ?3) \
+                         ON CONFLICT (target_kind, target_id) DO UPDATE SET profile_id = excluded.profile_id",
+                        params![target.as_str(), target_id.to_string(), profile_id],
+                    ),
+                    None => connection.execute(
+                        "DELETE FROM credential_assignments WHERE target_kind = ?1 AND target_id = ?2",
+                        params![target.as_str(), target_id.to_string()],
+                    ),
+                }
+                .map_err(database_error)?;
+                Ok(())
+            })
+            .await?;
+
+        info!(
+            target = target.as_str(),
+            target_id = %target_id,
+            profile = ?profile.as_ref().map(|p| &p.name),
+            "Credential assignment changed"
+        );
+        self.effective(target, name).await
+    }
+
+    /// What a device or group logs in with; devices inherit from their groups
+    pub async fn effective(&self, target: AssignmentTarget, name: &str) -> ApiResult<EffectiveCredentials> {
+        let (profile, source) = match target {
+            AssignmentTarget::Device => {
+                let device = self.inventory.get(name).await?;
+                self.resolve(&device).await?
+            }
+            AssignmentTarget::Group => {
+                let group = self.groups.get(name).await?;
+                let assignments = self.assignments().await?;
+                match assignments.get(&(AssignmentTarget::Group, group.id)) {
+                    Some(profile_id) => {
+                        let source = format!("group:{}", group.name);
+                        (Some(self.find(&profile_id.to_string()).await?), source)
+                    }
+                    None => (None, "default".to_string()),
+                }
+            }
+        };
+        Ok(EffectiveCredentials {
+            profile: profile.map(|stored| stored.profile),
+            source,
+        })
+    }
+
+    /// Re-encrypt every profile with the current key
+    pub async fn rotate(&self) -> ApiResult<RotationReport> {
+        let current = self.keys.current()?.id.clone();
+        let profiles = self
+            .database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare(&format!("SELECT {} FROM credential_profiles ORDER BY name", PROFILE_COLUMNS))
+                    .map_err(database_error)?;
+                let profiles = statement
+                    .query_map([], read_profile)
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+                Ok(profiles)
+            })
+            .await?;
+
+        let mut report = RotationReport {
+            key_id: current.clone(),
+            rotated: 0,
+            unchanged: 0,
+            unreadable: Vec::new(),
+        };
+        let mut resealed = Vec::new();
+        for stored in &profiles {
+            if stored.profile.key_id == current {
+                report.unchanged += 1;
+                continue;
+            }
+            match self.keys.decrypt(&stored.profile.key_id, &stored.ciphertext, stored.profile.id.as_bytes()) {
+                Ok(plaintext) => {
+                    let (_, ciphertext) = self.keys.encrypt(&plaintext, stored.profile.id.as_bytes())?;
+                    resealed.push((stored.profile.id, stored.profile.key_id.clone(), ciphertext));
+                }
+                Err(e) => {
+                    warn!(profile = %stored.profile.name, key_id = %stored.profile.key_id, error = %e, "Cannot rotate credential profile");
+                    report.unreadable.push(stored.profile.name.clone());
+                }
+            }
+        }
+
+        let key_id = current.clone();
+        report.rotated = self
+            .database
+            .blocking(move |connection| {
+                let transaction = connection.transaction().map_err(database_error)?;
+                let mut rotated = 0;
+                for (id, old_key_id, ciphertext) in resealed {
+                    // Skip rows rewritten since they were read; they are on the current key already
+                    rotated += transaction
+                        .execute(
+                            "UPDATE credential_profiles SET key_id = ?3, ciphertext = ?4 WHERE id = ?1 AND key_id = ?2",
+                            params![id.to_string(), old_key_id, key_id, ciphertext],
+                        )
+                        .map_err(database_error)?;
+                }
+                transaction.commit().map_err(database_error)?;
+                Ok(rotated)
+            })
+            .await?;
+
+        info!(
+            key_id = %current,
+            rotated = report.rotated,
+            unchanged = report.unchanged,
+            unreadable = report.unreadable.len(),
+            "Credential vault rotated"
+        );
+        Ok(report)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // HELPERS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    async fn find(&self, profile: &str) -> ApiResult<StoredProfile> {
+        let profile = profile.to_string();
+        self.database
+            .blocking(move |connection| {
+                connection
+                    .query_row(
+                        &format!(
+                            "SELECT {} FROM credential_profiles WHERE id = ?1 OR name = ?1 \
+                             ORDER BY id = ?1 DESC LIMIT 1",
+                            PROFILE_COLUMNS
+                        ),
+                        params![profile],
+                        read_profile,
+                    )
+                    .optional()
+                    .map_err(database_error)?
+                    .ok_or_else(|| ApiError::NotFound(format!("Credential profile '{}' not found", profile)))
+            })
+            .await
+    }
+
+    /// Every assignment: (target kind, target ID) -> profile ID
+    async fn assignments(&self) -> ApiResult<HashMap<(AssignmentTarget, Uuid), Uuid>> {
+        self.database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare("SELECT target_kind, target_id, profile_id FROM credential_assignments")
+                    .map_err(database_error)?;
+                let rows = statement
+                    .query_map([], |row| {
+                        let kind = parse_column(row, 0, |v: String| match v.as_str() {
+                            "device" => Some(AssignmentTarget::Device),
+                            "group" => Some(AssignmentTarget::Group),
+                            _ => None,
+                        })?;
+                        let target = parse_column(row, 1, |v: String| Uuid::parse_str(&v).ok())?;
+                        let profile = parse_column(row, 2, |v: String| Uuid::parse_str(&v).ok())?;
+                        Ok(((kind, target), profile))
+                    })
+                    .map_err(database_error)?
+                    .collect::<Result<HashMap<_, _>, _>>()
+                    .map_err(database_error)?;
+                Ok(rows)
+            })
+            .await
+    }
+
+    /// The profile a device logs in with and where it comes from
+    async fn resolve(&self, device: &Device) -> ApiResult<(Option<StoredProfile>, String)> {
+        let assignments = self.assignments().await?;
+        if assignments.is_empty() {
+            return Ok((None, "default".to_string()));
+        }
+        if let Some(profile_id) = assignments.get(&(AssignmentTarget::Device, device.id)) {
+            return Ok((Some(self.find(&profile_id.to_string()).await?), "device".to_string()));
+        }
+        for group in self.groups.containing(device).await? {
+            if let Some(profile_id) = assignments.get(&(AssignmentTarget::Group, group.id)) {
+                let source = format!("group:{}", group.name);
+                return Ok((Some(self.find(&profile_id.to_string()).await?), source));
+            }
+        }
+        Ok((None, "default".to_string()))
+    }
+
+    /// Check an input, merge it over the stored secrets and encrypt the result
+    fn seal(
+        &self,
+        id: Uuid,
+        input: CredentialProfileInput,
+        mut secrets: SecretBundle,
+        created_at: chrono::DateTime<chrono::Utc>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> ApiResult<(CredentialProfile, String)> {
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid credential profile: {}", reason));
+        if !self.keys.is_unlocked() {
+            return Err(locked());
+        }
+
+        let name = input.name.trim().to_string();
+        if !device_inventory::is_valid_name(&name) {
+            return Err(invalid(format!(
+                "name '{}' must match ^[A-Za-z0-9][A-Za-z0-9._-]*$ and be at most 128 characters",
+                name
+            )));
+        }
+        // Route segments under /api/credentials
+        if matches!(name.as_str(), "vault" | "rotate") {
+            return Err(invalid(format!("name '{}' is reserved", name)));
+        }
+        let username = input.username.trim().to_string();
+        if username.is_empty() || username.contains(char::is_whitespace) {
+            return Err(invalid("username must not be empty or contain whitespace".to_string()));
+        }
+
+        let merge = |slot: &mut Option<String>, value: Option<Secret>| {
+            if let Some(value) = value {
+                *slot = (!value.is_empty()).then(|| value.expose().to_string());
+            }
+        };
+        merge(&mut secrets.password, input.password);
+        merge(&mut secrets.private_key, input.private_key);
+        merge(&mut secrets.passphrase, input.passphrase);
+        if secrets.private_key.as_deref().is_some_and(|key| !key.contains("PRIVATE KEY-----")) {
+            return Err(invalid("private_key must be a PEM or OpenSSH private key".to_string()));
+        }
+        if secrets.passphrase.is_some() && secrets.private_key.is_none() {
+            return Err(invalid("a passphrase needs a private_key".to_string()));
+        }
+
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(&secrets).map_err(|e| ApiError::SerializationError(e.to_string()))?,
+        );
+        let (key_id, ciphertext) = self.keys.encrypt(&plaintext, id.as_bytes())?;
+        let profile = CredentialProfile {
+            id,
+            name,
+            description: input.description.trim().to_string(),
+            username,
+            secrets: secrets.kinds(),
+            key_id,
+            created_at,
+            updated_at,
+        };
+        Ok((profile, ciphertext))
+    }
+
+    /// Decrypt a profile's secrets
+    fn open(&self, stored: &StoredProfile) -> ApiResult<SecretBundle> {
+        let plaintext = self
+            .keys
+            .decrypt(&stored.profile.key_id, &stored.ciphertext, stored.profile.id.as_bytes())?;
+        serde_json::from_slice(&plaintext).map_err(|e| ApiError::DeserializationError(e.to_string()))
+    }
+
+    async fn publish(&self, change: DeviceChange, profile: &CredentialProfile) {
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data: json!({
+                "event": "credentials_changed",
+                "change": change,
+                "profile_id": profile.id,
+                "profile": profile,
+            }),
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(profile_id = %profile.id, error = %e, "Failed to publish credential profile change");
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialSource for CredentialVault {
+    async fn credentials_for(&self, address: &DeviceAddress) -> ApiResult<Option<SshCredentials>> {
+        let devices = self.inventory.all().await?;
+        let Some(device) = devices.iter().find(|d| {
+            d.fields.name == address.host || DeviceAddress::parse(&d.fields.address).is_ok_and(|a| a == *address)
+        }) else {
+            debug!(device = %address, "Device not in inventory, using default credentials");
+            return Ok(None);
+        };
+
+        let (Some(stored), source) = self.resolve(device).await? else {
+            return Ok(None);
+        };
+        let secrets = self.open(&stored).map_err(|e| {
+            ApiError::DeviceError(format!(
+                "Cannot use credential profile '{}' for {}: {}",
+                stored.profile.name, device.fields.name, e
+            ))
+        })?;
+        debug!(
+            device = %device.fields.name,
+            profile = %stored.profile.name,
+            source = %source,
+            "Using vault credentials"
+        );
+        Ok(Some(SshCredentials {
+            username: stored.profile.username.clone(),
+            password: secrets.password.clone(),
+            private_key: None,
+            private_key_pem: secrets.private_key.clone(),
+            passphrase: secrets.passphrase.clone(),
+        }))
+    }
+}
+
+/// Unique name violations become `Conflict`
+fn write_error(e: rusqlite::Error, name: &str) -> ApiError {
+    match &e {
+        rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::ConstraintViolation => {
+            ApiError::Conflict(format!("Credential profile '{}' already exists", name))
+        }
+        _ => database_error(e),
+    }
+}
+
+fn read_profile(row: &Row) -> rusqlite::Result<StoredProfile> {
+    let time = |index: usize| {
+        parse_column(row, index, |v: String| {
+            chrono::DateTime::parse_from_rfc3339(&v)
+                .ok()
+                .map(|t| t.with_timezone(&chrono::Utc))
+        })
+    };
+
+    Ok(StoredProfile {
+        profile: CredentialProfile {
+            id: parse_column(row, 0, |v: String| Uuid::parse_str(&v).ok())?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            username: row.get(3)?,
+            secrets: parse_column(row, 4, |v: String| serde_json::from_str(&v).ok())?,
+            key_id: row.get(5)?,
+            created_at: time(7)?,
+            updated_at: time(8)?,
+        },
+        ciphertext: row.get(6)?,
+    })
+}
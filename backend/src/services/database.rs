@@ -0,0 +1,281 @@
+// backend/src/services/database.rs
+
+//! # Database
+//!
+//! ## Description
+//! The embedded SQLite database holding backend-owned state, in
+//! `<THALYX_DATA_DIR>/results.db` (named after its first tables). Services own their
+//! tables and queries; this module owns the connection and the schema migrations, and
+//! provides the row helpers they share:
+//! - report results (`services::results_store`)
+//! - the device inventory (`services::device_inventory`) and its groups
+//!   (`services::device_groups`)
+//! - the credential vault (`services::credential_vault`)
+//! - configuration backups (`services::config_backups`)
+//!
+//! ## Configuration
+//! - `THALYX_DATA_DIR` - directory holding the database (`data`)
+//!
+//! ## How to Use
+//! 1. `let database = Arc::new(Database::open(DatabaseConfig::from_env())?);`
+//! 2. Hand it to the services: `DeviceInventory::new(database.clone(), websocket)`
+//! 3. Inside a service: `self.database.blocking(move |connection| ...).await`
+//!
+//! SQLite calls are blocking, so every operation runs on the blocking thread pool.
+
+use rusqlite::{Connection, Row};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tracing::info;
+
+use crate::models::{ApiError, ApiResult};
+
+/// Page size when a query does not ask for one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Largest page a query may request
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// File name of the database inside the data directory
+const DATABASE_FILE: &str = "results.db";
+
+/// Schema migrations of every service's tables, applied in order and tracked with
+/// `PRAGMA user_version`; append only, existing databases are at some index of this list
+const MIGRATIONS: &[&str] = &[r#"
+    CREATE TABLE report_results (
+        run_id        TEXT PRIMARY KEY,
+        batch_run_id  TEXT,
+        report_id     TEXT NOT NULL,
+        title         TEXT NOT NULL,
+        device        TEXT NOT NULL,
+        rpc           TEXT NOT NULL,
+        status        TEXT NOT NULL,
+        started_at    TEXT NOT NULL,   -- RFC 3339 UTC with fixed precision, sorts as text
+        duration_ms   INTEGER NOT NULL,
+        row_count     INTEGER NOT NULL,
+        error         TEXT,
+        columns       TEXT NOT NULL,   -- JSON
+        rows          TEXT NOT NULL,   -- JSON
+        misses        TEXT NOT NULL    -- JSON
+    );
+    CREATE INDEX report_results_report_device ON report_results (report_id, device, started_at);
+    CREATE INDEX report_results_started_at ON report_results (started_at);
+    CREATE INDEX report_results_batch ON report_results (batch_run_id);
+"#, r#"
+    ALTER TABLE report_results ADD COLUMN verdict TEXT;      -- pass | warn | fail, NULL without assertions
+    ALTER TABLE report_results ADD COLUMN assertions TEXT;   -- JSON
+    CREATE INDEX report_results_verdict ON report_results (report_id, verdict);
+"#, r#"
+    ALTER TABLE report_results ADD COLUMN parameters TEXT;   -- JSON, NULL for reports without parameters
+"#, r#"
+    CREATE TABLE devices (
+        id          TEXT PRIMARY KEY,
+        name        TEXT NOT NULL UNIQUE,
+        address     TEXT NOT NULL,
+        vendor      TEXT NOT NULL,
+        platform    TEXT NOT NULL,
+        type        TEXT NOT NULL,
+        site        TEXT NOT NULL,
+        rack        TEXT NOT NULL,
+        tags        TEXT NOT NULL,   -- JSON array
+        status      TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+    CREATE INDEX devices_type ON devices (type);
+    CREATE INDEX devices_site ON devices (site);
+"#, r#"
+    CREATE TABLE device_groups (
+        id          TEXT PRIMARY KEY,
+        name        TEXT NOT NULL UNIQUE,
+        description TEXT NOT NULL,
+        filter      TEXT,            -- NULL for static groups
+        devices     TEXT NOT NULL,   -- JSON array of device IDs
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+"#, r#"
+    CREATE TABLE credential_profiles (
+        id          TEXT PRIMARY KEY,
+        name        TEXT NOT NULL UNIQUE,
+        description TEXT NOT NULL,
+        username    TEXT NOT NULL,
+        secrets     TEXT NOT NULL,   -- JSON array of the secret kinds held, never values
+        key_id      TEXT NOT NULL,   -- master key the ciphertext is encrypted with
+        ciphertext  TEXT NOT NULL,   -- base64 of nonce + AES-256-GCM ciphertext
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+    CREATE TABLE credential_assignments (
+        target_kind TEXT NOT NULL,   -- device | group
+        target_id   TEXT NOT NULL,
+        profile_id  TEXT NOT NULL REFERENCES credential_profiles (id),
+        PRIMARY KEY (target_kind, target_id)
+    );
+    CREATE INDEX credential_assignments_profile ON credential_assignments (profile_id);
+"#, r#"
+    ALTER TABLE devices ADD COLUMN discovery TEXT NOT NULL DEFAULT '{}';      -- JSON DiscoveryState
+    ALTER TABLE devices ADD COLUMN reachability TEXT NOT NULL DEFAULT 'unknown'; -- copy of discovery.reachability
+    CREATE INDEX devices_reachability ON devices (reachability);
+"#, r#"
+    CREATE TABLE config_blobs (
+        hash        TEXT PRIMARY KEY,   -- hex SHA-256 of content
+        content     TEXT NOT NULL,
+        size        INTEGER NOT NULL,
+        created_at  TEXT NOT NULL
+    );
+    CREATE TABLE config_versions (
+        id           TEXT PRIMARY KEY,
+        device       TEXT NOT NULL,
+        format       TEXT NOT NULL,     -- text | set | xml
+        hash         TEXT NOT NULL REFERENCES config_blobs (hash),
+        job_id       TEXT NOT NULL,
+        taken_at     TEXT NOT NULL,
+        confirmed_at TEXT NOT NULL
+    );
+    CREATE INDEX config_versions_device ON config_versions (device, format, taken_at);
+    CREATE INDEX config_versions_job ON config_versions (job_id);
+"#];
+
+/// Where the database lives
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub data_dir: PathBuf,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: PathBuf::from("data"),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn from_env() -> Self {
+        Self {
+            data_dir: std::env::var("THALYX_DATA_DIR")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| Self::default().data_dir),
+        }
+    }
+}
+
+/// Shared SQLite connection, migrated to the latest schema
+#[derive(Debug, Clone)]
+pub struct Database {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    /// Open (creating if needed) the database in the configured data directory
+    pub fn open(config: DatabaseConfig) -> ApiResult<Self> {
+        std::fs::create_dir_all(&config.data_dir)?;
+        let path = config.data_dir.join(DATABASE_FILE);
+        let mut connection = Connection::open(&path).map_err(database_error)?;
+
+        // WAL keeps readers from blocking the writer; NORMAL sync is safe with WAL
+        connection
+            .execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+            .map_err(database_error)?;
+        migrate(&mut connection)?;
+
+        info!(path = %path.display(), "Database ready");
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Run a closure on the connection from the blocking thread pool
+    pub async fn blocking<T, F>(&self, operation: F) -> ApiResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> ApiResult<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| ApiError::InternalError("Database lock poisoned".to_string()))?;
+            operation(&mut connection)
+        })
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Database task failed: {}", e)))?
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+fn migrate(connection: &mut Connection) -> ApiResult<()> {
+    let version: usize = connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(database_error)?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction().map_err(database_error)?;
+        transaction.execute_batch(migration).map_err(database_error)?;
+        transaction
+            .pragma_update(None, "user_version", index + 1)
+            .map_err(database_error)?;
+        transaction.commit().map_err(database_error)?;
+        info!(version = index + 1, "Applied database migration");
+    }
+    Ok(())
+}
+
+/// Read a text column and convert it, reporting unreadable values as conversion errors
+pub fn parse_column<T>(row: &Row, index: usize, parse: impl FnOnce(String) -> Option<T>) -> rusqlite::Result<T> {
+    let value: String = row.get(index)?;
+    parse(value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            rusqlite::types::Type::Text,
+            format!("unreadable value in column {}", index).into(),
+        )
+    })
+}
+
+/// Fixed-precision UTC timestamps compare correctly as text
+pub fn timestamp(time: &chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+pub fn to_json<T: serde::Serialize>(value: &T) -> ApiResult<String> {
+    serde_json::to_string(value).map_err(|e| ApiError::SerializationError(e.to_string()))
+}
+
+pub fn database_error(e: rusqlite::Error) -> ApiError {
+    ApiError::InternalError(format!("Database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn migrations_apply_once() {
+        let data_dir = std::env::temp_dir().join(format!("thalyx-database-{}", uuid::Uuid::new_v4()));
+        let version = |database: Database| async move {
+            database
+                .blocking(|connection| {
+                    connection
+                        .query_row("PRAGMA user_version", [], |row| row.get::<_, usize>(0))
+                        .map_err(database_error)
+                })
+                .await
+                .unwrap()
+        };
+
+        let first = Database::open(DatabaseConfig { data_dir: data_dir.clone() }).unwrap();
+        assert_eq!(version(first).await, MIGRATIONS.len());
+        let reopened = Database::open(DatabaseConfig { data_dir: data_dir.clone() }).unwrap();
+        assert_eq!(version(reopened).await, MIGRATIONS.len());
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
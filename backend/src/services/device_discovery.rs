@@ -0,0 +1,709 @@
+// backend/src/services/device_discovery.rs
+
+//! # Device Discovery
+//!
+//! ## Description
+//! Probes inventory devices and keeps what it finds on their records
+//! (`Device.discovery`). A probe connects to the management address over TCP, reads
+//! the SSH banner, and, when full, opens a NETCONF session (with the vault's
+//! credentials) to read facts: hostname, model, OS version, serial number and uptime.
+//! Devices are probed fully when added or when their address changes, and polled
+//! periodically afterwards; changes of reachability and facts are announced on the
+//! `data:device-status` topic.
+//!
+//! ## Probing
+//! - Polls only connect and read the SSH banner; a poll becomes a full probe while a
+//!   device is not `reachable` and when its facts are older than the facts interval
+//! - `unreachable`: the TCP connect failed; `degraded`: the port answered but SSH or
+//!   the last NETCONF attempt did not; `reachable`: every step succeeded
+//! - Only `active` and `maintenance` devices are probed in the background;
+//!   `discover` probes any device on request
+//! - `tcp://` addresses (stand-ins) have no SSH step
+//!
+//! ## Facts
+//! Read with the Junos RPCs `get-system-information` and `get-system-uptime-information`.
+//! Facts a device does not report stay empty; when a device reports none, the previous
+//! facts are kept. The NETCONF stand-in answers both from the shared fixtures, so
+//! `tcp://127.0.0.1:8300` exercises the whole probe without a device.
+//!
+//! ## Configuration
+//! - `THALYX_DISCOVERY_INTERVAL` - seconds between polls, `0` disables polling (`300`)
+//! - `THALYX_DISCOVERY_TIMEOUT` - seconds allowed per probe step (`15`)
+//! - `THALYX_DISCOVERY_CONCURRENCY` - devices probed at once (`8`)
+//! - `THALYX_DISCOVERY_FACTS_HOURS` - age after which facts are read again (`24`)
+//!
+//! ## How to Use
+//! 1. `let discovery = Arc::new(DeviceDiscovery::new(inventory, netconf, websocket, DiscoveryConfig::from_env()));`
+//! 2. `discovery.start_background_tasks();` to probe added devices and poll
+//! 3. `discovery.discover("r1.lab").await?` to probe one device now
+//! 4. Subscribe to `data:device-status` for `device_status_changed` and `device_facts_changed`
+
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+use serde_json::json;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::AsyncReadExt,
+    net::TcpStream,
+    sync::{broadcast::error::RecvError, Semaphore},
+};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        devices::{
+            Device, DeviceChange, DeviceFacts, DeviceStatus, DiscoveryState, ProbeReport, ProbeStep, Reachability,
+        },
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        netconf::{
+            session::NetconfSession,
+            transport::{DeviceAddress, Scheme},
+            NetconfTransport,
+        },
+        report_engine::RpcCall,
+        DeviceInventory, WebSocketService,
+    },
+};
+
+/// `DataUpdate.source` of reachability and facts events
+const TOPIC_SOURCE: &str = "device-status";
+
+/// Upper bound for probes at once
+pub const MAX_CONCURRENCY: usize = 256;
+
+/// Bytes read while looking for the SSH identification line (RFC 4253 4.2)
+const MAX_BANNER_BYTES: usize = 1024;
+
+/// Junos RPCs facts are read with
+const SYSTEM_INFORMATION_RPC: &str = "get-system-information";
+const UPTIME_RPC: &str = "get-system-uptime-information";
+
+/// How often and how hard devices are probed
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    /// `None` disables polling; added devices are still probed
+    pub poll_interval: Option<Duration>,
+    /// Time allowed for each probe step
+    pub timeout: Duration,
+    pub concurrency: usize,
+    /// Facts older than this are read again on the next poll
+    pub facts_max_age: Duration,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Some(Duration::from_secs(300)),
+            timeout: Duration::from_secs(15),
+            concurrency: 8,
+            facts_max_age: Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        let positive = |name: &str| number(name).filter(|n| *n > 0);
+
+        Self {
+            poll_interval: number("THALYX_DISCOVERY_INTERVAL")
+                .map_or(defaults.poll_interval, |n| (n > 0).then(|| Duration::from_secs(n))),
+            timeout: positive("THALYX_DISCOVERY_TIMEOUT").map_or(defaults.timeout, Duration::from_secs),
+            concurrency: positive("THALYX_DISCOVERY_CONCURRENCY")
+                .map_or(defaults.concurrency, |n| (n as usize).min(MAX_CONCURRENCY)),
+            facts_max_age: positive("THALYX_DISCOVERY_FACTS_HOURS")
+                .map_or(defaults.facts_max_age, |n| Duration::from_secs(n * 3600)),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SERVICE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Probes inventory devices on change, periodically and on request
+#[derive(Debug)]
+pub struct DeviceDiscovery {
+    inventory: Arc<DeviceInventory>,
+    netconf: Arc<NetconfTransport>,
+    websocket: Arc<WebSocketService>,
+    config: DiscoveryConfig,
+    limit: Semaphore,
+    /// Devices with a background probe under way, so triggers do not pile up
+    probing: Mutex<HashSet<Uuid>>,
+}
+
+impl DeviceDiscovery {
+    pub fn new(
+        inventory: Arc<DeviceInventory>,
+        netconf: Arc<NetconfTransport>,
+        websocket: Arc<WebSocketService>,
+        config: DiscoveryConfig,
+    ) -> Self {
+        Self {
+            inventory,
+            netconf,
+            websocket,
+            limit: Semaphore::new(config.concurrency),
+            config,
+            probing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Spawn the tasks probing added and re-addressed devices, and the poll loop
+    pub fn start_background_tasks(self: &Arc<Self>) {
+        let discovery = self.clone();
+        let mut changes = self.inventory.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok((DeviceChange::Created | DeviceChange::Updated, device)) => {
+                        let discovery_state = &device.discovery;
+                        let moved = discovery_state
+                            .probe
+                            .as_ref()
+                            .is_some_and(|probe| probe.address != device.fields.address);
+                        if is_polled(&device) && (discovery_state.reachability == Reachability::Unknown || moved) {
+                            discovery.spawn_probe(device, true);
+                        }
+                    }
+                    Ok((DeviceChange::Deleted, _)) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Discovery missed inventory changes; the next poll catches up");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let Some(interval) = self.config.poll_interval else {
+            info!("Device reachability polling disabled");
+            return;
+        };
+        let discovery = self.clone();
+        tokio::spawn(async move {
+            info!(interval_secs = interval.as_secs(), "Device reachability polling started");
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = discovery.poll().await {
+                    error!(error = %e, "Device reachability poll failed");
+                }
+            }
+        });
+    }
+
+    /// Probe one device fully now, whatever its status, and return it updated
+    pub async fn discover(&self, device: &str) -> ApiResult<Device> {
+        let device = self.inventory.get(device).await?;
+        self.probe_device(device, true).await
+    }
+
+    /// Probe every polled device, fully where its state calls for it
+    async fn poll(self: &Arc<Self>) -> ApiResult<()> {
+        let devices: Vec<Device> = self.inventory.all().await?.into_iter().filter(is_polled).collect();
+        let timer = Instant::now();
+        let count = devices.len();
+        futures_util::stream::iter(devices)
+            .for_each_concurrent(None, |device| async move {
+                let full = device.discovery.reachability != Reachability::Reachable
+                    || self.facts_stale(&device.discovery);
+                if self.begin(device.id) {
+                    let id = device.id;
+                    if let Err(e) = self.probe_device(device, full).await {
+                        warn!(device_id = %id, error = %e, "Device probe failed");
+                    }
+                    self.end(id);
+                }
+            })
+            .await;
+        debug!(devices = count, duration_ms = timer.elapsed().as_millis() as u64, "Device reachability poll done");
+        Ok(())
+    }
+
+    fn spawn_probe(self: &Arc<Self>, device: Device, full: bool) {
+        if !self.begin(device.id) {
+            return;
+        }
+        let discovery = self.clone();
+        tokio::spawn(async move {
+            let (id, name) = (device.id, device.fields.name.clone());
+            if let Err(e) = discovery.probe_device(device, full).await {
+                warn!(device = %name, error = %e, "Device probe failed");
+            }
+            discovery.end(id);
+        });
+    }
+
+    /// Probe, store the outcome and announce what changed
+    async fn probe_device(&self, mut device: Device, full: bool) -> ApiResult<Device> {
+        let _permit = self
+            .limit
+            .acquire()
+            .await
+            .map_err(|_| ApiError::InternalError("Discovery limiter closed".to_string()))?;
+
+        let (report, facts) = probe(&self.netconf, &device.fields.address, full, self.config.timeout).await;
+        let previous = std::mem::take(&mut device.discovery);
+        let next = next_state(&previous, report, facts, full);
+        if !self.inventory.record_discovery(device.id, next.clone()).await? {
+            debug!(device = %device.fields.name, "Device removed while being probed");
+            return Err(ApiError::NotFound(format!("Device '{}' not found", device.fields.name)));
+        }
+        device.discovery = next;
+
+        let state = &device.discovery;
+        debug!(
+            device = %device.fields.name,
+            reachability = state.reachability.as_str(),
+            full,
+            "Device probed"
+        );
+        if state.reachability != previous.reachability {
+            if state.reachability == Reachability::Reachable {
+                info!(device = %device.fields.name, from = previous.reachability.as_str(), "Device reachable");
+            } else {
+                warn!(
+                    device = %device.fields.name,
+                    from = previous.reachability.as_str(),
+                    to = state.reachability.as_str(),
+                    "Device reachability changed"
+                );
+            }
+            self.publish(&device, json!({
+                "event": "device_status_changed",
+                "device_id": device.id,
+                "name": &device.fields.name,
+                "previous": previous.reachability,
+                "reachability": state.reachability,
+                "probe": &state.probe,
+            }))
+            .await;
+        }
+        if facts_differ(previous.facts.as_ref(), state.facts.as_ref()) {
+            info!(device = %device.fields.name, facts = ?state.facts, "Device facts changed");
+            self.publish(&device, json!({
+                "event": "device_facts_changed",
+                "device_id": device.id,
+                "name": &device.fields.name,
+                "facts": &state.facts,
+            }))
+            .await;
+        }
+        Ok(device)
+    }
+
+    fn facts_stale(&self, state: &DiscoveryState) -> bool {
+        state.facts.as_ref().is_none_or(|facts| {
+            (chrono::Utc::now() - facts.collected_at).to_std().unwrap_or_default() >= self.config.facts_max_age
+        })
+    }
+
+    /// Mark a device as being probed; `false` if it already is
+    fn begin(&self, id: Uuid) -> bool {
+        self.probing.lock().unwrap_or_else(|e| e.into_inner()).insert(id)
+    }
+
+    fn end(&self, id: Uuid) {
+        self.probing.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+    }
+
+    async fn publish(&self, device: &Device, data: serde_json::Value) {
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data,
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(device_id = %device.id, error = %e, "Failed to publish device status");
+        }
+    }
+}
+
+/// Devices probed in the background
+fn is_polled(device: &Device) -> bool {
+    matches!(device.fields.status, DeviceStatus::Active | DeviceStatus::Maintenance)
+}
+
+/// Fold a probe into the previous state
+/// Polls keep the last NETCONF outcome and facts of the same address
+fn next_state(previous: &DiscoveryState, mut report: ProbeReport, facts: Option<DeviceFacts>, full: bool) -> DiscoveryState {
+    let same_address = previous.probe.as_ref().is_some_and(|p| p.address == report.address);
+    if !full && report.tcp.ok && same_address {
+        report.netconf = previous.probe.as_ref().and_then(|p| p.netconf.clone());
+    }
+
+    let failed = |step: &Option<ProbeStep>| step.as_ref().is_some_and(|s| !s.ok);
+    let reachability = if !report.tcp.ok {
+        Reachability::Unreachable
+    } else if failed(&report.ssh) || failed(&report.netconf) {
+        Reachability::Degraded
+    } else {
+        Reachability::Reachable
+    };
+
+    let now = chrono::Utc::now();
+    DiscoveryState {
+        reachability,
+        checked_at: Some(now),
+        last_seen: if report.tcp.ok { Some(now) } else { previous.last_seen },
+        facts: facts.or_else(|| previous.facts.clone().filter(|_| same_address)),
+        probe: Some(report),
+    }
+}
+
+/// Whether facts changed, ignoring when they were read and the uptime
+fn facts_differ(before: Option<&DeviceFacts>, after: Option<&DeviceFacts>) -> bool {
+    let key = |facts: Option<&DeviceFacts>| {
+        facts.map(|f| (f.hostname.clone(), f.model.clone(), f.os_version.clone(), f.serial_number.clone()))
+    };
+    key(before) != key(after)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// PROBES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Probe a device address: TCP connect and SSH banner, then with `full` a NETCONF
+/// session reading facts. `timeout` applies to each step
+pub async fn probe(
+    netconf: &NetconfTransport,
+    target: &str,
+    full: bool,
+    timeout: Duration,
+) -> (ProbeReport, Option<DeviceFacts>) {
+    let mut report = ProbeReport {
+        address: target.to_string(),
+        tcp: ProbeStep { ok: false, latency_ms: 0, detail: None },
+        ssh: None,
+        netconf: None,
+    };
+    let address = match DeviceAddress::parse(target) {
+        Ok(address) => address,
+        Err(e) => {
+            report.tcp.detail = Some(e.to_string());
+            return (report, None);
+        }
+    };
+
+    let timer = Instant::now();
+    let connected = tokio::time::timeout(timeout, TcpStream::connect((address.host.as_str(), address.port))).await;
+    let mut stream = match connected {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => {
+            report.tcp = failed(timer, format!("Cannot connect to {}: {}", address, e));
+            return (report, None);
+        }
+        Err(_) => {
+            report.tcp = failed(timer, format!("Connecting to {} timed out after {}s", address, timeout.as_secs()));
+            return (report, None);
+        }
+    };
+    report.tcp = ProbeStep { ok: true, latency_ms: elapsed_ms(timer), detail: None };
+
+    if address.scheme == Scheme::Ssh {
+        let timer = Instant::now();
+        report.ssh = Some(match tokio::time::timeout(timeout, read_banner(&mut stream)).await {
+            Ok(Ok(banner)) => ProbeStep { ok: true, latency_ms: elapsed_ms(timer), detail: Some(banner) },
+            Ok(Err(e)) => failed(timer, e),
+            Err(_) => failed(timer, format!("No SSH banner within {}s", timeout.as_secs())),
+        });
+    }
+    drop(stream);
+
+    if !full || report.ssh.as_ref().is_some_and(|s| !s.ok) {
+        return (report, None);
+    }
+    let timer = Instant::now();
+    let facts = match tokio::time::timeout(timeout, read_facts(netconf, &address)).await {
+        Ok(Ok((latency_ms, facts))) => {
+            report.netconf = Some(ProbeStep { ok: true, latency_ms, detail: None });
+            facts
+        }
+        Ok(Err(e)) => {
+            report.netconf = Some(failed(timer, e.to_string()));
+            None
+        }
+        Err(_) => {
+            report.netconf = Some(failed(timer, format!("NETCONF did not answer within {}s", timeout.as_secs())));
+            None
+        }
+    };
+    (report, facts)
+}
+
+fn failed(timer: Instant, detail: String) -> ProbeStep {
+    ProbeStep { ok: false, latency_ms: elapsed_ms(timer), detail: Some(detail) }
+}
+
+fn elapsed_ms(timer: Instant) -> u64 {
+    timer.elapsed().as_millis() as u64
+}
+
+/// The server's identification line, e.g. `SSH-2.0-OpenSSH_9.6`; servers may send other
+/// lines before it
+async fn read_banner(stream: &mut TcpStream) -> Result<String, String> {
+    let mut received = Vec::with_capacity(256);
+    let mut chunk = [0u8; 256];
+    while received.len() < MAX_BANNER_BYTES {
+        let read = stream.read(&mut chunk).await.map_err(|e| format!("Reading the SSH banner failed: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        received.extend_from_slice(&chunk[..read]);
+        let text = String::from_utf8_lossy(&received);
+        let Some(end) = text.rfind('\n') else { continue };
+        if let Some(line) = text[..end].split('\n').map(str::trim_end).find(|line| line.starts_with("SSH-")) {
+            return Ok(line.to_string());
+        }
+    }
+    Err("The port does not speak SSH (no SSH banner)".to_string())
+}
+
+/// Open a session, read the facts and close it; returns the session setup time
+async fn read_facts(netconf: &NetconfTransport, address: &DeviceAddress) -> ApiResult<(u64, Option<DeviceFacts>)> {
+    let timer = Instant::now();
+    let mut session = netconf.connect(address).await?;
+    let latency_ms = elapsed_ms(timer);
+
+    let mut facts = DeviceFacts {
+        hostname: None,
+        model: None,
+        os_version: None,
+        serial_number: None,
+        uptime_seconds: None,
+        collected_at: chrono::Utc::now(),
+    };
+    if let Some(reply) = call(&mut session, address, SYSTEM_INFORMATION_RPC).await {
+        read_system_information(&reply, &mut facts);
+    }
+    if let Some(reply) = call(&mut session, address, UPTIME_RPC).await {
+        facts.uptime_seconds = read_uptime(&reply);
+    }
+    session.close().await;
+
+    let empty = facts.hostname.is_none()
+        && facts.model.is_none()
+        && facts.os_version.is_none()
+        && facts.serial_number.is_none()
+        && facts.uptime_seconds.is_none();
+    Ok((latency_ms, (!empty).then_some(facts)))
+}
+
+/// A facts RPC; devices without it simply report fewer facts
+async fn call(session: &mut NetconfSession, address: &DeviceAddress, rpc: &str) -> Option<String> {
+    let call = RpcCall { name: rpc.to_string(), args: IndexMap::new() };
+    match session.rpc(&call).await {
+        Ok(reply) => Some(reply),
+        Err(e) => {
+            debug!(device = %address, rpc, error = %e, "Facts RPC failed");
+            None
+        }
+    }
+}
+
+fn read_system_information(reply: &str, facts: &mut DeviceFacts) {
+    let Ok(document) = roxmltree::Document::parse(reply) else {
+        return;
+    };
+    let text = |name: &str| {
+        element(document.root(), name)
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    facts.hostname = text("host-name");
+    facts.model = text("hardware-model");
+    facts.os_version = text("os-version");
+    facts.serial_number = text("serial-number");
+}
+
+/// `system-booted-time/time-length/@junos:seconds`, else `up-time/@junos:seconds`
+fn read_uptime(reply: &str) -> Option<u64> {
+    let document = roxmltree::Document::parse(reply).ok()?;
+    let seconds = |node: roxmltree::Node| node.attributes().find(|a| a.name() == "seconds")?.value().parse().ok();
+    let root = document.root();
+    element(root, "system-booted-time")
+        .and_then(|booted| element(booted, "time-length"))
+        .and_then(seconds)
+        .or_else(|| element(root, "up-time").and_then(seconds))
+}
+
+/// First descendant element with a local name, in any namespace
+fn element<'a, 'input>(parent: roxmltree::Node<'a, 'input>, name: &str) -> Option<roxmltree::Node<'a, 'input>> {
+    parent.descendants().find(|node| node.is_element() && node.tag_name().name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::devices::DeviceInput,
+        services::{
+            message_bus::{BusEvent, LocalBus, MessageBus},
+            netconf::{standin::StandInServer, NetconfConfig},
+            database::DatabaseConfig,
+            report_engine::StaticTransport,
+            Database,
+        },
+    };
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// NETCONF stand-in answering from the shared fixtures; returns its `tcp://` address
+    async fn standin() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("tcp://{}", listener.local_addr().unwrap());
+        let replies = StaticTransport::from_dir("../shared/fixtures/rpc-replies").unwrap();
+        tokio::spawn(Arc::new(StandInServer::new(replies, false)).serve(listener));
+        address
+    }
+
+    /// Listener answering every connection with `greeting`, then closing it
+    async fn greeter(greeting: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = stream.write_all(greeting).await;
+            }
+        });
+        port
+    }
+
+    fn netconf() -> NetconfTransport {
+        NetconfTransport::new(NetconfConfig {
+            allow_plain_tcp: true,
+            ..NetconfConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn standin_is_reachable_with_facts() {
+        let (report, facts) = probe(&netconf(), &standin().await, true, TIMEOUT).await;
+
+        assert!(report.tcp.ok);
+        assert!(report.ssh.is_none(), "tcp:// targets have no SSH step");
+        assert!(report.netconf.as_ref().is_some_and(|step| step.ok), "{:?}", report.netconf);
+        let facts = facts.expect("facts from the fixtures");
+        assert_eq!(facts.hostname.as_deref(), Some("r1-lab"));
+        assert_eq!(facts.model.as_deref(), Some("mx204"));
+        assert_eq!(facts.os_version.as_deref(), Some("21.4R3-S5.4"));
+        assert_eq!(facts.serial_number.as_deref(), Some("DN1234AB5CDE"));
+        assert_eq!(facts.uptime_seconds, Some(1_211_600));
+    }
+
+    #[tokio::test]
+    async fn closed_port_reports_the_tcp_failure() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (report, facts) = probe(&netconf(), &format!("127.0.0.1:{}", port), true, TIMEOUT).await;
+
+        assert!(!report.tcp.ok);
+        let detail = report.tcp.detail.unwrap_or_default();
+        assert!(detail.starts_with("Cannot connect to"), "{}", detail);
+        assert!(report.ssh.is_none() && report.netconf.is_none());
+        assert!(facts.is_none());
+    }
+
+    #[tokio::test]
+    async fn non_ssh_listener_reports_no_banner() {
+        let port = greeter(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+
+        let (report, facts) = probe(&netconf(), &format!("127.0.0.1:{}", port), true, TIMEOUT).await;
+
+        assert!(report.tcp.ok);
+        let ssh = report.ssh.expect("SSH step");
+        assert!(!ssh.ok);
+        assert_eq!(ssh.detail.as_deref(), Some("The port does not speak SSH (no SSH banner)"));
+        assert!(report.netconf.is_none(), "NETCONF is not tried without SSH");
+        assert!(facts.is_none());
+    }
+
+    #[tokio::test]
+    async fn ssh_banner_is_reported() {
+        let port = greeter(b"Welcome\r\nSSH-2.0-Stand_In\r\n").await;
+
+        let (report, _) = probe(&netconf(), &format!("127.0.0.1:{}", port), false, TIMEOUT).await;
+
+        let ssh = report.ssh.expect("SSH step");
+        assert!(ssh.ok);
+        assert_eq!(ssh.detail.as_deref(), Some("SSH-2.0-Stand_In"));
+    }
+
+    #[tokio::test]
+    async fn status_change_is_published() {
+        let data_dir = std::env::temp_dir().join(format!("thalyx-discovery-{}", Uuid::new_v4()));
+        let database = Arc::new(Database::open(DatabaseConfig { data_dir: data_dir.clone() }).unwrap());
+        let bus = LocalBus::new();
+        let mut envelopes = bus.subscribe().await.unwrap();
+        let websocket = Arc::new(WebSocketService::new(None).with_message_bus(Arc::new(bus)));
+        let inventory = Arc::new(DeviceInventory::new(database, websocket.clone()));
+        inventory
+            .create(DeviceInput {
+                name: "r1.lab".to_string(),
+                address: standin().await,
+                vendor: "juniper".to_string(),
+                platform: String::new(),
+                kind: Default::default(),
+                site: String::new(),
+                rack: String::new(),
+                tags: Vec::new(),
+                status: DeviceStatus::Active,
+            })
+            .await
+            .unwrap();
+        let discovery = DeviceDiscovery::new(inventory, Arc::new(netconf()), websocket, DiscoveryConfig::default());
+
+        let device = discovery.discover("r1.lab").await.unwrap();
+        assert_eq!(device.discovery.reachability, Reachability::Reachable);
+
+        let status = loop {
+            let envelope = tokio::time::timeout(TIMEOUT, envelopes.next())
+                .await
+                .expect("no device status event")
+                .unwrap();
+            if let BusEvent::Broadcast { topic, message: WsMessage::DataUpdate { data, .. } } = envelope.event {
+                if data["event"] == "device_status_changed" {
+                    assert_eq!(topic, "data:device-status");
+                    break data;
+                }
+            }
+        };
+        assert_eq!(status["name"], "r1.lab");
+        assert_eq!(status["previous"], "unknown");
+        assert_eq!(status["reachability"], "reachable");
+
+        // Probing again with nothing changed announces nothing
+        discovery.discover("r1.lab").await.unwrap();
+        let quiet = tokio::time::timeout(Duration::from_millis(200), async {
+            while let Some(envelope) = envelopes.next().await {
+                if let BusEvent::Broadcast { message: WsMessage::DataUpdate { data, .. }, .. } = envelope.event {
+                    if data["event"] == "device_status_changed" {
+                        return data;
+                    }
+                }
+            }
+            serde_json::Value::Null
+        })
+        .await;
+        assert!(quiet.is_err(), "unexpected {:?}", quiet);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,431 @@
+// backend/src/services/device_filter.rs
+
+//! # Device Filters
+//!
+//! ## Description
+//! Boolean expressions over inventory fields and tags, selecting the members of dynamic
+//! device groups (`services::device_groups`):
+//!
+//! ```text
+//! type == router and site == LON and tag == pe
+//! vendor in (juniper, arista) and not status == decommissioned
+//! name =~ "leaf*" or (tag == spine and rack != "")
+//! ```
+//!
+//! ## Syntax
+//! - Fields: `name`, `address`, `vendor`, `platform`, `type`, `site`, `rack`, `status`
+//!   and `tag` (any of the device's tags)
+//! - `==` and `!=` compare text case-insensitively; `=~` matches a glob (`*` any text,
+//!   `?` one character); `in (a, b)` is true if any value is equal
+//! - `and` binds tighter than `or`; `not` negates; parentheses group, up to 64 levels deep
+//! - Values are bare words (letters, digits, `. _ - : / * ?`) or quoted with `"` or `'`
+//! - `type` and `status` values must be known types and statuses
+//!
+//! ## How to Use
+//! 1. `let filter = DeviceFilter::parse("type == router and site == LON")?;`
+//! 2. `filter.matches(&device.fields)`
+
+use std::fmt;
+
+use crate::models::{
+    devices::{DeviceInput, DeviceStatus, DeviceType},
+    ApiError, ApiResult,
+};
+
+/// Longest accepted filter expression
+const MAX_FILTER_LENGTH: usize = 4096;
+/// Deepest nesting of parentheses and `not`; bounds the parser's recursion
+const MAX_FILTER_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Address,
+    Vendor,
+    Platform,
+    Type,
+    Site,
+    Rack,
+    Status,
+    Tag,
+}
+
+impl Field {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word.to_lowercase().as_str() {
+            "name" => Field::Name,
+            "address" => Field::Address,
+            "vendor" => Field::Vendor,
+            "platform" => Field::Platform,
+            "type" => Field::Type,
+            "site" => Field::Site,
+            "rack" => Field::Rack,
+            "status" => Field::Status,
+            "tag" | "tags" => Field::Tag,
+            _ => return None,
+        })
+    }
+
+    /// Values of the field on a device; several for `tag`
+    fn values(self, device: &DeviceInput) -> Vec<&str> {
+        match self {
+            Field::Name => vec![&device.name],
+            Field::Address => vec![&device.address],
+            Field::Vendor => vec![&device.vendor],
+            Field::Platform => vec![&device.platform],
+            Field::Type => vec![device.kind.as_str()],
+            Field::Site => vec![&device.site],
+            Field::Rack => vec![&device.rack],
+            Field::Status => vec![device.status.as_str()],
+            Field::Tag => device.tags.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Glob,
+    In,
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    /// Values are lower-cased
+    Compare { field: Field, operator: Operator, values: Vec<String> },
+}
+
+impl Expression {
+    fn matches(&self, device: &DeviceInput) -> bool {
+        match self {
+            Expression::And(left, right) => left.matches(device) && right.matches(device),
+            Expression::Or(left, right) => left.matches(device) || right.matches(device),
+            Expression::Not(inner) => !inner.matches(device),
+            Expression::Compare { field, operator, values } => {
+                let actual = field.values(device);
+                let any = |test: &dyn Fn(&str) -> bool| actual.iter().any(|v| test(&v.to_lowercase()));
+                match operator {
+                    Operator::Eq | Operator::In => any(&|v| values.iter().any(|expected| expected == v)),
+                    Operator::Ne => !any(&|v| v == values[0]),
+                    Operator::Glob => any(&|v| glob_matches(&values[0], v)),
+                }
+            }
+        }
+    }
+}
+
+/// A parsed filter expression
+#[derive(Debug, Clone)]
+pub struct DeviceFilter {
+    expression: Expression,
+}
+
+impl DeviceFilter {
+    /// Parse an expression; fails with `ValidationError` describing the first problem
+    pub fn parse(source: &str) -> ApiResult<Self> {
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid filter '{}': {}", source.trim(), reason));
+        if source.len() > MAX_FILTER_LENGTH {
+            return Err(invalid(format!("longer than {} characters", MAX_FILTER_LENGTH)));
+        }
+        let tokens = tokenize(source).map_err(invalid)?;
+        if tokens.is_empty() {
+            return Err(invalid("the expression is empty".to_string()));
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.or().map_err(invalid)?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(format!("unexpected {}", token)));
+        }
+        Ok(Self { expression })
+    }
+
+    pub fn matches(&self, device: &DeviceInput) -> bool {
+        self.expression.matches(device)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// PARSER
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Comma,
+    Operator(Operator),
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+            Token::Operator(Operator::Eq) => write!(f, "'=='"),
+            Token::Operator(Operator::Ne) => write!(f, "'!='"),
+            Token::Operator(Operator::Glob) => write!(f, "'=~'"),
+            Token::Operator(Operator::In) => write!(f, "'in'"),
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '!' => {
+                chars.next();
+                let operator = match (c, chars.next()) {
+                    ('=', Some('=')) => Operator::Eq,
+                    ('=', Some('~')) => Operator::Glob,
+                    ('!', Some('=')) => Operator::Ne,
+                    _ => return Err(format!("unknown operator starting with '{}'; use ==, != or =~", c)),
+                };
+                tokens.push(Token::Operator(operator));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let text: String = std::iter::from_fn(|| chars.next_if(|n| *n != c)).collect();
+                if chars.next() != Some(c) {
+                    return Err(format!("unterminated quote {}{}", c, text));
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            c if is_word_char(c) => {
+                let word: String = std::iter::from_fn(|| chars.next_if(|n| is_word_char(*n))).collect();
+                if word.eq_ignore_ascii_case("in") {
+                    tokens.push(Token::Operator(Operator::In));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/' | '*' | '?')
+}
+
+/// Recursive descent over `or` > `and` > `not` > comparison
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Parentheses and `not`s currently open
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        let mut left = self.and()?;
+        while self.keyword("or") {
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        let mut left = self.unary()?;
+        while self.keyword("and") {
+            left = Expression::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.keyword("not") {
+            self.descend()?;
+            let inner = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Not(Box::new(inner)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.position += 1;
+            self.descend()?;
+            let inner = self.or()?;
+            self.depth -= 1;
+            return match self.next() {
+                Some(Token::Close) => Ok(inner),
+                Some(token) => Err(format!("expected ')', found {}", token)),
+                None => Err("missing ')'".to_string()),
+            };
+        }
+        self.comparison()
+    }
+
+    /// Enter one level of nesting, refusing expressions that would exhaust the stack
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_FILTER_DEPTH {
+            return Err(format!("nested deeper than {} levels", MAX_FILTER_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let field = match self.next() {
+            Some(Token::Word(word)) => Field::parse(&word).ok_or_else(|| {
+                format!(
+                    "unknown field '{}'; fields: name, address, vendor, platform, type, site, rack, status, tag",
+                    word
+                )
+            })?,
+            Some(token) => return Err(format!("expected a field, found {}", token)),
+            None => return Err("expected a field at the end".to_string()),
+        };
+        let operator = match self.next() {
+            Some(Token::Operator(operator)) => operator,
+            Some(token) => return Err(format!("expected ==, !=, =~ or in after the field, found {}", token)),
+            None => return Err("expected ==, !=, =~ or in at the end".to_string()),
+        };
+
+        let values = if operator == Operator::In {
+            if self.next() != Some(Token::Open) {
+                return Err("expected '(' after in".to_string());
+            }
+            let mut values = vec![self.value()?];
+            loop {
+                match self.next() {
+                    Some(Token::Comma) => values.push(self.value()?),
+                    Some(Token::Close) => break,
+                    Some(token) => return Err(format!("expected ',' or ')' in the list, found {}", token)),
+                    None => return Err("missing ')' after the list".to_string()),
+                }
+            }
+            values
+        } else {
+            vec![self.value()?]
+        };
+
+        let values: Vec<String> = values.into_iter().map(|v| v.to_lowercase()).collect();
+        if operator != Operator::Glob {
+            for value in &values {
+                check_value(field, value)?;
+            }
+        }
+        Ok(Expression::Compare { field, operator, values })
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(word)) | Some(Token::Quoted(word)) => Ok(word),
+            Some(token) => Err(format!("expected a value, found {}", token)),
+            None => Err("expected a value at the end".to_string()),
+        }
+    }
+}
+
+/// Reject types and statuses no device can have, which would silently match nothing
+fn check_value(field: Field, value: &str) -> Result<(), String> {
+    let known = match field {
+        Field::Type => serde_json::from_value::<DeviceType>(serde_json::Value::String(value.to_string())).is_ok(),
+        Field::Status => serde_json::from_value::<DeviceStatus>(serde_json::Value::String(value.to_string())).is_ok(),
+        _ => true,
+    };
+    if known {
+        Ok(())
+    } else {
+        Err(format!("unknown {} '{}'", if field == Field::Type { "type" } else { "status" }, value))
+    }
+}
+
+/// Whole-text glob match with `*` and `?`
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star;
+                    t = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_deeply_nested_parentheses() {
+        let source = format!("{}name == r1{}", "(".repeat(2000), ")".repeat(2000));
+        let error = DeviceFilter::parse(&source).unwrap_err();
+        assert!(error.to_string().contains("nested deeper than 64 levels"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_long_not_chains() {
+        let source = format!("{}name == r1", "not ".repeat(1000));
+        let error = DeviceFilter::parse(&source).unwrap_err();
+        assert!(error.to_string().contains("nested deeper than 64 levels"), "{}", error);
+    }
+
+    #[test]
+    fn accepts_nesting_up_to_the_limit() {
+        let source = format!("{}name == r1{}", "(".repeat(MAX_FILTER_DEPTH), ")".repeat(MAX_FILTER_DEPTH));
+        assert!(DeviceFilter::parse(&source).is_ok());
+    }
+}
@@ -0,0 +1,381 @@
+// backend/src/services/device_groups.rs
+
+//! # Device Groups
+//!
+//! ## Description
+//! Named sets of inventory devices, usable wherever a device list is accepted. A
+//! static group lists its devices; a dynamic group holds a filter expression
+//! (`services::device_filter`) evaluated against the inventory every time the group is
+//! used, so devices added later join it on their own. Groups live in the
+//! `device_groups` table next to the inventory, and every change is announced on the
+//! `data:device-groups` topic.
+//!
+//! ## Targets
+//! In device lists (report runs, schedules) an entry `@name` stands for the members of
+//! group `name`, replaced by their management addresses. Other entries are kept as
+//! they are, and duplicates are dropped by the consumer.
+//!
+//! ## Rules
+//! - Names are unique and follow the device name rule
+//! - A group has either a `filter` or `devices`, not both
+//! - Static members are stored by ID: renaming a device keeps it in its groups, and
+//!   deleting it removes it from them
+//!
+//! ## How to Use
+//! 1. `groups.create(DeviceGroupInput { name: "lon-pe".into(), filter: Some("site == LON and tag == pe".into()), .. })`
+//! 2. `groups.members("lon-pe").await?` - the devices right now
+//! 3. `groups.expand_targets(vec!["@lon-pe".into(), "10.0.0.9".into()]).await?`
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        devices::{Device, DeviceChange, DeviceGroup, DeviceGroupInput, GroupKind, GroupMembers},
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        device_filter::DeviceFilter,
+        device_inventory,
+        database::{database_error, parse_column, timestamp, to_json, Database},
+        DeviceInventory, WebSocketService,
+    },
+};
+
+/// `DataUpdate.source` of group change events
+const TOPIC_SOURCE: &str = "device-groups";
+
+/// Prefix marking a group in a device list
+pub const GROUP_PREFIX: char = '@';
+
+const GROUP_COLUMNS: &str = "id, name, description, filter, devices, created_at, updated_at";
+
+/// Device groups stored in the embedded database
+#[derive(Debug)]
+pub struct DeviceGroups {
+    database: Arc<Database>,
+    inventory: Arc<DeviceInventory>,
+    websocket: Arc<WebSocketService>,
+}
+
+impl DeviceGroups {
+    pub fn new(database: Arc<Database>, inventory: Arc<DeviceInventory>, websocket: Arc<WebSocketService>) -> Self {
+        Self {
+            database,
+            inventory,
+            websocket,
+        }
+    }
+
+    /// Every group, ordered by name
+    pub async fn list(&self) -> ApiResult<Vec<DeviceGroup>> {
+        self.database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare(&format!("SELECT {} FROM device_groups ORDER BY name", GROUP_COLUMNS))
+                    .map_err(database_error)?;
+                let groups = statement
+                    .query_map([], read_group)
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+                Ok(groups)
+            })
+            .await
+    }
+
+    /// One group by ID or name
+    pub async fn get(&self, group: &str) -> ApiResult<DeviceGroup> {
+        let group = group.to_string();
+        self.database
+            .blocking(move |connection| {
+                find(connection, &group)?.ok_or_else(|| ApiError::NotFound(format!("Device group '{}' not found", group)))
+            })
+            .await
+    }
+
+    /// A group and the devices it holds now
+    pub async fn members(&self, group: &str) -> ApiResult<GroupMembers> {
+        let group = self.get(group).await?;
+        let devices = self.resolve(&group, self.inventory.all().await?)?;
+        Ok(GroupMembers { group, devices })
+    }
+
+    /// Add a group; fails with `Conflict` if the name is taken
+    pub async fn create(&self, input: DeviceGroupInput) -> ApiResult<DeviceGroup> {
+        let now = chrono::Utc::now();
+        let group = self.build(Uuid::new_v4(), input, now, now).await?;
+
+        let stored = group.clone();
+        self.database
+            .blocking(move |connection| {
+                connection
+                    .execute(
+                        &format!("INSERT INTO device_groups ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", GROUP_COLUMNS),
+                        params![
+                            stored.id.to_string(),
+                            stored.name,
+                            stored.description,
+                            stored.filter,
+                            to_json(&stored.devices)?,
+                            timestamp(&stored.created_at),
+                            timestamp(&stored.updated_at),
+                        ],
+                    )
+                    .map_err(|e| write_error(e, &stored.name))?;
+                Ok(())
+            })
+            .await?;
+
+        info!(group_id = %group.id, name = %group.name, kind = ?group.kind, "Device group created");
+        self.publish(DeviceChange::Created, &group).await;
+        Ok(group)
+    }
+
+    /// Replace a group; fails with `NotFound` if there is none and with `Conflict` when
+    /// renaming it to a taken name
+    pub async fn update(&self, group: &str, input: DeviceGroupInput) -> ApiResult<DeviceGroup> {
+        let existing = self.get(group).await?;
+        let updated = self.build(existing.id, input, existing.created_at, chrono::Utc::now()).await?;
+
+        let stored = updated.clone();
+        self.database
+            .blocking(move |connection| {
+                let changed = connection
+                    .execute(
+                        "UPDATE device_groups SET name = ?2, description = ?3, filter = ?4, devices = ?5, \
+                         updated_at = ?6 WHERE id = ?1",
+                        params![
+                            stored.id.to_string(),
+                            stored.name,
+                            stored.description,
+                            stored.filter,
+                            to_json(&stored.devices)?,
+                            timestamp(&stored.updated_at),
+                        ],
+                    )
+                    .map_err(|e| write_error(e, &stored.name))?;
+                if changed == 0 {
+                    return Err(ApiError::NotFound(format!("Device group '{}' not found", stored.id)));
+                }
+                Ok(())
+            })
+            .await?;
+
+        info!(group_id = %updated.id, name = %updated.name, kind = ?updated.kind, "Device group updated");
+        self.publish(DeviceChange::Updated, &updated).await;
+        Ok(updated)
+    }
+
+    /// Remove a group and its credential assignment; its devices stay in the inventory
+    pub async fn delete(&self, group: &str) -> ApiResult<DeviceGroup> {
+        let group = group.to_string();
+        let removed = self
+            .database
+            .blocking(move |connection| {
+                let existing = find(connection, &group)?
+                    .ok_or_else(|| ApiError::NotFound(format!("Device group '{}' not found", group)))?;
+                let transaction = connection.transaction().map_err(database_error)?;
+                transaction
+                    .execute("DELETE FROM device_groups WHERE id = ?1", params![existing.id.to_string()])
+                    .map_err(database_error)?;
+                transaction
+                    .execute(
+                        "DELETE FROM credential_assignments WHERE target_kind = 'group' AND target_id = ?1",
+                        params![existing.id.to_string()],
+                    )
+                    .map_err(database_error)?;
+                transaction.commit().map_err(database_error)?;
+                Ok(existing)
+            })
+            .await?;
+
+        info!(group_id = %removed.id, name = %removed.name, "Device group deleted");
+        self.publish(DeviceChange::Deleted, &removed).await;
+        Ok(removed)
+    }
+
+    /// Replace every `@group` entry of a device list by the addresses of its members
+    /// Fails on unknown groups, and when the list names groups but ends up empty
+    pub async fn expand_targets(&self, targets: Vec<String>) -> ApiResult<Vec<String>> {
+        if !targets.iter().any(|t| t.trim().starts_with(GROUP_PREFIX)) {
+            return Ok(targets);
+        }
+
+        let devices = self.inventory.all().await?;
+        let mut expanded = Vec::with_capacity(targets.len());
+        let mut groups = Vec::new();
+        for target in targets {
+            let Some(name) = target.trim().strip_prefix(GROUP_PREFIX) else {
+                expanded.push(target);
+                continue;
+            };
+            let group = self.get(name.trim()).await.map_err(|e| match e {
+                ApiError::NotFound(_) => ApiError::ValidationError(format!("Unknown device group '{}'", target.trim())),
+                other => other,
+            })?;
+            let members = self.resolve(&group, devices.clone())?;
+            debug!(group = %group.name, members = members.len(), "Expanded device group");
+            expanded.extend(members.into_iter().map(|device| device.fields.address));
+            groups.push(format!("{}{}", GROUP_PREFIX, group.name));
+        }
+
+        if expanded.iter().all(|t| t.trim().is_empty()) {
+            return Err(ApiError::ValidationError(format!(
+                "Device groups {} have no devices",
+                groups.join(", ")
+            )));
+        }
+        Ok(expanded)
+    }
+
+    /// Groups a device is in, ordered by name
+    pub async fn containing(&self, device: &Device) -> ApiResult<Vec<DeviceGroup>> {
+        let mut groups = Vec::new();
+        for group in self.list().await? {
+            if !self.resolve(&group, vec![device.clone()])?.is_empty() {
+                groups.push(group);
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Members of a group among `devices`
+    fn resolve(&self, group: &DeviceGroup, devices: Vec<Device>) -> ApiResult<Vec<Device>> {
+        match &group.filter {
+            Some(filter) => {
+                let filter = DeviceFilter::parse(filter)?;
+                Ok(devices.into_iter().filter(|device| filter.matches(&device.fields)).collect())
+            }
+            None => Ok(devices.into_iter().filter(|device| group.devices.contains(&device.id)).collect()),
+        }
+    }
+
+    /// Check an input and resolve its members to device IDs
+    async fn build(
+        &self,
+        id: Uuid,
+        input: DeviceGroupInput,
+        created_at: chrono::DateTime<chrono::Utc>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> ApiResult<DeviceGroup> {
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid device group: {}", reason));
+
+        let name = input.name.trim().to_string();
+        if !device_inventory::is_valid_name(&name) {
+            return Err(invalid(format!(
+                "name '{}' must match ^[A-Za-z0-9][A-Za-z0-9._-]*$ and be at most 128 characters",
+                name
+            )));
+        }
+        let filter = input.filter.map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+        if filter.is_some() && !input.devices.is_empty() {
+            return Err(invalid("a group has either a filter or devices, not both".to_string()));
+        }
+
+        let mut devices: Vec<Uuid> = Vec::new();
+        match &filter {
+            Some(filter) => {
+                DeviceFilter::parse(filter)?;
+            }
+            None if !input.devices.is_empty() => {
+                let inventory = self.inventory.all().await?;
+                for member in &input.devices {
+                    let member = member.trim();
+                    let device = inventory
+                        .iter()
+                        .find(|d| d.id.to_string() == member)
+                        .or_else(|| inventory.iter().find(|d| d.fields.name == member))
+                        .ok_or_else(|| invalid(format!("device '{}' is not in the inventory", member)))?;
+                    if !devices.contains(&device.id) {
+                        devices.push(device.id);
+                    }
+                }
+            }
+            None => {}
+        }
+
+        Ok(DeviceGroup {
+            id,
+            name,
+            description: input.description.trim().to_string(),
+            kind: if filter.is_some() { GroupKind::Dynamic } else { GroupKind::Static },
+            filter,
+            devices,
+            created_at,
+            updated_at,
+        })
+    }
+
+    async fn publish(&self, change: DeviceChange, group: &DeviceGroup) {
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data: json!({
+                "event": "device_groups_changed",
+                "change": change,
+                "group_id": group.id,
+                "group": group,
+            }),
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(group_id = %group.id, error = %e, "Failed to publish device group change");
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A group by ID or name
+fn find(connection: &rusqlite::Connection, group: &str) -> ApiResult<Option<DeviceGroup>> {
+    connection
+        .query_row(
+            &format!(
+                "SELECT {} FROM device_groups WHERE id = ?1 OR name = ?1 ORDER BY id = ?1 DESC LIMIT 1",
+                GROUP_COLUMNS
+            ),
+            params![group],
+            read_group,
+        )
+        .optional()
+        .map_err(database_error)
+}
+
+/// Unique name violations become `Conflict`
+fn write_error(e: rusqlite::Error, name: &str) -> ApiError {
+    match &e {
+        rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::ConstraintViolation => {
+            ApiError::Conflict(format!("Device group '{}' already exists", name))
+        }
+        _ => database_error(e),
+    }
+}
+
+fn read_group(row: &Row) -> rusqlite::Result<DeviceGroup> {
+    let time = |index: usize| {
+        parse_column(row, index, |v: String| {
+            chrono::DateTime::parse_from_rfc3339(&v)
+                .ok()
+                .map(|t| t.with_timezone(&chrono::Utc))
+        })
+    };
+    let filter: Option<String> = row.get(3)?;
+
+    Ok(DeviceGroup {
+        id: parse_column(row, 0, |v: String| Uuid::parse_str(&v).ok())?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        kind: if filter.is_some() { GroupKind::Dynamic } else { GroupKind::Static },
+        filter,
+        devices: parse_column(row, 4, |v: String| serde_json::from_str(&v).ok())?,
+        created_at: time(5)?,
+        updated_at: time(6)?,
+    })
+}
@@ -0,0 +1,543 @@
+// backend/src/services/device_inventory.rs
+
+//! # Device Inventory
+//!
+//! ## Description
+//! The devices reports run against: name, management address, vendor, platform, type,
+//! site, rack, tags and administrative status. Devices live in the `devices` table of
+//! the embedded database (`services::database`), and every change is
+//! announced on the `data:devices` topic and to in-process listeners (`subscribe`).
+//!
+//! ## Rules
+//! - Names are unique and match `^[A-Za-z0-9][A-Za-z0-9._-]*$` (at most 128 characters)
+//! - Addresses are non-empty and contain no whitespace
+//! - Tags are trimmed, de-duplicated and may not contain commas (the filter separator)
+//! - A device is addressed by its ID or its name
+//!
+//! ## How to Use
+//! 1. `let inventory = DeviceInventory::new(database, websocket_service);`
+//! 2. `inventory.create(input).await?` - `409` if the name is taken
+//! 3. `inventory.list(DeviceQuery { kind: Some("router".into()), .. }).await?`
+//! 4. Subscribe to `data:devices` for `devices_changed` events
+//! 5. Filter by probe outcome with `DeviceQuery { reachability: Some("unreachable".into()), .. }`
+
+use rusqlite::{params, params_from_iter, OptionalExtension, Row};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        devices::{Device, DeviceChange, DeviceInput, DevicePage, DeviceStatus, DeviceType, DiscoveryState, Reachability},
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        database::{database_error, parse_column, timestamp, to_json, Database, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        WebSocketService,
+    },
+};
+
+/// `DataUpdate.source` of inventory change events
+const TOPIC_SOURCE: &str = "devices";
+
+/// Longest accepted device name
+const MAX_NAME_LENGTH: usize = 128;
+
+const DEVICE_COLUMNS: &str =
+    "id, name, address, vendor, platform, type, site, rack, tags, status, created_at, updated_at, discovery";
+
+/// Inventory changes buffered for in-process listeners such as discovery
+const CHANGE_BUFFER: usize = 256;
+
+/// Filters for listing devices; list filters take comma-separated values, any of which matches
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeviceQuery {
+    /// Case-insensitive text in name, address, vendor, platform, site or rack
+    pub q: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub site: Option<String>,
+    pub tag: Option<String>,
+    pub status: Option<String>,
+    /// `unknown`, `reachable`, `degraded` or `unreachable`
+    pub reachability: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Devices stored in the embedded database
+#[derive(Debug)]
+pub struct DeviceInventory {
+    database: Arc<Database>,
+    websocket: Arc<WebSocketService>,
+    changes: broadcast::Sender<(DeviceChange, Device)>,
+}
+
+impl DeviceInventory {
+    pub fn new(database: Arc<Database>, websocket: Arc<WebSocketService>) -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_BUFFER);
+        Self { database, websocket, changes }
+    }
+
+    /// Every later create, update and delete, in process; lagging listeners miss changes
+    pub fn subscribe(&self) -> broadcast::Receiver<(DeviceChange, Device)> {
+        self.changes.subscribe()
+    }
+
+    /// Page of devices matching the filters, ordered by name
+    pub async fn list(&self, query: DeviceQuery) -> ApiResult<DevicePage> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(ApiError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+        let offset = query.offset.unwrap_or(0);
+
+        let kinds = parse_list::<DeviceType>("type", query.kind.as_deref())?;
+        let statuses = parse_list::<DeviceStatus>("status", query.status.as_deref())?;
+        let reachability = parse_list::<Reachability>("reachability", query.reachability.as_deref())?;
+        let sites = split_list(query.site.as_deref());
+        let tags = split_list(query.tag.as_deref());
+        let text = query.q.map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+
+        self.database
+            .blocking(move |connection| {
+                let mut conditions = Vec::new();
+                let mut values: Vec<String> = Vec::new();
+                let mut any_of = |template: &str, options: Vec<String>| {
+                    if options.is_empty() {
+                        return;
+                    }
+                    let placeholders: Vec<String> = options
+                        .into_iter()
+                        .map(|option| {
+                            values.push(option);
+                            format!("?{}", values.len())
+                        })
+                        .collect();
+                    conditions.push(template.replace("{}", &placeholders.join(", ")));
+                };
+                any_of("type IN ({})", kinds.iter().map(|k| k.as_str().to_string()).collect());
+                any_of("status IN ({})", statuses.iter().map(|s| s.as_str().to_string()).collect());
+                any_of("reachability IN ({})", reachability.iter().map(|r| r.as_str().to_string()).collect());
+                any_of("site IN ({})", sites);
+                any_of("EXISTS (SELECT 1 FROM json_each(devices.tags) WHERE value IN ({}))", tags);
+                if let Some(text) = text {
+                    values.push(format!("%{}%", text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+                    let placeholder = format!("?{}", values.len());
+                    conditions.push(format!(
+                        "({})",
+                        ["name", "address", "vendor", "platform", "site", "rack"]
+                            .iter()
+                            .map(|column| format!("lower({}) LIKE {} ESCAPE '\\'", column, placeholder))
+                            .collect::<Vec<_>>()
+                            .join(" OR ")
+                    ));
+                }
+
+                let clause = if conditions.is_empty() {
+                    String::new()
+                } else {
+                    format!("WHERE {}", conditions.join(" AND "))
+                };
+                let total: i64 = connection
+                    .query_row(
+                        &format!("SELECT COUNT(*) FROM devices {}", clause),
+                        params_from_iter(values.iter()),
+                        |row| row.get(0),
+                    )
+                    .map_err(database_error)?;
+
+                let sql = format!(
+                    "SELECT {} FROM devices {} ORDER BY name LIMIT {} OFFSET {}",
+                    DEVICE_COLUMNS, clause, limit, offset
+                );
+                let mut statement = connection.prepare(&sql).map_err(database_error)?;
+                let devices = statement
+                    .query_map(params_from_iter(values.iter()), read_device)
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+
+                Ok(DevicePage {
+                    total: total as usize,
+                    limit,
+                    offset,
+                    devices,
+                })
+            })
+            .await
+    }
+
+    /// Every device, ordered by name
+    pub async fn all(&self) -> ApiResult<Vec<Device>> {
+        self.database
+            .blocking(|connection| {
+                let mut statement = connection
+                    .prepare(&format!("SELECT {} FROM devices ORDER BY name", DEVICE_COLUMNS))
+                    .map_err(database_error)?;
+                let devices = statement
+                    .query_map([], read_device)
+                    .map_err(database_error)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(database_error)?;
+                Ok(devices)
+            })
+            .await
+    }
+
+    /// Create and replace many devices in one transaction: all of them are written or none
+    /// `updates` name existing devices by ID; inputs must already be normalized
+    pub async fn import(&self, creates: Vec<DeviceInput>, updates: Vec<(Uuid, DeviceInput)>) -> ApiResult<Vec<(DeviceChange, Device)>> {
+        let changed = self
+            .database
+            .blocking(move |connection| {
+                let now = chrono::Utc::now();
+                let transaction = connection.transaction().map_err(database_error)?;
+                let mut changed = Vec::with_capacity(creates.len() + updates.len());
+                for (id, fields) in updates {
+                    let existing = find(&transaction, &id.to_string())?
+                        .ok_or_else(|| ApiError::Conflict(format!("Device '{}' was removed during the import", id)))?;
+                    let device = Device {
+                        id,
+                        fields,
+                        created_at: existing.created_at,
+                        updated_at: now,
+                        discovery: existing.discovery,
+                    };
+                    update_device(&transaction, &device)?;
+                    changed.push((DeviceChange::Updated, device));
+                }
+                for fields in creates {
+                    let device = Device {
+                        id: Uuid::new_v4(),
+                        fields,
+                        created_at: now,
+                        updated_at: now,
+                        discovery: DiscoveryState::default(),
+                    };
+                    insert_device(&transaction, &device)?;
+                    changed.push((DeviceChange::Created, device));
+                }
+                transaction.commit().map_err(database_error)?;
+                Ok(changed)
+            })
+            .await?;
+
+        info!(changed = changed.len(), "Devices imported into inventory");
+        for (change, device) in &changed {
+            self.publish(*change, device).await;
+        }
+        Ok(changed)
+    }
+
+    /// One device by ID or name
+    pub async fn get(&self, device: &str) -> ApiResult<Device> {
+        let device = device.to_string();
+        self.database
+            .blocking(move |connection| {
+                find(connection, &device)?.ok_or_else(|| ApiError::NotFound(format!("Device '{}' not found", device)))
+            })
+            .await
+    }
+
+    /// Add a device; fails with `Conflict` if the name is taken
+    pub async fn create(&self, input: DeviceInput) -> ApiResult<Device> {
+        let input = normalize(input)?;
+        let now = chrono::Utc::now();
+        let device = Device {
+            id: Uuid::new_v4(),
+            fields: input,
+            created_at: now,
+            updated_at: now,
+            discovery: DiscoveryState::default(),
+        };
+
+        let stored = device.clone();
+        self.database
+            .blocking(move |connection| insert_device(connection, &stored))
+            .await?;
+
+        info!(device_id = %device.id, name = %device.fields.name, "Device added to inventory");
+        self.publish(DeviceChange::Created, &device).await;
+        Ok(device)
+    }
+
+    /// Replace every field of a device; fails with `NotFound` if there is none and with
+    /// `Conflict` when renaming it to a taken name
+    pub async fn update(&self, device: &str, input: DeviceInput) -> ApiResult<Device> {
+        let input = normalize(input)?;
+        let device = device.to_string();
+        let updated = self
+            .database
+            .blocking(move |connection| {
+                let existing = find(connection, &device)?
+                    .ok_or_else(|| ApiError::NotFound(format!("Device '{}' not found", device)))?;
+                let updated = Device {
+                    id: existing.id,
+                    fields: input,
+                    created_at: existing.created_at,
+                    updated_at: chrono::Utc::now(),
+                    discovery: existing.discovery,
+                };
+                update_device(connection, &updated)?;
+                Ok(updated)
+            })
+            .await?;
+
+        info!(device_id = %updated.id, name = %updated.fields.name, "Device updated in inventory");
+        self.publish(DeviceChange::Updated, &updated).await;
+        Ok(updated)
+    }
+
+    /// Remove a device, from every static group and its credential assignment; fails with `NotFound` if there is none
+    pub async fn delete(&self, device: &str) -> ApiResult<Device> {
+        let device = device.to_string();
+        let removed = self
+            .database
+            .blocking(move |connection| {
+                let existing = find(connection, &device)?
+                    .ok_or_else(|| ApiError::NotFound(format!("Device '{}' not found", device)))?;
+                let transaction = connection.transaction().map_err(database_error)?;
+                transaction
+                    .execute("DELETE FROM devices WHERE id = ?1", params![existing.id.to_string()])
+                    .map_err(database_error)?;
+                // Static groups list members by ID (`services::device_groups`)
+                transaction
+                    .execute(
+                        "UPDATE device_groups SET devices = \
+                         (SELECT json_group_array(value) FROM json_each(device_groups.devices) WHERE value != ?1) \
+                         WHERE EXISTS (SELECT 1 FROM json_each(device_groups.devices) WHERE value = ?1)",
+                        params![existing.id.to_string()],
+                    )
+                    .map_err(database_error)?;
+                transaction
+                    .execute(
+                        "DELETE FROM credential_assignments WHERE target_kind = 'device' AND target_id = ?1",
+                        params![existing.id.to_string()],
+                    )
+                    .map_err(database_error)?;
+                transaction.commit().map_err(database_error)?;
+                Ok(existing)
+            })
+            .await?;
+
+        info!(device_id = %removed.id, name = %removed.fields.name, "Device removed from inventory");
+        self.publish(DeviceChange::Deleted, &removed).await;
+        Ok(removed)
+    }
+
+    /// Store what probing found; unlike edits this is not announced as a device change
+    /// Returns `false` if the device was removed meanwhile
+    pub async fn record_discovery(&self, id: Uuid, discovery: DiscoveryState) -> ApiResult<bool> {
+        self.database
+            .blocking(move |connection| {
+                let changed = connection
+                    .execute(
+                        "UPDATE devices SET discovery = ?2, reachability = ?3 WHERE id = ?1",
+                        params![id.to_string(), to_json(&discovery)?, discovery.reachability.as_str()],
+                    )
+                    .map_err(database_error)?;
+                Ok(changed > 0)
+            })
+            .await
+    }
+
+    async fn publish(&self, change: DeviceChange, device: &Device) {
+        // No receivers is the normal case without discovery
+        let _ = self.changes.send((change, device.clone()));
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data: json!({
+                "event": "devices_changed",
+                "change": change,
+                "device_id": device.id,
+                "device": device,
+            }),
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(device_id = %device.id, error = %e, "Failed to publish inventory change");
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Check and tidy a submitted device
+pub fn normalize(mut input: DeviceInput) -> ApiResult<DeviceInput> {
+    let invalid = |reason: String| ApiError::ValidationError(format!("Invalid device: {}", reason));
+
+    input.name = input.name.trim().to_string();
+    if !is_valid_name(&input.name) {
+        return Err(invalid(format!(
+            "name '{}' must match ^[A-Za-z0-9][A-Za-z0-9._-]*$ and be at most {} characters",
+            input.name, MAX_NAME_LENGTH
+        )));
+    }
+    input.address = input.address.trim().to_string();
+    if input.address.is_empty() || input.address.contains(char::is_whitespace) {
+        return Err(invalid("address must not be empty or contain whitespace".to_string()));
+    }
+    for field in [&mut input.vendor, &mut input.platform, &mut input.site, &mut input.rack] {
+        *field = field.trim().to_string();
+    }
+
+    let mut tags: Vec<String> = Vec::with_capacity(input.tags.len());
+    for tag in &input.tags {
+        let tag = tag.trim();
+        if tag.is_empty() || tag.contains(',') {
+            return Err(invalid(format!("tag '{}' must not be empty or contain commas", tag)));
+        }
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    input.tags = tags;
+    Ok(input)
+}
+
+/// The device name rule, shared by device groups
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.len() <= MAX_NAME_LENGTH
+        && chars.next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+/// Comma-separated filter values, trimmed, without empties
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Comma-separated enum filter values, rejecting unknown ones
+fn parse_list<T: serde::de::DeserializeOwned>(filter: &str, value: Option<&str>) -> ApiResult<Vec<T>> {
+    split_list(value)
+        .into_iter()
+        .map(|option| {
+            serde_json::from_value(serde_json::Value::String(option.to_lowercase()))
+                .map_err(|_| ApiError::ValidationError(format!("Unknown {} '{}'", filter, option)))
+        })
+        .collect()
+}
+
+fn insert_device(connection: &rusqlite::Connection, device: &Device) -> ApiResult<()> {
+    let fields = &device.fields;
+    connection
+        .execute(
+            &format!(
+                "INSERT INTO devices ({}, reachability) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                DEVICE_COLUMNS
+            ),
+            params![
+                device.id.to_string(),
+                fields.name,
+                fields.address,
+                fields.vendor,
+                fields.platform,
+                fields.kind.as_str(),
+                fields.site,
+                fields.rack,
+                to_json(&fields.tags)?,
+                fields.status.as_str(),
+                timestamp(&device.created_at),
+                timestamp(&device.updated_at),
+                to_json(&device.discovery)?,
+                device.discovery.reachability.as_str(),
+            ],
+        )
+        .map_err(|e| write_error(e, &fields.name))?;
+    Ok(())
+}
+
+fn update_device(connection: &rusqlite::Connection, device: &Device) -> ApiResult<()> {
+    let fields = &device.fields;
+    connection
+        .execute(
+            "UPDATE devices SET name = ?2, address = ?3, vendor = ?4, platform = ?5, type = ?6, \
+             site = ?7, rack = ?8, tags = ?9, status = ?10, updated_at = ?11 WHERE id = ?1",
+            params![
+                device.id.to_string(),
+                fields.name,
+                fields.address,
+                fields.vendor,
+                fields.platform,
+                fields.kind.as_str(),
+                fields.site,
+                fields.rack,
+                to_json(&fields.tags)?,
+                fields.status.as_str(),
+                timestamp(&device.updated_at),
+            ],
+        )
+        .map_err(|e| write_error(e, &fields.name))?;
+    Ok(())
+}
+
+/// A device by ID or name
+fn find(connection: &rusqlite::Connection, device: &str) -> ApiResult<Option<Device>> {
+    connection
+        .query_row(
+            &format!("SELECT {} FROM devices WHERE id = ?1 OR name = ?1 ORDER BY id = ?1 DESC LIMIT 1",
+                DEVICE_COLUMNS),
+            params![device],
+            read_device,
+        )
+        .optional()
+        .map_err(database_error)
+}
+
+/// Unique name violations become `Conflict`
+fn write_error(e: rusqlite::Error, name: &str) -> ApiError {
+    match &e {
+        rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::ConstraintViolation => {
+            ApiError::Conflict(format!("Device '{}' already exists", name))
+        }
+        _ => database_error(e),
+    }
+}
+
+fn read_device(row: &Row) -> rusqlite::Result<Device> {
+    let time = |index: usize| {
+        parse_column(row, index, |v: String| {
+            chrono::DateTime::parse_from_rfc3339(&v)
+                .ok()
+                .map(|t| t.with_timezone(&chrono::Utc))
+        })
+    };
+
+    Ok(Device {
+        id: parse_column(row, 0, |v: String| Uuid::parse_str(&v).ok())?,
+        fields: DeviceInput {
+            name: row.get(1)?,
+            address: row.get(2)?,
+            vendor: row.get(3)?,
+            platform: row.get(4)?,
+            kind: parse_column(row, 5, parse_enum)?,
+            site: row.get(6)?,
+            rack: row.get(7)?,
+            tags: parse_column(row, 8, |v: String| serde_json::from_str(&v).ok())?,
+            status: parse_column(row, 9, parse_enum)?,
+        },
+        created_at: time(10)?,
+        updated_at: time(11)?,
+        discovery: parse_column(row, 12, |v: String| serde_json::from_str(&v).ok())?,
+    })
+}
+
+/// A snake_case enum stored by name
+fn parse_enum<T: serde::de::DeserializeOwned>(value: String) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(value)).ok()
+}
@@ -0,0 +1,362 @@
+// backend/src/services/field_types.rs
+
+//! # Field Types
+//!
+//! ## Description
+//! Converts the raw text extracted for a report field into the type declared in
+//! `reports.yaml` (`FieldSpec::Typed`), so results carry real numbers, booleans,
+//! durations in seconds and normalized addresses the frontend can sort and filter on.
+//! Numeric fields can also be converted between units, e.g. `bytes` to `mbps`.
+//!
+//! ## Units
+//! - Data (quantities and per-second rates share a scale): `bits`, `bytes`, `kilobits`,
+//!   `kilobytes`, `megabits`, `megabytes`, `gigabits`, `gigabytes`, `terabits`,
+//!   `terabytes`, `bps`, `kbps`, `mbps`, `gbps`, `tbps`
+//! - Time: `ms`, `seconds`, `minutes`, `hours`, `days`
+//!
+//! ## How to Use
+//! 1. `let transform = FieldTransform::compile("Input Rate", &spec)?;`
+//! 2. `transform.apply(cell)` - the converted cell, or `Err(raw)` when it does not parse
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::net::IpAddr;
+
+use crate::models::{
+    reports::{FieldSpec, FieldType},
+    ApiError, ApiResult,
+};
+
+/// Measurement families; units only convert within one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Data,
+    Time,
+}
+
+/// Known units with their size in the family's base unit (bits, seconds)
+const UNITS: &[(&str, Dimension, f64)] = &[
+    ("bits", Dimension::Data, 1.0),
+    ("bytes", Dimension::Data, 8.0),
+    ("kilobits", Dimension::Data, 1e3),
+    ("kilobytes", Dimension::Data, 8e3),
+    ("megabits", Dimension::Data, 1e6),
+    ("megabytes", Dimension::Data, 8e6),
+    ("gigabits", Dimension::Data, 1e9),
+    ("gigabytes", Dimension::Data, 8e9),
+    ("terabits", Dimension::Data, 1e12),
+    ("terabytes", Dimension::Data, 8e12),
+    ("bps", Dimension::Data, 1.0),
+    ("kbps", Dimension::Data, 1e3),
+    ("mbps", Dimension::Data, 1e6),
+    ("gbps", Dimension::Data, 1e9),
+    ("tbps", Dimension::Data, 1e12),
+    ("ms", Dimension::Time, 1e-3),
+    ("seconds", Dimension::Time, 1.0),
+    ("minutes", Dimension::Time, 60.0),
+    ("hours", Dimension::Time, 3600.0),
+    ("days", Dimension::Time, 86_400.0),
+];
+
+/// Seconds per Junos duration suffix
+const DURATION_SUFFIXES: [(char, u64); 5] = [('w', 604_800), ('d', 86_400), ('h', 3600), ('m', 60), ('s', 1)];
+
+/// A field's declared type, validated once per report
+#[derive(Debug, Clone, Default)]
+pub struct FieldTransform {
+    kind: FieldType,
+    map: IndexMap<String, Value>,
+    /// Multiplier from the source unit to the target unit
+    scale: Option<f64>,
+    unit: Option<String>,
+    precision: Option<u32>,
+}
+
+impl FieldTransform {
+    /// Validate a field declaration; `name` is the column display name used in errors
+    pub fn compile(name: &str, spec: &FieldSpec) -> ApiResult<Self> {
+        let FieldSpec::Typed { kind, map, unit, precision, .. } = spec else {
+            return Ok(Self::default());
+        };
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid field '{}': {}", name, reason));
+
+        match kind {
+            FieldType::Enum if map.is_empty() => return Err(invalid("type enum requires a map".to_string())),
+            FieldType::Enum => {}
+            _ if !map.is_empty() => return Err(invalid("map is only allowed with type enum".to_string())),
+            _ => {}
+        }
+        if precision.is_some() && *kind != FieldType::Float {
+            return Err(invalid("precision is only allowed with type float".to_string()));
+        }
+
+        let scale = match unit {
+            None => None,
+            Some(_) if !kind.is_numeric() => {
+                return Err(invalid("unit is only allowed with type integer or float".to_string()))
+            }
+            Some(conversion) => {
+                let (from_dimension, from_size) = lookup_unit(&conversion.from).ok_or_else(|| unknown_unit(name, &conversion.from))?;
+                let (to_dimension, to_size) = lookup_unit(&conversion.to).ok_or_else(|| unknown_unit(name, &conversion.to))?;
+                if from_dimension != to_dimension {
+                    return Err(invalid(format!(
+                        "cannot convert {} to {}",
+                        conversion.from, conversion.to
+                    )));
+                }
+                Some(from_size / to_size)
+            }
+        };
+
+        Ok(Self {
+            kind: *kind,
+            map: map.clone(),
+            scale,
+            unit: unit.as_ref().map(|u| u.to.clone()),
+            precision: *precision,
+        })
+    }
+
+    pub fn kind(&self) -> FieldType {
+        self.kind
+    }
+
+    /// Unit of the converted values
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    /// Convert one extracted cell; `null` passes through, unparsable values come back as `Err(raw)`
+    pub fn apply(&self, cell: Value) -> Result<Value, String> {
+        if cell.is_null() || self.kind == FieldType::String {
+            return Ok(cell);
+        }
+        let raw = match &cell {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let converted = match self.kind {
+            FieldType::String => Some(cell),
+            FieldType::Integer => parse_number(&raw)
+                .map(|n| n * self.scale.unwrap_or(1.0))
+                .filter(|n| self.scale.is_some() || n.fract() == 0.0)
+                .map(|n| Value::from(n.round() as i64)),
+            FieldType::Float => parse_number(&raw)
+                .map(|n| round(n * self.scale.unwrap_or(1.0), self.precision))
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            FieldType::Boolean => parse_boolean(&raw).map(Value::Bool),
+            FieldType::Duration => parse_duration(&raw).map(Value::from),
+            FieldType::Ip => normalize_ip(&raw).map(Value::String),
+            FieldType::Enum => Some(self.map.get(&raw).cloned().unwrap_or(cell)),
+        };
+        converted.ok_or(raw)
+    }
+}
+
+fn lookup_unit(name: &str) -> Option<(Dimension, f64)> {
+    let name = name.trim().to_ascii_lowercase();
+    UNITS
+        .iter()
+        .find(|(unit, _, _)| *unit == name)
+        .map(|(_, dimension, size)| (*dimension, *size))
+}
+
+fn unknown_unit(field: &str, unit: &str) -> ApiError {
+    ApiError::ValidationError(format!(
+        "Invalid field '{}': unknown unit '{}'; known units: {}",
+        field,
+        unit,
+        UNITS.iter().map(|(name, _, _)| *name).collect::<Vec<_>>().join(", ")
+    ))
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+fn round(value: f64, precision: Option<u32>) -> f64 {
+    match precision {
+        Some(places) => {
+            let factor = 10f64.powi(places as i32);
+            (value * factor).round() / factor
+        }
+        None => value,
+    }
+}
+
+fn parse_boolean(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "up" | "enabled" | "on" | "1" => Some(true),
+        "false" | "no" | "down" | "disabled" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whole seconds from Junos-style durations: `3w2d 04:12:11`, `1d 2h 3m 4s`, `04:12:11`,
+/// `12:11` (minutes and seconds) or a plain number of seconds
+fn parse_duration(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Ok(seconds) = text.parse::<u64>() {
+        return Some(seconds);
+    }
+
+    let mut total = 0u64;
+    let mut seen = false;
+    for token in text.split_whitespace() {
+        if token.contains(':') {
+            let parts = token
+                .split(':')
+                .map(|part| part.parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            total += match parts.as_slice() {
+                [minutes, seconds] => minutes * 60 + seconds,
+                [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+                _ => return None,
+            };
+            seen = true;
+            continue;
+        }
+
+        let mut number = String::new();
+        for ch in token.chars() {
+            if ch.is_ascii_digit() {
+                number.push(ch);
+                continue;
+            }
+            let (_, seconds) = DURATION_SUFFIXES.iter().find(|(suffix, _)| *suffix == ch)?;
+            total += number.parse::<u64>().ok()? * seconds;
+            number.clear();
+            seen = true;
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    seen.then_some(total)
+}
+
+/// Canonical form of an address or prefix; Junos appends the TCP port to BGP peers (`10.0.0.1+179`)
+fn normalize_ip(text: &str) -> Option<String> {
+    let text = text.trim();
+    let text = match text.rsplit_once('+') {
+        Some((address, port)) if port.parse::<u16>().is_ok() => address,
+        _ => text,
+    };
+    let (address, prefix) = match text.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u8>().ok()?)),
+        None => (text, None),
+    };
+
+    let address: IpAddr = address.parse().ok()?;
+    match prefix {
+        Some(length) if length > if address.is_ipv4() { 32 } else { 128 } => None,
+        Some(length) => Some(format!("{}/{}", address, length)),
+        None => Some(address.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transform(yaml: &str) -> FieldTransform {
+        FieldTransform::compile("Field", &serde_yaml::from_str(yaml).unwrap()).unwrap()
+    }
+
+    fn compile_error(yaml: &str) -> String {
+        FieldTransform::compile("Field", &serde_yaml::from_str(yaml).unwrap())
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn integers() {
+        let integer = transform("{path: x, type: integer}");
+        assert_eq!(integer.apply(json!("65002")), Ok(json!(65002)));
+        assert_eq!(integer.apply(json!(" -3 ")), Ok(json!(-3)));
+        assert_eq!(integer.apply(json!(12)), Ok(json!(12)));
+        assert_eq!(integer.apply(json!("1.5")), Err("1.5".to_string()));
+        assert_eq!(integer.apply(json!("unknown")), Err("unknown".to_string()));
+        assert_eq!(integer.apply(Value::Null), Ok(Value::Null));
+    }
+
+    #[test]
+    fn floats_with_units_and_precision() {
+        let float = transform("{path: x, type: float, precision: 2}");
+        assert_eq!(float.apply(json!("2.71828")), Ok(json!(2.72)));
+        assert_eq!(float.apply(json!("NaN")), Err("NaN".to_string()));
+        assert_eq!(float.apply(json!("fast")), Err("fast".to_string()));
+
+        let rate = transform("{path: x, type: float, unit: {from: bytes, to: mbps}}");
+        assert_eq!(rate.unit(), Some("mbps"));
+        assert_eq!(rate.apply(json!("250000")), Ok(json!(2.0)));
+
+        // A scaled integer rounds instead of rejecting the fraction
+        let kilobits = transform("{path: x, type: integer, unit: {from: bits, to: kilobits}}");
+        assert_eq!(kilobits.apply(json!("1499")), Ok(json!(1)));
+    }
+
+    #[test]
+    fn booleans() {
+        let boolean = transform("{path: x, type: boolean}");
+        for raw in ["true", "Yes", "UP", "enabled", "on", "1"] {
+            assert_eq!(boolean.apply(json!(raw)), Ok(json!(true)), "{}", raw);
+        }
+        for raw in ["false", "no", "Down", "disabled", "off", "0"] {
+            assert_eq!(boolean.apply(json!(raw)), Ok(json!(false)), "{}", raw);
+        }
+        assert_eq!(boolean.apply(json!("maybe")), Err("maybe".to_string()));
+    }
+
+    #[test]
+    fn durations() {
+        let duration = transform("{path: x, type: duration}");
+        assert_eq!(duration.apply(json!("3600")), Ok(json!(3600)));
+        assert_eq!(duration.apply(json!("1:00:00")), Ok(json!(3600)));
+        assert_eq!(duration.apply(json!("12:11")), Ok(json!(731)));
+        assert_eq!(duration.apply(json!("1d 2h 3m 4s")), Ok(json!(93_784)));
+        assert_eq!(duration.apply(json!("3w2d 04:12:11")), Ok(json!(2_002_331)));
+        for raw in ["", "soon", "5x", "1:2:3:4", "12h 30"] {
+            assert_eq!(duration.apply(json!(raw)), Err(raw.to_string()), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn addresses() {
+        let ip = transform("{path: x, type: ip}");
+        assert_eq!(ip.apply(json!("10.0.0.2+179")), Ok(json!("10.0.0.2")));
+        assert_eq!(ip.apply(json!(" 192.168.1.0/24 ")), Ok(json!("192.168.1.0/24")));
+        assert_eq!(ip.apply(json!("2001:DB8:0::1")), Ok(json!("2001:db8::1")));
+        assert_eq!(ip.apply(json!("2001:db8::/64")), Ok(json!("2001:db8::/64")));
+        for raw in ["10.0.0.256", "10.0.0.0/33", "2001:db8::/129", "10.0.0.1/x", "router1"] {
+            assert_eq!(ip.apply(json!(raw)), Err(raw.to_string()), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn enums_translate_known_values_and_keep_others() {
+        let state = transform("{path: x, type: enum, map: {Established: up, Active: 2}}");
+        assert_eq!(state.apply(json!("Established")), Ok(json!("up")));
+        assert_eq!(state.apply(json!("Active")), Ok(json!(2)));
+        assert_eq!(state.apply(json!("Idle")), Ok(json!("Idle")));
+    }
+
+    #[test]
+    fn strings_pass_through() {
+        let plain = FieldTransform::compile("Field", &FieldSpec::Path("x".to_string())).unwrap();
+        assert_eq!(plain.kind(), FieldType::String);
+        assert_eq!(plain.apply(json!(" anything ")), Ok(json!(" anything ")));
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        assert!(compile_error("{path: x, type: enum}").contains("type enum requires a map"));
+        assert!(compile_error("{path: x, type: integer, map: {a: b}}").contains("map is only allowed"));
+        assert!(compile_error("{path: x, type: integer, precision: 1}").contains("precision is only allowed"));
+        assert!(compile_error("{path: x, type: boolean, unit: {from: bits, to: bytes}}").contains("unit is only allowed"));
+        assert!(compile_error("{path: x, type: float, unit: {from: furlongs, to: bytes}}").contains("unknown unit 'furlongs'"));
+        assert!(compile_error("{path: x, type: float, unit: {from: bytes, to: seconds}}").contains("cannot convert"));
+    }
+}
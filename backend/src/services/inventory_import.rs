@@ -0,0 +1,855 @@
+// backend/src/services/inventory_import.rs
+
+//! # Inventory Import
+//!
+//! ## Description
+//! Loads many devices at once from spreadsheets and Ansible inventories. A source is
+//! parsed into records, each record's columns are mapped onto device fields, and the
+//! result is planned against the inventory: every record becomes a `create`, `update`,
+//! `unchanged`, `conflict` or `error` row carrying its source line. Nothing is written
+//! unless the import is committed, and then all creates and updates land in one
+//! transaction.
+//!
+//! ## Formats
+//! - `csv` - a header row, then one device per row
+//! - `yaml` - a list of devices, or a mapping of names to devices
+//! - `ansible_ini` - `hosts` files: `[group]`, `[group:vars]` and `[group:children]`
+//!   sections, `host key=value` lines and numeric ranges such as `leaf[01:04]`, up to
+//!   `MAX_EXPANDED_HOSTS` hosts per pattern and per source
+//! - `ansible_yaml` - `all: { hosts, vars, children }` trees
+//!
+//! Without an explicit format, Ansible INI is recognized by `[section]` or
+//! `ansible_host=` lines, YAML by parsing to a list or mapping (Ansible when it has
+//! `all`, `hosts` or `children` groups), and anything else is read as CSV.
+//!
+//! ## Mapping
+//! Columns (CSV headers, YAML keys, Ansible host variables) are matched to fields
+//! case-insensitively, with spaces and dashes read as `_`. Well-known names map on
+//! their own (`hostname` -> `name`, `ansible_host`/`mgmt_ip` -> `address`,
+//! `ansible_network_os`/`model` -> `platform`, ...); `mapping` (`field:column`) wins
+//! over them. Ansible hosts default their address to their name and are tagged with
+//! their groups, including parent groups.
+//!
+//! ## Conflicts
+//! A record is a conflict when an earlier record has the same name, or when its address
+//! belongs to another device (in the inventory or earlier in the source). A commit with
+//! conflicts or errors is refused unless `skip_invalid` is set, which writes the rest.
+//!
+//! ## How to Use
+//! 1. `let options = ImportOptions { format: None, mapping: parse_mapping("name:Host")?, .. };`
+//! 2. `let report = import(&inventory, &text, &options).await?;` - a plan by default
+//! 3. Set `options.commit` to write it
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        devices::{
+            Device, DeviceInput, DeviceStatus, DeviceType, ImportAction, ImportFormat, ImportReport, ImportRow,
+            ImportSummary,
+        },
+        ApiError, ApiResult,
+    },
+    services::device_inventory::{self, DeviceInventory},
+};
+
+/// Device fields a column can map to, with the column names recognized for each
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+    ("name", &["name", "hostname", "host", "device", "device_name", "inventory_hostname"]),
+    (
+        "address",
+        &["address", "ip", "ip_address", "mgmt_ip", "management_ip", "management_address", "ansible_host"],
+    ),
+    ("vendor", &["vendor", "manufacturer", "make"]),
+    ("platform", &["platform", "model", "os", "ansible_network_os"]),
+    ("type", &["type", "device_type", "role"]),
+    ("site", &["site", "location", "datacenter"]),
+    ("rack", &["rack"]),
+    ("tags", &["tags", "labels"]),
+    ("status", &["status", "state"]),
+];
+
+/// Ansible groups every host is in; not useful as tags
+const IMPLICIT_GROUPS: &[&str] = &["all", "ungrouped"];
+
+/// Largest import source accepted
+pub const MAX_IMPORT_BYTES: usize = 2 * 1024 * 1024;
+/// Hosts an Ansible range pattern may expand to, and all patterns of a source together
+pub const MAX_EXPANDED_HOSTS: u64 = 10_000;
+
+/// How to read and apply one import
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportOptions {
+    /// Detected from the content when absent
+    pub format: Option<ImportFormat>,
+    /// Device field -> source column, overriding the well-known names
+    #[serde(default)]
+    pub mapping: HashMap<String, String>,
+    /// Write the creates and updates; otherwise only plan them
+    #[serde(default)]
+    pub commit: bool,
+    /// Commit the valid rows even when others are conflicts or errors
+    #[serde(default)]
+    pub skip_invalid: bool,
+}
+
+/// `field:column,field:column` as a mapping; fields must be device fields
+pub fn parse_mapping(text: &str) -> ApiResult<HashMap<String, String>> {
+    let mut mapping = HashMap::new();
+    for pair in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (field, column) = pair
+            .split_once(':')
+            .ok_or_else(|| ApiError::ValidationError(format!("Mapping '{}' must look like field:column", pair)))?;
+        mapping.insert(field.trim().to_string(), column.trim().to_string());
+    }
+    Ok(mapping)
+}
+
+/// Parse, map and plan `text`; with `options.commit`, also write the plan
+pub async fn import(inventory: &DeviceInventory, text: &str, options: &ImportOptions) -> ApiResult<ImportReport> {
+    if text.len() > MAX_IMPORT_BYTES {
+        return Err(ApiError::PayloadTooLarge(format!(
+            "Import source is {} bytes; the limit is {}",
+            text.len(),
+            MAX_IMPORT_BYTES
+        )));
+    }
+    for field in options.mapping.keys() {
+        if !FIELD_ALIASES.iter().any(|(name, _)| name == field) {
+            return Err(ApiError::ValidationError(format!(
+                "Unknown device field '{}' in mapping; fields: {}",
+                field,
+                FIELD_ALIASES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+            )));
+        }
+    }
+
+    let format = options.format.unwrap_or_else(|| detect(text));
+    let records = match format {
+        ImportFormat::Csv => parse_csv(text)?,
+        ImportFormat::Yaml => parse_yaml(text)?,
+        ImportFormat::AnsibleIni => parse_ansible_ini(text)?,
+        ImportFormat::AnsibleYaml => parse_ansible_yaml(text)?,
+    };
+    info!(format = format.as_str(), records = records.len(), commit = options.commit, "Importing devices");
+
+    let existing = inventory.all().await?;
+    let rows = plan(records, &options.mapping, &existing);
+    let mut summary = ImportSummary::default();
+    for row in &rows {
+        match row.action {
+            ImportAction::Create => summary.create += 1,
+            ImportAction::Update => summary.update += 1,
+            ImportAction::Unchanged => summary.unchanged += 1,
+            ImportAction::Conflict => summary.conflict += 1,
+            ImportAction::Error => summary.error += 1,
+        }
+    }
+
+    let mut report = ImportReport {
+        format,
+        committed: false,
+        message: None,
+        summary,
+        rows,
+    };
+    if !options.commit {
+        return Ok(report);
+    }
+    if (summary.conflict > 0 || summary.error > 0) && !options.skip_invalid {
+        warn!(conflicts = summary.conflict, errors = summary.error, "Device import not committed");
+        report.message = Some(format!(
+            "Not committed: {} conflicts and {} errors; fix them or commit with skip_invalid",
+            summary.conflict, summary.error
+        ));
+        return Ok(report);
+    }
+
+    let mut creates = Vec::new();
+    let mut updates = Vec::new();
+    for row in &report.rows {
+        match (row.action, &row.device, row.device_id) {
+            (ImportAction::Create, Some(device), _) => creates.push(device.clone()),
+            (ImportAction::Update, Some(device), Some(id)) => updates.push((id, device.clone())),
+            _ => {}
+        }
+    }
+    let written = inventory.import(creates, updates).await?;
+    // Attach the IDs of created devices to their rows
+    let ids: HashMap<&str, Uuid> = written.iter().map(|(_, d)| (d.fields.name.as_str(), d.id)).collect();
+    for row in &mut report.rows {
+        if row.action == ImportAction::Create {
+            row.device_id = row.name.as_deref().and_then(|name| ids.get(name).copied());
+        }
+    }
+    report.committed = true;
+    Ok(report)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// PLANNING
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// One source record: where it starts and its columns
+#[derive(Debug, Clone, Default)]
+struct Record {
+    line: usize,
+    values: IndexMap<String, Value>,
+    /// Tags implied by the source (Ansible groups)
+    tags: Vec<String>,
+    /// A problem found while parsing the record
+    error: Option<String>,
+}
+
+fn plan(records: Vec<Record>, mapping: &HashMap<String, String>, existing: &[Device]) -> Vec<ImportRow> {
+    let by_name: HashMap<&str, &Device> = existing.iter().map(|d| (d.fields.name.as_str(), d)).collect();
+    let mut names: HashMap<String, usize> = HashMap::new();
+    let mut addresses: HashMap<String, usize> = HashMap::new();
+
+    records
+        .into_iter()
+        .map(|record| {
+            let line = record.line;
+            let mut row = ImportRow {
+                line,
+                name: None,
+                action: ImportAction::Error,
+                device: None,
+                device_id: None,
+                changes: Vec::new(),
+                message: None,
+            };
+            let device = match to_device(record, mapping) {
+                Ok(device) => device,
+                Err((name, message)) => {
+                    row.name = name;
+                    row.message = Some(message);
+                    return row;
+                }
+            };
+            row.name = Some(device.name.clone());
+            let current = by_name.get(device.name.as_str()).copied();
+
+            let conflict = if let Some(first) = names.get(&device.name) {
+                Some(format!("name '{}' is also on line {}", device.name, first))
+            } else if let Some(first) = addresses.get(&device.address) {
+                Some(format!("address '{}' is also on line {}", device.address, first))
+            } else {
+                existing
+                    .iter()
+                    .find(|d| d.fields.address == device.address && d.fields.name != device.name)
+                    .map(|other| format!("address '{}' belongs to device '{}'", device.address, other.fields.name))
+            };
+            names.entry(device.name.clone()).or_insert(line);
+            addresses.entry(device.address.clone()).or_insert(line);
+
+            row.device_id = current.map(|d| d.id);
+            if let Some(message) = conflict {
+                row.action = ImportAction::Conflict;
+                row.message = Some(message);
+            } else if let Some(current) = current {
+                row.changes = changed_fields(&current.fields, &device);
+                row.action = if row.changes.is_empty() { ImportAction::Unchanged } else { ImportAction::Update };
+            } else {
+                row.action = ImportAction::Create;
+            }
+            row.device = Some(device);
+            row
+        })
+        .collect()
+}
+
+/// Map a record onto a normalized device; errors carry the name when one was found
+fn to_device(record: Record, mapping: &HashMap<String, String>) -> Result<DeviceInput, (Option<String>, String)> {
+    let columns: HashMap<String, &Value> = record.values.iter().map(|(k, v)| (column_key(k), v)).collect();
+    let value = |field: &str| -> Option<&Value> {
+        if let Some(column) = mapping.get(field) {
+            return columns.get(&column_key(column)).copied();
+        }
+        let aliases = FIELD_ALIASES.iter().find(|(name, _)| *name == field).map_or(&[][..], |(_, a)| a);
+        aliases.iter().find_map(|alias| columns.get(*alias).copied())
+    };
+    let text = |field: &str| value(field).map(scalar_text).unwrap_or_default();
+
+    let name = Some(text("name")).filter(|n| !n.is_empty());
+    let fail = |message: String| Err((name.clone(), message));
+    if let Some(error) = record.error {
+        return fail(error);
+    }
+    let Some(device_name) = name.clone() else {
+        return fail("no name; map a column to 'name'".to_string());
+    };
+    let address = text("address");
+    if address.is_empty() {
+        return fail("no address; map a column to 'address'".to_string());
+    }
+
+    let kind = match text("type").to_lowercase().as_str() {
+        "" => DeviceType::default(),
+        other => match serde_json::from_value(Value::String(other.to_string())) {
+            Ok(kind) => kind,
+            Err(_) => return fail(format!("unknown type '{}'", other)),
+        },
+    };
+    let status = match text("status").to_lowercase().as_str() {
+        "" => DeviceStatus::default(),
+        other => match serde_json::from_value(Value::String(other.to_string())) {
+            Ok(status) => status,
+            Err(_) => return fail(format!("unknown status '{}'", other)),
+        },
+    };
+    let mut tags = record.tags;
+    match value("tags") {
+        Some(Value::Array(items)) => tags.extend(items.iter().map(scalar_text)),
+        Some(other) => tags.extend(scalar_text(other).split([',', ';']).map(str::to_string)),
+        None => {}
+    }
+    tags.retain(|tag| !tag.trim().is_empty());
+
+    device_inventory::normalize(DeviceInput {
+        name: device_name,
+        address,
+        vendor: text("vendor"),
+        platform: text("platform"),
+        kind,
+        site: text("site"),
+        rack: text("rack"),
+        tags,
+        status,
+    })
+    .map_err(|e| match e {
+        ApiError::ValidationError(message) => (name.clone(), message),
+        other => (name.clone(), other.to_string()),
+    })
+}
+
+fn changed_fields(current: &DeviceInput, new: &DeviceInput) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, same: bool| {
+        if !same {
+            changes.push(field.to_string());
+        }
+    };
+    compare("address", current.address == new.address);
+    compare("vendor", current.vendor == new.vendor);
+    compare("platform", current.platform == new.platform);
+    compare("type", current.kind == new.kind);
+    compare("site", current.site == new.site);
+    compare("rack", current.rack == new.rack);
+    compare("tags", current.tags == new.tags);
+    compare("status", current.status == new.status);
+    changes
+}
+
+/// Lower case with spaces and dashes as `_`
+fn column_key(column: &str) -> String {
+    column.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// FORMATS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Guess the format of an import from its content
+pub fn detect(text: &str) -> ImportFormat {
+    let lines = || text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with(['#', ';']));
+    if lines().next().is_some_and(|l| l.starts_with('[') && l.ends_with(']')) || lines().any(|l| l.contains("ansible_host=")) {
+        return ImportFormat::AnsibleIni;
+    }
+    match serde_yaml::from_str::<Value>(text) {
+        Ok(Value::Object(map)) if map.contains_key("all") || map.values().any(is_ansible_group) => {
+            ImportFormat::AnsibleYaml
+        }
+        Ok(Value::Object(_)) | Ok(Value::Array(_)) => ImportFormat::Yaml,
+        _ => ImportFormat::Csv,
+    }
+}
+
+fn is_ansible_group(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|group| group.contains_key("hosts") || group.contains_key("children"))
+}
+
+fn parse_csv(text: &str) -> ApiResult<Vec<Record>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(text.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| ApiError::ValidationError(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    let mut records = Vec::new();
+    for result in reader.records() {
+        match result {
+            Ok(row) => {
+                if row.iter().all(str::is_empty) {
+                    continue;
+                }
+                let line = row.position().map_or(0, |p| p.line() as usize);
+                let values = headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(header, cell)| (header.to_string(), Value::String(cell.to_string())))
+                    .collect();
+                records.push(Record { line, values, ..Record::default() });
+            }
+            Err(e) => records.push(Record {
+                line: e.position().map_or(0, |p| p.line() as usize),
+                error: Some(format!("unreadable CSV row: {}", e)),
+                ..Record::default()
+            }),
+        }
+    }
+    Ok(records)
+}
+
+fn parse_yaml(text: &str) -> ApiResult<Vec<Record>> {
+    let document: Value = serde_yaml::from_str(text).map_err(|e| ApiError::YamlParseError(e.to_string()))?;
+    let mut locator = Locator::new(text);
+    let entries: Vec<(Option<String>, Value)> = match document {
+        Value::Array(items) => items.into_iter().map(|item| (None, item)).collect(),
+        Value::Object(map) => map.into_iter().map(|(name, item)| (Some(name), item)).collect(),
+        _ => return Err(ApiError::ValidationError("A YAML import must be a list or a mapping of devices".to_string())),
+    };
+
+    Ok(entries
+        .into_iter()
+        .map(|(key, item)| {
+            let Value::Object(fields) = item else {
+                return Record {
+                    line: key.as_deref().map_or(0, |k| locator.key(k)),
+                    error: Some("a device must be a mapping of fields".to_string()),
+                    ..Record::default()
+                };
+            };
+            let mut values: IndexMap<String, Value> = fields.into_iter().collect();
+            if let Some(key) = &key {
+                values.entry("name".to_string()).or_insert_with(|| Value::String(key.clone()));
+            }
+            let line = match &key {
+                Some(key) => locator.key(key),
+                None => locator.item(values.values().next().map(scalar_text).as_deref().unwrap_or_default()),
+            };
+            Record { line, values, ..Record::default() }
+        })
+        .collect())
+}
+
+/// An Ansible host while groups are being collected
+#[derive(Debug, Default)]
+struct AnsibleHost {
+    line: usize,
+    vars: IndexMap<String, Value>,
+    groups: Vec<String>,
+    error: Option<String>,
+}
+
+/// Ansible hosts in first-seen order, with group variables and parent groups applied
+#[derive(Debug, Default)]
+struct AnsibleInventory {
+    hosts: IndexMap<String, AnsibleHost>,
+    group_vars: HashMap<String, IndexMap<String, Value>>,
+    /// child group -> parent groups
+    parents: HashMap<String, Vec<String>>,
+}
+
+impl AnsibleInventory {
+    fn add_host(&mut self, name: &str, group: &str, line: usize, vars: IndexMap<String, Value>) {
+        let host = self.hosts.entry(name.to_string()).or_insert_with(|| AnsibleHost {
+            line,
+            ..AnsibleHost::default()
+        });
+        host.vars.extend(vars);
+        if !host.groups.iter().any(|g| g == group) {
+            host.groups.push(group.to_string());
+        }
+    }
+
+    /// Groups of a host and all their ancestors, in order, without duplicates
+    fn ancestry(&self, groups: &[String]) -> Vec<String> {
+        let mut seen: Vec<String> = Vec::new();
+        let mut pending: Vec<String> = groups.to_vec();
+        while let Some(group) = pending.first().cloned() {
+            pending.remove(0);
+            if seen.contains(&group) {
+                continue;
+            }
+            if let Some(parents) = self.parents.get(&group) {
+                pending.extend(parents.iter().cloned());
+            }
+            seen.push(group);
+        }
+        seen
+    }
+
+    fn into_records(self) -> Vec<Record> {
+        let mut records = Vec::with_capacity(self.hosts.len());
+        for (name, host) in &self.hosts {
+            let groups = self.ancestry(&host.groups);
+            // Outermost groups first, so nearer groups and the host itself override them
+            let mut values: IndexMap<String, Value> = IndexMap::new();
+            for group in groups.iter().rev() {
+                if let Some(vars) = self.group_vars.get(group) {
+                    values.extend(vars.clone());
+                }
+            }
+            values.extend(host.vars.clone());
+            values.insert("inventory_hostname".to_string(), Value::String(name.clone()));
+            let has_address = values
+                .keys()
+                .any(|k| FIELD_ALIASES[1].1.contains(&column_key(k).as_str()));
+            if !has_address {
+                values.insert("ansible_host".to_string(), Value::String(name.clone()));
+            }
+
+            records.push(Record {
+                line: host.line,
+                values,
+                tags: groups.into_iter().filter(|g| !IMPLICIT_GROUPS.contains(&g.as_str())).collect(),
+                error: host.error.clone(),
+            });
+        }
+        records
+    }
+}
+
+fn parse_ansible_ini(text: &str) -> ApiResult<Vec<Record>> {
+    enum Section {
+        Hosts(String),
+        Vars(String),
+        Children(String),
+    }
+
+    let mut inventory = AnsibleInventory::default();
+    let mut section = Section::Hosts("ungrouped".to_string());
+    let mut errors = Vec::new();
+    // Distinct hosts added by accepted range patterns so far, against `MAX_EXPANDED_HOSTS`
+    let mut expanded: u64 = 0;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.trim();
+        if content.is_empty() || content.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(header) = content.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+            section = match header.split_once(':') {
+                Some((group, "vars")) => Section::Vars(group.to_string()),
+                Some((group, "children")) => Section::Children(group.to_string()),
+                Some((_, kind)) => {
+                    errors.push(Record {
+                        line,
+                        error: Some(format!("unknown section kind ':{}'", kind)),
+                        ..Record::default()
+                    });
+                    Section::Vars(String::new())
+                }
+                None => Section::Hosts(header.to_string()),
+            };
+            continue;
+        }
+
+        match &section {
+            Section::Hosts(group) => {
+                let (pattern, rest) = content.split_once(char::is_whitespace).unwrap_or((content, ""));
+                let vars = match parse_ini_vars(rest) {
+                    Ok(vars) => vars,
+                    Err(message) => {
+                        errors.push(Record { line, error: Some(message), ..Record::default() });
+                        continue;
+                    }
+                };
+                let names = expand_host_pattern(pattern).and_then(|names| {
+                    if names.len() <= 1 {
+                        return Ok(names);
+                    }
+                    let added = names
+                        .iter()
+                        .filter(|name| !inventory.hosts.contains_key(name.as_str()))
+                        .count() as u64;
+                    if expanded + added > MAX_EXPANDED_HOSTS {
+                        return Err(format!(
+                            "host ranges expand to more than {} hosts in total ({} added so far)",
+                            MAX_EXPANDED_HOSTS, expanded
+                        ));
+                    }
+                    expanded += added;
+                    Ok(names)
+                });
+                match names {
+                    Ok(names) => {
+                        for name in names {
+                            inventory.add_host(&name, group, line, vars.clone());
+                        }
+                    }
+                    Err(message) => {
+                        inventory.add_host(pattern, group, line, vars);
+                        if let Some(host) = inventory.hosts.get_mut(pattern) {
+                            host.error = Some(message);
+                        }
+                    }
+                }
+            }
+            Section::Vars(group) => {
+                if group.is_empty() {
+                    continue;
+                }
+                match parse_ini_vars(content) {
+                    Ok(vars) => inventory.group_vars.entry(group.clone()).or_default().extend(vars),
+                    Err(message) => errors.push(Record { line, error: Some(message), ..Record::default() }),
+                }
+            }
+            Section::Children(group) => {
+                inventory.parents.entry(content.to_string()).or_default().push(group.clone());
+            }
+        }
+    }
+
+    let mut records = inventory.into_records();
+    records.extend(errors);
+    records.sort_by_key(|record| record.line);
+    Ok(records)
+}
+
+/// `key=value key="quoted value"` pairs of an INI host or vars line
+fn parse_ini_vars(text: &str) -> Result<IndexMap<String, Value>, String> {
+    let mut vars = IndexMap::new();
+    let mut chars = text.trim().chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() || chars.peek() == Some(&'#') {
+            return Ok(vars);
+        }
+        let key: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=' && !c.is_whitespace())).collect();
+        if chars.next() != Some('=') {
+            return Err(format!("'{}' is not a key=value pair", key));
+        }
+        let value: String = match chars.peek().copied() {
+            Some(quote @ ('"' | '\'')) => {
+                chars.next();
+                let value = std::iter::from_fn(|| chars.next_if(|c| *c != quote)).collect();
+                if chars.next() != Some(quote) {
+                    return Err(format!("unterminated quote in '{}'", key));
+                }
+                value
+            }
+            _ => std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect(),
+        };
+        vars.insert(key, Value::String(value));
+    }
+}
+
+/// `leaf[01:04].lab` -> `leaf01.lab` .. `leaf04.lab`; names without a range are kept
+/// Every range is read before any name is built, so patterns expanding to more than
+/// `MAX_EXPANDED_HOSTS` are refused without allocating them
+fn expand_host_pattern(pattern: &str) -> Result<Vec<String>, String> {
+    // Literal text before each range, with the range's first and last number and padding
+    let mut ranges = Vec::new();
+    let mut rest = pattern;
+    let mut count: u64 = 1;
+    while let Some(open) = rest.find('[') {
+        let close = rest[open..]
+            .find(']')
+            .map(|c| open + c)
+            .ok_or_else(|| format!("unclosed range in '{}'", pattern))?;
+        let range = &rest[open + 1..close];
+        let (start, end) = range
+            .split_once(':')
+            .ok_or_else(|| format!("range '[{}]' must look like [start:end]", range))?;
+        let (Ok(first), Ok(last)) = (start.parse::<u64>(), end.parse::<u64>()) else {
+            return Err(format!("only numeric ranges are supported, not '[{}]'", range));
+        };
+        if first > last {
+            return Err(format!("range '[{}]' runs backwards", range));
+        }
+        count = (last - first)
+            .checked_add(1)
+            .and_then(|hosts| count.checked_mul(hosts))
+            .filter(|hosts| *hosts <= MAX_EXPANDED_HOSTS)
+            .ok_or_else(|| format!("'{}' expands to more than {} hosts", pattern, MAX_EXPANDED_HOSTS))?;
+
+        let width = if start.len() > 1 && start.starts_with('0') { start.len() } else { 0 };
+        ranges.push((&rest[..open], first, last, width));
+        rest = &rest[close + 1..];
+    }
+
+    let mut names = vec![String::new()];
+    for (prefix, first, last, width) in ranges {
+        names = names
+            .iter()
+            .flat_map(|name| (first..=last).map(move |number| format!("{}{}{:0width$}", name, prefix, number, width = width)))
+            .collect();
+    }
+    Ok(names.into_iter().map(|name| name + rest).collect())
+}
+
+fn parse_ansible_yaml(text: &str) -> ApiResult<Vec<Record>> {
+    let document: Value = serde_yaml::from_str(text).map_err(|e| ApiError::YamlParseError(e.to_string()))?;
+    let Value::Object(groups) = document else {
+        return Err(ApiError::ValidationError("An Ansible YAML inventory must be a mapping of groups".to_string()));
+    };
+
+    let mut inventory = AnsibleInventory::default();
+    let mut locator = Locator::new(text);
+    let mut visited = HashSet::new();
+    for (group, body) in &groups {
+        collect_ansible_group(&mut inventory, &mut locator, &mut visited, group, body);
+    }
+    let mut records = inventory.into_records();
+    records.sort_by_key(|record| record.line);
+    Ok(records)
+}
+
+fn collect_ansible_group(
+    inventory: &mut AnsibleInventory,
+    locator: &mut Locator,
+    visited: &mut HashSet<String>,
+    group: &str,
+    body: &Value,
+) {
+    let Value::Object(body) = body else {
+        return;
+    };
+    if let Some(Value::Object(vars)) = body.get("vars") {
+        let vars: IndexMap<String, Value> = vars.clone().into_iter().collect();
+        inventory.group_vars.entry(group.to_string()).or_default().extend(vars);
+    }
+    if let Some(Value::Object(hosts)) = body.get("hosts") {
+        for (name, vars) in hosts {
+            let vars = match vars {
+                Value::Object(vars) => vars.clone().into_iter().collect(),
+                _ => IndexMap::new(),
+            };
+            let line = inventory.hosts.get(name).map_or_else(|| locator.key(name), |h| h.line);
+            inventory.add_host(name, group, line, vars);
+        }
+    }
+    if let Some(Value::Object(children)) = body.get("children") {
+        for (child, child_body) in children {
+            let parents = inventory.parents.entry(child.clone()).or_default();
+            if !parents.iter().any(|p| p == group) {
+                parents.push(group.to_string());
+            }
+            // A group may be listed under several parents; its hosts are read once
+            if visited.insert(child.clone()) {
+                collect_ansible_group(inventory, locator, visited, child, child_body);
+            }
+        }
+    }
+}
+
+/// Finds source lines of YAML records, scanning forward so repeated text maps to
+/// successive occurrences
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+    next: usize,
+}
+
+impl<'a> Locator<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            lines: text.lines().collect(),
+            next: 0,
+        }
+    }
+
+    /// Line of the next `key:` mapping key
+    fn key(&mut self, key: &str) -> usize {
+        self.find(|line| {
+            let line = line.trim_start();
+            [key.to_string(), format!("\"{}\"", key), format!("'{}'", key)]
+                .iter()
+                .any(|candidate| line.strip_prefix(candidate.as_str()).is_some_and(|rest| rest.starts_with(':')))
+        })
+    }
+
+    /// Line of the next list item, preferably one mentioning `hint`
+    fn item(&mut self, hint: &str) -> usize {
+        let start = self.next;
+        let found = self.find(|line| line.trim_start().starts_with('-') && line.contains(hint));
+        if found > 0 {
+            return found;
+        }
+        self.next = start;
+        self.find(|line| line.trim_start().starts_with('-'))
+    }
+
+    fn find(&mut self, matches: impl Fn(&str) -> bool) -> usize {
+        match self.lines.iter().skip(self.next).position(|line| matches(line)) {
+            Some(offset) => {
+                let index = self.next + offset;
+                self.next = index + 1;
+                index + 1
+            }
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_ranges_in_order() {
+        assert!(expand_host_pattern("leaf[a:b]").unwrap_err().contains("only numeric ranges"));
+        assert_eq!(
+            expand_host_pattern("leaf[01:02]-p[1:2].lab").unwrap(),
+            ["leaf01-p1.lab", "leaf01-p2.lab", "leaf02-p1.lab", "leaf02-p2.lab"]
+        );
+        assert_eq!(expand_host_pattern("r1.lab").unwrap(), ["r1.lab"]);
+    }
+
+    #[test]
+    fn refuses_oversized_ranges_without_expanding_them() {
+        for pattern in ["host[0:99999999999]", "a[0:99999][0:99999]", "x[0:18446744073709551615]"] {
+            let error = expand_host_pattern(pattern).unwrap_err();
+            assert!(error.contains("expands to more than 10000 hosts"), "{}", error);
+        }
+        assert_eq!(expand_host_pattern("h[1:10000]").unwrap().len(), 10_000);
+    }
+
+    #[test]
+    fn oversized_ranges_are_row_errors_with_their_line() {
+        let text = "[core]\nr1.lab\nhost[0:99999999999]\n[edge]\ne[1:6000]\nf[1:6000]\n";
+        let records = parse_ansible_ini(text).unwrap();
+        let errors: Vec<(usize, &str)> = records
+            .iter()
+            .filter_map(|r| r.error.as_deref().map(|e| (r.line, e)))
+            .collect();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0].0, 3);
+        assert!(errors[0].1.contains("expands to more than 10000 hosts"));
+        assert_eq!(errors[1].0, 6);
+        assert!(errors[1].1.contains("more than 10000 hosts in total (6000 added so far)"));
+        assert_eq!(records.len(), 1 + 1 + 6000 + 1);
+    }
+
+    #[test]
+    fn rejected_and_repeated_ranges_do_not_count_against_the_total() {
+        let text = "[edge]
+e[1:6000]
+f[1:6000]
+g[1:4000]
+[spine]
+e[1:6000]
+";
+        let records = parse_ansible_ini(text).unwrap();
+        let errors: Vec<usize> = records.iter().filter(|r| r.error.is_some()).map(|r| r.line).collect();
+        assert_eq!(errors, vec![3]);
+        assert_eq!(records.len(), 6000 + 1 + 4000);
+    }
+}
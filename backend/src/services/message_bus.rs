@@ -0,0 +1,435 @@
+// backend/src/services/message_bus.rs
+
+//! # Message Bus
+//!
+//! ## Description
+//! Cross-node fan-out for WebSocket broadcasts. Every `WebSocketService` delivers a
+//! broadcast to its own clients immediately and also publishes it on the bus; the
+//! other nodes receive the envelope and deliver it to their clients. Nodes also
+//! publish periodic registry snapshots so connection registries can be queried
+//! cluster-wide.
+//!
+//! ## Implementations
+//! - `LocalBus` - in-process bus, the default for single-node deployments and for
+//!   wiring several services together inside one process
+//! - `RedisBus` - Redis pub/sub, for multiple backend instances behind a load balancer.
+//!   Works against any server speaking the RESP `PUBLISH`/`SUBSCRIBE` commands.
+//!
+//! ## How to Use
+//! 1. Pick a bus: `Arc::new(LocalBus::new())` or `Arc::new(RedisBus::connect(url, channel).await?)`
+//! 2. Attach it: `WebSocketService::new(config).with_message_bus(bus)`
+//! 3. `start_background_tasks()` spawns the bus listener and registry heartbeat
+
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::models::{
+    websocket::{ConnectionInfo, WsMessage},
+    ApiError,
+};
+
+/// Identifier of a backend instance
+pub type NodeId = Uuid;
+
+/// Default Redis channel used for WebSocket fan-out
+pub const DEFAULT_BUS_CHANNEL: &str = "thalyx:ws";
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// BUS ENVELOPES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Message exchanged between nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusEnvelope {
+    /// Node that published the envelope
+    pub origin: NodeId,
+    pub event: BusEvent,
+}
+
+/// Events carried by the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data")]
+pub enum BusEvent {
+    /// A topic broadcast to deliver to local subscribers
+    Broadcast { topic: String, message: WsMessage },
+    /// Periodic snapshot of a node's connection registry
+    Registry(NodeSnapshot),
+    /// A node shutting down; peers drop its registry snapshot
+    NodeLeft,
+}
+
+/// Connection registry of a single node at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub node_id: NodeId,
+    pub reported_at: chrono::DateTime<chrono::Utc>,
+    pub connections: Vec<ConnectionInfo>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// BUS TRAIT
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Pluggable transport underneath `WebSocketService::broadcast_to_topic`
+#[async_trait]
+pub trait MessageBus: Send + Sync + std::fmt::Debug {
+    /// Short name for logging and diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Publish an envelope to every node, including the publisher
+    async fn publish(&self, envelope: BusEnvelope) -> Result<(), ApiError>;
+
+    /// Stream of envelopes published by any node
+    /// The stream ends if the underlying connection is lost; callers re-subscribe
+    async fn subscribe(&self) -> Result<BoxStream<'static, BusEnvelope>, ApiError>;
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// IN-PROCESS BUS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// In-process bus backed by a tokio broadcast channel
+#[derive(Debug, Clone)]
+pub struct LocalBus {
+    sender: broadcast::Sender<BusEnvelope>,
+}
+
+impl LocalBus {
+    pub fn new() -> Self {
+        let (sender, _rx) = broadcast::channel(1024);
+        Self { sender }
+    }
+}
+
+impl Default for LocalBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MessageBus for LocalBus {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn publish(&self, envelope: BusEnvelope) -> Result<(), ApiError> {
+        // No subscribers is not an error for a single node
+        let _ = self.sender.send(envelope);
+        Ok(())
+    }
+
+    async fn subscribe(&self) -> Result<BoxStream<'static, BusEnvelope>, ApiError> {
+        let receiver = self.sender.subscribe();
+        let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(envelope) => return Some((envelope, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Local bus subscriber lagged");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(stream.boxed())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REDIS PUB/SUB BUS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Redis pub/sub bus; envelopes are JSON encoded on a single channel
+pub struct RedisBus {
+    client: redis::Client,
+    publisher: redis::aio::ConnectionManager,
+    channel: String,
+}
+
+impl std::fmt::Debug for RedisBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisBus")
+            .field("server", &self.client.get_connection_info().addr)
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl RedisBus {
+    /// Connect to a Redis server, e.g. `redis://127.0.0.1:6379`
+    pub async fn connect(url: &str, channel: &str) -> Result<Self, ApiError> {
+        let client = redis::Client::open(url)
+            .map_err(|e| ApiError::ValidationError(format!("Invalid bus URL '{}': {}", url, e)))?;
+        let publisher = redis::aio::ConnectionManager::new(client.clone())
+            .await
+            .map_err(|e| ApiError::WebSocketError(format!("Failed to connect to bus: {}", e)))?;
+
+        info!(server = %client.get_connection_info().addr, channel = %channel, "Connected to Redis message bus");
+        Ok(Self {
+            client,
+            publisher,
+            channel: channel.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl MessageBus for RedisBus {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn publish(&self, envelope: BusEnvelope) -> Result<(), ApiError> {
+        let payload = serde_json::to_vec(&envelope)
+            .map_err(|e| ApiError::SerializationError(e.to_string()))?;
+
+        // ConnectionManager reconnects transparently; clone is cheap
+        let mut connection = self.publisher.clone();
+        let receivers: i64 = redis::cmd("PUBLISH")
+            .arg(&self.channel)
+            .arg(payload)
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| ApiError::WebSocketError(format!("Failed to publish to bus: {}", e)))?;
+
+        debug!(channel = %self.channel, receivers, "Published envelope to Redis bus");
+        Ok(())
+    }
+
+    async fn subscribe(&self) -> Result<BoxStream<'static, BusEnvelope>, ApiError> {
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| ApiError::WebSocketError(format!("Failed to open bus subscription: {}", e)))?;
+        pubsub
+            .subscribe(&self.channel)
+            .await
+            .map_err(|e| ApiError::WebSocketError(format!("Failed to subscribe to bus: {}", e)))?;
+
+        let stream = pubsub.into_on_message().filter_map(|msg| async move {
+            let payload: Vec<u8> = match msg.get_payload() {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!(error = %e, "Unreadable bus message payload");
+                    return None;
+                }
+            };
+            match serde_json::from_slice::<BusEnvelope>(&payload) {
+                Ok(envelope) => Some(envelope),
+                Err(e) => {
+                    warn!(error = %e, "Discarding malformed bus envelope");
+                    None
+                }
+            }
+        });
+        Ok(stream.boxed())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::{TcpListener, TcpStream},
+        sync::mpsc,
+    };
+
+    type Subscribers = Arc<Mutex<HashMap<Vec<u8>, Vec<mpsc::UnboundedSender<Vec<u8>>>>>>;
+
+    /// Minimal RESP server implementing `PUBLISH` and `SUBSCRIBE`, enough to run
+    /// `RedisBus` without a Redis server; other commands are acknowledged with `+OK`
+    pub struct RespStandin {
+        pub url: String,
+        subscribers: Subscribers,
+    }
+
+    impl RespStandin {
+        pub async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("redis://{}", listener.local_addr().unwrap());
+            let subscribers = Subscribers::default();
+            let accept_subscribers = subscribers.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, accept_subscribers.clone()));
+                }
+            });
+            Self { url, subscribers }
+        }
+
+        /// Open subscriptions to a channel
+        pub fn subscriber_count(&self, channel: &str) -> usize {
+            let subscribers = self.subscribers.lock().unwrap();
+            subscribers
+                .get(channel.as_bytes())
+                .map_or(0, |senders| senders.iter().filter(|s| !s.is_closed()).count())
+        }
+
+        /// Wait until `count` connections are subscribed to a channel
+        pub async fn wait_for_subscribers(&self, channel: &str, count: usize) {
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while self.subscriber_count(channel) < count {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("subscribers did not arrive");
+        }
+    }
+
+    fn bulk(out: &mut Vec<u8>, data: &[u8]) {
+        out.extend_from_slice(format!("${}\r\n", data.len()).as_bytes());
+        out.extend_from_slice(data);
+        out.extend_from_slice(b"\r\n");
+    }
+
+    /// Read one command, an array of bulk strings; `None` at end of stream
+    async fn read_command<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Option<Vec<Vec<u8>>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|n| *n > 0)?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            args.push(arg);
+        }
+        Some(args)
+    }
+
+    async fn serve(stream: TcpStream, subscribers: Subscribers) {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        // Replies and pushed messages share one writer so frames never interleave
+        let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if writer.write_all(&frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut subscribed = 0;
+        while let Some(args) = read_command(&mut reader).await {
+            let Some(name) = args.first() else { continue };
+            let mut reply = Vec::new();
+            match name.to_ascii_uppercase().as_slice() {
+                b"PUBLISH" if args.len() == 3 => {
+                    let message = {
+                        let mut frame = b"*3\r\n".to_vec();
+                        bulk(&mut frame, b"message");
+                        bulk(&mut frame, &args[1]);
+                        bulk(&mut frame, &args[2]);
+                        frame
+                    };
+                    let mut subscribers = subscribers.lock().unwrap();
+                    let senders = subscribers.entry(args[1].clone()).or_default();
+                    senders.retain(|sender| sender.send(message.clone()).is_ok());
+                    reply.extend_from_slice(format!(":{}\r\n", senders.len()).as_bytes());
+                }
+                b"SUBSCRIBE" => {
+                    for channel in &args[1..] {
+                        subscribers
+                            .lock()
+                            .unwrap()
+                            .entry(channel.clone())
+                            .or_default()
+                            .push(tx.clone());
+                        subscribed += 1;
+                        reply.extend_from_slice(b"*3\r\n");
+                        bulk(&mut reply, b"subscribe");
+                        bulk(&mut reply, channel);
+                        reply.extend_from_slice(format!(":{}\r\n", subscribed).as_bytes());
+                    }
+                }
+                b"PING" => reply.extend_from_slice(b"+PONG\r\n"),
+                _ => reply.extend_from_slice(b"+OK\r\n"),
+            }
+            if tx.send(reply).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn broadcast(origin: NodeId, text: &str) -> BusEnvelope {
+        BusEnvelope {
+            origin,
+            event: BusEvent::Broadcast {
+                topic: "data_updates".to_string(),
+                message: WsMessage::Custom {
+                    event: "test".to_string(),
+                    data: serde_json::json!(text),
+                },
+            },
+        }
+    }
+
+    async fn next_envelope(stream: &mut BoxStream<'static, BusEnvelope>) -> BusEnvelope {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("no envelope within 5s")
+            .expect("bus stream ended")
+    }
+
+    #[tokio::test]
+    async fn redis_bus_fans_out_to_every_node() {
+        let server = RespStandin::start().await;
+        let node_a = RedisBus::connect(&server.url, DEFAULT_BUS_CHANNEL).await.unwrap();
+        let node_b = RedisBus::connect(&server.url, DEFAULT_BUS_CHANNEL).await.unwrap();
+        let mut stream_a = node_a.subscribe().await.unwrap();
+        let mut stream_b = node_b.subscribe().await.unwrap();
+        server.wait_for_subscribers(DEFAULT_BUS_CHANNEL, 2).await;
+
+        let origin = Uuid::new_v4();
+        node_a.publish(broadcast(origin, "hello")).await.unwrap();
+
+        for stream in [&mut stream_a, &mut stream_b] {
+            let envelope = next_envelope(stream).await;
+            assert_eq!(envelope.origin, origin);
+            match envelope.event {
+                BusEvent::Broadcast { topic, message: WsMessage::Custom { data, .. } } => {
+                    assert_eq!(topic, "data_updates");
+                    assert_eq!(data, "hello");
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn redis_bus_skips_malformed_payloads() {
+        let server = RespStandin::start().await;
+        let bus = RedisBus::connect(&server.url, DEFAULT_BUS_CHANNEL).await.unwrap();
+        let mut stream = bus.subscribe().await.unwrap();
+        server.wait_for_subscribers(DEFAULT_BUS_CHANNEL, 1).await;
+
+        let mut connection = bus.publisher.clone();
+        let _: i64 = redis::cmd("PUBLISH")
+            .arg(DEFAULT_BUS_CHANNEL)
+            .arg("not json")
+            .query_async(&mut connection)
+            .await
+            .unwrap();
+        let origin = Uuid::new_v4();
+        bus.publish(broadcast(origin, "after")).await.unwrap();
+
+        assert_eq!(next_envelope(&mut stream).await.origin, origin);
+    }
+}
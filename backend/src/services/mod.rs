@@ -2,6 +2,44 @@
 
 pub mod yaml_service;
 pub mod websocket_service;
+pub mod rpc_registry;
+pub mod ws_codec;
+pub mod rate_limit;
+pub mod message_bus;
+pub mod report_engine;
+pub mod report_replay;
+pub mod report_runs;
+pub mod report_catalog;
+pub mod report_parameters;
+pub mod report_join;
+pub mod computed_columns;
+pub mod assertions;
+pub mod field_types;
+pub mod database;
+pub mod results_store;
+pub mod result_diff;
+pub mod report_export;
+pub mod scheduler;
+pub mod device_inventory;
+pub mod device_filter;
+pub mod device_groups;
+pub mod device_discovery;
+pub mod inventory_import;
+pub mod credential_vault;
+pub mod config_backups;
+pub mod netconf;
+pub mod xpath;
 
 pub use yaml_service::YamlService;
 pub use websocket_service::WebSocketService;
+pub use report_engine::ReportEngine;
+pub use report_runs::ReportRunner;
+pub use report_catalog::ReportCatalog;
+pub use database::Database;
+pub use results_store::ResultsStore;
+pub use scheduler::Scheduler;
+pub use device_inventory::DeviceInventory;
+pub use device_groups::DeviceGroups;
+pub use device_discovery::DeviceDiscovery;
+pub use credential_vault::CredentialVault;
+pub use config_backups::ConfigBackups;
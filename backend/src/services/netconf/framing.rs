@@ -0,0 +1,202 @@
+// backend/src/services/netconf/framing.rs
+
+//! NETCONF message framing (RFC 6242)
+//!
+//! Two framings exist:
+//! - End-of-message: each message is terminated by `]]>]]>` (NETCONF 1.0, and
+//!   always used for the `<hello>` exchange)
+//! - Chunked: `\n#<size>\n<data>` chunks terminated by `\n##\n` (NETCONF 1.1)
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::models::{ApiError, ApiResult};
+
+/// End-of-message marker of the 1.0 framing
+pub const EOM_MARKER: &[u8] = b"]]>]]>";
+
+/// Largest chunk size allowed by RFC 6242
+const MAX_CHUNK_SIZE: usize = 4_294_967_295;
+
+/// Framing in effect on a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    EndOfMessage,
+    Chunked,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// READER
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Buffered reader splitting a byte stream into NETCONF messages
+pub struct FrameReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    max_message_size: usize,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    pub fn new(inner: R, max_message_size: usize) -> Self {
+        Self {
+            inner,
+            buffer: Vec::with_capacity(8192),
+            max_message_size,
+        }
+    }
+
+    /// Read the next complete message
+    pub async fn read_message(&mut self, framing: Framing) -> ApiResult<String> {
+        let message = match framing {
+            Framing::EndOfMessage => self.read_end_of_message().await?,
+            Framing::Chunked => self.read_chunked().await?,
+        };
+
+        String::from_utf8(message)
+            .map_err(|_| ApiError::DeviceError("NETCONF message is not valid UTF-8".to_string()))
+    }
+
+    async fn read_end_of_message(&mut self) -> ApiResult<Vec<u8>> {
+        let mut scanned: usize = 0;
+        loop {
+            // Resume a little before the previous end in case the marker straddles two reads
+            let from = scanned.saturating_sub(EOM_MARKER.len() - 1);
+            if let Some(position) = find(&self.buffer[from..], EOM_MARKER) {
+                let end = from + position;
+                let message = self.buffer[..end].trim_ascii().to_vec();
+                self.buffer.drain(..end + EOM_MARKER.len());
+                return Ok(message);
+            }
+            if self.buffer.len() > self.max_message_size {
+                return Err(self.too_large());
+            }
+            scanned = self.buffer.len();
+            self.fill().await?;
+        }
+    }
+
+    async fn read_chunked(&mut self) -> ApiResult<Vec<u8>> {
+        // Tolerate stray whitespace left between messages, e.g. after the hello
+        loop {
+            self.ensure(2).await?;
+            if self.buffer.starts_with(b"\n#") || !self.buffer[0].is_ascii_whitespace() {
+                break;
+            }
+            self.buffer.remove(0);
+        }
+
+        let mut message = Vec::new();
+        loop {
+            self.ensure(3).await?;
+            if &self.buffer[..2] != b"\n#" {
+                return Err(malformed("expected chunk header"));
+            }
+
+            // End-of-chunks marker
+            if self.buffer[2] == b'#' {
+                self.ensure(4).await?;
+                if self.buffer[3] != b'\n' {
+                    return Err(malformed("invalid end-of-chunks marker"));
+                }
+                self.buffer.drain(..4);
+                return Ok(message);
+            }
+
+            // Chunk size: 1 to 10 digits followed by a newline
+            let newline = loop {
+                if let Some(position) = self.buffer[2..].iter().position(|b| *b == b'\n') {
+                    break position + 2;
+                }
+                if self.buffer.len() > 13 {
+                    return Err(malformed("chunk size too long"));
+                }
+                self.fill().await?;
+            };
+            let size = std::str::from_utf8(&self.buffer[2..newline])
+                .ok()
+                .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|digits| digits.parse::<usize>().ok())
+                .filter(|size| (1..=MAX_CHUNK_SIZE).contains(size))
+                .ok_or_else(|| malformed("invalid chunk size"))?;
+
+            if message.len() + size > self.max_message_size {
+                return Err(self.too_large());
+            }
+
+            self.buffer.drain(..=newline);
+            self.ensure(size).await?;
+            message.extend(self.buffer.drain(..size));
+        }
+    }
+
+    /// Read until the buffer holds at least `len` bytes
+    async fn ensure(&mut self, len: usize) -> ApiResult<()> {
+        while self.buffer.len() < len {
+            self.fill().await?;
+        }
+        Ok(())
+    }
+
+    async fn fill(&mut self) -> ApiResult<()> {
+        let mut chunk = [0u8; 8192];
+        let read = self
+            .inner
+            .read(&mut chunk)
+            .await
+            .map_err(|e| ApiError::DeviceError(format!("NETCONF read failed: {}", e)))?;
+        if read == 0 {
+            return Err(ApiError::DeviceError("NETCONF session closed by peer".to_string()));
+        }
+        self.buffer.extend_from_slice(&chunk[..read]);
+        Ok(())
+    }
+
+    fn too_large(&self) -> ApiError {
+        ApiError::DeviceError(format!(
+            "NETCONF message exceeds {} bytes",
+            self.max_message_size
+        ))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// WRITER
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Write a single message using the given framing
+pub async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    framing: Framing,
+    message: &str,
+) -> ApiResult<()> {
+    let frame = match framing {
+        Framing::EndOfMessage => {
+            let mut frame = Vec::with_capacity(message.len() + EOM_MARKER.len() + 1);
+            frame.extend_from_slice(message.as_bytes());
+            frame.push(b'\n');
+            frame.extend_from_slice(EOM_MARKER);
+            frame
+        }
+        Framing::Chunked => {
+            let mut frame = format!("\n#{}\n", message.len()).into_bytes();
+            frame.extend_from_slice(message.as_bytes());
+            frame.extend_from_slice(b"\n##\n");
+            frame
+        }
+    };
+
+    writer.write_all(&frame).await.map_err(write_failed)?;
+    writer.flush().await.map_err(write_failed)?;
+    Ok(())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn write_failed(e: std::io::Error) -> ApiError {
+    ApiError::DeviceError(format!("NETCONF write failed: {}", e))
+}
+
+fn malformed(reason: &str) -> ApiError {
+    ApiError::DeviceError(format!("Malformed NETCONF chunked frame: {}", reason))
+}
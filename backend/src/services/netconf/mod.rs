@@ -0,0 +1,29 @@
+// backend/src/services/netconf/mod.rs
+
+//! # NETCONF Client
+//!
+//! ## Description
+//! Native async NETCONF client used to execute Junos RPCs such as
+//! `get-bgp-summary-information` for the report engine.
+//!
+//! ## Modules
+//! - `framing` - RFC 6242 end-of-message (`]]>]]>`) and chunked framing
+//! - `session` - hello/capabilities exchange, `<rpc>` rendering, `<rpc-error>` mapping
+//! - `ssh` - the `netconf` SSH subsystem bridged onto async streams
+//! - `transport` - per-device session pool implementing `ReportTransport`
+//! - `standin` - canned-XML NETCONF server for development and client testing
+//!
+//! ## How to Use
+//! 1. `let transport = Arc::new(NetconfTransport::new(NetconfConfig::from_env()));`
+//!    Add `.with_credentials(vault)` to log in with per-device credentials
+//! 2. `transport.start_maintenance();` to close idle sessions in the background
+//! 3. `ReportEngine::new(transport)` and run reports against `host`, `host:port`
+//!    or, for stand-ins, `tcp://host:port`
+
+pub mod framing;
+pub mod session;
+pub mod ssh;
+pub mod standin;
+pub mod transport;
+
+pub use transport::{NetconfConfig, NetconfTransport};
@@ -0,0 +1,381 @@
+// backend/src/services/netconf/session.rs
+
+//! NETCONF session over any byte stream
+//!
+//! The session performs the `<hello>` exchange, switches to chunked framing when
+//! both peers announce `base:1.1`, sends `<rpc>` requests with increasing
+//! `message-id`s and maps `<rpc-error>` replies to `ApiError::DeviceError`.
+//! It is transport agnostic: SSH channels, TCP sockets and in-memory duplex
+//! streams all work the same way.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tracing::{debug, info, warn};
+
+use super::framing::{write_message, FrameReader, Framing};
+use crate::{
+    models::{ApiError, ApiResult},
+    services::report_engine::RpcCall,
+};
+
+/// NETCONF 1.0 base capability (end-of-message framing)
+pub const BASE_1_0: &str = "urn:ietf:params:netconf:base:1.0";
+/// NETCONF 1.1 base capability (chunked framing)
+pub const BASE_1_1: &str = "urn:ietf:params:netconf:base:1.1";
+/// Namespace of the NETCONF protocol elements
+pub const NETCONF_NS: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+
+/// Type-erased read half of a session stream
+pub type BoxReader = Box<dyn AsyncRead + Send + Unpin>;
+/// Type-erased write half of a session stream
+pub type BoxWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// RPC ERRORS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A single `<rpc-error>` element of a reply
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RpcError {
+    pub error_type: String,
+    pub error_tag: String,
+    pub error_severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+impl RpcError {
+    pub fn is_warning(&self) -> bool {
+        self.error_severity.eq_ignore_ascii_case("warning")
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.error_tag, self.error_type)?;
+        if let Some(message) = &self.error_message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(path) = &self.error_path {
+            write!(f, " at {}", path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collect the `<rpc-error>` elements of a reply
+pub fn parse_rpc_errors(reply: &roxmltree::Document) -> Vec<RpcError> {
+    reply
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name() == "rpc-error")
+        .map(|error| {
+            let mut parsed = RpcError::default();
+            for child in error.children().filter(|n| n.is_element()) {
+                let text = child.text().map(|t| t.trim().to_string()).unwrap_or_default();
+                match child.tag_name().name() {
+                    "error-type" => parsed.error_type = text,
+                    "error-tag" => parsed.error_tag = text,
+                    "error-severity" => parsed.error_severity = text,
+                    "error-path" => parsed.error_path = Some(text),
+                    "error-message" => parsed.error_message = Some(text),
+                    _ => {}
+                }
+            }
+            parsed
+        })
+        .collect()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SESSION
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// An established NETCONF session
+pub struct NetconfSession {
+    reader: FrameReader<BoxReader>,
+    writer: BoxWriter,
+    framing: Framing,
+    session_id: Option<u32>,
+    capabilities: Vec<String>,
+    next_message_id: u64,
+    last_used: Instant,
+    /// Set once the stream is unusable (I/O failure, framing error, timeout)
+    broken: bool,
+}
+
+impl std::fmt::Debug for NetconfSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetconfSession")
+            .field("session_id", &self.session_id)
+            .field("framing", &self.framing)
+            .field("capabilities", &self.capabilities.len())
+            .field("broken", &self.broken)
+            .finish()
+    }
+}
+
+impl NetconfSession {
+    /// Exchange `<hello>` messages and negotiate the framing
+    pub async fn establish(
+        reader: BoxReader,
+        writer: BoxWriter,
+        max_message_size: usize,
+    ) -> ApiResult<Self> {
+        let mut session = Self {
+            reader: FrameReader::new(reader, max_message_size),
+            writer,
+            framing: Framing::EndOfMessage,
+            session_id: None,
+            capabilities: Vec::new(),
+            next_message_id: 1,
+            last_used: Instant::now(),
+            broken: false,
+        };
+
+        // Both peers send their hello immediately, so the order does not matter
+        write_message(&mut session.writer, Framing::EndOfMessage, &client_hello()).await?;
+        let hello = session.reader.read_message(Framing::EndOfMessage).await?;
+        let (session_id, capabilities) = parse_hello(&hello)?;
+
+        if !capabilities.iter().any(|c| c.starts_with(BASE_1_0) || c.starts_with(BASE_1_1)) {
+            return Err(ApiError::DeviceError(
+                "Peer does not announce a NETCONF base capability".to_string(),
+            ));
+        }
+        if capabilities.iter().any(|c| c.starts_with(BASE_1_1)) {
+            session.framing = Framing::Chunked;
+        }
+
+        info!(
+            session_id = ?session_id,
+            framing = ?session.framing,
+            capabilities = capabilities.len(),
+            "NETCONF session established"
+        );
+        session.session_id = session_id;
+        session.capabilities = capabilities;
+        Ok(session)
+    }
+
+    /// Session ID assigned by the server
+    pub fn session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+
+    /// Capabilities announced by the server
+    pub fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+
+    /// How long the session has been idle
+    pub fn idle_for(&self) -> Duration {
+        self.last_used.elapsed()
+    }
+
+    /// Whether the underlying stream failed and the session must be discarded
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// Send an RPC and return the raw `<rpc-reply>` document
+    /// `<rpc-error>`s with severity `error` fail the call; warnings are logged
+    pub async fn rpc(&mut self, call: &RpcCall) -> ApiResult<String> {
+        if self.broken {
+            return Err(ApiError::DeviceError("NETCONF session is no longer usable".to_string()));
+        }
+
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+        let request = render_rpc(message_id, call)?;
+
+        // Any transport failure leaves the stream in an unknown state
+        self.broken = true;
+        write_message(&mut self.writer, self.framing, &request).await?;
+        let reply = self.reader.read_message(self.framing).await?;
+        self.broken = false;
+        self.last_used = Instant::now();
+
+        debug!(rpc = %call.name, message_id, bytes = reply.len(), "Received NETCONF reply");
+        check_reply(&reply, message_id, &call.name)?;
+        Ok(reply)
+    }
+
+    /// Politely end the session with `<close-session/>`
+    pub async fn close(mut self) {
+        if self.broken {
+            return;
+        }
+        let call = RpcCall {
+            name: "close-session".to_string(),
+            args: Default::default(),
+        };
+        match tokio::time::timeout(Duration::from_secs(5), self.rpc(&call)).await {
+            Ok(Ok(_)) => debug!(session_id = ?self.session_id, "NETCONF session closed"),
+            Ok(Err(e)) => debug!(session_id = ?self.session_id, error = %e, "close-session failed"),
+            Err(_) => debug!(session_id = ?self.session_id, "close-session timed out"),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// MESSAGE RENDERING AND PARSING
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// `<hello>` announcing both base versions
+pub fn client_hello() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><hello xmlns="{}"><capabilities><capability>{}</capability><capability>{}</capability></capabilities></hello>"#,
+        NETCONF_NS, BASE_1_0, BASE_1_1
+    )
+}
+
+/// Extract the session ID and capabilities from a `<hello>`
+pub fn parse_hello(hello: &str) -> ApiResult<(Option<u32>, Vec<String>)> {
+    let document = roxmltree::Document::parse(hello)
+        .map_err(|e| ApiError::DeviceError(format!("Invalid NETCONF hello: {}", e)))?;
+    if document.root_element().tag_name().name() != "hello" {
+        return Err(ApiError::DeviceError("Expected a NETCONF <hello>".to_string()));
+    }
+
+    let text_of = |name: &'static str| {
+        document
+            .descendants()
+            .filter(move |n| n.is_element() && n.tag_name().name() == name)
+            .filter_map(|n| n.text().map(|t| t.trim().to_string()))
+    };
+
+    let capabilities = text_of("capability").filter(|c| !c.is_empty()).collect();
+    let session_id = text_of("session-id").next().and_then(|id| id.parse().ok());
+    Ok((session_id, capabilities))
+}
+
+/// Render `<rpc>` with arguments as child elements of the operation
+/// Booleans render as empty flag elements (`terse: true` -> `<terse/>`, false omits it),
+/// arrays repeat the element and objects nest
+pub fn render_rpc(message_id: u64, call: &RpcCall) -> ApiResult<String> {
+    check_element_name(&call.name)?;
+    let mut xml = format!(r#"<rpc message-id="{}" xmlns="{}"><{}"#, message_id, NETCONF_NS, call.name);
+    let (attributes, children): (Vec<_>, Vec<_>) = call.args.iter().partition(|(name, _)| name.starts_with('@'));
+    for (name, value) in attributes {
+        render_attribute(&mut xml, &name[1..], value)?;
+    }
+    if children.is_empty() {
+        xml.push_str("/>");
+    } else {
+        xml.push('>');
+        for (name, value) in children {
+            render_arg(&mut xml, name, value)?;
+        }
+        xml.push_str(&format!("</{}>", call.name));
+    }
+    xml.push_str("</rpc>");
+    Ok(xml)
+}
+
+fn render_arg(xml: &mut String, name: &str, value: &Value) -> ApiResult<()> {
+    check_element_name(name)?;
+    match value {
+        Value::Null | Value::Bool(false) => {}
+        Value::Bool(true) => xml.push_str(&format!("<{}/>", name)),
+        Value::String(text) => xml.push_str(&format!("<{0}>{1}</{0}>", name, xml_escape(text))),
+        Value::Number(number) => xml.push_str(&format!("<{0}>{1}</{0}>", name, number)),
+        Value::Array(items) => {
+            for item in items {
+                render_arg(xml, name, item)?;
+            }
+        }
+        Value::Object(children) => {
+            xml.push_str(&format!("<{}>", name));
+            for (child, value) in children {
+                render_arg(xml, child, value)?;
+            }
+            xml.push_str(&format!("</{}>", name));
+        }
+    }
+    Ok(())
+}
+
+/// `@name` arguments become attributes of the RPC element, e.g. `format="text"`
+fn render_attribute(xml: &mut String, name: &str, value: &Value) -> ApiResult<()> {
+    check_element_name(name)?;
+    match value {
+        Value::Null | Value::Bool(false) => {}
+        Value::String(text) => xml.push_str(&format!(r#" {}="{}""#, name, xml_escape(text))),
+        Value::Number(_) | Value::Bool(true) => xml.push_str(&format!(r#" {}="{}""#, name, value)),
+        Value::Array(_) | Value::Object(_) => {
+            return Err(ApiError::ValidationError(format!("RPC attribute '@{}' must be a scalar", name)));
+        }
+    }
+    Ok(())
+}
+
+/// RPC and argument names become element names and must not inject markup
+fn check_element_name(name: &str) -> ApiResult<()> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::ValidationError(format!("Invalid RPC element name '{}'", name)))
+    }
+}
+
+/// Escape text for inclusion in XML content or attributes
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Validate an `<rpc-reply>`: matching message-id and no `<rpc-error>` of severity error
+fn check_reply(reply: &str, message_id: u64, rpc: &str) -> ApiResult<()> {
+    let document = roxmltree::Document::parse(reply)
+        .map_err(|e| ApiError::DeviceError(format!("Invalid NETCONF reply to '{}': {}", rpc, e)))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "rpc-reply" {
+        return Err(ApiError::DeviceError(format!(
+            "Expected <rpc-reply> to '{}', got <{}>",
+            rpc,
+            root.tag_name().name()
+        )));
+    }
+
+    // Some devices omit the message-id; only a mismatch is an error
+    if let Some(id) = root.attribute("message-id") {
+        if id != message_id.to_string() {
+            return Err(ApiError::DeviceError(format!(
+                "NETCONF reply message-id {} does not match request {}",
+                id, message_id
+            )));
+        }
+    }
+
+    let (warnings, errors): (Vec<RpcError>, Vec<RpcError>) =
+        parse_rpc_errors(&document).into_iter().partition(RpcError::is_warning);
+    for warning in &warnings {
+        warn!(rpc = %rpc, warning = %warning, "NETCONF RPC returned a warning");
+    }
+    if !errors.is_empty() {
+        let details: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        return Err(ApiError::DeviceError(format!(
+            "RPC '{}' failed: {}",
+            rpc,
+            details.join("; ")
+        )));
+    }
+    Ok(())
+}
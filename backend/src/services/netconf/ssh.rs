@@ -0,0 +1,448 @@
+// backend/src/services/netconf/ssh.rs
+
+//! SSH transport for NETCONF (the `netconf` subsystem, RFC 6242)
+//!
+//! libssh2 is a blocking library, so every SSH connection is owned by a dedicated
+//! thread that drives the channel in non-blocking mode. The async side talks to that
+//! thread through channels wrapped as `AsyncRead`/`AsyncWrite`, which makes SSH
+//! sessions interchangeable with any other stream for `NetconfSession`.
+//! The thread also sends SSH keepalives so idle pooled sessions stay open.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::{mpsc, oneshot},
+};
+use tracing::{debug, info, warn};
+
+use super::session::{BoxReader, BoxWriter};
+use crate::models::{ApiError, ApiResult};
+
+/// How a client authenticates to the device
+#[derive(Clone, Default)]
+pub struct SshCredentials {
+    pub username: String,
+    pub password: Option<String>,
+    pub private_key: Option<PathBuf>,
+    /// Private key text, e.g. from the credential vault; preferred over `private_key`
+    pub private_key_pem: Option<String>,
+    pub passphrase: Option<String>,
+}
+
+impl std::fmt::Debug for SshCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SshCredentials")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("private_key", &self.private_key)
+            .field("private_key_pem", &self.private_key_pem.as_ref().map(|_| "<redacted>"))
+            .field("passphrase", &self.passphrase.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Connection parameters for one SSH session
+#[derive(Debug, Clone)]
+pub struct SshOptions {
+    pub host: String,
+    pub port: u16,
+    pub credentials: SshCredentials,
+    /// OpenSSH `known_hosts` file host keys are verified against
+    pub known_hosts: Option<PathBuf>,
+    /// Accept any host key when `known_hosts` is unset; otherwise such connections are refused
+    pub insecure_skip_host_key: bool,
+    pub connect_timeout: Duration,
+    pub keepalive_interval: Duration,
+}
+
+/// Open an SSH connection and start the `netconf` subsystem
+/// Refused without a `known_hosts` file unless `insecure_skip_host_key` is set
+pub async fn connect(options: SshOptions) -> ApiResult<(BoxReader, BoxWriter)> {
+    if options.known_hosts.is_none() && !options.insecure_skip_host_key {
+        return Err(ApiError::DeviceError(format!(
+            "Cannot verify the host key of {}: set NETCONF_KNOWN_HOSTS, or NETCONF_INSECURE_SKIP_HOST_KEY=1 to connect without verification",
+            options.host
+        )));
+    }
+
+    let (ready_tx, ready_rx) = oneshot::channel();
+    let (incoming_tx, incoming_rx) = mpsc::channel::<Vec<u8>>(64);
+    let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let target = format!("{}:{}", options.host, options.port);
+
+    std::thread::Builder::new()
+        .name(format!("netconf-ssh-{}", target))
+        .spawn(move || {
+            let channel = match open_channel(&options) {
+                Ok(opened) => {
+                    let _ = ready_tx.send(Ok(()));
+                    opened
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            pump(channel, &options, incoming_tx, outgoing_rx);
+        })
+        .map_err(|e| ApiError::InternalError(format!("Failed to spawn SSH thread: {}", e)))?;
+
+    ready_rx
+        .await
+        .map_err(|_| ApiError::DeviceError(format!("SSH connection to {} aborted", target)))??;
+
+    let reader = ChannelReader {
+        receiver: incoming_rx,
+        pending: Vec::new(),
+        offset: 0,
+    };
+    let writer = ChannelWriter {
+        sender: Some(outgoing_tx),
+    };
+    Ok((Box::new(reader), Box::new(writer)))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// BLOCKING SIDE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+struct OpenChannel {
+    session: ssh2::Session,
+    channel: ssh2::Channel,
+}
+
+fn open_channel(options: &SshOptions) -> ApiResult<OpenChannel> {
+    let target = format!("{}:{}", options.host, options.port);
+    let device_error = |what: &str, e: &dyn std::fmt::Display| {
+        ApiError::DeviceError(format!("{} {}: {}", what, target, e))
+    };
+
+    let address = target
+        .to_socket_addrs()
+        .map_err(|e| device_error("Cannot resolve", &e))?
+        .next()
+        .ok_or_else(|| ApiError::DeviceError(format!("Cannot resolve {}", target)))?;
+    let tcp = TcpStream::connect_timeout(&address, options.connect_timeout)
+        .map_err(|e| device_error("Cannot connect to", &e))?;
+
+    let mut session = ssh2::Session::new().map_err(|e| device_error("SSH setup failed for", &e))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(options.connect_timeout.as_millis() as u32);
+    session.handshake().map_err(|e| device_error("SSH handshake failed with", &e))?;
+
+    verify_host_key(&session, options)?;
+    authenticate(&session, &options.credentials).map_err(|e| device_error("SSH authentication failed for", &e))?;
+
+    let mut channel = session
+        .channel_session()
+        .map_err(|e| device_error("Cannot open SSH channel to", &e))?;
+    channel
+        .subsystem("netconf")
+        .map_err(|e| device_error("NETCONF subsystem unavailable on", &e))?;
+
+    let interval = options.keepalive_interval.as_secs().clamp(1, u64::from(u32::MAX)) as u32;
+    session.set_keepalive(true, interval);
+    session.set_blocking(false);
+
+    info!(target = %target, user = %options.credentials.username, "SSH NETCONF channel opened");
+    Ok(OpenChannel { session, channel })
+}
+
+fn verify_host_key(session: &ssh2::Session, options: &SshOptions) -> ApiResult<()> {
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| ApiError::DeviceError(format!("{} sent no host key", options.host)))?;
+    let fingerprint = session
+        .host_key_hash(ssh2::HashType::Sha256)
+        .map(hex_fingerprint)
+        .unwrap_or_default();
+
+    // `connect` only gets here without known_hosts when skipping was asked for
+    let Some(path) = &options.known_hosts else {
+        warn!(host = %options.host, fingerprint = %fingerprint, "Host key not verified (NETCONF_INSECURE_SKIP_HOST_KEY)");
+        return Ok(());
+    };
+
+    check_known_hosts(session, path, &options.host, options.port, key, &fingerprint)
+}
+
+/// Look a host key up in an OpenSSH known_hosts file
+fn check_known_hosts(
+    session: &ssh2::Session,
+    path: &Path,
+    host: &str,
+    port: u16,
+    key: &[u8],
+    fingerprint: &str,
+) -> ApiResult<()> {
+    let mut known_hosts = session
+        .known_hosts()
+        .map_err(|e| ApiError::InternalError(format!("known_hosts unavailable: {}", e)))?;
+    known_hosts
+        .read_file(path, ssh2::KnownHostFileKind::OpenSSH)
+        .map_err(|e| ApiError::InternalError(format!("Cannot read {}: {}", path.display(), e)))?;
+
+    match known_hosts.check_port(host, port, key) {
+        ssh2::CheckResult::Match => Ok(()),
+        ssh2::CheckResult::Mismatch => Err(ApiError::DeviceError(format!(
+            "Host key for {} does not match known_hosts (SHA256 {})",
+            host, fingerprint
+        ))),
+        ssh2::CheckResult::NotFound => Err(ApiError::DeviceError(format!(
+            "Host {} is not in known_hosts (SHA256 {})",
+            host, fingerprint
+        ))),
+        ssh2::CheckResult::Failure => Err(ApiError::DeviceError(format!(
+            "Host key check failed for {}",
+            host
+        ))),
+    }
+}
+
+fn authenticate(session: &ssh2::Session, credentials: &SshCredentials) -> Result<(), ssh2::Error> {
+    if let Some(pem) = &credentials.private_key_pem {
+        session.userauth_pubkey_memory(&credentials.username, None, pem, credentials.passphrase.as_deref())?;
+    } else if let Some(key) = &credentials.private_key {
+        session.userauth_pubkey_file(&credentials.username, None, key, credentials.passphrase.as_deref())?;
+    } else if let Some(password) = &credentials.password {
+        session.userauth_password(&credentials.username, password)?;
+    } else {
+        session.userauth_agent(&credentials.username)?;
+    }
+    Ok(())
+}
+
+/// Shuttle bytes between the SSH channel and the async side until either end closes
+fn pump(
+    mut opened: OpenChannel,
+    options: &SshOptions,
+    incoming: mpsc::Sender<Vec<u8>>,
+    mut outgoing: mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    let target = format!("{}:{}", options.host, options.port);
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buffer = [0u8; 16384];
+    let mut next_keepalive = Instant::now() + options.keepalive_interval;
+    let mut idle_sleep = Duration::from_millis(1);
+
+    let reason = loop {
+        let mut progressed = false;
+
+        // Outbound: drain queued writes from the session
+        let mut dropped = false;
+        loop {
+            match outgoing.try_recv() {
+                Ok(bytes) => pending.extend(bytes),
+                Err(mpsc::error::TryRecvError::Empty) => break,
+                Err(mpsc::error::TryRecvError::Disconnected) => {
+                    dropped = true;
+                    break;
+                }
+            }
+        }
+        if dropped && pending.is_empty() {
+            let _ = opened.channel.close();
+            break "session dropped";
+        }
+        if !pending.is_empty() {
+            match opened.channel.write(&pending) {
+                Ok(written) => {
+                    pending.drain(..written);
+                    progressed = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => {
+                    warn!(target = %target, error = %e, "SSH write failed");
+                    break "write failed";
+                }
+            }
+        }
+
+        // Inbound: forward everything the device sent
+        match opened.channel.read(&mut buffer) {
+            Ok(0) if opened.channel.eof() => break "closed by device",
+            Ok(0) => {}
+            Ok(read) => {
+                if incoming.blocking_send(buffer[..read].to_vec()).is_err() {
+                    break "session dropped";
+                }
+                progressed = true;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => {
+                warn!(target = %target, error = %e, "SSH read failed");
+                break "read failed";
+            }
+        }
+
+        if Instant::now() >= next_keepalive {
+            match opened.session.keepalive_send() {
+                Ok(seconds) => next_keepalive = Instant::now() + Duration::from_secs(u64::from(seconds.max(1))),
+                Err(e) if e.code() == ssh2::ErrorCode::Session(-37) => {} // EAGAIN
+                Err(e) => {
+                    warn!(target = %target, error = %e, "SSH keepalive failed");
+                    break "keepalive failed";
+                }
+            }
+        }
+
+        // Back off while idle; stay responsive while traffic flows
+        if progressed {
+            idle_sleep = Duration::from_millis(1);
+        } else {
+            std::thread::sleep(idle_sleep);
+            idle_sleep = (idle_sleep * 2).min(Duration::from_millis(20));
+        }
+    };
+
+    debug!(target = %target, reason, "SSH NETCONF channel closed");
+}
+
+fn hex_fingerprint(hash: &[u8]) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// ASYNC SIDE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Read half fed by the SSH thread
+struct ChannelReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+impl AsyncRead for ChannelReader {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        if self.offset >= self.pending.len() {
+            match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(chunk)) => {
+                    self.pending = chunk;
+                    self.offset = 0;
+                }
+                // Thread exited: end of stream
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let available = &self.pending[self.offset..];
+        let count = available.len().min(buf.remaining());
+        buf.put_slice(&available[..count]);
+        self.offset += count;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Write half queuing bytes for the SSH thread
+struct ChannelWriter {
+    sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
+}
+
+impl AsyncWrite for ChannelWriter {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        let sent = self
+            .sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(buf.to_vec()).is_ok());
+        if sent {
+            Poll::Ready(Ok(buf.len()))
+        } else {
+            Poll::Ready(Err(std::io::Error::new(ErrorKind::BrokenPipe, "SSH channel closed")))
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.sender = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    const KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAILWPq4K8wtMaC65WsuGo9NU9CAw1ziRrLLJ3D5VWIW9t";
+    const KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIKLv4D9M6DBC89eSGz/+xrvs6rUI5LPrxQccqdpXYnN2";
+
+    fn options(port: u16, known_hosts: Option<PathBuf>, insecure_skip_host_key: bool) -> SshOptions {
+        SshOptions {
+            host: "127.0.0.1".to_string(),
+            port,
+            credentials: SshCredentials {
+                username: "netconf".to_string(),
+                password: Some("secret".to_string()),
+                ..SshCredentials::default()
+            },
+            known_hosts,
+            insecure_skip_host_key,
+            connect_timeout: Duration::from_secs(5),
+            keepalive_interval: Duration::from_secs(30),
+        }
+    }
+
+    fn check(known_hosts: &str, host: &str, port: u16, key: &str) -> ApiResult<()> {
+        let path = std::env::temp_dir().join(format!("thalyx-known-hosts-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, known_hosts).unwrap();
+        let key = base64::engine::general_purpose::STANDARD.decode(key).unwrap();
+        let session = ssh2::Session::new().unwrap();
+        let result = check_known_hosts(&session, &path, host, port, &key, "fp");
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    #[tokio::test]
+    async fn refuses_ssh_without_known_hosts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let err = connect(options(port, None, false)).await.err().unwrap();
+        assert!(matches!(err, ApiError::DeviceError(_)));
+        assert!(err.to_string().contains("NETCONF_KNOWN_HOSTS"), "{}", err);
+
+        // Refused before any bytes reach the device
+        let accepted = tokio::time::timeout(Duration::from_millis(200), listener.accept()).await;
+        assert!(accepted.is_err());
+    }
+
+    #[tokio::test]
+    async fn skipping_host_keys_still_requires_an_ssh_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+        });
+
+        let err = connect(options(port, None, true)).await.err().unwrap();
+        assert!(err.to_string().contains("SSH handshake failed"), "{}", err);
+    }
+
+    #[test]
+    fn known_hosts_matches_host_and_port() {
+        let file = format!("[r1.lab]:830 ssh-ed25519 {}\n", KEY_A);
+        assert!(check(&file, "r1.lab", 830, KEY_A).is_ok());
+
+        let mismatch = check(&file, "r1.lab", 830, KEY_B).unwrap_err();
+        assert!(mismatch.to_string().contains("does not match"), "{}", mismatch);
+
+        let other_port = check(&file, "r1.lab", 22, KEY_A).unwrap_err();
+        assert!(other_port.to_string().contains("not in known_hosts"), "{}", other_port);
+        let other_host = check(&file, "r2.lab", 830, KEY_A).unwrap_err();
+        assert!(other_host.to_string().contains("not in known_hosts"), "{}", other_host);
+    }
+}
@@ -0,0 +1,189 @@
+// backend/src/services/netconf/standin.rs
+
+//! Stand-in NETCONF server answering from canned XML
+//!
+//! Speaks NETCONF (hello exchange, both framings, `<rpc>`/`<rpc-reply>`) over plain
+//! TCP or any in-memory stream and answers each RPC with the recorded reply of the
+//! same name (per `format` attribute, when recorded that way). Used for local
+//! development and for exercising the client without lab gear:
+//! `thalyx-backend netconf-standin --listen 127.0.0.1:8300`, then run reports
+//! against `tcp://127.0.0.1:8300` with `NETCONF_ALLOW_PLAIN_TCP=1`.
+
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use tracing::{debug, info, warn};
+
+use super::{
+    framing::{write_message, FrameReader, Framing},
+    session::{parse_hello, xml_escape, BASE_1_0, BASE_1_1, NETCONF_NS},
+};
+use crate::{
+    models::{ApiError, ApiResult},
+    services::report_engine::{ReportTransport, RpcCall, StaticTransport},
+};
+
+/// Canned-reply NETCONF server
+#[derive(Debug)]
+pub struct StandInServer {
+    replies: StaticTransport,
+    /// Announce `base:1.1` so clients switch to chunked framing
+    chunked: bool,
+    next_session_id: AtomicU32,
+}
+
+impl StandInServer {
+    pub fn new(replies: StaticTransport, chunked: bool) -> Self {
+        Self {
+            replies,
+            chunked,
+            next_session_id: AtomicU32::new(1),
+        }
+    }
+
+    /// Accept connections until the listener fails
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> ApiResult<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                let (reader, writer) = stream.into_split();
+                if let Err(e) = server.serve_session(reader, writer).await {
+                    debug!(peer = %peer, error = %e, "Stand-in session ended");
+                }
+            });
+        }
+    }
+
+    /// Run one NETCONF session on an arbitrary stream
+    pub async fn serve_session<R, W>(&self, reader: R, mut writer: W) -> ApiResult<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let session_id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        let mut reader = FrameReader::new(reader, 16 * 1024 * 1024);
+
+        write_message(&mut writer, Framing::EndOfMessage, &self.hello(session_id)).await?;
+        let (_, client_capabilities) = parse_hello(&reader.read_message(Framing::EndOfMessage).await?)?;
+        let framing = if self.chunked && client_capabilities.iter().any(|c| c.starts_with(BASE_1_1)) {
+            Framing::Chunked
+        } else {
+            Framing::EndOfMessage
+        };
+        info!(session_id, framing = ?framing, "Stand-in NETCONF session started");
+
+        loop {
+            let request = reader.read_message(framing).await?;
+            let (reply, done) = self.answer(&request).await;
+            write_message(&mut writer, framing, &reply).await?;
+            if done {
+                info!(session_id, "Stand-in NETCONF session closed");
+                return Ok(());
+            }
+        }
+    }
+
+    fn hello(&self, session_id: u32) -> String {
+        let mut capabilities = format!("<capability>{}</capability>", BASE_1_0);
+        if self.chunked {
+            capabilities.push_str(&format!("<capability>{}</capability>", BASE_1_1));
+        }
+        capabilities.push_str("<capability>http://xml.juniper.net/netconf/junos/1.0</capability>");
+        format!(
+            r#"<hello xmlns="{}"><capabilities>{}</capabilities><session-id>{}</session-id></hello>"#,
+            NETCONF_NS, capabilities, session_id
+        )
+    }
+
+    /// Build the reply to one request; the flag ends the session
+    async fn answer(&self, request: &str) -> (String, bool) {
+        let Ok(document) = roxmltree::Document::parse(request) else {
+            return (rpc_error(None, "rpc", "malformed-message", "Request is not well-formed XML"), false);
+        };
+        let rpc = document.root_element();
+        let message_id = rpc.attribute("message-id");
+        if rpc.tag_name().name() != "rpc" {
+            return (rpc_error(message_id, "rpc", "unknown-element", "Expected <rpc>"), false);
+        }
+        let Some(operation) = rpc.children().find(|n| n.is_element()) else {
+            return (rpc_error(message_id, "rpc", "missing-element", "Empty <rpc>"), false);
+        };
+
+        let name = operation.tag_name().name();
+        debug!(rpc = %name, message_id = ?message_id, "Stand-in received RPC");
+        if name == "close-session" {
+            return (ok_reply(message_id), true);
+        }
+
+        // Attributes such as `format` select between replies recorded per format
+        let call = RpcCall {
+            name: name.to_string(),
+            args: operation
+                .attributes()
+                .map(|a| (format!("@{}", a.name()), a.value().into()))
+                .collect(),
+        };
+        match self.replies.execute("stand-in", &call).await {
+            Ok(xml) => (with_message_id(&xml, message_id), false),
+            Err(_) => {
+                warn!(rpc = %name, "Stand-in has no recorded reply");
+                let message = format!("No recorded reply for RPC '{}'", name);
+                (rpc_error(message_id, "protocol", "operation-not-supported", &message), false)
+            }
+        }
+    }
+}
+
+/// Serve the stand-in on a TCP address until Ctrl+C
+pub async fn run(listen: &str, replies: StaticTransport, chunked: bool) -> ApiResult<()> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Cannot listen on {}: {}", listen, e)))?;
+    info!(listen = %listen, rpcs = ?replies.rpc_names(), chunked, "NETCONF stand-in listening");
+
+    let server = Arc::new(StandInServer::new(replies, chunked));
+    tokio::select! {
+        result = server.serve(listener) => result,
+        _ = tokio::signal::ctrl_c() => {
+            info!("NETCONF stand-in stopped");
+            Ok(())
+        }
+    }
+}
+
+/// Tag a recorded `<rpc-reply>` with the request's message-id (or wrap a bare body)
+fn with_message_id(xml: &str, message_id: Option<&str>) -> String {
+    let mut body = xml.trim();
+    if body.starts_with("<?xml") {
+        body = body.split_once("?>").map_or(body, |(_, rest)| rest.trim_start());
+    }
+    let id_attribute = message_id
+        .map(|id| format!(r#" message-id="{}""#, xml_escape(id)))
+        .unwrap_or_default();
+
+    match body.strip_prefix("<rpc-reply") {
+        Some(rest) => format!("<rpc-reply{}{}", id_attribute, rest),
+        None => format!(r#"<rpc-reply{} xmlns="{}">{}</rpc-reply>"#, id_attribute, NETCONF_NS, body),
+    }
+}
+
+fn ok_reply(message_id: Option<&str>) -> String {
+    with_message_id("<ok/>", message_id)
+}
+
+fn rpc_error(message_id: Option<&str>, error_type: &str, tag: &str, message: &str) -> String {
+    let error = format!(
+        "<rpc-error><error-type>{}</error-type><error-tag>{}</error-tag>\
+         <error-severity>error</error-severity><error-message>{}</error-message></rpc-error>",
+        error_type,
+        tag,
+        xml_escape(message)
+    );
+    with_message_id(&error, message_id)
+}
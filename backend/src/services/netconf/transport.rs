@@ -0,0 +1,595 @@
+// backend/src/services/netconf/transport.rs
+
+//! Pooled NETCONF transport for the report engine
+//!
+//! Sessions are opened on demand, reused for subsequent RPCs to the same device and
+//! closed after `idle_timeout`. The number of concurrent sessions per device is capped
+//! because Junos limits NETCONF sessions per user. Pooled sessions remember the
+//! credentials they logged in with: once a device's credentials change (profile edited,
+//! reassigned or rotated), its idle sessions are closed instead of reused.
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    net::TcpStream,
+    sync::{Mutex, Semaphore},
+};
+use tracing::{debug, info, warn};
+
+use super::{
+    session::{BoxReader, BoxWriter, NetconfSession},
+    ssh::{self, SshCredentials, SshOptions},
+};
+use crate::{
+    models::{ApiError, ApiResult},
+    services::report_engine::{ReportTransport, RpcCall},
+};
+
+/// Default NETCONF-over-SSH port
+pub const NETCONF_PORT: u16 = 830;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// NETCONF client configuration
+#[derive(Debug, Clone)]
+pub struct NetconfConfig {
+    /// Credentials used for devices the `CredentialSource` has none for
+    pub credentials: SshCredentials,
+    /// OpenSSH `known_hosts` file for strict host key checking
+    pub known_hosts: Option<PathBuf>,
+    /// Connect over SSH without verifying host keys when `known_hosts` is unset
+    pub insecure_skip_host_key: bool,
+    pub connect_timeout: Duration,
+    pub rpc_timeout: Duration,
+    /// Interval of SSH keepalives and of the idle session sweep
+    pub keepalive_interval: Duration,
+    /// Idle sessions older than this are closed
+    pub idle_timeout: Duration,
+    pub max_sessions_per_device: usize,
+    pub max_message_size: usize,
+    /// Accept `tcp://host:port` targets speaking NETCONF without SSH (stand-ins, labs)
+    pub allow_plain_tcp: bool,
+}
+
+impl Default for NetconfConfig {
+    fn default() -> Self {
+        Self {
+            credentials: SshCredentials::default(),
+            known_hosts: None,
+            insecure_skip_host_key: false,
+            connect_timeout: Duration::from_secs(10),
+            rpc_timeout: Duration::from_secs(60),
+            keepalive_interval: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(300),
+            max_sessions_per_device: 4,
+            max_message_size: 64 * 1024 * 1024,
+            allow_plain_tcp: false,
+        }
+    }
+}
+
+impl NetconfConfig {
+    /// Defaults overridden by `NETCONF_*` environment variables
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let seconds = |name: &str, default: Duration| {
+            var(name)
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+
+        Self {
+            credentials: SshCredentials {
+                username: var("NETCONF_USERNAME").unwrap_or_else(|| "netconf".to_string()),
+                password: var("NETCONF_PASSWORD"),
+                private_key: var("NETCONF_PRIVATE_KEY").map(PathBuf::from),
+                private_key_pem: None,
+                passphrase: var("NETCONF_KEY_PASSPHRASE"),
+            },
+            known_hosts: var("NETCONF_KNOWN_HOSTS").map(PathBuf::from),
+            insecure_skip_host_key: var("NETCONF_INSECURE_SKIP_HOST_KEY").is_some_and(|v| v == "1" || v == "true"),
+            connect_timeout: seconds("NETCONF_CONNECT_TIMEOUT", defaults.connect_timeout),
+            rpc_timeout: seconds("NETCONF_RPC_TIMEOUT", defaults.rpc_timeout),
+            keepalive_interval: seconds("NETCONF_KEEPALIVE_INTERVAL", defaults.keepalive_interval),
+            idle_timeout: seconds("NETCONF_IDLE_TIMEOUT", defaults.idle_timeout),
+            max_sessions_per_device: var("NETCONF_MAX_SESSIONS_PER_DEVICE")
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_sessions_per_device),
+            max_message_size: defaults.max_message_size,
+            allow_plain_tcp: var("NETCONF_ALLOW_PLAIN_TCP").is_some_and(|v| v == "1" || v == "true"),
+        }
+    }
+}
+
+/// Per-device credentials, consulted whenever a new session is opened
+#[async_trait]
+pub trait CredentialSource: Send + Sync + std::fmt::Debug {
+    /// Credentials for a device, or `None` for the configured defaults
+    async fn credentials_for(&self, address: &DeviceAddress) -> ApiResult<Option<SshCredentials>>;
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// DEVICE ADDRESSES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Wire protocol used to reach a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    Ssh,
+    Tcp,
+}
+
+/// Parsed device target: `host`, `host:port`, `[v6]:port`, `ssh://…` or `tcp://…`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceAddress {
+    pub scheme: Scheme,
+    pub host: String,
+    pub port: u16,
+}
+
+impl DeviceAddress {
+    pub fn parse(target: &str) -> ApiResult<Self> {
+        let target = target.trim();
+        let (scheme, rest) = match target.split_once("://") {
+            Some(("ssh", rest)) => (Scheme::Ssh, rest),
+            Some(("tcp", rest)) => (Scheme::Tcp, rest),
+            Some((other, _)) => {
+                return Err(ApiError::ValidationError(format!("Unsupported device scheme '{}'", other)))
+            }
+            None => (Scheme::Ssh, target),
+        };
+
+        let invalid = || ApiError::ValidationError(format!("Invalid device address '{}'", target));
+        let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            // [2001:db8::1]:830
+            let (host, after) = bracketed.split_once(']').ok_or_else(invalid)?;
+            let port = match after.strip_prefix(':') {
+                Some(port) => Some(port),
+                None if after.is_empty() => None,
+                None => return Err(invalid()),
+            };
+            (host, port)
+        } else if rest.matches(':').count() == 1 {
+            let (host, port) = rest.split_once(':').ok_or_else(invalid)?;
+            (host, Some(port))
+        } else {
+            // Bare hostname, IPv4 or unbracketed IPv6 address
+            (rest, None)
+        };
+
+        if host.is_empty() || host.chars().any(|c| c.is_whitespace() || c == '/') {
+            return Err(invalid());
+        }
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None => NETCONF_PORT,
+        };
+
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+impl std::fmt::Display for DeviceAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scheme = match self.scheme {
+            Scheme::Ssh => "ssh",
+            Scheme::Tcp => "tcp",
+        };
+        if self.host.contains(':') {
+            write!(f, "{}://[{}]:{}", scheme, self.host, self.port)
+        } else {
+            write!(f, "{}://{}:{}", scheme, self.host, self.port)
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SESSION POOL
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// SHA-256 over the credentials a session logged in with; `None` for plain TCP
+type CredentialFingerprint = Option<[u8; 32]>;
+
+/// An idle session and the credentials it was opened with
+#[derive(Debug)]
+struct PooledSession {
+    session: NetconfSession,
+    credentials: CredentialFingerprint,
+}
+
+/// NETCONF client with per-device session pooling
+#[derive(Debug)]
+pub struct NetconfTransport {
+    config: NetconfConfig,
+    credentials: Option<Arc<dyn CredentialSource>>,
+    idle: Mutex<HashMap<DeviceAddress, Vec<PooledSession>>>,
+    limits: Mutex<HashMap<DeviceAddress, Arc<Semaphore>>>,
+}
+
+impl NetconfTransport {
+    pub fn new(config: NetconfConfig) -> Self {
+        Self {
+            config,
+            credentials: None,
+            idle: Mutex::new(HashMap::new()),
+            limits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Look up per-device credentials in `source` before falling back to `config.credentials`
+    pub fn with_credentials(mut self, source: Arc<dyn CredentialSource>) -> Self {
+        self.credentials = Some(source);
+        self
+    }
+
+    /// Spawn the task closing sessions that have been idle longer than `idle_timeout`
+    pub fn start_maintenance(self: &Arc<Self>) {
+        let transport = Arc::downgrade(self);
+        let interval = self.config.keepalive_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(transport) = transport.upgrade() else { break };
+                transport.sweep_idle().await;
+            }
+        });
+    }
+
+    /// Open a fresh session to a device
+    pub async fn connect(&self, address: &DeviceAddress) -> ApiResult<NetconfSession> {
+        let credentials = self.credentials_for(address).await?;
+        self.open(address, credentials).await
+    }
+
+    /// Credentials an SSH session to the device logs in with; `None` for plain TCP
+    async fn credentials_for(&self, address: &DeviceAddress) -> ApiResult<Option<SshCredentials>> {
+        if address.scheme != Scheme::Ssh {
+            return Ok(None);
+        }
+        let credentials = match &self.credentials {
+            Some(source) => source.credentials_for(address).await?,
+            None => None,
+        };
+        Ok(Some(credentials.unwrap_or_else(|| self.config.credentials.clone())))
+    }
+
+    async fn open(&self, address: &DeviceAddress, credentials: Option<SshCredentials>) -> ApiResult<NetconfSession> {
+        let (reader, writer): (BoxReader, BoxWriter) = match address.scheme {
+            Scheme::Ssh => {
+                ssh::connect(SshOptions {
+                    host: address.host.clone(),
+                    port: address.port,
+                    credentials: credentials.unwrap_or_else(|| self.config.credentials.clone()),
+                    known_hosts: self.config.known_hosts.clone(),
+                    insecure_skip_host_key: self.config.insecure_skip_host_key,
+                    connect_timeout: self.config.connect_timeout,
+                    keepalive_interval: self.config.keepalive_interval,
+                })
+                .await?
+            }
+            Scheme::Tcp if self.config.allow_plain_tcp => {
+                let stream = tokio::time::timeout(
+                    self.config.connect_timeout,
+                    TcpStream::connect((address.host.as_str(), address.port)),
+                )
+                .await
+                .map_err(|_| ApiError::DeviceError(format!("Timed out connecting to {}", address)))?
+                .map_err(|e| ApiError::DeviceError(format!("Cannot connect to {}: {}", address, e)))?;
+                let (reader, writer) = stream.into_split();
+                (Box::new(reader), Box::new(writer))
+            }
+            Scheme::Tcp => {
+                return Err(ApiError::ValidationError(
+                    "Plain TCP NETCONF targets are disabled (set NETCONF_ALLOW_PLAIN_TCP=1)".to_string(),
+                ))
+            }
+        };
+
+        let session = tokio::time::timeout(
+            self.config.connect_timeout,
+            NetconfSession::establish(reader, writer, self.config.max_message_size),
+        )
+        .await
+        .map_err(|_| ApiError::DeviceError(format!("Timed out waiting for NETCONF hello from {}", address)))??;
+
+        debug!(device = %address, capabilities = ?session.capabilities(), "NETCONF capabilities");
+        Ok(session)
+    }
+
+    /// Execute one RPC, reusing an idle session when possible
+    pub async fn rpc(&self, target: &str, call: &RpcCall) -> ApiResult<String> {
+        let address = DeviceAddress::parse(target)?;
+        let limit = self.limit_for(&address).await;
+        let _permit = limit
+            .acquire_owned()
+            .await
+            .map_err(|_| ApiError::InternalError("NETCONF session limiter closed".to_string()))?;
+
+        // Resolved on every call so sessions opened with outdated credentials are not reused
+        let credentials = self.credentials_for(&address).await?;
+        let fingerprint = credentials.as_ref().map(fingerprint);
+
+        // A pooled session may have been closed by the device; retry once on a fresh one
+        if let Some(session) = self.take_idle(&address, fingerprint).await {
+            debug!(device = %address, session_id = ?session.session_id(), "Reusing NETCONF session");
+            match self.rpc_on(&address, fingerprint, session, call).await {
+                Err((e, true)) => debug!(device = %address, error = %e, "Pooled session failed, reconnecting"),
+                result => return result.map_err(|(e, _)| e),
+            }
+        }
+
+        let session = self.open(&address, credentials).await?;
+        self.rpc_on(&address, fingerprint, session, call).await.map_err(|(e, _)| e)
+    }
+
+    /// Run an RPC and return the session to the pool if it is still healthy
+    /// Errors carry whether the session broke, i.e. whether a retry may help
+    async fn rpc_on(
+        &self,
+        address: &DeviceAddress,
+        credentials: CredentialFingerprint,
+        mut session: NetconfSession,
+        call: &RpcCall,
+    ) -> Result<String, (ApiError, bool)> {
+        let result = match tokio::time::timeout(self.config.rpc_timeout, session.rpc(call)).await {
+            Ok(result) => result,
+            Err(_) => Err(ApiError::DeviceError(format!(
+                "RPC '{}' on {} timed out after {}s",
+                call.name,
+                address,
+                self.config.rpc_timeout.as_secs()
+            ))),
+        };
+
+        // The timeout drops the in-flight future, which leaves the session marked broken
+        let broken = session.is_broken();
+        if broken {
+            warn!(device = %address, session_id = ?session.session_id(), "Discarding broken NETCONF session");
+        } else {
+            self.release(address, credentials, session).await;
+        }
+        result.map_err(|e| (e, broken))
+    }
+
+    async fn release(&self, address: &DeviceAddress, credentials: CredentialFingerprint, session: NetconfSession) {
+        self.idle
+            .lock()
+            .await
+            .entry(address.clone())
+            .or_default()
+            .push(PooledSession { session, credentials });
+    }
+
+    /// An idle session opened with these credentials; sessions of the device opened with
+    /// other credentials are closed
+    async fn take_idle(&self, address: &DeviceAddress, credentials: CredentialFingerprint) -> Option<NetconfSession> {
+        let mut idle = self.idle.lock().await;
+        let sessions = idle.get_mut(address)?;
+
+        let (current, stale): (Vec<_>, Vec<_>) = sessions.drain(..).partition(|s| s.credentials == credentials);
+        *sessions = current;
+        if !stale.is_empty() {
+            info!(device = %address, count = stale.len(), "Device credentials changed, closing pooled sessions");
+            for pooled in stale {
+                tokio::spawn(pooled.session.close());
+            }
+        }
+
+        while let Some(pooled) = sessions.pop() {
+            if pooled.session.idle_for() < self.config.idle_timeout {
+                return Some(pooled.session);
+            }
+            tokio::spawn(pooled.session.close());
+        }
+        None
+    }
+
+    async fn limit_for(&self, address: &DeviceAddress) -> Arc<Semaphore> {
+        self.limits
+            .lock()
+            .await
+            .entry(address.clone())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_sessions_per_device)))
+            .clone()
+    }
+
+    async fn sweep_idle(&self) {
+        let expired: Vec<NetconfSession> = {
+            let mut idle = self.idle.lock().await;
+            let mut expired = Vec::new();
+            for sessions in idle.values_mut() {
+                let (keep, drop): (Vec<_>, Vec<_>) = sessions
+                    .drain(..)
+                    .partition(|s| s.session.idle_for() < self.config.idle_timeout);
+                *sessions = keep;
+                expired.extend(drop.into_iter().map(|s| s.session));
+            }
+            idle.retain(|_, sessions| !sessions.is_empty());
+            expired
+        };
+
+        if !expired.is_empty() {
+            debug!(count = expired.len(), "Closing idle NETCONF sessions");
+        }
+        for session in expired {
+            session.close().await;
+        }
+    }
+
+    /// Close every pooled session
+    pub async fn close_all(&self) {
+        let sessions: Vec<NetconfSession> = self
+            .idle
+            .lock()
+            .await
+            .drain()
+            .flat_map(|(_, sessions)| sessions.into_iter().map(|s| s.session))
+            .collect();
+        info!(count = sessions.len(), "Closing pooled NETCONF sessions");
+        for session in sessions {
+            session.close().await;
+        }
+    }
+}
+
+/// Fingerprint of everything an SSH login depends on
+fn fingerprint(credentials: &SshCredentials) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    let mut field = |value: Option<&[u8]>| match value {
+        // Length-prefixed so adjacent fields cannot run into each other
+        Some(bytes) => {
+            hasher.update([1]);
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+        }
+        None => hasher.update([0]),
+    };
+    field(Some(credentials.username.as_bytes()));
+    field(credentials.password.as_deref().map(str::as_bytes));
+    field(credentials.private_key.as_deref().map(|path| path.as_os_str().as_encoded_bytes()));
+    field(credentials.private_key_pem.as_deref().map(str::as_bytes));
+    field(credentials.passphrase.as_deref().map(str::as_bytes));
+    hasher.finalize().into()
+}
+
+#[async_trait]
+impl ReportTransport for NetconfTransport {
+    fn name(&self) -> &'static str {
+        "netconf"
+    }
+
+    async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String> {
+        self.rpc(device, call).await
+    }
+
+    async fn shutdown(&self) {
+        self.close_all().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{netconf::standin::StandInServer, report_engine::StaticTransport};
+    use indexmap::IndexMap;
+    use tokio::net::TcpListener;
+
+    async fn standin() -> DeviceAddress {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = DeviceAddress::parse(&format!("tcp://{}", listener.local_addr().unwrap())).unwrap();
+        let replies = StaticTransport::from_dir("../shared/fixtures/rpc-replies").unwrap();
+        tokio::spawn(Arc::new(StandInServer::new(replies, false)).serve(listener));
+        address
+    }
+
+    fn transport() -> NetconfTransport {
+        NetconfTransport::new(NetconfConfig {
+            allow_plain_tcp: true,
+            ..NetconfConfig::default()
+        })
+    }
+
+    async fn idle_count(transport: &NetconfTransport, address: &DeviceAddress) -> usize {
+        transport.idle.lock().await.get(address).map_or(0, Vec::len)
+    }
+
+    /// Credentials that can be swapped between calls, like a reassigned vault profile
+    #[derive(Debug, Default)]
+    struct SwappableCredentials(std::sync::Mutex<Option<SshCredentials>>);
+
+    #[async_trait]
+    impl CredentialSource for SwappableCredentials {
+        async fn credentials_for(&self, _address: &DeviceAddress) -> ApiResult<Option<SshCredentials>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn login(username: &str, password: &str) -> SshCredentials {
+        SshCredentials {
+            username: username.to_string(),
+            password: Some(password.to_string()),
+            ..SshCredentials::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn sessions_are_reused() {
+        let address = standin().await;
+        let transport = transport();
+        let call = RpcCall { name: "get-system-information".to_string(), args: IndexMap::new() };
+
+        for _ in 0..3 {
+            let reply = transport.rpc(&address.to_string(), &call).await.unwrap();
+            assert!(reply.contains("<host-name>r1-lab</host-name>"));
+            assert_eq!(idle_count(&transport, &address).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn sessions_of_other_credentials_are_closed_not_reused() {
+        let address = standin().await;
+        let transport = transport();
+        let (old, new) = (Some([1; 32]), Some([2; 32]));
+
+        transport.release(&address, old, transport.connect(&address).await.unwrap()).await;
+        transport.release(&address, old, transport.connect(&address).await.unwrap()).await;
+        assert!(transport.take_idle(&address, new).await.is_none());
+        assert_eq!(idle_count(&transport, &address).await, 0);
+
+        transport.release(&address, new, transport.connect(&address).await.unwrap()).await;
+        assert!(transport.take_idle(&address, new).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn credentials_are_resolved_on_every_call() {
+        let source = Arc::new(SwappableCredentials::default());
+        let transport = transport().with_credentials(source.clone());
+        let ssh = DeviceAddress::parse("r1.lab").unwrap();
+
+        let defaults = transport.credentials_for(&ssh).await.unwrap().unwrap();
+        assert_eq!(defaults.username, "");
+        *source.0.lock().unwrap() = Some(login("ops", "one"));
+        let first = transport.credentials_for(&ssh).await.unwrap().unwrap();
+        *source.0.lock().unwrap() = Some(login("ops", "two"));
+        let second = transport.credentials_for(&ssh).await.unwrap().unwrap();
+        assert_ne!(fingerprint(&first), fingerprint(&second));
+
+        // Plain TCP sessions do not log in
+        let tcp = DeviceAddress::parse("tcp://127.0.0.1:8300").unwrap();
+        assert!(transport.credentials_for(&tcp).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn fingerprint_covers_every_credential() {
+        let base = login("ops", "secret");
+        assert_eq!(fingerprint(&base), fingerprint(&base.clone()));
+
+        let variants = [
+            login("admin", "secret"),
+            login("ops", "other"),
+            login("opss", "ecret"),
+            SshCredentials { private_key_pem: Some("KEY".to_string()), ..base.clone() },
+            SshCredentials { passphrase: Some("pass".to_string()), ..base.clone() },
+            SshCredentials { private_key: Some(PathBuf::from("/keys/ops")), ..base.clone() },
+        ];
+        for variant in &variants {
+            assert_ne!(fingerprint(&base), fingerprint(variant), "{:?}", variant);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// backend/src/services/rate_limit.rs
+
+//! Token bucket rate limiter
+//!
+//! Used per WebSocket connection to bound the rate of inbound messages.
+//! The bucket starts full, refills continuously at `refill_per_second`
+//! and never holds more than `capacity` tokens.
+
+use std::time::Instant;
+
+/// Classic token bucket; not thread-safe, owned by a single connection task
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_second: refill_per_second.max(0.0),
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take one token; returns false when the bucket is empty
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn full_bucket_allows_a_burst_of_capacity() {
+        let mut bucket = TokenBucket::new(3, 0.0);
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+    }
+
+    #[test]
+    fn empty_bucket_refills_over_time() {
+        let mut bucket = TokenBucket::new(1, 20.0);
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(bucket.try_acquire());
+    }
+
+    #[test]
+    fn refill_never_exceeds_capacity() {
+        let mut bucket = TokenBucket::new(2, 10.0);
+        std::thread::sleep(Duration::from_millis(300));
+
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+    }
+
+    #[test]
+    fn zero_capacity_is_raised_to_one() {
+        let mut bucket = TokenBucket::new(0, 0.0);
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+    }
+}
@@ -0,0 +1,542 @@
+// backend/src/services/report_catalog.rs
+
+//! # Report Catalog
+//!
+//! ## Description
+//! Creates, replaces and deletes report definitions in `shared/data/reports.yaml`.
+//! Edits are made on the file text, one top-level entry at a time, so the other
+//! entries and every comment outside the edited entry survive untouched. Each change
+//! is checked against `reports.json` and compiled like a run would before the file
+//! is replaced, then announced on the `data:reports` topic. Composite reports reading
+//! from the changed report are compiled too, and a report that is still a source of
+//! another cannot be deleted.
+//!
+//! ## Entries
+//! An entry is a top-level `test_...:` line plus the indented lines below it. Comment
+//! lines directly above the key belong to it: they are kept when the entry is replaced
+//! and removed with it. New entries go after the last existing one, before any
+//! trailing comments.
+//!
+//! ## How to Use
+//! 1. `let catalog = ReportCatalog::new(yaml_service, websocket_service);`
+//! 2. `catalog.create("test_bgp_flaps", body).await?` - `409` if the ID exists
+//! 3. `catalog.update(...)` / `catalog.delete(...)` - `404` if it does not
+//! 4. Subscribe to `data:reports` for `reports_changed` events
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{
+    models::{
+        reports::Report,
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    routes::reports::load_reports,
+    services::{report_engine::CompiledReport, report_join, WebSocketService, YamlService},
+};
+
+/// Schema (and data file stem) the catalog edits
+const SCHEMA: &str = "reports";
+
+/// `DataUpdate.source` of catalog change events
+const TOPIC_SOURCE: &str = "reports";
+
+/// What happened to a report definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Writes report definitions back to `reports.yaml`
+pub struct ReportCatalog {
+    yaml_service: Arc<YamlService>,
+    websocket: Arc<WebSocketService>,
+    /// Serializes read-modify-write cycles on the file
+    write_lock: Mutex<()>,
+}
+
+impl ReportCatalog {
+    pub fn new(yaml_service: Arc<YamlService>, websocket: Arc<WebSocketService>) -> Self {
+        Self {
+            yaml_service,
+            websocket,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Add a report; fails with `Conflict` if the ID is taken
+    pub async fn create(&self, report_id: &str, body: Value) -> ApiResult<Report> {
+        let report = self.validate(report_id, body)?;
+        self.edit(report_id, CatalogChange::Created, Some(&report)).await?;
+        Ok(report)
+    }
+
+    /// Replace an existing report; fails with `NotFound` if there is none
+    pub async fn update(&self, report_id: &str, body: Value) -> ApiResult<Report> {
+        let report = self.validate(report_id, body)?;
+        self.edit(report_id, CatalogChange::Updated, Some(&report)).await?;
+        Ok(report)
+    }
+
+    /// Remove a report; fails with `NotFound` if there is none
+    pub async fn delete(&self, report_id: &str) -> ApiResult<()> {
+        self.edit(report_id, CatalogChange::Deleted, None).await
+    }
+
+    /// Check a submitted definition the way the file and a run would
+    fn validate(&self, report_id: &str, body: Value) -> ApiResult<Report> {
+        if !is_valid_report_id(report_id) {
+            return Err(ApiError::ValidationError(format!(
+                "Report ID '{}' must match ^test_[a-zA-Z0-9_]+$",
+                report_id
+            )));
+        }
+        self.yaml_service
+            .validate_against_schema(SCHEMA, &json!({ report_id: body }))?;
+        serde_json::from_value(body).map_err(|e| ApiError::ValidationError(format!("Invalid report: {}", e)))
+    }
+
+    /// Compile the changed report and every composite report reading from it against
+    /// the catalogue as it will be; deleting a report that is still a source is refused
+    async fn check_dependents(&self, report_id: &str, report: Option<&Report>) -> ApiResult<()> {
+        let mut reports = load_reports(&self.yaml_service).await?;
+        match report {
+            Some(report) => reports.insert(report_id.to_string(), report.clone()),
+            None => reports.remove(report_id),
+        };
+        report_join::link_sources(&mut reports);
+
+        let mut dependents: Vec<&String> = reports
+            .iter()
+            .filter(|(_, r)| r.sources.iter().any(|source| source.report == report_id))
+            .map(|(id, _)| id)
+            .collect();
+        dependents.sort();
+        if report.is_none() && !dependents.is_empty() {
+            return Err(ApiError::Conflict(format!(
+                "Report '{}' is a source of {}; remove it there first",
+                report_id,
+                dependents.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+            )));
+        }
+
+        if let Some(report) = reports.get(report_id) {
+            CompiledReport::compile(report)?;
+        }
+        for dependent in dependents {
+            CompiledReport::compile(&reports[dependent]).map_err(|e| match e {
+                ApiError::ValidationError(message) => {
+                    ApiError::ValidationError(format!("Composite report '{}' would break: {}", dependent, message))
+                }
+                other => other,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Apply one change to the file, verify the result and announce it
+    async fn edit(&self, report_id: &str, change: CatalogChange, report: Option<&Report>) -> ApiResult<()> {
+        let _guard = self.write_lock.lock().await;
+        let path = self.yaml_service.data_file(SCHEMA);
+        let text = tokio::fs::read_to_string(&path).await?;
+        let entries = top_level_entries(&text);
+        let existing = entries.iter().find(|e| e.key == report_id);
+
+        let rendered = report
+            .map(|r| render_entry(report_id, r))
+            .transpose()?
+            .unwrap_or_default();
+        let updated = match (change, existing) {
+            (CatalogChange::Created, Some(_)) => {
+                return Err(ApiError::Conflict(format!("Report '{}' already exists", report_id)));
+            }
+            (CatalogChange::Created, None) => insert_entry(&text, &entries, &rendered),
+            (_, None) => return Err(ApiError::NotFound(format!("Report '{}' not found", report_id))),
+            (CatalogChange::Updated, Some(entry)) => splice(&text, entry.body.clone(), &rendered),
+            (CatalogChange::Deleted, Some(entry)) => remove_entry(&text, entry),
+        };
+        self.check_dependents(report_id, report).await?;
+
+        self.verify(&text, &updated, report_id, report)?;
+
+        // Write-then-rename so a crash never leaves a truncated catalogue
+        let temporary = path.with_extension("yaml.tmp");
+        tokio::fs::write(&temporary, &updated).await?;
+        tokio::fs::rename(&temporary, &path).await?;
+        info!(report_id = %report_id, change = ?change, file = %path.display(), "Report catalogue changed");
+
+        self.publish(report_id, change, report).await;
+        Ok(())
+    }
+
+    /// Make sure the edited text parses, matches the schema, holds the intended entry
+    /// and leaves every other entry exactly as it was
+    fn verify(&self, before: &str, after: &str, report_id: &str, report: Option<&Report>) -> ApiResult<()> {
+        let parse = |text: &str| -> ApiResult<HashMap<String, Value>> {
+            let value: Value = serde_yaml::from_str(text).map_err(|e| ApiError::YamlParseError(e.to_string()))?;
+            match value {
+                Value::Object(map) => Ok(map.into_iter().collect()),
+                Value::Null => Ok(HashMap::new()),
+                _ => Err(ApiError::ValidationError("reports.yaml must be a mapping".to_string())),
+            }
+        };
+        let mut previous = parse(before)?;
+        let mut current = parse(after)?;
+
+        let document = Value::Object(current.clone().into_iter().collect());
+        self.yaml_service.validate_against_schema(SCHEMA, &document)?;
+
+        let written = current.remove(report_id);
+        previous.remove(report_id);
+        let expected = report
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| ApiError::SerializationError(e.to_string()))?;
+        if written != expected || previous != current {
+            warn!(report_id = %report_id, "Edited report catalogue does not round-trip, leaving the file untouched");
+            return Err(ApiError::InternalError(format!(
+                "Could not edit report '{}' without disturbing reports.yaml",
+                report_id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn publish(&self, report_id: &str, change: CatalogChange, report: Option<&Report>) {
+        let message = WsMessage::DataUpdate {
+            source: TOPIC_SOURCE.to_string(),
+            data: json!({
+                "event": "reports_changed",
+                "change": change,
+                "report_id": report_id,
+                "report": report,
+            }),
+            timestamp: chrono::Utc::now(),
+        };
+        let topic = SubscriptionTopic::DataUpdates(TOPIC_SOURCE.to_string());
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(report_id = %report_id, error = %e, "Failed to publish report catalogue change");
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// TEXT EDITING
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A top-level mapping entry, as byte ranges of the file text
+#[derive(Debug, Clone)]
+struct Entry {
+    key: String,
+    /// Comment lines directly above the key
+    comments: std::ops::Range<usize>,
+    /// Key line through the last indented line
+    body: std::ops::Range<usize>,
+}
+
+/// `^test_[a-zA-Z0-9_]+$`, the key pattern of `reports.json`
+fn is_valid_report_id(report_id: &str) -> bool {
+    report_id
+        .strip_prefix("test_")
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+/// Lines with the byte offset they start at, line endings included
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line)
+        })
+        .collect()
+}
+
+/// Key of a top-level mapping line, unquoted
+fn top_level_key(line: &str) -> Option<String> {
+    if line.starts_with([' ', '\t', '#', '-']) || line.trim().is_empty() {
+        return None;
+    }
+    let line = line.trim_end();
+    for quote in ['"', '\''] {
+        if let Some(rest) = line.strip_prefix(quote) {
+            let (key, after) = rest.split_once(quote)?;
+            return after.trim_start().starts_with(':').then(|| key.to_string());
+        }
+    }
+    let (key, _) = line.split_once(':')?;
+    Some(key.trim().to_string())
+}
+
+fn top_level_entries(text: &str) -> Vec<Entry> {
+    let lines = lines_with_offsets(text);
+    let mut entries = Vec::new();
+
+    for (index, (start, line)) in lines.iter().enumerate() {
+        let Some(key) = top_level_key(line) else {
+            continue;
+        };
+
+        // The entry runs until the next line at column 0, minus trailing blank lines
+        let mut end = start + line.len();
+        for (offset, next) in &lines[index + 1..] {
+            if next.trim().is_empty() {
+                continue;
+            }
+            if !next.starts_with([' ', '\t']) {
+                break;
+            }
+            end = offset + next.len();
+        }
+
+        let mut comments_start = *start;
+        for (offset, previous) in lines[..index].iter().rev() {
+            if !previous.starts_with('#') {
+                break;
+            }
+            comments_start = *offset;
+        }
+
+        entries.push(Entry {
+            key,
+            comments: comments_start..*start,
+            body: *start..end,
+        });
+    }
+    entries
+}
+
+/// A report as a YAML block: `report_id:` followed by its indented definition
+fn render_entry(report_id: &str, report: &Report) -> ApiResult<String> {
+    let mut entry = IndexMap::new();
+    entry.insert(report_id, report);
+    serde_yaml::to_string(&entry).map_err(|e| ApiError::SerializationError(e.to_string()))
+}
+
+fn splice(text: &str, range: std::ops::Range<usize>, replacement: &str) -> String {
+    let mut result = String::with_capacity(text.len() + replacement.len());
+    result.push_str(&text[..range.start]);
+    result.push_str(replacement);
+    result.push_str(&text[range.end..]);
+    result
+}
+
+/// Insert after the last entry, keeping a blank line on each side
+fn insert_entry(text: &str, entries: &[Entry], rendered: &str) -> String {
+    match entries.last() {
+        Some(last) => {
+            // Trailing comments right after the last entry stay separated from the new one
+            let after = &text[last.body.end..];
+            let gap = if after.is_empty() || after.starts_with('\n') { "" } else { "\n" };
+            splice(text, last.body.end..last.body.end, &format!("\n{}{}", rendered, gap))
+        }
+        None if text.trim().is_empty() => rendered.to_string(),
+        None => {
+            let separator = if text.ends_with('\n') { "\n" } else { "\n\n" };
+            format!("{}{}{}", text, separator, rendered)
+        }
+    }
+}
+
+/// Remove an entry with its leading comments and the blank lines that separated it
+fn remove_entry(text: &str, entry: &Entry) -> String {
+    let mut start = entry.comments.start;
+    let blank_length = |lines: &mut dyn Iterator<Item = &str>| -> usize {
+        lines.take_while(|line| line.trim().is_empty()).map(str::len).sum()
+    };
+    let end = entry.body.end + blank_length(&mut text[entry.body.end..].split_inclusive('\n'));
+    // At the end of the file, drop the blank lines before the entry instead
+    if end == text.len() {
+        start -= blank_length(&mut text[..start].split_inclusive('\n').rev());
+    }
+    splice(text, start..end, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    const CATALOGUE: &str = "\
+# Report catalogue
+
+# Interface counters
+test_alpha:
+  title: Alpha
+  category: Interfaces
+  rpc: get-alpha
+  xpath: .//alpha
+  fields:
+    Name: name
+
+# BGP peers
+test_beta:
+  title: Beta
+  category: Routing
+  rpc: get-beta
+  xpath: .//beta
+  fields:
+    Peer: peer
+
+# Add new reports above
+";
+
+    fn report(title: &str) -> Report {
+        serde_json::from_value(json!({
+            "title": title,
+            "category": "Test",
+            "rpc": "get-gamma",
+            "xpath": ".//gamma",
+            "fields": { "Name": "name" }
+        }))
+        .unwrap()
+    }
+
+    fn keys(text: &str) -> Vec<String> {
+        top_level_entries(text).into_iter().map(|e| e.key).collect()
+    }
+
+    /// A catalog editing a copy of `text` in a fresh data directory
+    async fn catalog(text: &str) -> (ReportCatalog, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("thalyx-catalog-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("reports.yaml"), text).unwrap();
+        let yaml_service = YamlService::new("../shared/schemas").await.unwrap().with_data_dir(&dir);
+        let catalog = ReportCatalog::new(Arc::new(yaml_service), Arc::new(WebSocketService::new(None)));
+        (catalog, dir)
+    }
+
+    #[test]
+    fn entries_own_their_comments_but_not_trailing_blank_lines() {
+        let entries = top_level_entries(CATALOGUE);
+        assert_eq!(keys(CATALOGUE), ["test_alpha", "test_beta"]);
+
+        assert_eq!(&CATALOGUE[entries[0].comments.clone()], "# Interface counters\n");
+        assert!(CATALOGUE[entries[0].body.clone()].starts_with("test_alpha:\n"));
+        assert!(CATALOGUE[entries[0].body.clone()].ends_with("    Name: name\n"));
+        assert_eq!(&CATALOGUE[entries[1].comments.clone()], "# BGP peers\n");
+        assert!(CATALOGUE[entries[1].body.clone()].ends_with("    Peer: peer\n"));
+    }
+
+    #[test]
+    fn quoted_and_nested_keys() {
+        let text = "\"test_quoted\":\n  title: Q\n  nested: {a: 1}\n- not a key\n";
+        assert_eq!(keys(text), ["test_quoted"]);
+    }
+
+    #[test]
+    fn insert_goes_after_the_last_entry_before_trailing_comments() {
+        let rendered = render_entry("test_gamma", &report("Gamma")).unwrap();
+        let updated = insert_entry(CATALOGUE, &top_level_entries(CATALOGUE), &rendered);
+
+        assert_eq!(keys(&updated), ["test_alpha", "test_beta", "test_gamma"]);
+        assert!(updated.starts_with(&CATALOGUE[..CATALOGUE.find("# Add new").unwrap() - 1]));
+        assert!(updated.contains("    Peer: peer\n\ntest_gamma:\n"));
+        assert!(updated.ends_with("    Name: name\n\n# Add new reports above\n"));
+    }
+
+    #[test]
+    fn insert_after_an_entry_ending_the_file() {
+        let text = "test_alpha:\n  title: Alpha\n";
+        let rendered = render_entry("test_gamma", &report("Gamma")).unwrap();
+        let updated = insert_entry(text, &top_level_entries(text), &rendered);
+
+        assert!(updated.starts_with("test_alpha:\n  title: Alpha\n\ntest_gamma:\n"));
+        assert_eq!(keys(&updated), ["test_alpha", "test_gamma"]);
+        assert_eq!(insert_entry("", &[], &rendered), rendered);
+    }
+
+    #[test]
+    fn update_replaces_only_the_entry_body() {
+        let entries = top_level_entries(CATALOGUE);
+        let rendered = render_entry("test_alpha", &report("Alpha 2")).unwrap();
+        let updated = splice(CATALOGUE, entries[0].body.clone(), &rendered);
+
+        assert!(updated.contains("# Interface counters\ntest_alpha:\n  title: Alpha 2\n"));
+        assert_eq!(
+            &updated[updated.find("\n\n# BGP peers").unwrap()..],
+            &CATALOGUE[CATALOGUE.find("\n\n# BGP peers").unwrap()..]
+        );
+    }
+
+    #[test]
+    fn remove_takes_comments_and_one_blank_line() {
+        let entries = top_level_entries(CATALOGUE);
+        let updated = remove_entry(CATALOGUE, &entries[0]);
+
+        assert_eq!(
+            updated,
+            CATALOGUE.replace(
+                "# Interface counters\ntest_alpha:\n  title: Alpha\n  category: Interfaces\n  \
+                 rpc: get-alpha\n  xpath: .//alpha\n  fields:\n    Name: name\n\n",
+                ""
+            )
+        );
+    }
+
+    #[test]
+    fn remove_the_last_entry_of_the_file() {
+        let text = "test_alpha:\n  title: Alpha\n\n# Beta\ntest_beta:\n  title: Beta\n";
+        let entries = top_level_entries(text);
+        assert_eq!(remove_entry(text, &entries[1]), "test_alpha:\n  title: Alpha\n");
+
+        let only = "# Alpha\ntest_alpha:\n  title: Alpha\n";
+        assert_eq!(remove_entry(only, &top_level_entries(only)[0]), "");
+    }
+
+    #[tokio::test]
+    async fn create_update_delete_keep_the_rest_of_the_file() {
+        let (catalog, dir) = catalog(CATALOGUE).await;
+        let path = dir.join("reports.yaml");
+        let body = |title: &str| serde_json::to_value(report(title)).unwrap();
+
+        catalog.create("test_gamma", body("Gamma")).await.unwrap();
+        let created = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(keys(&created), ["test_alpha", "test_beta", "test_gamma"]);
+        assert!(created.ends_with("\n# Add new reports above\n"));
+        assert!(matches!(catalog.create("test_gamma", body("Again")).await, Err(ApiError::Conflict(_))));
+
+        catalog.update("test_alpha", body("Alpha 2")).await.unwrap();
+        let updated = std::fs::read_to_string(&path).unwrap();
+        assert!(updated.contains("# Interface counters\ntest_alpha:\n  title: Alpha 2\n"));
+        assert!(updated.contains("# BGP peers\ntest_beta:\n  title: Beta\n"));
+
+        catalog.delete("test_gamma").await.unwrap();
+        catalog.delete("test_beta").await.unwrap();
+        let deleted = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(keys(&deleted), ["test_alpha"]);
+        assert!(deleted.starts_with("# Report catalogue\n\n# Interface counters\ntest_alpha:\n"));
+        assert!(deleted.ends_with("\n# Add new reports above\n"));
+        assert!(!deleted.contains("BGP peers"));
+        assert!(matches!(catalog.delete("test_beta").await, Err(ApiError::NotFound(_))));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_verification_leaves_the_file_unchanged() {
+        // test_beta reuses test_alpha's fields through an alias, so rewriting test_alpha
+        // drops the anchor and the edited text no longer parses
+        let text = CATALOGUE
+            .replace("  fields:\n    Name: name\n", "  fields: &fields\n    Name: name\n")
+            .replace("  fields:\n    Peer: peer\n", "  fields: *fields\n");
+        let (catalog, dir) = catalog(&text).await;
+        let path = dir.join("reports.yaml");
+
+        let result = catalog.update("test_alpha", serde_json::to_value(report("Alpha 2")).unwrap()).await;
+        assert!(matches!(result, Err(ApiError::YamlParseError(_))), "{:?}", result);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert!(!dir.join("reports.yaml.tmp").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
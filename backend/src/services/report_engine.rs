@@ -0,0 +1,766 @@
+// backend/src/services/report_engine.rs
+
+//! # Report Engine
+//!
+//! ## Description
+//! Runs `Report` definitions against devices. A run invokes the report's RPC through a
+//! `ReportTransport`, applies `Report.xpath` to the XML reply and projects every matched
+//! node through `Report.fields` into a table whose columns follow the declaration order.
+//!
+//! ## Transports
+//! - `NetconfTransport` - NETCONF over SSH to live devices (`services::netconf`)
+//! - `StaticTransport` - answers from recorded XML replies (fixtures, demos, tests)
+//! - `ReplayTransport` / `CaptureTransport` - per-device captured replies
+//!   (`services::report_replay`)
+//!
+//! ## How to Use
+//! 1. Build an engine: `ReportEngine::new(Arc::new(StaticTransport::from_dir(dir)?))`
+//! 2. Run a report: `engine.run("test_bgp_summary", &report, "r1.lab", &parameters).await?`
+//! 3. Or extract from an XML string directly with `CompiledReport::compile(&report)?.extract(xml, &values)`
+//! 4. Check a whole catalogue against recorded replies with `check_catalogue`
+//!
+//! ## Expressions
+//! `Report.xpath` and field paths are XPath 1.0 (`services::xpath`). Fields are
+//! evaluated with the row node as context: `peer-address`, `@junos:seconds`,
+//! `alarm-time/@junos:seconds`, `logical-interface[1]/name`, `count(logical-interface)`,
+//! `normalize-space(text())`. Unprefixed names match any namespace, so Junos
+//! namespaces do not need to be declared. A field that matches nothing yields a
+//! `null` cell and is listed in `ReportResult.misses`.
+//!
+//! ## Types
+//! Fields declared with a `type` (`services::field_types`) are converted after
+//! extraction: integers, floats with optional unit conversion, booleans, durations in
+//! seconds, normalized IP addresses and enum mappings. Values that do not parse become
+//! `null` and are listed in `ReportResult.misses` with reason `unparsed`.
+//!
+//! ## Assertions
+//! `Report.assertions` are checked against every extracted table
+//! (`services::assertions`); the verdicts land in `ReportResult.assertions`.
+//!
+//! ## Parameters
+//! Reports declaring `Report.parameters` are templates (`services::report_parameters`):
+//! each run binds values that replace `$name` in `rpc_args` and are visible to the
+//! row and field expressions as XPath variables. The values used are returned in
+//! `ReportResult.parameters`.
+//!
+//! ## Composite Reports
+//! Reports declaring `Report.sources` run every source report and join their tables
+//! on key columns (`services::report_join`) instead of calling an RPC themselves.
+//! `Report.computed` appends columns calculated from the other cells of each row
+//! (`services::computed_columns`) to any report, before assertions are checked.
+
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        reports::{
+            find_column, AssertionResults, FieldMiss, MissReason, ParameterValues, Report, ReportColumn,
+            ReportResult, ReportRow, Verdict,
+        },
+        ApiError, ApiResult,
+    },
+    services::{
+        assertions::CompiledAssertions,
+        computed_columns::ComputedColumns,
+        field_types::FieldTransform,
+        report_join::CompiledJoin,
+        report_parameters,
+        xpath::{self, XNode, XPath, XPathError, XPathOptions},
+    },
+};
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// TRANSPORT
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// A single RPC invocation sent to a device
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcCall {
+    /// RPC name, e.g. `get-bgp-summary-information`
+    pub name: String,
+    /// Arguments rendered as child elements of the RPC; `@name` keys become attributes
+    /// of the RPC element instead (`"@format": "text"`)
+    pub args: IndexMap<String, Value>,
+}
+
+impl RpcCall {
+    /// Build the call described by a report definition, with parameter values bound
+    pub fn for_report(report: &Report, parameters: &ParameterValues) -> Self {
+        Self {
+            name: report.rpc.clone(),
+            args: report
+                .rpc_args
+                .as_ref()
+                .map(|args| report_parameters::bind_rpc_args(args, parameters))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Pluggable way of executing RPCs on devices
+#[async_trait]
+pub trait ReportTransport: Send + Sync + std::fmt::Debug {
+    /// Short name for logging and diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Execute an RPC on a device and return the raw XML reply
+    async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String>;
+
+    /// Release connections before the server exits
+    async fn shutdown(&self) {}
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// STATIC TRANSPORT
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Transport answering every device from recorded XML replies keyed by RPC name
+/// Calls with an `@format` attribute prefer a reply recorded as `<rpc-name>.<format>.xml`
+#[derive(Debug, Clone, Default)]
+pub struct StaticTransport {
+    replies: HashMap<String, String>,
+}
+
+impl StaticTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `<rpc-name>.xml` file in a directory
+    pub fn from_dir(dir: impl AsRef<Path>) -> ApiResult<Self> {
+        let dir = dir.as_ref();
+        let mut transport = Self::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("xml") {
+                continue;
+            }
+            if let Some(rpc) = path.file_stem().and_then(|s| s.to_str()) {
+                let xml = std::fs::read_to_string(&path)?;
+                transport.replies.insert(rpc.to_string(), xml);
+            }
+        }
+
+        info!(dir = %dir.display(), replies = transport.replies.len(), "Loaded recorded RPC replies");
+        Ok(transport)
+    }
+
+    /// RPC names with a recorded reply
+    pub fn rpc_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.replies.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[async_trait]
+impl ReportTransport for StaticTransport {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String> {
+        debug!(device = %device, rpc = %call.name, "Answering RPC from recorded reply");
+        let formatted = call
+            .args
+            .get("@format")
+            .and_then(Value::as_str)
+            .and_then(|format| self.replies.get(&format!("{}.{}", call.name, format)));
+        formatted.or_else(|| self.replies.get(&call.name)).cloned().ok_or_else(|| {
+            ApiError::DeviceError(format!("No recorded reply for RPC '{}'", call.name))
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// ENGINE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Executes report definitions through a transport
+#[derive(Debug, Clone)]
+pub struct ReportEngine {
+    transport: Arc<dyn ReportTransport>,
+}
+
+impl ReportEngine {
+    pub fn new(transport: Arc<dyn ReportTransport>) -> Self {
+        Self { transport }
+    }
+
+    /// Name of the underlying transport
+    pub fn transport_name(&self) -> &'static str {
+        self.transport.name()
+    }
+
+    /// Release transport resources (pooled device sessions)
+    pub async fn shutdown(&self) {
+        self.transport.shutdown().await;
+    }
+
+    /// Send a single RPC to a device and return the raw reply, for callers that need
+    /// the document itself rather than a table (configuration backups)
+    pub async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String> {
+        let device = device.trim();
+        if device.is_empty() {
+            return Err(ApiError::ValidationError("Device must not be empty".to_string()));
+        }
+        debug!(device = %device, rpc = %call.name, transport = self.transport.name(), "Executing RPC");
+        self.transport.execute(device, call).await
+    }
+
+    /// Run a report against one device; `parameters` are checked against
+    /// `Report.parameters` and completed with defaults
+    pub async fn run(
+        &self,
+        report_id: &str,
+        report: &Report,
+        device: &str,
+        parameters: &ParameterValues,
+    ) -> ApiResult<ReportResult> {
+        let device = device.trim();
+        if device.is_empty() {
+            return Err(ApiError::ValidationError("Device must not be empty".to_string()));
+        }
+
+        // Reject broken expressions before contacting the device
+        let compiled = CompiledReport::compile(report)?;
+        let parameters = report_parameters::resolve(&report.parameters, parameters)?;
+
+        let started_at = chrono::Utc::now();
+        let timer = Instant::now();
+        let rpc = report.rpc_names();
+
+        info!(
+            report_id = %report_id,
+            device = %device,
+            rpc = %rpc,
+            parameters = %serde_json::Value::Object(parameters.clone()),
+            transport = self.transport.name(),
+            "Running report"
+        );
+
+        let table = if report.is_composite() {
+            let tables = self.fetch_sources(report_id, report, device, &parameters).await?;
+            compiled.join(tables, &parameters)?
+        } else {
+            let reply = self.fetch(report_id, report, device, &parameters).await?;
+            compiled.extract(&reply, &parameters)?
+        };
+        if !table.misses.is_empty() {
+            warn!(
+                report_id = %report_id,
+                device = %device,
+                fields = ?table.misses.iter().map(|m| m.field.as_str()).collect::<Vec<_>>(),
+                "Report fields matched nothing or did not parse in some rows"
+            );
+        }
+
+        if let Some(assertions) = &table.assertions {
+            let failed = assertions.checks.iter().filter(|c| c.verdict != Verdict::Pass).count();
+            match assertions.verdict {
+                Verdict::Pass => debug!(report_id = %report_id, device = %device, "Report assertions passed"),
+                verdict => warn!(
+                    report_id = %report_id,
+                    device = %device,
+                    verdict = verdict.as_str(),
+                    failed,
+                    checks = assertions.checks.len(),
+                    "Report assertions did not pass"
+                ),
+            }
+        }
+
+        let duration_ms = timer.elapsed().as_millis() as u64;
+        info!(report_id = %report_id, device = %device, rows = table.rows.len(), duration_ms, "Report completed");
+
+        Ok(ReportResult {
+            run_id: Uuid::new_v4(),
+            report_id: report_id.to_string(),
+            title: report.title.clone(),
+            device: device.to_string(),
+            rpc,
+            started_at,
+            duration_ms,
+            columns: table.columns,
+            rows: table.rows,
+            misses: table.misses,
+            assertions: table.assertions,
+            parameters,
+        })
+    }
+
+    /// Send a report's RPC and return the raw reply
+    async fn fetch(&self, report_id: &str, report: &Report, device: &str, parameters: &ParameterValues) -> ApiResult<String> {
+        let call = RpcCall::for_report(report, parameters);
+        self.transport.execute(device, &call).await.inspect_err(|e| {
+            warn!(report_id = %report_id, device = %device, rpc = %call.name, error = %e, "Report RPC failed");
+        })
+    }
+
+    /// Tables of every source of a composite report, fetched concurrently
+    /// Source parameters are resolved first so invalid values fail before any RPC is sent
+    async fn fetch_sources(
+        &self,
+        report_id: &str,
+        report: &Report,
+        device: &str,
+        parameters: &ParameterValues,
+    ) -> ApiResult<Vec<ExtractedTable>> {
+        let mut sources = Vec::with_capacity(report.sources.len());
+        for source in &report.sources {
+            let definition = source.definition.as_deref().ok_or_else(|| {
+                ApiError::ValidationError(format!("Invalid source '{}': no such report", source.report))
+            })?;
+            let supplied = report_parameters::bind_source_values(&source.parameters, parameters);
+            let values = report_parameters::resolve(&definition.parameters, &supplied).map_err(|e| match e {
+                ApiError::ValidationError(message) => {
+                    ApiError::ValidationError(format!("Invalid source '{}': {}", source.report, message))
+                }
+                other => other,
+            })?;
+            sources.push((source.report.as_str(), definition, CompiledReport::compile(definition)?, values));
+        }
+
+        let fetches = sources.iter().map(|(source_id, definition, compiled, values)| async move {
+            debug!(report_id = %report_id, source = %source_id, device = %device, "Fetching composite report source");
+            let reply = self.fetch(source_id, definition, device, values).await?;
+            compiled.extract(&reply, values)
+        });
+        futures_util::future::try_join_all(fetches).await
+    }
+
+    /// Run every report of a catalogue against one device, collecting failures instead of stopping
+    /// Templates run with their defaults, or stand-in values for required parameters
+    pub async fn check_catalogue(&self, reports: &HashMap<String, Report>, device: &str) -> Vec<CatalogueCheck> {
+        let mut report_ids: Vec<&String> = reports.keys().collect();
+        report_ids.sort();
+
+        let mut checks = Vec::with_capacity(report_ids.len());
+        for report_id in report_ids {
+            let report = &reports[report_id];
+            let parameters = report_parameters::sample_values(&report.parameters);
+            let (rows, misses, verdict, error) = match self.run(report_id, report, device, &parameters).await {
+                Ok(result) => (result.rows.len(), result.misses, result.assertions.map(|a| a.verdict), None),
+                Err(e) => (0, Vec::new(), None, Some(e.to_string())),
+            };
+            checks.push(CatalogueCheck {
+                report_id: report_id.clone(),
+                rpc: report.rpc_names(),
+                rows,
+                misses,
+                verdict,
+                error,
+            });
+        }
+        checks
+    }
+}
+
+/// Outcome of one report in a catalogue check
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogueCheck {
+    pub report_id: String,
+    pub rpc: String,
+    pub rows: usize,
+    pub misses: Vec<FieldMiss>,
+    /// Assertion verdict on the recorded reply, when the report declares assertions
+    pub verdict: Option<Verdict>,
+    /// Compile, transport or evaluation error
+    pub error: Option<String>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// EXTRACTION
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Table extracted from one RPC reply
+#[derive(Debug, Clone)]
+pub struct ExtractedTable {
+    pub columns: Vec<ReportColumn>,
+    pub rows: Vec<ReportRow>,
+    pub misses: Vec<FieldMiss>,
+    /// Verdicts of `Report.assertions`, when it declares any
+    pub assertions: Option<AssertionResults>,
+}
+
+/// A report's row and field expressions, compiled once per run
+#[derive(Debug, Clone)]
+pub struct CompiledReport {
+    rows: RowSource,
+    computed: ComputedColumns,
+    assertions: CompiledAssertions,
+}
+
+/// Where a report's rows come from
+#[derive(Debug, Clone)]
+enum RowSource {
+    /// Nodes of the RPC reply selected by `Report.xpath`, projected through the fields
+    Rpc { rows: XPath, fields: Vec<CompiledField> },
+    /// Tables of `Report.sources` joined on their keys
+    Join(CompiledJoin),
+}
+
+/// One column: where its cells come from and how they are typed
+#[derive(Debug, Clone)]
+struct CompiledField {
+    column: ReportColumn,
+    path: XPath,
+    transform: FieldTransform,
+}
+
+impl CompiledReport {
+    /// Compile `Report.xpath` and every field, or the sources of a composite report,
+    /// naming the offending expression on error
+    /// Also checks field types, computed columns, that `Report.key` and `Report.assertions`
+    /// refer to the columns and that every `$name` refers to a declared parameter
+    pub fn compile(report: &Report) -> ApiResult<Self> {
+        report_parameters::check_declarations(&report.parameters)?;
+        let rows = if report.is_composite() {
+            if !report.rpc.is_empty() || !report.xpath.is_empty() || !report.fields.is_empty() || report.rpc_args.is_some() {
+                return Err(ApiError::ValidationError(
+                    "Composite reports take their columns from sources; remove rpc, rpc_args, xpath and fields"
+                        .to_string(),
+                ));
+            }
+            RowSource::Join(CompiledJoin::compile(report)?)
+        } else {
+            if report.rpc.is_empty() || report.xpath.is_empty() || report.fields.is_empty() {
+                return Err(ApiError::ValidationError(
+                    "A report needs rpc, xpath and fields, or sources".to_string(),
+                ));
+            }
+            Self::compile_rpc(report)?
+        };
+
+        let mut columns = match &rows {
+            RowSource::Rpc { fields, .. } => fields.iter().map(|f| f.column.clone()).collect(),
+            RowSource::Join(join) => join.columns().to_vec(),
+        };
+        let computed = ComputedColumns::compile(&report.computed, &columns, &report.parameters)?;
+        columns.extend(computed.columns().cloned());
+
+        if let Some(key) = &report.key {
+            if find_column(&columns, key).is_none() {
+                return Err(ApiError::ValidationError(format!(
+                    "Key '{}' is neither a column name nor a field of the report",
+                    key
+                )));
+            }
+        }
+        let assertions = CompiledAssertions::compile(&report.assertions, &columns)?;
+        Ok(Self { rows, computed, assertions })
+    }
+
+    fn compile_rpc(report: &Report) -> ApiResult<RowSource> {
+        let rows = XPath::compile(&report.xpath)
+            .map_err(|e| invalid_expression("xpath", &report.xpath, &e))?;
+        let fields = report
+            .fields
+            .iter()
+            .map(|(name, spec)| {
+                let field = spec.path();
+                let path = XPath::compile(field).map_err(|e| invalid_expression(name, field, &e))?;
+                let transform = FieldTransform::compile(name, spec)?;
+                let column = ReportColumn {
+                    name: name.clone(),
+                    field: field.to_string(),
+                    kind: transform.kind(),
+                    unit: transform.unit().map(str::to_string),
+                };
+                Ok(CompiledField { column, path, transform })
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+
+        for name in rows.variables() {
+            report_parameters::check_reference(&report.parameters, "xpath", name)?;
+        }
+        for field in &fields {
+            for name in field.path.variables() {
+                report_parameters::check_reference(&report.parameters, &format!("Field '{}'", field.column.name), name)?;
+            }
+        }
+        if let Some(args) = &report.rpc_args {
+            for (location, name) in report_parameters::value_references("rpc_args", args) {
+                report_parameters::check_reference(&report.parameters, &location, &name)?;
+            }
+        }
+        Ok(RowSource::Rpc { rows, fields })
+    }
+
+    /// Columns of the result: fields or joined source columns, then computed columns
+    pub fn columns(&self) -> Vec<ReportColumn> {
+        let base = match &self.rows {
+            RowSource::Rpc { fields, .. } => fields.iter().map(|f| f.column.clone()).collect(),
+            RowSource::Join(join) => join.columns().to_vec(),
+        };
+        let mut columns: Vec<ReportColumn> = base;
+        columns.extend(self.computed.columns().cloned());
+        columns
+    }
+
+    /// Select the rows of an XML reply and evaluate every field against each of them, with
+    /// parameter values bound as XPath variables
+    pub fn extract(&self, xml: &str, parameters: &ParameterValues) -> ApiResult<ExtractedTable> {
+        let RowSource::Rpc { rows: selector, fields } = &self.rows else {
+            return Err(ApiError::InternalError(
+                "Composite reports are joined from their sources, not extracted".to_string(),
+            ));
+        };
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|e| ApiError::DeviceError(format!("Invalid XML reply: {}", e)))?;
+        let options = XPathOptions {
+            variables: parameters.iter().map(|(name, value)| (name.clone(), value.clone())).collect(),
+            ..XPathOptions::default()
+        };
+
+        let nodes = selector
+            .select(XNode::Tree(document.root()), &options)
+            .map_err(|e| evaluation_failed(selector.source(), &e))?;
+
+        let mut missing: Vec<Vec<usize>> = vec![Vec::new(); fields.len()];
+        let mut unparsed: Vec<Vec<(usize, String)>> = vec![Vec::new(); fields.len()];
+        let mut rows = Vec::with_capacity(nodes.len());
+        for (index, node) in nodes.into_iter().enumerate() {
+            let mut row = ReportRow::with_capacity(fields.len());
+            for (column, field) in fields.iter().enumerate() {
+                let value = field
+                    .path
+                    .evaluate(node, &options)
+                    .map_err(|e| evaluation_failed(field.path.source(), &e))?;
+                let cell = cell_value(value);
+                if cell.is_null() {
+                    missing[column].push(index);
+                }
+                let cell = field.transform.apply(cell).unwrap_or_else(|raw| {
+                    unparsed[column].push((index, raw));
+                    Value::Null
+                });
+                row.push(cell);
+            }
+            rows.push(row);
+        }
+
+        let mut misses = Vec::new();
+        for ((field, missing), unparsed) in fields.iter().zip(missing).zip(unparsed) {
+            let column = &field.column;
+            if !missing.is_empty() {
+                debug!(
+                    column = %column.name,
+                    field = %column.field,
+                    missing = missing.len(),
+                    rows = rows.len(),
+                    "Report field matched nothing"
+                );
+                misses.push(FieldMiss {
+                    column: column.name.clone(),
+                    field: column.field.clone(),
+                    reason: MissReason::NoMatch,
+                    rows: missing,
+                    values: Vec::new(),
+                });
+            }
+            if !unparsed.is_empty() {
+                debug!(
+                    column = %column.name,
+                    field = %column.field,
+                    kind = ?column.kind,
+                    unparsed = unparsed.len(),
+                    sample = %unparsed[0].1,
+                    "Report field values did not parse"
+                );
+                let (rows, values) = unparsed.into_iter().unzip();
+                misses.push(FieldMiss {
+                    column: column.name.clone(),
+                    field: column.field.clone(),
+                    reason: MissReason::Unparsed,
+                    rows,
+                    values,
+                });
+            }
+        }
+
+        self.finish(rows, misses, parameters)
+    }
+
+    /// Join the tables of a composite report's sources, one per source in order
+    pub fn join(&self, tables: Vec<ExtractedTable>, parameters: &ParameterValues) -> ApiResult<ExtractedTable> {
+        let RowSource::Join(join) = &self.rows else {
+            return Err(ApiError::InternalError("Only composite reports join sources".to_string()));
+        };
+        let (rows, misses) = join.join(tables);
+        debug!(rows = rows.len(), misses = misses.len(), "Joined report sources");
+        self.finish(rows, misses, parameters)
+    }
+
+    /// Append the computed columns, then evaluate the assertions on the complete rows
+    fn finish(&self, mut rows: Vec<ReportRow>, mut misses: Vec<FieldMiss>, parameters: &ParameterValues) -> ApiResult<ExtractedTable> {
+        misses.extend(self.computed.apply(&mut rows, parameters)?);
+        Ok(ExtractedTable {
+            columns: self.columns(),
+            assertions: self.assertions.evaluate(&rows),
+            rows,
+            misses,
+        })
+    }
+}
+
+/// Convert a field result into a cell; `null` marks a miss
+pub fn cell_value(value: xpath::Value) -> Value {
+    match value {
+        xpath::Value::Nodes(nodes) => nodes
+            .first()
+            .map_or(Value::Null, |node| Value::String(node.string_value().trim().to_string())),
+        xpath::Value::String(text) => Value::String(text.trim().to_string()),
+        xpath::Value::Number(number) => serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number),
+        xpath::Value::Boolean(flag) => Value::Bool(flag),
+    }
+}
+
+fn invalid_expression(name: &str, expression: &str, error: &XPathError) -> ApiError {
+    ApiError::ValidationError(format!("Invalid XPath for '{}' ('{}'): {}", name, expression, error))
+}
+
+fn evaluation_failed(expression: &str, error: &XPathError) -> ApiError {
+    ApiError::ValidationError(format!("Cannot evaluate '{}': {}", expression, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    const BGP_REPLY: &str = r#"<rpc-reply xmlns:junos="http://xml.juniper.net/junos/21.4R0/junos">
+  <bgp-information xmlns="http://xml.juniper.net/junos/21.4R0/junos-routing">
+    <bgp-peer>
+      <peer-address>10.0.0.2+179</peer-address>
+      <peer-as>65002</peer-as>
+      <peer-state>Established</peer-state>
+      <elapsed-time junos:seconds="3600">1:00:00</elapsed-time>
+    </bgp-peer>
+    <bgp-peer>
+      <peer-address>10.0.0.3</peer-address>
+      <peer-as>unknown</peer-as>
+      <peer-state>Active</peer-state>
+    </bgp-peer>
+  </bgp-information>
+</rpc-reply>"#;
+
+    fn report(yaml: &str) -> Report {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn bgp_report() -> Report {
+        report(
+            r#"
+title: BGP
+category: Routing
+rpc: get-bgp-summary-information
+xpath: .//bgp-peer
+fields:
+  Address: { path: peer-address, type: ip }
+  AS: { path: peer-as, type: integer }
+  State: peer-state
+  Seconds: { path: "elapsed-time/@junos:seconds", type: integer }
+"#,
+        )
+    }
+
+    fn engine(replies: &[(&str, &str)]) -> ReportEngine {
+        let mut transport = StaticTransport::new();
+        for (rpc, xml) in replies {
+            transport.replies.insert(rpc.to_string(), xml.to_string());
+        }
+        ReportEngine::new(Arc::new(transport))
+    }
+
+    async fn run_error(engine: &ReportEngine, report: &Report, device: &str) -> ApiError {
+        engine.run("bgp", report, device, &ParameterValues::new()).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn runs_reports_into_typed_tables() {
+        let engine = engine(&[("get-bgp-summary-information", BGP_REPLY)]);
+        let result = engine.run("bgp", &bgp_report(), " r1.lab ", &ParameterValues::new()).await.unwrap();
+
+        assert_eq!(result.device, "r1.lab");
+        assert_eq!(result.rpc, "get-bgp-summary-information");
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Address", "AS", "State", "Seconds"]);
+        assert_eq!(result.columns[1].kind, crate::models::reports::FieldType::Integer);
+        assert_eq!(
+            result.rows,
+            vec![
+                vec![Value::from("10.0.0.2"), Value::from(65002), Value::from("Established"), Value::from(3600)],
+                vec![Value::from("10.0.0.3"), Value::Null, Value::from("Active"), Value::Null],
+            ]
+        );
+
+        let misses: Vec<(&str, MissReason, &[usize])> = result
+            .misses
+            .iter()
+            .map(|m| (m.column.as_str(), m.reason, m.rows.as_slice()))
+            .collect();
+        assert_eq!(misses, [("AS", MissReason::Unparsed, &[1][..]), ("Seconds", MissReason::NoMatch, &[1][..])]);
+    }
+
+    #[tokio::test]
+    async fn empty_reply_gives_an_empty_table() {
+        let engine = engine(&[("get-bgp-summary-information", "<rpc-reply><bgp-information/></rpc-reply>")]);
+        let result = engine.run("bgp", &bgp_report(), "r1.lab", &ParameterValues::new()).await.unwrap();
+
+        assert_eq!(result.columns.len(), 4);
+        assert!(result.rows.is_empty());
+        assert!(result.misses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn errors_map_to_http_statuses() {
+        let bgp = bgp_report();
+
+        // Device side: no reply, or a reply that is not XML
+        let missing = run_error(&engine(&[]), &bgp, "r1.lab").await;
+        assert!(matches!(missing, ApiError::DeviceError(_)), "{:?}", missing);
+        assert_eq!(missing.status_code(), StatusCode::BAD_GATEWAY);
+        let garbled = run_error(&engine(&[("get-bgp-summary-information", "<rpc-reply>")]), &bgp, "r1.lab").await;
+        assert_eq!(garbled.status_code(), StatusCode::BAD_GATEWAY);
+
+        // Request side: rejected before the device is contacted
+        let engine = engine(&[("get-bgp-summary-information", BGP_REPLY)]);
+        let blank = run_error(&engine, &bgp, "  ").await;
+        assert_eq!(blank.status_code(), StatusCode::BAD_REQUEST);
+
+        let mut broken = bgp.clone();
+        broken.xpath = ".//bgp-peer[".to_string();
+        let invalid = run_error(&engine, &broken, "r1.lab").await;
+        assert_eq!(invalid.status_code(), StatusCode::BAD_REQUEST);
+        assert!(invalid.to_string().contains("Invalid XPath"), "{}", invalid);
+
+        let template = report(
+            r#"
+title: Route
+category: Routing
+rpc: get-route-information
+rpc_args: { destination: $prefix }
+xpath: .//rt
+fields: { Prefix: rt-destination }
+parameters:
+  prefix: { type: string, required: true }
+"#,
+        );
+        let unbound = run_error(&engine, &template, "r1.lab").await;
+        assert_eq!(unbound.status_code(), StatusCode::BAD_REQUEST);
+        assert!(unbound.to_string().contains("prefix"), "{}", unbound);
+    }
+}
@@ -0,0 +1,563 @@
+// backend/src/services/report_export.rs
+
+//! # Report Exports
+//!
+//! ## Description
+//! Renders report results as downloadable documents. Every format starts with a
+//! header block of run metadata (report, device, run ID, timing) followed by the
+//! table, with column headers taken from the display names in `Report.fields`.
+//!
+//! ## Formats
+//! | `?format=` | Content type                                                        |
+//! | ---------- | ------------------------------------------------------------------- |
+//! | `json`     | `application/json` (the regular API response)                      |
+//! | `csv`      | `text/csv`                                                          |
+//! | `xlsx`     | `application/vnd.openxmlformats-officedocument.spreadsheetml.sheet` |
+//! | `jsonl`    | `application/x-ndjson`                                              |
+//! | `html`     | `text/html` - self-contained, prints cleanly to PDF                |
+//!
+//! ## How to Use
+//! 1. `let format = ExportFormat::negotiate(query.format.as_deref(), &headers)?;`
+//! 2. For anything but `Json`: `ExportTable::from_result(&stored).render(format)?`
+//! 3. Wrap the bytes with `export_response` for the content type and filename
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::models::{
+    reports::{DeviceRunStatus, MissReason, ParameterValues, ReportRun, StoredResult, Verdict},
+    ApiError, ApiResult,
+};
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// `?format=` on the endpoints that return results
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+}
+
+/// Output format of a result export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Xlsx,
+    JsonLines,
+    Html,
+}
+
+impl ExportFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "xlsx" | "excel" => Some(Self::Xlsx),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" | "*/*" | "application/*" => Some(Self::Json),
+            "text/csv" => Some(Self::Csv),
+            XLSX_CONTENT_TYPE => Some(Self::Xlsx),
+            "application/x-ndjson" | "application/jsonl" | "application/jsonlines" => Some(Self::JsonLines),
+            "text/html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// Pick the format from `?format=`, else from the `Accept` header, else JSON
+    /// An explicit but unknown `format` is rejected; unsupported media types are skipped
+    pub fn negotiate(format: Option<&str>, headers: &HeaderMap) -> ApiResult<Self> {
+        if let Some(name) = format {
+            return Self::from_name(name).ok_or_else(|| {
+                ApiError::ValidationError(format!(
+                    "Unknown export format '{}'; expected json, csv, xlsx, jsonl or html",
+                    name
+                ))
+            });
+        }
+
+        let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+            return Ok(Self::Json);
+        };
+        // Highest quality first; the sort is stable so ties keep header order
+        let mut ranges: Vec<(&str, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let media_type = parts.next().unwrap_or_default();
+                let quality = parts
+                    .find_map(|p| p.strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                (media_type, quality)
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        Ok(ranges
+            .into_iter()
+            .find_map(|(media_type, _)| Self::from_media_type(&media_type.to_ascii_lowercase()))
+            .unwrap_or(Self::Json))
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Xlsx => XLSX_CONTENT_TYPE,
+            Self::JsonLines => "application/x-ndjson",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+            Self::JsonLines => "jsonl",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// A result table with its metadata, ready to render in any format
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    pub title: String,
+    /// Base name of the downloaded file, without extension
+    pub filename: String,
+    /// Header block: label -> value, in display order
+    pub metadata: Vec<(String, String)>,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl ExportTable {
+    /// Table of one stored result
+    pub fn from_result(result: &StoredResult) -> Self {
+        let summary = &result.summary;
+        let mut metadata = vec![
+            ("Report".to_string(), format!("{} ({})", summary.title, summary.report_id)),
+            ("Device".to_string(), summary.device.clone()),
+            ("RPC".to_string(), summary.rpc.clone()),
+            ("Run ID".to_string(), summary.run_id.to_string()),
+            ("Started".to_string(), timestamp(&summary.started_at)),
+            ("Duration".to_string(), format!("{} ms", summary.duration_ms)),
+            ("Status".to_string(), status_label(summary.status).to_string()),
+            ("Rows".to_string(), summary.row_count.to_string()),
+        ];
+        if let Some(batch_run_id) = summary.batch_run_id {
+            metadata.push(("Multi-device run".to_string(), batch_run_id.to_string()));
+        }
+        metadata.extend(parameter_rows(&summary.parameters));
+        if let Some(error) = &summary.error {
+            metadata.push(("Error".to_string(), error.clone()));
+        }
+        if let Some(assertions) = &result.assertions {
+            metadata.push(("Verdict".to_string(), assertions.verdict.as_str().to_string()));
+            for check in assertions.checks.iter().filter(|c| c.verdict != Verdict::Pass) {
+                metadata.push((
+                    format!("Check '{}'", check.check),
+                    match check.failures.first().filter(|f| f.row.is_none()) {
+                        Some(failure) => format!("{}: actual {}", check.verdict.as_str(), cell_text(&failure.actual)),
+                        None => format!("{}: {} of {} rows", check.verdict.as_str(), check.failures.len(), summary.row_count),
+                    },
+                ));
+            }
+        }
+        for miss in &result.misses {
+            metadata.push((
+                match miss.reason {
+                    MissReason::NoMatch => format!("Missing '{}'", miss.column),
+                    MissReason::Unparsed => format!("Unparsed '{}'", miss.column),
+                },
+                format!("{} of {} rows", miss.rows.len(), summary.row_count),
+            ));
+        }
+
+        Self {
+            title: summary.title.clone(),
+            filename: format!("{}-{}", summary.report_id, summary.run_id),
+            metadata,
+            columns: result.columns.iter().map(|c| c.name.clone()).collect(),
+            rows: result.rows.clone(),
+        }
+    }
+
+    /// Combined table of a multi-device run: a `Device` column followed by the report's columns
+    pub fn from_run(run: &ReportRun) -> Self {
+        let summary = &run.summary;
+        let mut metadata = vec![
+            ("Report".to_string(), format!("{} ({})", run.title, run.report_id)),
+            ("Run ID".to_string(), run.run_id.to_string()),
+            ("Started".to_string(), timestamp(&run.started_at)),
+            (
+                "Finished".to_string(),
+                run.finished_at.map_or_else(|| "still running".to_string(), |t| timestamp(&t)),
+            ),
+            (
+                "Devices".to_string(),
+                format!(
+                    "{} total, {} succeeded, {} failed, {} timed out, {} pending or running",
+                    summary.total,
+                    summary.succeeded,
+                    summary.failed,
+                    summary.timed_out,
+                    summary.pending + summary.running
+                ),
+            ),
+            ("Rows".to_string(), summary.rows.to_string()),
+        ];
+        metadata.extend(parameter_rows(&run.parameters));
+        if let Some(verdict) = summary.verdict {
+            metadata.push(("Verdict".to_string(), verdict.as_str().to_string()));
+        }
+        for device in &run.devices {
+            if let Some(error) = &device.error {
+                metadata.push((format!("Device {}", device.device), error.clone()));
+            }
+        }
+
+        let results: Vec<_> = run.devices.iter().filter_map(|d| d.result.as_ref()).collect();
+        let mut columns = vec!["Device".to_string()];
+        columns.extend(results.first().into_iter().flat_map(|r| r.columns.iter().map(|c| c.name.clone())));
+        let rows = results
+            .iter()
+            .flat_map(|result| {
+                result.rows.iter().map(|row| {
+                    let mut cells = vec![Value::String(result.device.clone())];
+                    cells.extend(row.iter().cloned());
+                    cells
+                })
+            })
+            .collect();
+
+        Self {
+            title: run.title.clone(),
+            filename: format!("{}-{}", run.report_id, run.run_id),
+            metadata,
+            columns,
+            rows,
+        }
+    }
+
+    /// Render the table; `Json` is served by the regular handlers and is not rendered here
+    pub fn render(&self, format: ExportFormat) -> ApiResult<Vec<u8>> {
+        match format {
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Xlsx => self.to_xlsx(),
+            ExportFormat::JsonLines => self.to_json_lines(),
+            ExportFormat::Html => Ok(self.to_html().into_bytes()),
+            ExportFormat::Json => Err(ApiError::InternalError("JSON is not an export format".to_string())),
+        }
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // RENDERERS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    /// Metadata rows, a blank row, then the table with its header; text that a
+    /// spreadsheet would evaluate as a formula is quoted with `csv_text`
+    fn to_csv(&self) -> ApiResult<Vec<u8>> {
+        let csv_error = |e: csv::Error| ApiError::SerializationError(format!("CSV export failed: {}", e));
+        let finish = |writer: csv::Writer<Vec<u8>>| {
+            writer
+                .into_inner()
+                .map_err(|e| ApiError::SerializationError(format!("CSV export failed: {}", e)))
+        };
+
+        let mut header = csv::Writer::from_writer(Vec::new());
+        for (label, value) in &self.metadata {
+            header.write_record([csv_text(label), csv_text(value)]).map_err(csv_error)?;
+        }
+        let mut output = finish(header)?;
+        // A bare blank line; an empty record would be written as `""`
+        output.push(b'\n');
+
+        let mut table = csv::Writer::from_writer(output);
+        table.write_record(self.columns.iter().map(|name| csv_text(name))).map_err(csv_error)?;
+        for row in &self.rows {
+            let cells = row.iter().map(|cell| match cell {
+                Value::String(s) => csv_text(s),
+                other => cell_text(other),
+            });
+            table.write_record(cells).map_err(csv_error)?;
+        }
+        finish(table)
+    }
+
+    /// One sheet: bold metadata labels, a blank row, then the table with filter buttons.
+    /// Text always goes through `write_string`, so a cell such as `=1+1` stays text
+    /// instead of becoming a formula
+    fn to_xlsx(&self) -> ApiResult<Vec<u8>> {
+        use rust_xlsxwriter::{Format, Workbook};
+
+        let xlsx_error = |e: rust_xlsxwriter::XlsxError| {
+            ApiError::SerializationError(format!("XLSX export failed: {}", e))
+        };
+        let bold = Format::new().set_bold();
+        let header = Format::new().set_bold().set_border_bottom(rust_xlsxwriter::FormatBorder::Thin);
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name("Results").map_err(xlsx_error)?;
+
+        for (row, (label, value)) in self.metadata.iter().enumerate() {
+            sheet.write_string_with_format(row as u32, 0, label, &bold).map_err(xlsx_error)?;
+            sheet.write_string(row as u32, 1, value).map_err(xlsx_error)?;
+        }
+
+        let header_row = self.metadata.len() as u32 + 1;
+        for (column, name) in self.columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(header_row, column as u16, name, &header)
+                .map_err(xlsx_error)?;
+        }
+        for (index, cells) in self.rows.iter().enumerate() {
+            let row = header_row + 1 + index as u32;
+            for (column, cell) in cells.iter().enumerate() {
+                let column = column as u16;
+                match cell {
+                    Value::Null => continue,
+                    Value::Bool(b) => sheet.write_boolean(row, column, *b),
+                    Value::Number(n) => match n.as_f64() {
+                        Some(n) => sheet.write_number(row, column, n),
+                        None => sheet.write_string(row, column, n.to_string()),
+                    },
+                    other => sheet.write_string(row, column, cell_text(other)),
+                }
+                .map_err(xlsx_error)?;
+            }
+        }
+
+        if !self.columns.is_empty() {
+            let last_row = header_row + self.rows.len() as u32;
+            sheet
+                .autofilter(header_row, 0, last_row, self.columns.len() as u16 - 1)
+                .map_err(xlsx_error)?;
+        }
+        sheet.autofit();
+        workbook.save_to_buffer().map_err(xlsx_error)
+    }
+
+    /// A metadata object, then one object per row keyed by column name
+    fn to_json_lines(&self) -> ApiResult<Vec<u8>> {
+        let metadata: serde_json::Map<String, Value> = self
+            .metadata
+            .iter()
+            .map(|(label, value)| (label.clone(), Value::String(value.clone())))
+            .collect();
+
+        let mut lines = vec![json!({ "metadata": metadata, "columns": &self.columns })];
+        lines.extend(self.rows.iter().map(|row| {
+            Value::Object(
+                self.columns
+                    .iter()
+                    .zip(row.iter().chain(std::iter::repeat(&Value::Null)))
+                    .map(|(column, cell)| (column.clone(), cell.clone()))
+                    .collect(),
+            )
+        }));
+
+        let mut output = Vec::new();
+        for line in lines {
+            serde_json::to_writer(&mut output, &line).map_err(|e| ApiError::SerializationError(e.to_string()))?;
+            output.push(b'\n');
+        }
+        Ok(output)
+    }
+
+    /// Standalone page with inline styles, including print styles for saving as PDF
+    fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape_html(&self.title)));
+        html.push_str(HTML_STYLE);
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n<table class=\"meta\">\n", escape_html(&self.title)));
+        for (label, value) in &self.metadata {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                escape_html(label),
+                escape_html(value)
+            ));
+        }
+        html.push_str("</table>\n<table class=\"data\">\n<thead><tr>");
+        for column in &self.columns {
+            html.push_str(&format!("<th>{}</th>", escape_html(column)));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for row in &self.rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape_html(&cell_text(cell))));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+const HTML_STYLE: &str = "<style>
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; font-size: 13px; margin: 2em; color: #1f2933; }
+h1 { font-size: 20px; margin-bottom: 0.5em; }
+table { border-collapse: collapse; }
+table.meta { margin-bottom: 1.5em; }
+table.meta th { text-align: left; padding: 2px 16px 2px 0; color: #52606d; font-weight: 600; }
+table.data { width: 100%; }
+table.data th, table.data td { border: 1px solid #cbd2d9; padding: 4px 8px; text-align: left; }
+table.data thead th { background: #f0f4f8; }
+table.data tbody tr:nth-child(even) { background: #f9fafb; }
+@media print { body { margin: 0; } table.data thead { display: table-header-group; } tr { page-break-inside: avoid; } }
+</style>
+";
+
+/// Attach the content type and a download filename to rendered bytes
+pub fn export_response(table: &ExportTable, format: ExportFormat, body: Vec<u8>) -> Response {
+    let disposition = format!("attachment; filename=\"{}.{}\"", sanitize_filename(&table.filename), format.extension());
+    let mut response = body.into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    response
+}
+
+fn timestamp(time: &chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Plain-text form of a cell: strings unquoted, `null` empty
+fn cell_text(cell: &Value) -> String {
+    match cell {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// CSV form of a text value: prefixed with `'` when it starts with `=`, `+`, `-`,
+/// `@`, tab or carriage return, so spreadsheets opening the file show it instead of
+/// evaluating it
+fn csv_text(text: &str) -> String {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// One `Parameter '<name>'` row per value a templated report ran with
+fn parameter_rows(parameters: &ParameterValues) -> impl Iterator<Item = (String, String)> + '_ {
+    parameters
+        .iter()
+        .map(|(name, value)| (format!("Parameter '{}'", name), cell_text(value)))
+}
+
+fn status_label(status: DeviceRunStatus) -> &'static str {
+    match status {
+        DeviceRunStatus::Pending => "pending",
+        DeviceRunStatus::Running => "running",
+        DeviceRunStatus::Succeeded => "succeeded",
+        DeviceRunStatus::Failed => "failed",
+        DeviceRunStatus::TimedOut => "timed out",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Keep filenames to characters every client accepts in `Content-Disposition`
+pub fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn table() -> ExportTable {
+        ExportTable {
+            title: "Interfaces".to_string(),
+            filename: "interfaces".to_string(),
+            metadata: vec![("Device".to_string(), "=HYPERLINK(\"x\")".to_string())],
+            columns: vec!["Name".to_string(), "Value".to_string()],
+            rows: vec![
+                vec![json!("=1+1"), json!(-5)],
+                vec![json!("@SUM(A1)"), json!("-5")],
+                vec![json!("+cmd"), json!("ge-0/0/0")],
+            ],
+        }
+    }
+
+    #[test]
+    fn csv_quotes_formula_text_but_not_numbers() {
+        let csv = String::from_utf8(table().render(ExportFormat::Csv).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Device,\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(lines[2], "Name,Value");
+        assert_eq!(lines[3], "'=1+1,-5");
+        assert_eq!(lines[4], "'@SUM(A1),'-5");
+        assert_eq!(lines[5], "'+cmd,ge-0/0/0");
+    }
+
+    #[test]
+    fn csv_text_only_prefixes_formula_triggers() {
+        assert_eq!(csv_text("=A1"), "'=A1");
+        assert_eq!(csv_text("-1"), "'-1");
+        assert_eq!(csv_text("\t=A1"), "'\t=A1");
+        assert_eq!(csv_text("\r=A1"), "'\r=A1");
+        assert_eq!(csv_text("a\t=b"), "a\t=b");
+        assert_eq!(csv_text("a=b"), "a=b");
+        assert_eq!(csv_text(""), "");
+    }
+
+    #[test]
+    fn xlsx_writes_formula_text_as_strings() {
+        let bytes = table().render(ExportFormat::Xlsx).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut sheet = String::new();
+        archive
+            .by_name("xl/worksheets/sheet1.xml")
+            .unwrap()
+            .read_to_string(&mut sheet)
+            .unwrap();
+        let mut strings = String::new();
+        archive.by_name("xl/sharedStrings.xml").unwrap().read_to_string(&mut strings).unwrap();
+
+        assert!(!sheet.contains("<f>"), "no cell may hold a formula: {}", sheet);
+        assert!(strings.contains("=1+1"));
+        assert!(strings.contains("@SUM(A1)"));
+        assert!(sheet.contains("<v>-5</v>"), "numbers stay numeric: {}", sheet);
+    }
+}
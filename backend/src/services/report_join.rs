@@ -0,0 +1,410 @@
+// backend/src/services/report_join.rs
+
+//! # Report Joins
+//!
+//! ## Description
+//! Composite reports (`Report.sources`) combine the tables of other reports from the
+//! same catalogue, so data spread over several RPCs (interface descriptions, traffic
+//! counters, LLDP neighbors) lands in one result. The first source drives the rows;
+//! every other source is matched to it on the declared key columns.
+//!
+//! ## Joins
+//! - Keys compare as text (typed values such as normalized IPs compare normalized);
+//!   rows with a `null` key never match
+//! - A row matching several rows of a source is repeated once per match
+//! - `join: left` (default) keeps rows without a match, with `null` cells for the
+//!   source; `join: inner` drops them
+//! - Columns keep their source's display names and types, which must not collide;
+//!   `columns` picks and orders the ones taken from a source
+//! - Field misses of the sources are carried over to the joined rows
+//!
+//! ## How to Use
+//! 1. `link_sources(&mut reports)` - done by `routes::reports::load_reports`
+//! 2. `let join = CompiledJoin::compile(&report)?;` then `join.columns()`
+//! 3. `let (rows, misses) = join.join(tables);` - one table per source, in order
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    models::{
+        reports::{find_column, FieldMiss, JoinKind, Report, ReportColumn, ReportRow},
+        ApiError, ApiResult,
+    },
+    services::{report_engine::{CompiledReport, ExtractedTable}, report_parameters},
+};
+
+/// Attach the definitions composite reports read from; unknown IDs stay unlinked and
+/// fail when the report is compiled
+pub fn link_sources(reports: &mut HashMap<String, Report>) {
+    let definitions = reports.clone();
+    for report in reports.values_mut() {
+        for source in &mut report.sources {
+            source.definition = definitions.get(&source.report).cloned().map(Box::new);
+        }
+    }
+}
+
+/// One source: where its key is and which of its columns are kept
+#[derive(Debug, Clone)]
+struct JoinSource {
+    key: usize,
+    join: JoinKind,
+    /// Column indexes in the source table, in output order
+    selected: Vec<usize>,
+    /// Source column names, to map misses onto output columns
+    columns: Vec<ReportColumn>,
+}
+
+/// A composite report's sources, checked against their definitions
+#[derive(Debug, Clone)]
+pub struct CompiledJoin {
+    sources: Vec<JoinSource>,
+    columns: Vec<ReportColumn>,
+}
+
+impl CompiledJoin {
+    /// Check every source: linked, not composite itself, valid, with existing key and
+    /// columns; output column names must be unique
+    pub fn compile(report: &Report) -> ApiResult<Self> {
+        let mut sources = Vec::with_capacity(report.sources.len());
+        let mut columns: Vec<ReportColumn> = Vec::new();
+
+        for (position, source) in report.sources.iter().enumerate() {
+            let invalid = |reason: String| {
+                ApiError::ValidationError(format!("Invalid source '{}': {}", source.report, reason))
+            };
+            let definition = source
+                .definition
+                .as_deref()
+                .ok_or_else(|| invalid("no such report".to_string()))?;
+            if definition.is_composite() {
+                return Err(invalid("composite reports cannot be sources".to_string()));
+            }
+            let source_columns = CompiledReport::compile(definition)
+                .map_err(|e| match e {
+                    ApiError::ValidationError(message) => invalid(message),
+                    other => other,
+                })?
+                .columns();
+            let names = || source_columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ");
+
+            let key = find_column(&source_columns, &source.key)
+                .ok_or_else(|| invalid(format!("key '{}' is not a column; available: {}", source.key, names())))?;
+            let selected = if source.columns.is_empty() {
+                (0..source_columns.len()).filter(|&c| position == 0 || c != key).collect()
+            } else {
+                source
+                    .columns
+                    .iter()
+                    .map(|name| {
+                        find_column(&source_columns, name)
+                            .ok_or_else(|| invalid(format!("'{}' is not a column; available: {}", name, names())))
+                    })
+                    .collect::<ApiResult<Vec<_>>>()?
+            };
+
+            if let Some(name) = source.parameters.keys().find(|name| !definition.parameters.contains_key(*name)) {
+                return Err(invalid(format!("it declares no parameter '{}'", name)));
+            }
+            for (name, value) in report_parameters::value_references(
+                &format!("sources[{}].parameters", position),
+                &source.parameters,
+            ) {
+                report_parameters::check_reference(&report.parameters, &name, &value)?;
+            }
+
+            for &index in &selected {
+                let column = &source_columns[index];
+                if columns.iter().any(|c| c.name == column.name) {
+                    return Err(invalid(format!(
+                        "column '{}' is already taken from another source; pick columns with `columns`",
+                        column.name
+                    )));
+                }
+                columns.push(column.clone());
+            }
+            sources.push(JoinSource {
+                key,
+                join: source.join,
+                selected,
+                columns: source_columns,
+            });
+        }
+
+        Ok(Self { sources, columns })
+    }
+
+    /// Columns of the joined table, source by source
+    pub fn columns(&self) -> &[ReportColumn] {
+        &self.columns
+    }
+
+    /// Join one table per source, in declaration order
+    pub fn join(&self, tables: Vec<ExtractedTable>) -> (Vec<ReportRow>, Vec<FieldMiss>) {
+        let Some((base, base_table)) = self.sources.first().zip(tables.first()) else {
+            return (Vec::new(), Vec::new());
+        };
+
+        // Each joined row remembers which source row it took, per source
+        let mut joined: Vec<(ReportRow, Vec<Option<usize>>)> = base_table
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (select(row, &base.selected), vec![Some(index)]))
+            .collect();
+
+        for (source, table) in self.sources.iter().zip(&tables).skip(1) {
+            let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+            for (index, row) in table.rows.iter().enumerate() {
+                if let Some(key) = row.get(source.key).and_then(key_text) {
+                    by_key.entry(key).or_default().push(index);
+                }
+            }
+
+            let mut next = Vec::with_capacity(joined.len());
+            for (row, origin) in joined {
+                let key = origin[0]
+                    .and_then(|index| base_table.rows[index].get(base.key))
+                    .and_then(key_text);
+                match key.and_then(|key| by_key.get(&key)) {
+                    Some(matches) => {
+                        for &index in matches {
+                            let mut row = row.clone();
+                            row.extend(select(&table.rows[index], &source.selected));
+                            let mut origin = origin.clone();
+                            origin.push(Some(index));
+                            next.push((row, origin));
+                        }
+                    }
+                    None if source.join == JoinKind::Inner => {}
+                    None => {
+                        let mut row = row;
+                        row.extend(std::iter::repeat_n(Value::Null, source.selected.len()));
+                        let mut origin = origin;
+                        origin.push(None);
+                        next.push((row, origin));
+                    }
+                }
+            }
+            joined = next;
+        }
+
+        let (rows, origins): (Vec<ReportRow>, Vec<Vec<Option<usize>>>) = joined.into_iter().unzip();
+        let mut misses = Vec::new();
+        for (position, (source, table)) in self.sources.iter().zip(&tables).enumerate() {
+            misses.extend(
+                table
+                    .misses
+                    .iter()
+                    .filter_map(|miss| remap_miss(miss, position, source, &origins)),
+            );
+        }
+        (rows, misses)
+    }
+}
+
+fn select(row: &ReportRow, columns: &[usize]) -> ReportRow {
+    columns
+        .iter()
+        .map(|&column| row.get(column).cloned().unwrap_or(Value::Null))
+        .collect()
+}
+
+/// Text a key cell is matched on; `None` for `null`
+fn key_text(cell: &Value) -> Option<String> {
+    match cell {
+        Value::Null => None,
+        Value::String(text) => Some(text.trim().to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// A source miss in terms of the joined rows, if its column was kept and any of its
+/// rows made it into the result
+fn remap_miss(
+    miss: &FieldMiss,
+    position: usize,
+    source: &JoinSource,
+    origins: &[Vec<Option<usize>>],
+) -> Option<FieldMiss> {
+    let column = source.columns.iter().position(|c| c.name == miss.column)?;
+    if !source.selected.contains(&column) {
+        return None;
+    }
+
+    let mut rows = Vec::new();
+    let mut values = Vec::new();
+    for (joined_index, origin) in origins.iter().enumerate() {
+        let Some(source_row) = origin.get(position).copied().flatten() else {
+            continue;
+        };
+        if let Some(found) = miss.rows.iter().position(|&row| row == source_row) {
+            rows.push(joined_index);
+            if let Some(value) = miss.values.get(found) {
+                values.push(value.clone());
+            }
+        }
+    }
+    (!rows.is_empty()).then(|| FieldMiss {
+        rows,
+        values,
+        ..miss.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::reports::MissReason, routes::reports::load_reports, services::YamlService};
+    use serde_json::json;
+
+    /// The shipped catalogue, with sources linked
+    async fn catalogue() -> HashMap<String, Report> {
+        load_reports(&YamlService::new("../shared/schemas").await.unwrap()).await.unwrap()
+    }
+
+    fn table(rows: Vec<Value>) -> ExtractedTable {
+        ExtractedTable {
+            columns: Vec::new(),
+            rows: rows.into_iter().map(|row| serde_json::from_value(row).unwrap()).collect(),
+            misses: Vec::new(),
+            assertions: None,
+        }
+    }
+
+    /// `test_mpls_interfaces` rows: Interface, Admin Status, Oper Status, RSVP Status, LDP Status
+    fn mpls(interfaces: &[Value]) -> ExtractedTable {
+        table(interfaces.iter().map(|name| json!([name, "Up", "Up", "Enabled", "Enabled"])).collect())
+    }
+
+    /// `test_ospf_neighbors` rows: Interface, Neighbor ID, State, Address, Up/Down Time
+    fn ospf(neighbors: &[(&str, &str, &str)]) -> ExtractedTable {
+        table(
+            neighbors
+                .iter()
+                .map(|(interface, id, state)| json!([interface, id, state, "10.1.1.2", 3600]))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn shipped_composite_compiles_with_selected_columns() {
+        let reports = catalogue().await;
+        let join = CompiledJoin::compile(&reports["test_mpls_ospf_adjacency"]).unwrap();
+        let names: Vec<&str> = join.columns().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Interface", "Admin Status", "Oper Status", "RSVP Status", "LDP Status", "Neighbor ID", "State"]
+        );
+    }
+
+    #[tokio::test]
+    async fn matched_and_unmatched_rows_of_a_left_join() {
+        let reports = catalogue().await;
+        let join = CompiledJoin::compile(&reports["test_mpls_ospf_adjacency"]).unwrap();
+
+        let (rows, misses) = join.join(vec![
+            mpls(&[json!("ge-0/0/0.0"), json!("ge-0/0/1.0")]),
+            // Keys compare trimmed
+            ospf(&[(" ge-0/0/0.0 ", "10.255.0.2", "Full")]),
+        ]);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0], json!("ge-0/0/0.0"));
+        assert_eq!(rows[0][5..], [json!("10.255.0.2"), json!("Full")]);
+        assert_eq!(rows[1][0], json!("ge-0/0/1.0"));
+        assert_eq!(rows[1][5..], [Value::Null, Value::Null]);
+        assert!(misses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn inner_join_drops_unmatched_rows() {
+        let mut reports = catalogue().await;
+        let mut report = reports.remove("test_mpls_ospf_adjacency").unwrap();
+        report.sources[1].join = JoinKind::Inner;
+        let join = CompiledJoin::compile(&report).unwrap();
+
+        let (rows, _) = join.join(vec![
+            mpls(&[json!("ge-0/0/0.0"), json!("ge-0/0/1.0")]),
+            ospf(&[("ge-0/0/1.0", "10.255.0.3", "Init")]),
+        ]);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], json!("ge-0/0/1.0"));
+        assert_eq!(rows[0][6], json!("Init"));
+    }
+
+    #[tokio::test]
+    async fn duplicate_keys_repeat_the_row_and_null_keys_never_match() {
+        let reports = catalogue().await;
+        let join = CompiledJoin::compile(&reports["test_mpls_ospf_adjacency"]).unwrap();
+
+        let mut neighbors = ospf(&[
+            ("ge-0/0/0.0", "10.255.0.2", "Full"),
+            ("ge-0/0/0.0", "10.255.0.4", "ExStart"),
+        ]);
+        neighbors.rows.push(vec![Value::Null, json!("10.255.0.9"), json!("Full")]);
+        let (rows, _) = join.join(vec![mpls(&[json!("ge-0/0/0.0"), Value::Null]), neighbors]);
+
+        let neighbor_ids: Vec<&Value> = rows.iter().map(|row| &row[5]).collect();
+        assert_eq!(neighbor_ids, [&json!("10.255.0.2"), &json!("10.255.0.4"), &Value::Null]);
+        assert_eq!(rows[0][..5], rows[1][..5]);
+        assert_eq!(rows[2][0], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn source_misses_follow_their_rows() {
+        let reports = catalogue().await;
+        let join = CompiledJoin::compile(&reports["test_mpls_ospf_adjacency"]).unwrap();
+
+        let mut neighbors = ospf(&[("ge-0/0/1.0", "10.255.0.3", "Full")]);
+        neighbors.rows[0][2] = Value::Null;
+        neighbors.misses = vec![
+            FieldMiss {
+                column: "State".to_string(),
+                field: "ospf-neighbor-state".to_string(),
+                reason: MissReason::NoMatch,
+                rows: vec![0],
+                values: Vec::new(),
+            },
+            // Address is not taken from this source
+            FieldMiss {
+                column: "Address".to_string(),
+                field: "neighbor-address".to_string(),
+                reason: MissReason::Unparsed,
+                rows: vec![0],
+                values: vec!["bogus".to_string()],
+            },
+        ];
+        let (_, misses) = join.join(vec![mpls(&[json!("ge-0/0/0.0"), json!("ge-0/0/1.0")]), neighbors]);
+
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].column, "State");
+        assert_eq!(misses[0].rows, [1]);
+    }
+
+    #[tokio::test]
+    async fn invalid_sources_are_rejected() {
+        let reports = catalogue().await;
+        let composite = &reports["test_mpls_ospf_adjacency"];
+        let error = |report: &Report| CompiledJoin::compile(report).unwrap_err().to_string();
+
+        let mut unknown = composite.clone();
+        unknown.sources[1].definition = None;
+        assert!(error(&unknown).contains("no such report"));
+
+        let mut bad_key = composite.clone();
+        bad_key.sources[1].key = "Port".to_string();
+        assert!(error(&bad_key).contains("key 'Port' is not a column"));
+
+        let mut collision = composite.clone();
+        collision.sources[1].columns = vec!["Interface".to_string(), "State".to_string()];
+        assert!(error(&collision).contains("already taken"));
+
+        let mut nested = composite.clone();
+        nested.sources[1].definition = Some(Box::new(composite.clone()));
+        assert!(error(&nested).contains("composite reports cannot be sources"));
+    }
+}
@@ -0,0 +1,457 @@
+// backend/src/services/report_parameters.rs
+
+//! # Report Parameters
+//!
+//! ## Description
+//! Turns one report definition into a template: `Report.parameters` declares typed
+//! values (an interface name, a routing instance, a `detail` flag) that each run
+//! supplies or leaves at their defaults. Declarations use JSON Schema keywords, so the
+//! list endpoint doubles as the schema the frontend renders its run form from, and
+//! supplied values are checked with the same validator as `reports.yaml`.
+//!
+//! ## Substitution
+//! - `rpc_args`: a value written as `"$name"` is replaced by the typed value, so a
+//!   boolean parameter bound to `false` drops a flag like `<detail/>` from the RPC;
+//!   `${name}` inside longer text is replaced by the value's text (`"ge-${fpc}/0/0"`)
+//! - `sources[].parameters` of composite reports: the same, passing values on to
+//!   templated sources
+//! - `xpath` and field paths: `$name` is an XPath variable bound to the value, e.g.
+//!   `//physical-interface[name = $interface]`
+//!
+//! Every reference must name a declared parameter; a parameter without a `default`
+//! must be supplied by each run.
+//!
+//! ## How to Use
+//! 1. `check_declarations(&report.parameters)?` - when a report is compiled
+//! 2. `let values = resolve(&report.parameters, &supplied)?;` - defaults filled in
+//! 3. `bind_rpc_args(&args, &values)` and `XPathOptions { variables: values, .. }`
+
+use indexmap::IndexMap;
+use serde_json::{json, Map, Value};
+
+use crate::models::{
+    reports::{ParameterSpec, ParameterType, ParameterValues},
+    ApiError, ApiResult,
+};
+
+/// Check every declaration: a usable name, a valid JSON Schema, and enum choices and a
+/// default that satisfy it
+pub fn check_declarations(parameters: &IndexMap<String, ParameterSpec>) -> ApiResult<()> {
+    for (name, spec) in parameters {
+        let invalid = |reason: String| ApiError::ValidationError(format!("Invalid parameter '{}': {}", name, reason));
+        if !is_valid_name(name) {
+            return Err(invalid("names must match ^[A-Za-z_][A-Za-z0-9_-]*$".to_string()));
+        }
+        let numeric = matches!(spec.kind, ParameterType::Integer | ParameterType::Number);
+        if (spec.minimum.is_some() || spec.maximum.is_some()) && !numeric {
+            return Err(invalid("minimum and maximum are only allowed with type integer or number".to_string()));
+        }
+        if spec.pattern.is_some() && spec.kind != ParameterType::String {
+            return Err(invalid("pattern is only allowed with type string".to_string()));
+        }
+
+        let schema = property_schema(spec)?;
+        jsonschema::validator_for(&schema).map_err(|e| invalid(e.to_string()))?;
+
+        // Choices are checked against the spec without its own enum
+        let mut unrestricted = spec.clone();
+        unrestricted.choices.clear();
+        for choice in &spec.choices {
+            violations(&property_schema(&unrestricted)?, choice)?
+                .map_or(Ok(()), |reason| Err(invalid(format!("enum value {}: {}", choice, reason))))?;
+        }
+        if let Some(default) = &spec.default {
+            violations(&schema, default)?
+                .map_or(Ok(()), |reason| Err(invalid(format!("default {}: {}", default, reason))))?;
+        }
+    }
+    Ok(())
+}
+
+/// Fail on a `$name` reference to an undeclared parameter; `location` names where it was found
+pub fn check_reference(parameters: &IndexMap<String, ParameterSpec>, location: &str, name: &str) -> ApiResult<()> {
+    if parameters.contains_key(name) {
+        return Ok(());
+    }
+    let declared: Vec<&str> = parameters.keys().map(String::as_str).collect();
+    Err(ApiError::ValidationError(format!(
+        "{} refers to undeclared parameter '${}'; declared: {}",
+        location,
+        name,
+        if declared.is_empty() { "none".to_string() } else { declared.join(", ") }
+    )))
+}
+
+/// Parameter names referenced from `rpc_args` or source values, with the entry they
+/// appear in (`<prefix>.<name>`)
+pub fn value_references<'a>(
+    prefix: &str,
+    args: impl IntoIterator<Item = (&'a String, &'a Value)>,
+) -> Vec<(String, String)> {
+    fn collect(location: &str, value: &Value, found: &mut Vec<(String, String)>) {
+        match value {
+            Value::String(text) => {
+                if let Some(name) = whole_reference(text) {
+                    found.push((location.to_string(), name.to_string()));
+                }
+                let mut rest = text.as_str();
+                while let Some((name, after)) = next_interpolation(rest) {
+                    found.push((location.to_string(), name.to_string()));
+                    rest = after;
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect(location, item, found)),
+            Value::Object(children) => children
+                .iter()
+                .for_each(|(child, item)| collect(&format!("{}.{}", location, child), item, found)),
+            _ => {}
+        }
+    }
+
+    let mut found = Vec::new();
+    for (name, value) in args {
+        collect(&format!("{}.{}", prefix, name), value, &mut found);
+    }
+    found
+}
+
+/// Values for one run: supplied values checked against the declarations, defaults for
+/// the rest
+pub fn resolve(parameters: &IndexMap<String, ParameterSpec>, supplied: &ParameterValues) -> ApiResult<ParameterValues> {
+    if parameters.is_empty() && supplied.is_empty() {
+        return Ok(ParameterValues::new());
+    }
+
+    let mut values = ParameterValues::new();
+    for (name, spec) in parameters {
+        if let Some(value) = supplied.get(name).or(spec.default.as_ref()) {
+            values.insert(name.clone(), value.clone());
+        }
+    }
+    // Unknown names are left in so the schema reports them
+    for (name, value) in supplied {
+        if !parameters.contains_key(name) {
+            values.insert(name.clone(), value.clone());
+        }
+    }
+
+    match violations(&form_schema(parameters)?, &Value::Object(values.clone()))? {
+        None => Ok(values),
+        Some(reason) => Err(ApiError::ValidationError(format!("Invalid parameters: {}", reason))),
+    }
+}
+
+/// Stand-in values for checking a template without a caller: defaults, else the first
+/// choice, else the smallest value of the type
+pub fn sample_values(parameters: &IndexMap<String, ParameterSpec>) -> ParameterValues {
+    parameters
+        .iter()
+        .map(|(name, spec)| {
+            let value = spec
+                .default
+                .clone()
+                .or_else(|| spec.choices.first().cloned())
+                .unwrap_or_else(|| match spec.kind {
+                    ParameterType::String => Value::String(String::new()),
+                    ParameterType::Integer => Value::from(spec.minimum.map_or(0, |m| m.ceil() as i64)),
+                    ParameterType::Number => Value::from(spec.minimum.unwrap_or(0.0)),
+                    ParameterType::Boolean => Value::Bool(false),
+                });
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// `rpc_args` with every reference replaced by its value
+pub fn bind_rpc_args(args: &IndexMap<String, Value>, values: &ParameterValues) -> IndexMap<String, Value> {
+    args.iter().map(|(name, value)| (name.clone(), bind_value(value, values))).collect()
+}
+
+/// Values a composite report passes to a templated source, with references replaced
+pub fn bind_source_values(supplied: &ParameterValues, values: &ParameterValues) -> ParameterValues {
+    supplied
+        .iter()
+        .map(|(name, value)| (name.clone(), bind_value(value, values)))
+        .collect()
+}
+
+fn bind_value(value: &Value, values: &ParameterValues) -> Value {
+    match value {
+        Value::String(text) => {
+            if let Some(bound) = whole_reference(text).and_then(|name| values.get(name)) {
+                return bound.clone();
+            }
+            let mut result = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let Some((name, after)) = next_interpolation(&rest[start..]) else {
+                    break;
+                };
+                result.push_str(&rest[..start]);
+                match values.get(name) {
+                    Some(Value::String(bound)) => result.push_str(bound),
+                    Some(Value::Null) | None => {}
+                    Some(bound) => result.push_str(&bound.to_string()),
+                }
+                rest = after;
+            }
+            result.push_str(rest);
+            Value::String(result)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| bind_value(item, values)).collect()),
+        Value::Object(children) => Value::Object(
+            children
+                .iter()
+                .map(|(name, item)| (name.clone(), bind_value(item, values)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SCHEMA
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// The declarations as one JSON Schema object: unknown names are rejected and
+/// parameters without a default are required
+fn form_schema(parameters: &IndexMap<String, ParameterSpec>) -> ApiResult<Value> {
+    let mut properties = Map::new();
+    for (name, spec) in parameters {
+        properties.insert(name.clone(), property_schema(spec)?);
+    }
+    let required: Vec<&String> = parameters
+        .iter()
+        .filter(|(_, spec)| spec.default.is_none())
+        .map(|(name, _)| name)
+        .collect();
+    Ok(json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    }))
+}
+
+fn property_schema(spec: &ParameterSpec) -> ApiResult<Value> {
+    serde_json::to_value(spec).map_err(|e| ApiError::SerializationError(e.to_string()))
+}
+
+/// Every way `value` breaks `schema`, joined, or `None` when it conforms
+fn violations(schema: &Value, value: &Value) -> ApiResult<Option<String>> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid parameter declaration: {}", e)))?;
+    let errors: Vec<String> = validator
+        .iter_errors(value)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", path, error)
+            }
+        })
+        .collect();
+    Ok((!errors.is_empty()).then(|| errors.join("; ")))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REFERENCES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// `name` of a value that is exactly `$name`
+fn whole_reference(text: &str) -> Option<&str> {
+    text.strip_prefix('$').filter(|name| is_valid_name(name))
+}
+
+/// First `${name}` in `text`: the name and the text after the closing brace
+fn next_interpolation(text: &str) -> Option<(&str, &str)> {
+    let start = text.find("${")? + 2;
+    let length = text[start..].find('}')?;
+    Some((&text[start..start + length], &text[start + length + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::reports::Report,
+        services::{
+            netconf::session::render_rpc,
+            report_engine::{CompiledReport, RpcCall},
+        },
+    };
+
+    fn declarations(yaml: &str) -> IndexMap<String, ParameterSpec> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn supplied(value: Value) -> ParameterValues {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// `interface` (required), `terse` (defaults to true), `count` (1..=10, defaults to 5)
+    fn interface_parameters() -> IndexMap<String, ParameterSpec> {
+        declarations(
+            r#"
+interface: { type: string, pattern: "^[a-z]+-[0-9/.]+$" }
+terse: { type: boolean, default: true }
+count: { type: integer, minimum: 1, maximum: 10, default: 5 }
+"#,
+        )
+    }
+
+    fn report(yaml: &str) -> Report {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn defaults_fill_in_what_is_not_supplied() {
+        let values = resolve(&interface_parameters(), &supplied(json!({ "interface": "ge-0/0/0" }))).unwrap();
+        assert_eq!(Value::Object(values), json!({ "interface": "ge-0/0/0", "terse": true, "count": 5 }));
+
+        let values = resolve(
+            &interface_parameters(),
+            &supplied(json!({ "interface": "xe-1/0/0", "terse": false, "count": 2 })),
+        )
+        .unwrap();
+        assert_eq!(values["terse"], json!(false));
+        assert_eq!(values["count"], json!(2));
+
+        assert!(resolve(&IndexMap::new(), &ParameterValues::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_required_values_are_rejected() {
+        let error = resolve(&interface_parameters(), &ParameterValues::new()).unwrap_err();
+        assert!(matches!(error, ApiError::ValidationError(_)));
+        assert!(error.to_string().contains("\"interface\" is a required property"), "{}", error);
+    }
+
+    #[test]
+    fn mistyped_and_unknown_values_are_rejected() {
+        for (values, reason) in [
+            (json!({ "interface": 7 }), "/interface"),
+            (json!({ "interface": "ge-0/0/0", "terse": "yes" }), "/terse"),
+            (json!({ "interface": "ge-0/0/0", "count": 2.5 }), "/count"),
+            (json!({ "interface": "ge-0/0/0", "count": 11 }), "/count"),
+            (json!({ "interface": "ge-0/0/0 or 1=1" }), "/interface"),
+            (json!({ "interface": "ge-0/0/0", "vrf": "blue" }), "vrf"),
+        ] {
+            let error = resolve(&interface_parameters(), &supplied(values.clone())).unwrap_err();
+            assert!(error.to_string().contains(reason), "{}: {}", values, error);
+        }
+    }
+
+    #[test]
+    fn invalid_declarations_are_rejected() {
+        for (yaml, reason) in [
+            ("1st: { type: string, default: x }", "names must match"),
+            ("name: { type: string, minimum: 1, default: x }", "minimum and maximum"),
+            ("count: { type: integer, pattern: '^1$', default: 1 }", "pattern is only allowed"),
+            ("count: { type: integer, default: many }", "default \"many\""),
+            ("unit: { type: integer, enum: [1, x], default: 1 }", "enum value \"x\""),
+        ] {
+            let error = check_declarations(&declarations(yaml)).unwrap_err();
+            assert!(error.to_string().contains(reason), "{}: {}", yaml, error);
+        }
+        check_declarations(&interface_parameters()).unwrap();
+    }
+
+    #[test]
+    fn rpc_args_keep_types_and_interpolate_text() {
+        let args: IndexMap<String, Value> = serde_json::from_value(json!({
+            "interface-name": "$interface",
+            "terse": "$terse",
+            "count": "$count",
+            "description": "port ${interface} (x${count})",
+            "literal": "$5 and ${undeclared",
+            "nested": { "names": ["$interface", "static"] }
+        }))
+        .unwrap();
+        let values = supplied(json!({ "interface": "ge-0/0/0", "terse": false, "count": 3 }));
+
+        let bound = bind_rpc_args(&args, &values);
+        assert_eq!(bound["interface-name"], json!("ge-0/0/0"));
+        assert_eq!(bound["terse"], json!(false));
+        assert_eq!(bound["count"], json!(3));
+        assert_eq!(bound["description"], json!("port ge-0/0/0 (x3)"));
+        assert_eq!(bound["literal"], json!("$5 and ${undeclared"));
+        assert_eq!(bound["nested"], json!({ "names": ["ge-0/0/0", "static"] }));
+
+        let references: Vec<String> = value_references("rpc_args", &args).into_iter().map(|(_, name)| name).collect();
+        assert_eq!(references, ["interface", "terse", "count", "interface", "count", "interface"]);
+    }
+
+    #[test]
+    fn rpc_arg_values_are_escaped_in_the_request() {
+        let template = report(
+            r#"
+title: Route
+category: Routing
+rpc: get-route-information
+rpc_args: { destination: $prefix, "@format": "${format}" }
+xpath: .//rt
+fields: { Prefix: rt-destination }
+parameters:
+  prefix: { type: string }
+  format: { type: string, default: xml }
+"#,
+        );
+        let values = supplied(json!({
+            "prefix": "10.0.0.0/8</destination><clear-bgp-neighbor/><destination>",
+            "format": "xml\" evil=\"1",
+        }));
+
+        let xml = render_rpc(1, &RpcCall::for_report(&template, &values)).unwrap();
+        assert!(!xml.contains("<clear-bgp-neighbor/>"), "{}", xml);
+        assert!(xml.contains(
+            "<destination>10.0.0.0/8&lt;/destination&gt;&lt;clear-bgp-neighbor/&gt;&lt;destination&gt;</destination>"
+        ));
+        assert!(xml.contains(r#"format="xml&quot; evil=&quot;1""#), "{}", xml);
+        roxmltree::Document::parse(&xml).unwrap();
+    }
+
+    #[test]
+    fn xpath_values_are_bound_not_spliced() {
+        let template = report(
+            r#"
+title: Interfaces
+category: Interfaces
+rpc: get-interface-information
+xpath: ".//physical-interface[name = $interface]"
+fields: { Name: name }
+parameters:
+  interface: { type: string }
+"#,
+        );
+        let compiled = CompiledReport::compile(&template).unwrap();
+        let reply = "<rpc-reply><physical-interface><name>ge-0/0/0</name></physical-interface>\
+                     <physical-interface><name>it's</name></physical-interface></rpc-reply>";
+        let names = |value: &str| -> Vec<Value> {
+            let table = compiled.extract(reply, &supplied(json!({ "interface": value }))).unwrap();
+            table.rows.into_iter().map(|mut row| row.remove(0)).collect()
+        };
+
+        assert_eq!(names("ge-0/0/0"), [json!("ge-0/0/0")]);
+        // Quotes are data, not syntax
+        assert_eq!(names("it's"), [json!("it's")]);
+        assert!(names("x' or '1'='1").is_empty());
+        assert!(names("x\" or name or \"").is_empty());
+    }
+
+    #[test]
+    fn undeclared_references_name_their_location() {
+        let error = check_reference(&interface_parameters(), "rpc_args.vrf", "vrf").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation error: rpc_args.vrf refers to undeclared parameter '$vrf'; declared: interface, terse, count"
+        );
+    }
+}
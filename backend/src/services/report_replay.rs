@@ -0,0 +1,369 @@
+// backend/src/services/report_replay.rs
+
+//! # Report Replay
+//!
+//! ## Description
+//! Runs reports without lab gear. `CaptureTransport` wraps a live transport and
+//! records every successful RPC reply to disk; `ReplayTransport` answers from such a
+//! directory afterwards, so demos and CI see exactly what the devices said.
+//!
+//! ## Layout
+//! ```text
+//! <dir>/<device>/<rpc>.xml              reply to the RPC without arguments
+//! <dir>/<device>/<rpc>~<digest>.xml     reply to the RPC with these arguments
+//! <dir>/<rpc>.xml                       answers every device (shared fixtures)
+//! ```
+//! Device names are used as directory names with characters other than
+//! `A-Z a-z 0-9 . _ -` replaced by `_` (`tcp://127.0.0.1:8300` -> `tcp___127.0.0.1_8300`).
+//! The digest is 16 hex digits of FNV-1a over the arguments as JSON with sorted keys,
+//! so the same call always maps to the same file. Captured files start with an XML
+//! comment naming the device, RPC, arguments and capture time.
+//!
+//! ## Lookup
+//! A call is answered by the first file that exists: the device's reply for these
+//! arguments, the device's reply for the RPC, then the same two at the top level. A
+//! call nothing answers fails with a `DeviceError` listing the files tried.
+//!
+//! ## How to Use
+//! 1. Record: `THALYX_REPORT_TRANSPORT=capture THALYX_REPORT_CAPTURE_DIR=captures`
+//!    and run reports against live devices
+//! 2. Replay: `THALYX_REPORT_TRANSPORT=replay THALYX_REPORT_REPLAY_DIR=captures`
+//! 3. Or check the catalogue offline: `check-reports --replies captures`
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::{path::PathBuf, sync::Arc};
+use tracing::{debug, info, warn};
+
+use crate::{
+    models::{ApiError, ApiResult},
+    services::report_engine::{ReportTransport, RpcCall},
+};
+
+/// Directory name a device's replies are stored under
+pub fn device_dir_name(device: &str) -> String {
+    let name: String = device
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+        .collect();
+    // Keep `.` and `..` from pointing outside the capture directory
+    if name.chars().all(|c| c == '.') {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// File name of the reply to one call: `<rpc>.xml`, or `<rpc>~<digest>.xml` with arguments
+pub fn reply_file_name(call: &RpcCall) -> String {
+    if call.args.is_empty() {
+        return format!("{}.xml", call.name);
+    }
+    format!("{}~{:016x}.xml", call.name, args_digest(call))
+}
+
+/// FNV-1a over the arguments as JSON with sorted keys; stable across runs and builds
+fn args_digest(call: &RpcCall) -> u64 {
+    fn canonical(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), canonical(v))).collect())
+            }
+            Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+            other => other.clone(),
+        }
+    }
+
+    let args = Value::Object(call.args.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+    let text = canonical(&args).to_string();
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REPLAY
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Transport answering from captured replies, per device
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    /// Serve replies from `dir`; fails if it is not a directory
+    pub fn new(dir: impl Into<PathBuf>) -> ApiResult<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(ApiError::NotFound(format!("Replay directory '{}' does not exist", dir.display())));
+        }
+        let transport = Self { dir };
+        info!(
+            dir = %transport.dir.display(),
+            devices = ?transport.devices()?,
+            "Replaying captured RPC replies"
+        );
+        Ok(transport)
+    }
+
+    /// Devices with a directory of their own, sorted
+    pub fn devices(&self) -> ApiResult<Vec<String>> {
+        let mut devices = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    devices.push(name.to_string());
+                }
+            }
+        }
+        devices.sort();
+        Ok(devices)
+    }
+
+    /// Files that may answer a call, most specific first
+    fn candidates(&self, device: &str, call: &RpcCall) -> Vec<PathBuf> {
+        let device_dir = self.dir.join(device_dir_name(device));
+        let exact = reply_file_name(call);
+        let plain = format!("{}.xml", call.name);
+
+        let mut candidates = vec![device_dir.join(&exact)];
+        if exact != plain {
+            candidates.push(device_dir.join(&plain));
+        }
+        candidates.push(self.dir.join(&exact));
+        if exact != plain {
+            candidates.push(self.dir.join(&plain));
+        }
+        candidates
+    }
+}
+
+#[async_trait]
+impl ReportTransport for ReplayTransport {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String> {
+        let candidates = self.candidates(device, call);
+        for path in &candidates {
+            match tokio::fs::read_to_string(path).await {
+                Ok(xml) => {
+                    debug!(device = %device, rpc = %call.name, file = %path.display(), "Answering RPC from captured reply");
+                    return Ok(xml);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        warn!(device = %device, rpc = %call.name, "No captured reply for RPC");
+        Err(ApiError::DeviceError(format!(
+            "No captured reply for RPC '{}' on device '{}'; tried {}",
+            call.name,
+            device,
+            candidates
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// CAPTURE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Transport recording every successful reply of another transport in the replay layout
+#[derive(Debug)]
+pub struct CaptureTransport {
+    inner: Arc<dyn ReportTransport>,
+    dir: PathBuf,
+}
+
+impl CaptureTransport {
+    pub fn new(inner: Arc<dyn ReportTransport>, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        info!(dir = %dir.display(), transport = inner.name(), "Capturing RPC replies");
+        Self { inner, dir }
+    }
+
+    /// Write one reply; a failed write is logged and does not fail the run
+    async fn record(&self, device: &str, call: &RpcCall, xml: &str) {
+        let device_dir = self.dir.join(device_dir_name(device));
+        let path = device_dir.join(reply_file_name(call));
+        let result = async {
+            tokio::fs::create_dir_all(&device_dir).await?;
+            // Write-then-rename so a replay never reads a half-written capture
+            let temporary = path.with_extension("xml.tmp");
+            tokio::fs::write(&temporary, with_header(device, call, xml)).await?;
+            tokio::fs::rename(&temporary, &path).await
+        }
+        .await;
+
+        match result {
+            Ok(()) => debug!(device = %device, rpc = %call.name, file = %path.display(), "Captured RPC reply"),
+            Err(e) => warn!(device = %device, rpc = %call.name, file = %path.display(), error = %e, "Failed to capture RPC reply"),
+        }
+    }
+}
+
+#[async_trait]
+impl ReportTransport for CaptureTransport {
+    fn name(&self) -> &'static str {
+        "capture"
+    }
+
+    async fn execute(&self, device: &str, call: &RpcCall) -> ApiResult<String> {
+        let xml = self.inner.execute(device, call).await?;
+        self.record(device, call, &xml).await;
+        Ok(xml)
+    }
+
+    async fn shutdown(&self) {
+        self.inner.shutdown().await;
+    }
+}
+
+/// The reply with a comment describing the call, after any XML declaration
+fn with_header(device: &str, call: &RpcCall, xml: &str) -> String {
+    let args = serde_json::to_string(&call.args).unwrap_or_default();
+    let description = format!(
+        "captured device={} rpc={} args={} at={}",
+        device,
+        call.name,
+        args,
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    );
+    // `--` may not appear inside an XML comment
+    let header = format!("<!-- {} -->\n", description.replace("--", "- -"));
+
+    let body = xml.trim_start();
+    let declaration = body
+        .starts_with("<?xml")
+        .then(|| body.find("?>"))
+        .flatten()
+        .map_or(0, |end| end + "?>".len());
+    if declaration == 0 {
+        return format!("{}{}", header, body);
+    }
+    format!("{}\n{}{}", &body[..declaration], header, body[declaration..].trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::reports::{ParameterValues, Report},
+        services::report_engine::{ReportEngine, StaticTransport},
+    };
+    use axum::http::StatusCode;
+    use indexmap::IndexMap;
+
+    const DEVICE: &str = "tcp://127.0.0.1:8300";
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("thalyx-replay-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn call(name: &str, args: &[(&str, Value)]) -> RpcCall {
+        RpcCall {
+            name: name.to_string(),
+            args: args.iter().map(|(k, v)| (k.to_string(), v.clone())).collect::<IndexMap<_, _>>(),
+        }
+    }
+
+    fn write(path: PathBuf, xml: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, xml).unwrap();
+    }
+
+    fn bgp_report() -> Report {
+        serde_yaml::from_str(
+            r#"
+title: BGP
+category: Routing
+rpc: get-bgp-summary-information
+xpath: .//bgp-peer
+fields:
+  Address: { path: peer-address, type: ip }
+  State: peer-state
+"#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn captured_runs_replay_identically() {
+        let dir = temp_dir();
+        let fixtures = StaticTransport::from_dir("../shared/fixtures/rpc-replies").unwrap();
+        let capture = ReportEngine::new(Arc::new(CaptureTransport::new(Arc::new(fixtures), &dir)));
+        let live = capture.run("bgp", &bgp_report(), DEVICE, &ParameterValues::new()).await.unwrap();
+        assert_eq!(live.rows.len(), 3);
+
+        let captured = dir.join("tcp___127.0.0.1_8300").join("get-bgp-summary-information.xml");
+        let xml = std::fs::read_to_string(&captured).unwrap();
+        assert!(xml.contains("<!-- captured device=tcp://127.0.0.1:8300 rpc=get-bgp-summary-information"), "{}", xml);
+
+        let replay = ReplayTransport::new(&dir).unwrap();
+        assert_eq!(replay.devices().unwrap(), ["tcp___127.0.0.1_8300"]);
+        let replayed = ReportEngine::new(Arc::new(replay))
+            .run("bgp", &bgp_report(), DEVICE, &ParameterValues::new())
+            .await
+            .unwrap();
+        assert_eq!(replayed.columns, live.columns);
+        assert_eq!(replayed.rows, live.rows);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn most_specific_reply_wins() {
+        let dir = temp_dir();
+        let with_args = call("get-route-information", &[("destination", Value::from("10.0.0.0/8"))]);
+        let device_dir = dir.join("r1.lab");
+        write(device_dir.join(reply_file_name(&with_args)), "<device-exact/>");
+        write(device_dir.join("get-route-information.xml"), "<device-plain/>");
+        write(dir.join("get-route-information.xml"), "<shared-plain/>");
+        let replay = ReplayTransport::new(&dir).unwrap();
+
+        assert_eq!(replay.execute("r1.lab", &with_args).await.unwrap(), "<device-exact/>");
+        let other_args = call("get-route-information", &[("destination", Value::from("192.0.2.0/24"))]);
+        assert_eq!(replay.execute("r1.lab", &other_args).await.unwrap(), "<device-plain/>");
+        assert_eq!(replay.execute("r2.lab", &with_args).await.unwrap(), "<shared-plain/>");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn unanswered_calls_are_device_errors() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let engine = ReportEngine::new(Arc::new(ReplayTransport::new(&dir).unwrap()));
+
+        let err = engine.run("bgp", &bgp_report(), "r1.lab", &ParameterValues::new()).await.unwrap_err();
+        assert!(matches!(err, ApiError::DeviceError(_)), "{:?}", err);
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(err.to_string().contains("r1.lab/get-bgp-summary-information.xml"), "{}", err);
+
+        let missing = ReplayTransport::new(dir.join("absent")).unwrap_err();
+        assert_eq!(missing.status_code(), StatusCode::NOT_FOUND);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn argument_order_does_not_change_the_file() {
+        let a = call("rpc", &[("a", Value::from(1)), ("b", Value::from(2))]);
+        let b = call("rpc", &[("b", Value::from(2)), ("a", Value::from(1))]);
+        assert_eq!(reply_file_name(&a), reply_file_name(&b));
+        assert_eq!(reply_file_name(&call("rpc", &[])), "rpc.xml");
+        assert_eq!(device_dir_name(".."), "_..");
+    }
+}
@@ -0,0 +1,436 @@
+// backend/src/services/report_runs.rs
+
+//! # Multi-Device Report Runs
+//!
+//! ## Description
+//! Fans a report out across a list of devices with bounded concurrency and a
+//! per-device timeout. `@group` entries of the list expand to the group's members
+//! (`services::device_groups`). Results are aggregated per device in a `ReportRun` kept in
+//! memory, and progress streams to WebSocket clients as `DataUpdate` messages on the
+//! run's own topic, `data:report-run:<run_id>`.
+//!
+//! ## Progress Events
+//! Every `DataUpdate.data` carries `run_id` and an `event`:
+//! - `run_started` - `run`: the full `ReportRun` with every device `pending`
+//! - `device_started` - `index`, `device`
+//! - `device_finished` - `index`, `device`: the `DeviceRun` (with its `result`), `summary`
+//! - `run_completed` - `summary`, `finished_at`
+//!
+//! Clients that subscribe after the run started catch up with `ReportRunner::get`
+//! (`GET /api/report-runs/:run_id`); device events carry full state, so applying them
+//! over a snapshot is idempotent.
+//!
+//! ## History
+//! Every device outcome, including single-device runs through `run_single`, is
+//! recorded in the `ResultsStore` with the run's ID as `batch_run_id`.
+//!
+//! ## Configuration
+//! - `THALYX_RUN_CONCURRENCY` - devices queried at once by default (16)
+//! - `THALYX_RUN_DEVICE_TIMEOUT` - seconds allowed per device by default (120)
+//! - `THALYX_RUN_HISTORY` - finished runs kept in memory (100)
+
+use futures_util::StreamExt;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_json::json;
+use std::{sync::Arc, time::{Duration, Instant}};
+use tokio::sync::RwLock;
+use tracing::{info, warn, Instrument};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        reports::{
+            DeviceRun, DeviceRunStatus, ParameterValues, Report, ReportResult, ReportRun, ReportRunStatus,
+            ReportRunSummary, StoredResult,
+        },
+        websocket::{SubscriptionTopic, WsMessage},
+        ApiError, ApiResult,
+    },
+    services::{
+        device_groups::GROUP_PREFIX, report_engine::CompiledReport, report_parameters, results_store::ResultsStore,
+        DeviceGroups, ReportEngine, WebSocketService,
+    },
+};
+
+/// Upper bound for a requested concurrency
+pub const MAX_CONCURRENCY: usize = 256;
+/// Upper bound for a requested per-device timeout
+pub const MAX_DEVICE_TIMEOUT_SECS: u64 = 3600;
+/// Devices accepted in a single run
+pub const MAX_DEVICES_PER_RUN: usize = 10_000;
+
+/// Defaults applied when a run request leaves options out
+#[derive(Debug, Clone)]
+pub struct RunnerConfig {
+    pub concurrency: usize,
+    pub device_timeout: Duration,
+    /// Finished runs retained for `get`; running runs are never evicted
+    pub history: usize,
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 16,
+            device_timeout: Duration::from_secs(120),
+            history: 100,
+        }
+    }
+}
+
+impl RunnerConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|n| *n > 0);
+
+        Self {
+            concurrency: number("THALYX_RUN_CONCURRENCY")
+                .map_or(defaults.concurrency, |n| (n as usize).min(MAX_CONCURRENCY)),
+            device_timeout: number("THALYX_RUN_DEVICE_TIMEOUT")
+                .map_or(defaults.device_timeout, |n| Duration::from_secs(n.min(MAX_DEVICE_TIMEOUT_SECS))),
+            history: number("THALYX_RUN_HISTORY").map_or(defaults.history, |n| n as usize),
+        }
+    }
+}
+
+/// What to run a report against and how
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunRequest {
+    /// Devices in the same forms `ReportEngine::run` accepts, or `@group` for the members
+    /// of a device group; duplicates are dropped
+    pub devices: Vec<String>,
+    /// Devices queried at once (1..=256)
+    pub concurrency: Option<usize>,
+    /// Seconds allowed per device, including connection setup (1..=3600)
+    pub timeout_secs: Option<u64>,
+    /// Values for the report's parameters; omitted ones take their defaults
+    #[serde(default)]
+    pub parameters: ParameterValues,
+}
+
+/// Starts multi-device runs and keeps their aggregated state
+#[derive(Debug)]
+pub struct ReportRunner {
+    engine: Arc<ReportEngine>,
+    websocket: Arc<WebSocketService>,
+    store: Arc<ResultsStore>,
+    groups: Arc<DeviceGroups>,
+    config: RunnerConfig,
+    /// Runs in start order, so the oldest finished ones are evicted first
+    runs: RwLock<IndexMap<Uuid, ReportRun>>,
+}
+
+impl ReportRunner {
+    pub fn new(
+        engine: Arc<ReportEngine>,
+        websocket: Arc<WebSocketService>,
+        store: Arc<ResultsStore>,
+        groups: Arc<DeviceGroups>,
+        config: RunnerConfig,
+    ) -> Self {
+        Self {
+            engine,
+            websocket,
+            store,
+            groups,
+            config,
+            runs: RwLock::new(IndexMap::new()),
+        }
+    }
+
+    /// Run a report on one device and record the outcome in the history
+    /// Requests rejected before reaching the device (bad XPath, invalid parameters, empty
+    /// device) are not recorded
+    pub async fn run_single(
+        &self,
+        report_id: &str,
+        report: &Report,
+        device: &str,
+        parameters: &ParameterValues,
+    ) -> ApiResult<ReportResult> {
+        if device.trim().starts_with(GROUP_PREFIX) {
+            return Err(ApiError::ValidationError(format!(
+                "'{}' is a device group; run groups as a multi-device run",
+                device.trim()
+            )));
+        }
+        let parameters = report_parameters::resolve(&report.parameters, parameters)?;
+        let started_at = chrono::Utc::now();
+        let timer = Instant::now();
+        let outcome = self.engine.run(report_id, report, device, &parameters).await;
+
+        match &outcome {
+            Ok(result) => self.record(StoredResult::succeeded(result.clone(), None)).await,
+            Err(ApiError::ValidationError(_)) => {}
+            Err(e) => {
+                let entry = DeviceRun {
+                    status: DeviceRunStatus::Failed,
+                    started_at: Some(started_at),
+                    duration_ms: Some(timer.elapsed().as_millis() as u64),
+                    error: Some(e.to_string()),
+                    ..DeviceRun::pending(device.trim().to_string())
+                };
+                self.record(StoredResult::unsuccessful(report_id, report, &parameters, &entry, None))
+                    .await;
+            }
+        }
+        outcome
+    }
+
+    /// Validate a request and start the run in the background
+    /// Returns the initial snapshot; progress follows on `ReportRun.topic`
+    pub async fn start(self: &Arc<Self>, report_id: &str, report: Report, request: RunRequest) -> ApiResult<ReportRun> {
+        // Broken expressions and parameters would fail on every device; reject them up front
+        CompiledReport::compile(&report)?;
+        let parameters = report_parameters::resolve(&report.parameters, &request.parameters)?;
+
+        let devices = normalize_devices(self.groups.expand_targets(request.devices).await?)?;
+        let concurrency = match request.concurrency {
+            Some(n) if n == 0 || n > MAX_CONCURRENCY => {
+                return Err(ApiError::ValidationError(format!(
+                    "concurrency must be between 1 and {}",
+                    MAX_CONCURRENCY
+                )));
+            }
+            Some(n) => n,
+            None => self.config.concurrency,
+        };
+        let device_timeout = match request.timeout_secs {
+            Some(s) if s == 0 || s > MAX_DEVICE_TIMEOUT_SECS => {
+                return Err(ApiError::ValidationError(format!(
+                    "timeout_secs must be between 1 and {}",
+                    MAX_DEVICE_TIMEOUT_SECS
+                )));
+            }
+            Some(s) => Duration::from_secs(s),
+            None => self.config.device_timeout,
+        };
+
+        let run_id = Uuid::new_v4();
+        let devices: Vec<DeviceRun> = devices.into_iter().map(DeviceRun::pending).collect();
+        let run = ReportRun {
+            run_id,
+            report_id: report_id.to_string(),
+            title: report.title.clone(),
+            status: ReportRunStatus::Running,
+            topic: run_topic(run_id).to_string(),
+            concurrency: concurrency.min(devices.len()),
+            device_timeout_secs: device_timeout.as_secs(),
+            started_at: chrono::Utc::now(),
+            finished_at: None,
+            summary: ReportRunSummary::of(&devices),
+            parameters,
+            devices,
+        };
+
+        {
+            let mut runs = self.runs.write().await;
+            runs.insert(run_id, run.clone());
+            self.evict(&mut runs);
+        }
+
+        info!(
+            run_id = %run_id,
+            report_id = %report_id,
+            devices = run.devices.len(),
+            concurrency = run.concurrency,
+            device_timeout_secs = run.device_timeout_secs,
+            "Starting multi-device report run"
+        );
+
+        let runner = self.clone();
+        let report_id = report_id.to_string();
+        let snapshot = run.clone();
+        tokio::spawn(
+            async move { runner.execute(run, report_id, report, device_timeout).await }
+                .instrument(tracing::info_span!("report_run", run_id = %run_id)),
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Current state of a run
+    pub async fn get(&self, run_id: Uuid) -> ApiResult<ReportRun> {
+        self.runs
+            .read()
+            .await
+            .get(&run_id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound(format!("Report run '{}' not found", run_id)))
+    }
+
+    async fn execute(self: Arc<Self>, run: ReportRun, report_id: String, report: Report, device_timeout: Duration) {
+        let run_id = run.run_id;
+        let timer = Instant::now();
+        self.publish(run_id, json!({ "event": "run_started", "run": &run })).await;
+
+        let devices: Vec<(usize, String)> = run.devices.iter().map(|d| d.device.clone()).enumerate().collect();
+        futures_util::stream::iter(devices)
+            .for_each_concurrent(run.concurrency, |(index, device)| {
+                let (runner, run, report) = (&self, &run, &report);
+                async move {
+                    runner.run_device(run, index, device, report, device_timeout).await;
+                }
+            })
+            .await;
+
+        let (summary, finished_at) = {
+            let mut runs = self.runs.write().await;
+            let Some(run) = runs.get_mut(&run_id) else { return };
+            run.status = ReportRunStatus::Completed;
+            run.finished_at = Some(chrono::Utc::now());
+            (run.summary.clone(), run.finished_at)
+        };
+
+        info!(
+            report_id = %report_id,
+            succeeded = summary.succeeded,
+            failed = summary.failed,
+            timed_out = summary.timed_out,
+            rows = summary.rows,
+            duration_ms = timer.elapsed().as_millis() as u64,
+            "Multi-device report run completed"
+        );
+        self.publish(
+            run_id,
+            json!({ "event": "run_completed", "summary": summary, "finished_at": finished_at }),
+        )
+        .await;
+    }
+
+    /// Run on one device of `run`, the snapshot taken at start
+    async fn run_device(&self, run: &ReportRun, index: usize, device: String, report: &Report, device_timeout: Duration) {
+        let (run_id, report_id, parameters) = (run.run_id, run.report_id.as_str(), &run.parameters);
+        self.update(run_id, index, |entry| {
+            entry.status = DeviceRunStatus::Running;
+            entry.started_at = Some(chrono::Utc::now());
+        })
+        .await;
+        self.publish(run_id, json!({ "event": "device_started", "index": index, "device": &device }))
+            .await;
+
+        let timer = Instant::now();
+        let outcome = tokio::time::timeout(device_timeout, self.engine.run(report_id, report, &device, parameters)).await;
+        let duration_ms = timer.elapsed().as_millis() as u64;
+
+        let finished = self
+            .update(run_id, index, |entry| {
+                entry.duration_ms = Some(duration_ms);
+                match outcome {
+                    Ok(Ok(result)) => {
+                        entry.status = DeviceRunStatus::Succeeded;
+                        entry.result = Some(result);
+                    }
+                    Ok(Err(e)) => {
+                        entry.status = DeviceRunStatus::Failed;
+                        entry.error = Some(e.to_string());
+                    }
+                    Err(_) => {
+                        warn!(device = %device, timeout_secs = device_timeout.as_secs(), "Device timed out");
+                        entry.status = DeviceRunStatus::TimedOut;
+                        entry.error = Some(format!("No result within {}s", device_timeout.as_secs()));
+                    }
+                }
+            })
+            .await;
+
+        if let Some((entry, summary)) = finished {
+            let stored = match &entry.result {
+                Some(result) => StoredResult::succeeded(result.clone(), Some(run_id)),
+                None => StoredResult::unsuccessful(report_id, report, parameters, &entry, Some(run_id)),
+            };
+            self.record(stored).await;
+            self.publish(
+                run_id,
+                json!({ "event": "device_finished", "index": index, "device": entry, "summary": summary }),
+            )
+            .await;
+        }
+    }
+
+    /// Apply a change to one device entry; returns the entry and refreshed summary
+    async fn update(
+        &self,
+        run_id: Uuid,
+        index: usize,
+        change: impl FnOnce(&mut DeviceRun),
+    ) -> Option<(DeviceRun, ReportRunSummary)> {
+        let mut runs = self.runs.write().await;
+        let run = runs.get_mut(&run_id)?;
+        let entry = run.devices.get_mut(index)?;
+        change(entry);
+        let entry = entry.clone();
+        run.summary = ReportRunSummary::of(&run.devices);
+        Some((entry, run.summary.clone()))
+    }
+
+    /// Persist an outcome; the history is best-effort and never fails a run
+    async fn record(&self, result: StoredResult) {
+        let (report_id, device) = (result.summary.report_id.clone(), result.summary.device.clone());
+        if let Err(e) = self.store.record(result).await {
+            warn!(report_id = %report_id, device = %device, error = %e, "Failed to record report result");
+        }
+    }
+
+    async fn publish(&self, run_id: Uuid, mut data: serde_json::Value) {
+        data["run_id"] = json!(run_id);
+        let topic = run_topic(run_id);
+        let message = WsMessage::DataUpdate {
+            source: topic_source(run_id),
+            data,
+            timestamp: chrono::Utc::now(),
+        };
+        if let Err(e) = self.websocket.broadcast_to_topic(topic, message).await {
+            warn!(run_id = %run_id, error = %e, "Failed to publish report run progress");
+        }
+    }
+
+    /// Drop the oldest finished runs beyond the history size
+    fn evict(&self, runs: &mut IndexMap<Uuid, ReportRun>) {
+        let mut finished = runs.values().filter(|r| r.status == ReportRunStatus::Completed).count();
+        while finished > self.config.history {
+            let Some(oldest) = runs
+                .iter()
+                .find(|(_, r)| r.status == ReportRunStatus::Completed)
+                .map(|(id, _)| *id)
+            else {
+                break;
+            };
+            runs.shift_remove(&oldest);
+            finished -= 1;
+        }
+    }
+}
+
+/// `DataUpdate.source` of a run's progress messages
+fn topic_source(run_id: Uuid) -> String {
+    format!("report-run:{}", run_id)
+}
+
+/// Topic carrying a run's progress: `data:report-run:<run_id>`
+pub fn run_topic(run_id: Uuid) -> SubscriptionTopic {
+    SubscriptionTopic::DataUpdates(topic_source(run_id))
+}
+
+/// Trim, drop empties and duplicates while keeping request order
+fn normalize_devices(devices: Vec<String>) -> ApiResult<Vec<String>> {
+    let mut seen = std::collections::HashSet::new();
+    let devices: Vec<String> = devices
+        .into_iter()
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty() && seen.insert(d.clone()))
+        .collect();
+
+    if devices.is_empty() {
+        return Err(ApiError::ValidationError("At least one device is required".to_string()));
+    }
+    if devices.len() > MAX_DEVICES_PER_RUN {
+        return Err(ApiError::ValidationError(format!(
+            "A run accepts at most {} devices, got {}",
+            MAX_DEVICES_PER_RUN,
+            devices.len()
+        )));
+    }
+    Ok(devices)
+}
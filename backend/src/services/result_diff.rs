@@ -0,0 +1,340 @@
+// backend/src/services/result_diff.rs
+
+//! # Report Result Diffs
+//!
+//! ## Description
+//! Compares two stored results of the same report, e.g. before and after a change
+//! window. Rows are matched on an identity column (`Report.key`, such as
+//! `peer-address` or `name`) and sorted into added, removed and changed rows, with
+//! the before and after value of every changed cell.
+//!
+//! ## Matching
+//! - Only columns present in both results are compared; columns added or removed
+//!   by an edited report definition are listed instead
+//! - Rows sharing a key value within one result are paired in order of appearance
+//!   and their key is listed in `duplicate_keys`
+//!
+//! ## How to Use
+//! 1. Load both sides with `ResultsStore::get`
+//! 2. `let diff = diff_results(&before, &after, report.key.as_deref())?;`
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::models::{
+    reports::{
+        find_column, ChangedRow, DeviceRunStatus, DiffRow, DiffSummary, FieldChange, ReportColumn,
+        ResultDiff, StoredResult,
+    },
+    ApiError, ApiResult,
+};
+
+/// Diff `from` (baseline) against `to`
+/// `key` is a column display name or field; `None` uses the first column
+pub fn diff_results(from: &StoredResult, to: &StoredResult, key: Option<&str>) -> ApiResult<ResultDiff> {
+    if from.summary.report_id != to.summary.report_id {
+        return Err(ApiError::ValidationError(format!(
+            "Results belong to different reports ('{}' and '{}')",
+            from.summary.report_id, to.summary.report_id
+        )));
+    }
+    for side in [from, to] {
+        if side.summary.status != DeviceRunStatus::Succeeded {
+            return Err(ApiError::ValidationError(format!(
+                "Result '{}' has no table to compare: {}",
+                side.summary.run_id,
+                side.summary.error.as_deref().unwrap_or("run did not succeed")
+            )));
+        }
+    }
+
+    let key_column = resolve_key(&to.columns, key)?;
+    let from_key = from
+        .columns
+        .iter()
+        .position(|c| c.name == key_column.name)
+        .ok_or_else(|| {
+            ApiError::ValidationError(format!(
+                "Key column '{}' is missing from result '{}'",
+                key_column.name, from.summary.run_id
+            ))
+        })?;
+    let to_key = to.columns.iter().position(|c| c.name == key_column.name).unwrap_or_default();
+
+    // (column, index in from, index in to) for every column both sides have
+    let shared: Vec<(&ReportColumn, usize, usize)> = to
+        .columns
+        .iter()
+        .enumerate()
+        .filter_map(|(to_index, column)| {
+            let from_index = from.columns.iter().position(|c| c.name == column.name)?;
+            Some((column, from_index, to_index))
+        })
+        .collect();
+    let columns_added = missing_columns(&to.columns, &from.columns);
+    let columns_removed = missing_columns(&from.columns, &to.columns);
+
+    let before = group_by_key(&from.rows, from_key);
+    let after = group_by_key(&to.rows, to_key);
+    let duplicate_keys: Vec<Value> = before
+        .iter()
+        .chain(after.iter())
+        .filter(|(_, group)| group.rows.len() > 1)
+        .map(|(_, group)| group.key.clone())
+        .fold(Vec::new(), |mut keys, key| {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+            keys
+        });
+
+    let mut diff = ResultDiff {
+        report_id: to.summary.report_id.clone(),
+        key: key_column,
+        from: from.summary.clone(),
+        to: to.summary.clone(),
+        summary: DiffSummary::default(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        columns_added,
+        columns_removed,
+        duplicate_keys,
+    };
+
+    for (identity, group) in &after {
+        let previous = before.get(identity).map_or(&[][..], |g| g.rows.as_slice());
+        for (occurrence, row) in group.rows.iter().enumerate() {
+            let Some(old) = previous.get(occurrence) else {
+                diff.added.push(diff_row(&group.key, &to.columns, row));
+                continue;
+            };
+            let changes: Vec<FieldChange> = shared
+                .iter()
+                .filter_map(|(column, from_index, to_index)| {
+                    let before = cell(old, *from_index);
+                    let after = cell(row, *to_index);
+                    (before != after).then(|| FieldChange {
+                        column: column.name.clone(),
+                        field: column.field.clone(),
+                        before,
+                        after,
+                    })
+                })
+                .collect();
+            if changes.is_empty() {
+                diff.summary.unchanged += 1;
+            } else {
+                diff.changed.push(ChangedRow {
+                    key: group.key.clone(),
+                    changes,
+                });
+            }
+        }
+    }
+
+    for (identity, group) in &before {
+        let remaining = after.get(identity).map_or(0, |g| g.rows.len());
+        for row in group.rows.iter().skip(remaining) {
+            diff.removed.push(diff_row(&group.key, &from.columns, row));
+        }
+    }
+
+    diff.summary.added = diff.added.len();
+    diff.summary.removed = diff.removed.len();
+    diff.summary.changed = diff.changed.len();
+    Ok(diff)
+}
+
+/// Rows of one result sharing a key value, in order of appearance
+struct KeyGroup<'r> {
+    key: Value,
+    rows: Vec<&'r Vec<Value>>,
+}
+
+/// Group rows by the JSON text of their key cell, so `"1"` and `1` stay distinct
+fn group_by_key(rows: &[Vec<Value>], key: usize) -> IndexMap<String, KeyGroup<'_>> {
+    let mut groups: IndexMap<String, KeyGroup> = IndexMap::new();
+    for row in rows {
+        let key = cell(row, key);
+        groups
+            .entry(key.to_string())
+            .or_insert_with(|| KeyGroup { key, rows: Vec::new() })
+            .rows
+            .push(row);
+    }
+    groups
+}
+
+fn resolve_key(columns: &[ReportColumn], key: Option<&str>) -> ApiResult<ReportColumn> {
+    let index = match key {
+        Some(key) => find_column(columns, key).ok_or_else(|| {
+            ApiError::ValidationError(format!(
+                "Key '{}' matches no column; available: {}",
+                key,
+                columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+            ))
+        })?,
+        None if columns.is_empty() => {
+            return Err(ApiError::ValidationError("Results have no columns to match rows on".to_string()));
+        }
+        None => 0,
+    };
+    Ok(columns[index].clone())
+}
+
+/// Names of `columns` absent from `other`
+fn missing_columns(columns: &[ReportColumn], other: &[ReportColumn]) -> Vec<String> {
+    columns
+        .iter()
+        .filter(|c| !other.iter().any(|o| o.name == c.name))
+        .map(|c| c.name.clone())
+        .collect()
+}
+
+fn diff_row(key: &Value, columns: &[ReportColumn], row: &[Value]) -> DiffRow {
+    DiffRow {
+        key: key.clone(),
+        cells: columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| (column.name.clone(), cell(row, index)))
+            .collect(),
+    }
+}
+
+/// Cell of a row, `null` for rows shorter than their columns
+fn cell(row: &[Value], index: usize) -> Value {
+    row.get(index).cloned().unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    const COLUMNS: [(&str, &str); 3] = [("Peer", "peer-address"), ("State", "peer-state"), ("Flaps", "flap-count")];
+
+    fn result(report_id: &str, columns: &[(&str, &str)], rows: Vec<Value>) -> StoredResult {
+        serde_json::from_value(json!({
+            "run_id": Uuid::new_v4(),
+            "report_id": report_id,
+            "title": "BGP Neighbor",
+            "device": "r1.lab",
+            "rpc": "get-bgp-summary-information",
+            "status": "succeeded",
+            "started_at": "2026-10-18T08:00:00Z",
+            "duration_ms": 12,
+            "row_count": rows.len(),
+            "columns": columns
+                .iter()
+                .map(|(name, field)| json!({ "name": name, "field": field }))
+                .collect::<Vec<_>>(),
+            "rows": rows,
+        }))
+        .unwrap()
+    }
+
+    fn bgp(rows: Vec<Value>) -> StoredResult {
+        result("test_bgp_summary", &COLUMNS, rows)
+    }
+
+    #[test]
+    fn rows_are_matched_on_the_key() {
+        let before = bgp(vec![
+            json!(["10.0.0.1", "Established", 0]),
+            json!(["10.0.0.2", "Established", 1]),
+            json!(["10.0.0.3", "Active", 4]),
+        ]);
+        let after = bgp(vec![
+            json!(["10.0.0.2", "Idle", 1]),
+            json!(["10.0.0.1", "Established", 0]),
+            json!(["10.0.0.4", "Established", 0]),
+        ]);
+
+        let diff = diff_results(&before, &after, Some("peer-address")).unwrap();
+
+        assert_eq!(diff.key.name, "Peer");
+        assert_eq!((diff.summary.added, diff.summary.removed, diff.summary.changed), (1, 1, 1));
+        assert_eq!(diff.summary.unchanged, 1);
+        assert_eq!(diff.added[0].key, json!("10.0.0.4"));
+        assert_eq!(diff.added[0].cells["State"], json!("Established"));
+        assert_eq!(diff.removed[0].key, json!("10.0.0.3"));
+        assert_eq!(diff.removed[0].cells["Flaps"], json!(4));
+
+        let changed = &diff.changed[0];
+        assert_eq!(changed.key, json!("10.0.0.2"));
+        assert_eq!(changed.changes.len(), 1);
+        assert_eq!(changed.changes[0].column, "State");
+        assert_eq!(changed.changes[0].field, "peer-state");
+        assert_eq!((&changed.changes[0].before, &changed.changes[0].after), (&json!("Established"), &json!("Idle")));
+        assert!(diff.duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn identical_results_have_no_differences() {
+        let rows = vec![json!(["10.0.0.1", "Established", 0]), json!(["10.0.0.2", "Active", 2])];
+        let diff = diff_results(&bgp(rows.clone()), &bgp(rows), None).unwrap();
+
+        assert_eq!(diff.key.name, "Peer");
+        assert_eq!(diff.summary.unchanged, 2);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+        assert!(diff.columns_added.is_empty() && diff.columns_removed.is_empty());
+    }
+
+    #[test]
+    fn rows_without_a_key_pair_in_order() {
+        // A null key cell and a row too short to have one both group under `null`
+        let before = bgp(vec![json!([null, "Established", 0]), json!(["10.0.0.1", "Active", 1])]);
+        let after = bgp(vec![json!([null, "Idle", 0]), json!([]), json!(["10.0.0.1", "Active", 1])]);
+
+        let diff = diff_results(&before, &after, None).unwrap();
+
+        assert_eq!(diff.duplicate_keys, vec![Value::Null]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, Value::Null);
+        assert_eq!(diff.changed[0].changes[0].after, json!("Idle"));
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].cells["State"], Value::Null);
+        assert_eq!(diff.summary.unchanged, 1);
+    }
+
+    #[test]
+    fn changed_columns_are_listed_not_compared() {
+        let before = result("test_bgp_summary", &COLUMNS[..2], vec![json!(["10.0.0.1", "Established"])]);
+        let after = result(
+            "test_bgp_summary",
+            &[COLUMNS[0], COLUMNS[2]],
+            vec![json!(["10.0.0.1", 3])],
+        );
+
+        let diff = diff_results(&before, &after, None).unwrap();
+        assert_eq!(diff.columns_added, ["Flaps"]);
+        assert_eq!(diff.columns_removed, ["State"]);
+        assert_eq!(diff.summary.unchanged, 1);
+    }
+
+    #[test]
+    fn different_reports_are_rejected() {
+        let before = bgp(vec![json!(["10.0.0.1", "Established", 0])]);
+        let after = result("test_interfaces", &COLUMNS, vec![json!(["10.0.0.1", "Established", 0])]);
+
+        let error = diff_results(&before, &after, None).unwrap_err();
+        assert!(matches!(error, ApiError::ValidationError(_)));
+        assert!(error.to_string().contains("different reports"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_key_and_failed_runs_are_rejected() {
+        let rows = vec![json!(["10.0.0.1", "Established", 0])];
+        let error = diff_results(&bgp(rows.clone()), &bgp(rows.clone()), Some("Uptime")).unwrap_err();
+        assert!(error.to_string().contains("matches no column"), "{}", error);
+
+        let mut failed = bgp(Vec::new());
+        failed.summary.status = DeviceRunStatus::Failed;
+        failed.summary.error = Some("connection refused".to_string());
+        let error = diff_results(&failed, &bgp(rows), None).unwrap_err();
+        assert!(error.to_string().contains("connection refused"), "{}", error);
+    }
+}
@@ -0,0 +1,411 @@
+// backend/src/services/results_store.rs
+
+//! # Report Results Store
+//!
+//! ## Description
+//! Embedded SQLite history of report executions. Every run of a report on a device,
+//! successful or not, is recorded with its table, errors and timing in
+//! the `report_results` table of the shared database (`services::database`), and old
+//! records are purged after the retention period. Retention only ever touches results.
+//!
+//! ## Configuration
+//! - `THALYX_RESULTS_RETENTION_DAYS` - days results are kept, `0` keeps them forever (`30`)
+//!
+//! ## How to Use
+//! 1. `let store = Arc::new(ResultsStore::new(database, StoreConfig::from_env()));`
+//! 2. `store.start_retention();` to purge expired results hourly
+//! 3. `store.record(StoredResult::succeeded(result, None)).await?`
+//! 4. Query with `list`, `get` and `latest_per_device`
+
+use rusqlite::{params, params_from_iter, OptionalExtension, Row};
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        reports::{DeviceRunStatus, StoredResult, StoredResultPage, StoredResultSummary, Verdict},
+        ApiError, ApiResult,
+    },
+    services::database::{
+        database_error, parse_column, timestamp, to_json, Database, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+    },
+};
+
+/// Interval between retention sweeps
+const RETENTION_INTERVAL: Duration = Duration::from_secs(3600);
+
+const SUMMARY_COLUMNS: &str = "run_id, batch_run_id, report_id, title, device, rpc, status, \
+                               started_at, duration_ms, row_count, error, verdict, parameters";
+
+/// How long results are kept
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    /// `None` keeps results forever
+    pub retention: Option<Duration>,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            retention: Some(Duration::from_secs(30 * 86_400)),
+        }
+    }
+}
+
+impl StoreConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        Self {
+            retention: match var("THALYX_RESULTS_RETENTION_DAYS").and_then(|v| v.parse::<u64>().ok()) {
+                Some(0) => None,
+                Some(days) => Some(Duration::from_secs(days * 86_400)),
+                None => defaults.retention,
+            },
+        }
+    }
+}
+
+/// Filters for listing stored results
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResultQuery {
+    pub report_id: Option<String>,
+    pub device: Option<String>,
+    pub status: Option<DeviceRunStatus>,
+    pub batch_run_id: Option<Uuid>,
+    /// Assertion verdict; results of reports without assertions never match
+    pub verdict: Option<Verdict>,
+    /// Only results started at or after this instant
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only results started before this instant
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// SQLite-backed history of report executions
+#[derive(Debug, Clone)]
+pub struct ResultsStore {
+    database: Arc<Database>,
+    config: StoreConfig,
+}
+
+impl ResultsStore {
+    pub fn new(database: Arc<Database>, config: StoreConfig) -> Self {
+        info!(
+            retention_days = config.retention.map(|r| r.as_secs() / 86_400),
+            "Report results store ready"
+        );
+        Self { database, config }
+    }
+
+    /// Purge expired results now and then every hour
+    pub fn start_retention(self: &Arc<Self>) {
+        let Some(retention) = self.config.retention else {
+            return;
+        };
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                let cutoff = chrono::Utc::now()
+                    - chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+                match store.purge_before(cutoff).await {
+                    Ok(0) => {}
+                    Ok(purged) => info!(purged, cutoff = %cutoff, "Purged expired report results"),
+                    Err(e) => error!(error = %e, "Failed to purge expired report results"),
+                }
+            }
+        });
+    }
+
+    /// Store one execution
+    pub async fn record(&self, result: StoredResult) -> ApiResult<()> {
+        self.database.blocking(move |connection| {
+            let summary = &result.summary;
+            connection
+                .execute(
+                    "INSERT OR REPLACE INTO report_results (run_id, batch_run_id, report_id, title, device, \
+                     rpc, status, started_at, duration_ms, row_count, error, verdict, parameters, columns, rows, \
+                     misses, assertions) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, \
+                     ?16, ?17)",
+                    params![
+                        summary.run_id.to_string(),
+                        summary.batch_run_id.map(|id| id.to_string()),
+                        summary.report_id,
+                        summary.title,
+                        summary.device,
+                        summary.rpc,
+                        status_name(summary.status),
+                        timestamp(&summary.started_at),
+                        summary.duration_ms as i64,
+                        summary.row_count as i64,
+                        summary.error,
+                        summary.verdict.map(Verdict::as_str),
+                        (!summary.parameters.is_empty())
+                            .then(|| to_json(&summary.parameters))
+                            .transpose()?,
+                        to_json(&result.columns)?,
+                        to_json(&result.rows)?,
+                        to_json(&result.misses)?,
+                        result.assertions.as_ref().map(to_json).transpose()?,
+                    ],
+                )
+                .map_err(database_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Newest-first page of results matching the filters
+    pub async fn list(&self, query: ResultQuery) -> ApiResult<StoredResultPage> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(ApiError::ValidationError(format!(
+                "limit must be between 1 and {}",
+                MAX_PAGE_SIZE
+            )));
+        }
+        let offset = query.offset.unwrap_or(0);
+
+        self.database.blocking(move |connection| {
+            let mut conditions = Vec::new();
+            let mut values: Vec<String> = Vec::new();
+            let mut filter = |condition: &str, value: Option<String>| {
+                if let Some(value) = value {
+                    values.push(value);
+                    conditions.push(format!("{} ?{}", condition, values.len()));
+                }
+            };
+            filter("report_id =", query.report_id);
+            filter("device =", query.device);
+            filter("status =", query.status.map(|s| status_name(s).to_string()));
+            filter("verdict =", query.verdict.map(|v| v.as_str().to_string()));
+            filter("batch_run_id =", query.batch_run_id.map(|id| id.to_string()));
+            filter("started_at >=", query.since.as_ref().map(timestamp));
+            filter("started_at <", query.until.as_ref().map(timestamp));
+
+            let clause = if conditions.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", conditions.join(" AND "))
+            };
+
+            let total: i64 = connection
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM report_results {}", clause),
+                    params_from_iter(values.iter()),
+                    |row| row.get(0),
+                )
+                .map_err(database_error)?;
+
+            let sql = format!(
+                "SELECT {} FROM report_results {} ORDER BY started_at DESC, rowid DESC LIMIT {} OFFSET {}",
+                SUMMARY_COLUMNS, clause, limit, offset
+            );
+            let mut statement = connection.prepare(&sql).map_err(database_error)?;
+            let results = statement
+                .query_map(params_from_iter(values.iter()), read_summary)
+                .map_err(database_error)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(database_error)?;
+
+            Ok(StoredResultPage {
+                total: total as usize,
+                limit,
+                offset,
+                results,
+            })
+        })
+        .await
+    }
+
+    /// One stored result with its table
+    pub async fn get(&self, run_id: Uuid) -> ApiResult<StoredResult> {
+        self.database.blocking(move |connection| {
+            connection
+                .query_row(
+                    &format!(
+                        "SELECT {}, columns, rows, misses, assertions FROM report_results WHERE run_id = ?1",
+                        SUMMARY_COLUMNS
+                    ),
+                    params![run_id.to_string()],
+                    read_result,
+                )
+                .optional()
+                .map_err(database_error)?
+                .ok_or_else(|| ApiError::NotFound(format!("Result '{}' not found", run_id)))
+        })
+        .await
+    }
+
+    /// Most recent result of a report on every device it ran on, ordered by device
+    /// With `status`, the most recent result having that status
+    pub async fn latest_per_device(
+        &self,
+        report_id: &str,
+        status: Option<DeviceRunStatus>,
+    ) -> ApiResult<Vec<StoredResult>> {
+        let report_id = report_id.to_string();
+        self.database.blocking(move |connection| {
+            let sql = format!(
+                "SELECT {}, columns, rows, misses, assertions FROM ( \
+                     SELECT *, ROW_NUMBER() OVER ( \
+                         PARTITION BY device ORDER BY started_at DESC, rowid DESC \
+                     ) AS position \
+                     FROM report_results \
+                     WHERE report_id = ?1 AND (?2 IS NULL OR status = ?2) \
+                 ) WHERE position = 1 ORDER BY device",
+                SUMMARY_COLUMNS
+            );
+            let mut statement = connection.prepare(&sql).map_err(database_error)?;
+            let results = statement
+                .query_map(params![report_id, status.map(status_name)], read_result)
+                .map_err(database_error)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(database_error)?;
+            Ok(results)
+        })
+        .await
+    }
+
+    /// Delete results started before `cutoff`; returns how many were removed
+    pub async fn purge_before(&self, cutoff: chrono::DateTime<chrono::Utc>) -> ApiResult<usize> {
+        self.database.blocking(move |connection| {
+            connection
+                .execute(
+                    "DELETE FROM report_results WHERE started_at < ?1",
+                    params![timestamp(&cutoff)],
+                )
+                .map_err(database_error)
+        })
+        .await
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+fn read_summary(row: &Row) -> rusqlite::Result<StoredResultSummary> {
+    Ok(StoredResultSummary {
+        run_id: parse_column(row, 0, |v: String| Uuid::parse_str(&v).ok())?,
+        batch_run_id: row
+            .get::<_, Option<String>>(1)?
+            .and_then(|v| Uuid::parse_str(&v).ok()),
+        report_id: row.get(2)?,
+        title: row.get(3)?,
+        device: row.get(4)?,
+        rpc: row.get(5)?,
+        status: parse_column(row, 6, |v: String| parse_status(&v))?,
+        started_at: parse_column(row, 7, |v: String| {
+            chrono::DateTime::parse_from_rfc3339(&v)
+                .ok()
+                .map(|t| t.with_timezone(&chrono::Utc))
+        })?,
+        duration_ms: row.get::<_, i64>(8)? as u64,
+        row_count: row.get::<_, i64>(9)? as usize,
+        error: row.get(10)?,
+        verdict: row
+            .get::<_, Option<String>>(11)?
+            .and_then(|v| serde_json::from_value(serde_json::Value::String(v)).ok()),
+        parameters: row
+            .get::<_, Option<String>>(12)?
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default(),
+    })
+}
+
+fn read_result(row: &Row) -> rusqlite::Result<StoredResult> {
+    Ok(StoredResult {
+        summary: read_summary(row)?,
+        columns: parse_column(row, 13, |v: String| serde_json::from_str(&v).ok())?,
+        rows: parse_column(row, 14, |v: String| serde_json::from_str(&v).ok())?,
+        misses: parse_column(row, 15, |v: String| serde_json::from_str(&v).ok())?,
+        assertions: row
+            .get::<_, Option<String>>(16)?
+            .and_then(|v| serde_json::from_str(&v).ok()),
+    })
+}
+
+fn status_name(status: DeviceRunStatus) -> &'static str {
+    match status {
+        DeviceRunStatus::Pending => "pending",
+        DeviceRunStatus::Running => "running",
+        DeviceRunStatus::Succeeded => "succeeded",
+        DeviceRunStatus::Failed => "failed",
+        DeviceRunStatus::TimedOut => "timed_out",
+    }
+}
+
+fn parse_status(name: &str) -> Option<DeviceRunStatus> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{devices::DeviceInput, reports::ReportResult},
+        services::{database::DatabaseConfig, DeviceInventory, WebSocketService},
+    };
+
+    fn result(device: &str, started_at: chrono::DateTime<chrono::Utc>) -> StoredResult {
+        StoredResult::succeeded(
+            ReportResult {
+                run_id: Uuid::new_v4(),
+                report_id: "bgp".to_string(),
+                title: "BGP".to_string(),
+                device: device.to_string(),
+                rpc: "get-bgp-summary-information".to_string(),
+                started_at,
+                duration_ms: 5,
+                columns: Vec::new(),
+                rows: Vec::new(),
+                misses: Vec::new(),
+                assertions: None,
+                parameters: Default::default(),
+            },
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn retention_purges_only_results() {
+        let data_dir = std::env::temp_dir().join(format!("thalyx-results-{}", Uuid::new_v4()));
+        let database = Arc::new(Database::open(DatabaseConfig { data_dir: data_dir.clone() }).unwrap());
+        let store = ResultsStore::new(database.clone(), StoreConfig::default());
+        let inventory = DeviceInventory::new(database, Arc::new(WebSocketService::new(None)));
+        inventory
+            .create(DeviceInput {
+                name: "r1.lab".to_string(),
+                address: "10.0.0.1".to_string(),
+                vendor: String::new(),
+                platform: String::new(),
+                kind: Default::default(),
+                site: String::new(),
+                rack: String::new(),
+                tags: Vec::new(),
+                status: Default::default(),
+            })
+            .await
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        store.record(result("r1.lab", now - chrono::Duration::days(40))).await.unwrap();
+        let recent = result("r1.lab", now);
+        let recent_id = recent.summary.run_id;
+        store.record(recent).await.unwrap();
+
+        assert_eq!(store.purge_before(now - chrono::Duration::days(30)).await.unwrap(), 1);
+        let page = store.list(ResultQuery::default()).await.unwrap();
+        assert_eq!(page.results.iter().map(|r| r.run_id).collect::<Vec<_>>(), [recent_id]);
+        assert!(inventory.get("r1.lab").await.is_ok());
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,184 @@
+// backend/src/services/rpc_registry.rs
+
+//! # WebSocket RPC Registry
+//!
+//! ## Description
+//! Registry of named request/response methods that clients can invoke over the
+//! WebSocket connection. A client sends `WsMessage::Request { id, method, params }`
+//! and the server replies with `WsMessage::Response` or `WsMessage::RequestFailed`
+//! carrying the same `id`.
+//!
+//! ## How to Use
+//! 1. Register methods from Rust: `registry.register("yaml.get", |ctx, params| async move { ... })`
+//! 2. The WebSocket service dispatches incoming `Request` messages through `dispatch()`
+//! 3. Handlers return `Result<serde_json::Value, ApiError>`; errors are mapped to
+//!    `RequestFailed` using the HTTP status code of the `ApiError`
+
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, RwLock},
+};
+use tracing::{debug, warn};
+
+use crate::models::{websocket::ConnectionId, ApiError, ApiResult};
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// HANDLER TYPES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Boxed future returned by RPC handlers
+pub type RpcFuture = Pin<Box<dyn Future<Output = ApiResult<Value>> + Send>>;
+
+/// Type-erased RPC handler
+pub type RpcHandler = Arc<dyn Fn(RpcContext, Value) -> RpcFuture + Send + Sync>;
+
+/// Per-request context passed to every handler
+#[derive(Debug, Clone)]
+pub struct RpcContext {
+    /// Connection that issued the request
+    pub connection_id: ConnectionId,
+    /// Client supplied request ID
+    pub request_id: String,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// REGISTRY
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Thread-safe registry of RPC methods, cheap to clone
+#[derive(Clone, Default)]
+pub struct RpcRegistry {
+    methods: Arc<RwLock<HashMap<String, RpcHandler>>>,
+}
+
+impl std::fmt::Debug for RpcRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcRegistry")
+            .field("methods", &self.method_names())
+            .finish()
+    }
+}
+
+impl RpcRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) a method handler
+    pub fn register<F, Fut>(&self, method: &str, handler: F)
+    where
+        F: Fn(RpcContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ApiResult<Value>> + Send + 'static,
+    {
+        let handler: RpcHandler = Arc::new(move |ctx, params| Box::pin(handler(ctx, params)));
+        let mut methods = self.methods.write().unwrap_or_else(|e| e.into_inner());
+        if methods.insert(method.to_string(), handler).is_some() {
+            warn!(method = %method, "Replaced existing RPC method handler");
+        } else {
+            debug!(method = %method, "Registered RPC method");
+        }
+    }
+
+    /// Sorted list of registered method names
+    pub fn method_names(&self) -> Vec<String> {
+        let methods = self.methods.read().unwrap_or_else(|e| e.into_inner());
+        let mut names: Vec<String> = methods.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Invoke a registered method
+    pub async fn dispatch(&self, ctx: RpcContext, method: &str, params: Value) -> ApiResult<Value> {
+        let handler = {
+            let methods = self.methods.read().unwrap_or_else(|e| e.into_inner());
+            methods.get(method).cloned()
+        };
+
+        match handler {
+            Some(handler) => handler(ctx, params).await,
+            None => Err(ApiError::NotFound(format!("Unknown method '{}'", method))),
+        }
+    }
+}
+
+/// Deserialize RPC params into a typed struct, mapping failures to a validation error
+pub fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> ApiResult<T> {
+    // Treat a missing params field as an empty object so optional-only params work
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid params: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[derive(serde::Deserialize)]
+    struct EchoParams {
+        text: String,
+        #[serde(default)]
+        times: usize,
+    }
+
+    fn context(request_id: &str) -> RpcContext {
+        RpcContext {
+            connection_id: Uuid::new_v4(),
+            request_id: request_id.to_string(),
+        }
+    }
+
+    fn registry() -> RpcRegistry {
+        let registry = RpcRegistry::new();
+        registry.register("echo", |ctx, params| async move {
+            let params: EchoParams = parse_params(params)?;
+            Ok(json!({ "id": ctx.request_id, "text": params.text.repeat(params.times.max(1)) }))
+        });
+        registry.register("empty", |_ctx, params| async move {
+            let params: HashMap<String, Value> = parse_params(params)?;
+            Ok(json!(params.len()))
+        });
+        registry
+    }
+
+    #[tokio::test]
+    async fn dispatch_runs_the_handler_with_its_context() {
+        let result = registry().dispatch(context("r1"), "echo", json!({ "text": "ab", "times": 2 })).await.unwrap();
+        assert_eq!(result, json!({ "id": "r1", "text": "abab" }));
+    }
+
+    #[tokio::test]
+    async fn unknown_methods_are_not_found() {
+        let error = registry().dispatch(context("r1"), "reports.lst", Value::Null).await.unwrap_err();
+        assert!(matches!(error, ApiError::NotFound(_)));
+        assert_eq!(error.status_code().as_u16(), 404);
+        assert!(error.to_string().contains("Unknown method 'reports.lst'"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn bad_params_are_validation_errors() {
+        let registry = registry();
+        for params in [Value::Null, json!({ "text": 5 }), json!("ab"), json!({ "text": "ab", "times": -1 })] {
+            let error = registry.dispatch(context("r1"), "echo", params.clone()).await.unwrap_err();
+            assert!(matches!(error, ApiError::ValidationError(_)), "{}: {:?}", params, error);
+            assert!(error.to_string().contains("Invalid params"), "{}", error);
+            assert_eq!(error.status_code().as_u16(), 400);
+        }
+        // Missing params count as an empty object
+        assert_eq!(registry.dispatch(context("r2"), "empty", Value::Null).await.unwrap(), json!(0));
+    }
+
+    #[tokio::test]
+    async fn registering_again_replaces_the_handler() {
+        let registry = registry();
+        registry.register("echo", |_ctx, _params| async move { Ok(json!("replaced")) });
+
+        assert_eq!(registry.method_names(), ["echo", "empty"]);
+        assert_eq!(registry.dispatch(context("r1"), "echo", Value::Null).await.unwrap(), json!("replaced"));
+    }
+}
@@ -0,0 +1,494 @@
+// backend/src/services/scheduler.rs
+
+//! # Report Scheduler
+//!
+//! ## Description
+//! Fires the report runs declared in `shared/data/schedules.yaml` on their cron
+//! expressions. A single background task sleeps until the next fire time, re-reading
+//! the file on every wake so edits apply without a restart, and starts each run
+//! through the `ReportRunner` (so progress streams over the WebSocket and results
+//! land in the history like any other run).
+//!
+//! ## Behaviour
+//! - **Overlap prevention** - a fire time is skipped while the schedule's previous run
+//!   is still in progress, and counted in `skipped_overlaps`
+//! - **Missed runs** - fire times noticed more than a minute late (backend down, host
+//!   suspended) follow the schedule's `missed` policy: `skip` or `run_once`
+//! - **Pause / resume** - paused schedules never fire; resuming does not catch up on the
+//!   paused period. Pauses and the last fire time persist in
+//!   `<THALYX_DATA_DIR>/schedules-state.json`
+//! - **Trigger now** - starts a run immediately without affecting the cron cadence
+//!
+//! ## How to Use
+//! 1. `let scheduler = Arc::new(Scheduler::new(yaml, runner, &data_dir).await?);`
+//! 2. `scheduler.start_background_tasks();`
+//! 3. `scheduler.list()`, `pause(id)`, `resume(id)`, `trigger(id)`
+
+use chrono::{DateTime, SubsecRound, Utc};
+use croner::Cron;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{Mutex, Notify};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        reports::{Report, ReportRun, ReportRunStatus},
+        schedules::{MissedRunPolicy, Schedule, ScheduleStatus},
+        ApiError, ApiResult,
+    },
+    routes::reports::load_reports,
+    services::{report_parameters, report_runs::RunRequest, ReportRunner, YamlService},
+};
+
+/// Longest the scheduler sleeps before re-reading `schedules.yaml`
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+/// A fire time noticed later than this counts as missed
+const LATE_TOLERANCE: chrono::Duration = chrono::Duration::seconds(60);
+
+/// Upper bound on missed fire times counted for one schedule in one pass
+const MAX_MISSED_SCAN: usize = 10_000;
+
+const STATE_FILE: &str = "schedules-state.json";
+
+/// Load and parse all schedule definitions from schedules.yaml
+pub async fn load_schedules(yaml_service: &YamlService) -> ApiResult<IndexMap<String, Schedule>> {
+    let data = match yaml_service.get_yaml_data("schedules", None).await {
+        Ok(data) => data,
+        // No file means no schedules
+        Err(ApiError::FileNotFound(_)) => return Ok(IndexMap::new()),
+        Err(e) => return Err(e),
+    };
+    parse_schedules(yaml_service, data)
+}
+
+/// Check schedule definitions against `schedules.json` and deserialize them, so a
+/// misspelt key is an error rather than a silently ignored field
+fn parse_schedules(yaml_service: &YamlService, data: Value) -> ApiResult<IndexMap<String, Schedule>> {
+    if data.is_null() {
+        return Ok(IndexMap::new());
+    }
+    yaml_service.validate_against_schema("schedules", &data)?;
+    serde_json::from_value(data)
+        .map_err(|e| ApiError::ValidationError(format!("Failed to parse schedules: {}", e)))
+}
+
+/// Runtime state of one schedule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ScheduleState {
+    #[serde(default)]
+    paused: bool,
+    /// Latest fire time handled (run, skipped or missed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_fire_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_run_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_run_id: Option<Uuid>,
+    #[serde(skip)]
+    skipped_overlaps: u64,
+}
+
+/// A schedule whose expression, time zone and report all resolved
+struct ResolvedSchedule {
+    cron: Cron,
+    timezone: chrono_tz::Tz,
+    report: Report,
+}
+
+impl ResolvedSchedule {
+    fn resolve(schedule: &Schedule, reports: &HashMap<String, Report>) -> ApiResult<Self> {
+        let cron = Cron::new(&schedule.cron)
+            .with_seconds_optional()
+            .parse()
+            .map_err(|e| ApiError::ValidationError(format!("Invalid cron expression '{}': {}", schedule.cron, e)))?;
+        let timezone: chrono_tz::Tz = schedule
+            .timezone
+            .parse()
+            .map_err(|_| ApiError::ValidationError(format!("Unknown time zone '{}'", schedule.timezone)))?;
+        let report = reports
+            .get(&schedule.report)
+            .cloned()
+            .ok_or_else(|| ApiError::ValidationError(format!("Unknown report '{}'", schedule.report)))?;
+        if schedule.targets.iter().all(|t| t.trim().is_empty()) {
+            return Err(ApiError::ValidationError("At least one target is required".to_string()));
+        }
+        report_parameters::resolve(&report.parameters, &schedule.parameters)?;
+        Ok(Self { cron, timezone, report })
+    }
+
+    /// First fire time strictly after `after`, on a whole second
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cron
+            .find_next_occurrence(&after.trunc_subsecs(0).with_timezone(&self.timezone), false)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+}
+
+/// Fires scheduled report runs and exposes their state
+pub struct Scheduler {
+    yaml_service: Arc<YamlService>,
+    runner: Arc<ReportRunner>,
+    state_path: PathBuf,
+    states: Mutex<HashMap<String, ScheduleState>>,
+    /// Wakes the scheduler loop early after pause/resume
+    wake: Notify,
+}
+
+impl Scheduler {
+    /// Create the scheduler and restore persisted pauses and fire times
+    pub async fn new(yaml_service: Arc<YamlService>, runner: Arc<ReportRunner>, data_dir: &Path) -> ApiResult<Self> {
+        let state_path = data_dir.join(STATE_FILE);
+        let states = match tokio::fs::read_to_string(&state_path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(path = %state_path.display(), error = %e, "Ignoring unreadable scheduler state");
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            yaml_service,
+            runner,
+            state_path,
+            states: Mutex::new(states),
+            wake: Notify::new(),
+        })
+    }
+
+    /// Spawn the scheduling loop
+    pub fn start_background_tasks(self: &Arc<Self>) {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            info!("Report scheduler started");
+            loop {
+                let sleep = match scheduler.tick().await {
+                    Ok(Some(next)) => (next - Utc::now()).to_std().unwrap_or_default().min(MAX_SLEEP),
+                    Ok(None) => MAX_SLEEP,
+                    Err(e) => {
+                        error!(error = %e, "Scheduler pass failed");
+                        MAX_SLEEP
+                    }
+                };
+                tokio::select! {
+                    _ = tokio::time::sleep(sleep) => {}
+                    _ = scheduler.wake.notified() => {}
+                }
+            }
+        });
+    }
+
+    /// Every schedule with its live state, in file order
+    pub async fn list(&self) -> ApiResult<Vec<ScheduleStatus>> {
+        let schedules = load_schedules(&self.yaml_service).await?;
+        let reports = load_reports(&self.yaml_service).await?;
+        let states = self.states.lock().await.clone();
+
+        let mut statuses = Vec::with_capacity(schedules.len());
+        for (id, schedule) in schedules {
+            let state = states.get(&id).cloned().unwrap_or_default();
+            statuses.push(self.status(id, schedule, &state, &reports).await);
+        }
+        Ok(statuses)
+    }
+
+    /// One schedule with its live state
+    pub async fn get(&self, id: &str) -> ApiResult<ScheduleStatus> {
+        let schedule = self.schedule(id).await?;
+        let reports = load_reports(&self.yaml_service).await?;
+        let state = self.states.lock().await.get(id).cloned().unwrap_or_default();
+        Ok(self.status(id.to_string(), schedule, &state, &reports).await)
+    }
+
+    /// Stop a schedule from firing until resumed
+    pub async fn pause(&self, id: &str) -> ApiResult<ScheduleStatus> {
+        self.schedule(id).await?;
+        self.update_state(id, |state| state.paused = true).await?;
+        info!(schedule = %id, "Schedule paused");
+        self.wake.notify_one();
+        self.get(id).await
+    }
+
+    /// Let a paused schedule fire again from its next fire time on
+    pub async fn resume(&self, id: &str) -> ApiResult<ScheduleStatus> {
+        self.schedule(id).await?;
+        self.update_state(id, |state| {
+            // The paused period is not a missed run
+            if state.paused {
+                state.last_fire_at = Some(Utc::now());
+            }
+            state.paused = false;
+        })
+        .await?;
+        info!(schedule = %id, "Schedule resumed");
+        self.wake.notify_one();
+        self.get(id).await
+    }
+
+    /// Start a run now, regardless of the cron expression, pause or `enabled`
+    pub async fn trigger(&self, id: &str) -> ApiResult<ReportRun> {
+        let schedule = self.schedule(id).await?;
+        let reports = load_reports(&self.yaml_service).await?;
+        let resolved = ResolvedSchedule::resolve(&schedule, &reports)?;
+
+        if let Some(run_id) = self.active_run(id).await {
+            return Err(ApiError::Conflict(format!(
+                "Schedule '{}' is still running (run {})",
+                id, run_id
+            )));
+        }
+        info!(schedule = %id, report = %schedule.report, "Schedule triggered on demand");
+        self.fire(id, &schedule, resolved.report).await
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // SCHEDULING LOOP
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    /// Fire everything that is due; returns the earliest upcoming fire time
+    async fn tick(&self) -> ApiResult<Option<DateTime<Utc>>> {
+        let schedules = load_schedules(&self.yaml_service).await?;
+        let reports = load_reports(&self.yaml_service).await?;
+        let now = Utc::now();
+        let mut next_wake: Option<DateTime<Utc>> = None;
+        self.forget_removed(&schedules).await?;
+
+        for (id, schedule) in &schedules {
+            if !schedule.enabled {
+                continue;
+            }
+            let resolved = match ResolvedSchedule::resolve(schedule, &reports) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    debug!(schedule = %id, error = %e, "Skipping invalid schedule");
+                    continue;
+                }
+            };
+
+            let state = self.states.lock().await.get(id).cloned().unwrap_or_default();
+            if state.paused {
+                continue;
+            }
+            let Some(last_fire_at) = state.last_fire_at else {
+                // New schedule: start counting from now rather than from the epoch
+                self.update_state(id, |state| state.last_fire_at = Some(now)).await?;
+                next_wake = earliest(next_wake, resolved.next_after(now));
+                continue;
+            };
+
+            // Fire times between the last handled one and now
+            let mut due = Vec::new();
+            let mut cursor = last_fire_at;
+            while let Some(time) = resolved.next_after(cursor).filter(|t| *t <= now) {
+                due.push(time);
+                cursor = time;
+                if due.len() >= MAX_MISSED_SCAN {
+                    break;
+                }
+            }
+
+            if let Some(&latest) = due.last() {
+                let on_time = now - latest <= LATE_TOLERANCE;
+                let missed = due.len() - usize::from(on_time);
+                if missed > 0 {
+                    warn!(
+                        schedule = %id,
+                        missed,
+                        since = %last_fire_at,
+                        policy = ?schedule.missed,
+                        "Schedule missed fire times"
+                    );
+                }
+                self.update_state(id, |state| state.last_fire_at = Some(latest)).await?;
+
+                if on_time || schedule.missed == MissedRunPolicy::RunOnce {
+                    self.fire_if_idle(id, schedule, resolved.report.clone()).await;
+                }
+            }
+            next_wake = earliest(next_wake, resolved.next_after(now));
+        }
+        Ok(next_wake)
+    }
+
+    /// Start a scheduled run unless the previous one is still going
+    async fn fire_if_idle(&self, id: &str, schedule: &Schedule, report: Report) {
+        if let Some(run_id) = self.active_run(id).await {
+            warn!(schedule = %id, run_id = %run_id, "Previous run still in progress, skipping fire time");
+            // State is in memory only; a failed save here loses nothing persisted
+            let _ = self.update_state(id, |state| state.skipped_overlaps += 1).await;
+            return;
+        }
+        if let Err(e) = self.fire(id, schedule, report).await {
+            error!(schedule = %id, error = %e, "Failed to start scheduled run");
+        }
+    }
+
+    async fn fire(&self, id: &str, schedule: &Schedule, report: Report) -> ApiResult<ReportRun> {
+        let request = RunRequest {
+            devices: schedule.targets.clone(),
+            concurrency: schedule.concurrency,
+            timeout_secs: schedule.timeout_secs,
+            parameters: schedule.parameters.clone(),
+        };
+        let run = self.runner.start(&schedule.report, report, request).await?;
+        info!(schedule = %id, run_id = %run.run_id, devices = run.devices.len(), "Scheduled run started");
+
+        let (run_id, started_at) = (run.run_id, run.started_at);
+        self.update_state(id, |state| {
+            state.last_run_id = Some(run_id);
+            state.last_run_at = Some(started_at);
+        })
+        .await?;
+        Ok(run)
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // HELPERS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    async fn schedule(&self, id: &str) -> ApiResult<Schedule> {
+        load_schedules(&self.yaml_service)
+            .await?
+            .shift_remove(id)
+            .ok_or_else(|| ApiError::NotFound(format!("Schedule '{}' not found", id)))
+    }
+
+    /// Run ID of the schedule's last run while it is still in progress
+    async fn active_run(&self, id: &str) -> Option<Uuid> {
+        let run_id = self.states.lock().await.get(id)?.last_run_id?;
+        match self.runner.get(run_id).await {
+            Ok(run) if run.status == ReportRunStatus::Running => Some(run_id),
+            _ => None,
+        }
+    }
+
+    async fn status(
+        &self,
+        id: String,
+        schedule: Schedule,
+        state: &ScheduleState,
+        reports: &HashMap<String, Report>,
+    ) -> ScheduleStatus {
+        let resolved = ResolvedSchedule::resolve(&schedule, reports);
+        let next_run_at = match &resolved {
+            Ok(resolved) if schedule.enabled && !state.paused => resolved.next_after(Utc::now()),
+            _ => None,
+        };
+        let running = match state.last_run_id {
+            Some(run_id) => matches!(
+                self.runner.get(run_id).await,
+                Ok(run) if run.status == ReportRunStatus::Running
+            ),
+            None => false,
+        };
+
+        ScheduleStatus {
+            id,
+            schedule,
+            paused: state.paused,
+            next_run_at,
+            last_run_at: state.last_run_at,
+            last_run_id: state.last_run_id,
+            running,
+            skipped_overlaps: state.skipped_overlaps,
+            error: resolved.err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Drop the state of schedules removed from the file
+    async fn forget_removed(&self, schedules: &IndexMap<String, Schedule>) -> ApiResult<()> {
+        let mut states = self.states.lock().await;
+        let before = states.len();
+        states.retain(|id, _| schedules.contains_key(id));
+        if states.len() == before {
+            return Ok(());
+        }
+        self.save(&states).await
+    }
+
+    /// Change one schedule's state and persist the result
+    async fn update_state(&self, id: &str, change: impl FnOnce(&mut ScheduleState)) -> ApiResult<()> {
+        let mut states = self.states.lock().await;
+        change(states.entry(id.to_string()).or_default());
+        self.save(&states).await
+    }
+
+    async fn save(&self, states: &HashMap<String, ScheduleState>) -> ApiResult<()> {
+        let content = serde_json::to_string_pretty(states)
+            .map_err(|e| ApiError::SerializationError(e.to_string()))?;
+        // Write-then-rename so a crash never leaves a truncated file
+        let temporary = self.state_path.with_extension("json.tmp");
+        if let Some(parent) = self.state_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&temporary, content).await?;
+        tokio::fs::rename(&temporary, &self.state_path).await?;
+        Ok(())
+    }
+}
+
+fn earliest(current: Option<DateTime<Utc>>, candidate: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (current, candidate) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn yaml_service() -> YamlService {
+        YamlService::new("../shared/schemas").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn shipped_schedules_load() {
+        let schedules = load_schedules(&yaml_service().await).await.unwrap();
+        assert!(schedules.contains_key("nightly_bgp_summary"));
+    }
+
+    #[tokio::test]
+    async fn misspelt_key_is_rejected() {
+        let data = json!({
+            "nightly": {
+                "report": "test_bgp_summary",
+                "targets": ["r1.lab"],
+                "cron": "0 5 * * *",
+                "enable": false
+            }
+        });
+        let err = parse_schedules(&yaml_service().await, data).unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)));
+        assert!(err.to_string().contains("enable"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_field_fails_deserialization() {
+        let data = json!({
+            "report": "r",
+            "targets": ["a"],
+            "cron": "* * * * *",
+            "enable": false
+        });
+        assert!(serde_json::from_value::<Schedule>(data).is_err());
+    }
+
+    #[tokio::test]
+    async fn empty_document_has_no_schedules() {
+        let schedules = parse_schedules(&yaml_service().await, Value::Null).unwrap();
+        assert!(schedules.is_empty());
+    }
+}
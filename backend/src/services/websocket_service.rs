@@ -20,7 +20,7 @@
 //! ## How to Use
 //! 1. Create a new service instance: `WebSocketService::new(Some(config))`
 //! 2. Start background tasks: `service.start_background_tasks().await`
-//! 3. Handle incoming connections: `service.handle_connection(socket).await`
+//! 3. Handle incoming connections: `service.handle_connection(socket, codec).await`
 //! 4. Broadcast messages: `service.broadcast_to_topic(topic, message).await`
 //!
 //! ## Connection Flow
@@ -36,10 +36,11 @@
 //! - Performance metrics and timing
 //! - Error context and stack traces
 
-use axum::extract::ws::{Message, WebSocket};
-use futures_util::{SinkExt, StreamExt};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
+use futures_util::{stream::SplitSink, SinkExt, StreamExt};
 use std::{
     collections::HashMap,
+    net::IpAddr,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
@@ -47,12 +48,19 @@ use std::{
     time::Instant,
 };
 use tokio::sync::{broadcast, RwLock};
-use tracing::{debug, error, info, warn, trace, instrument, Span};
+use tracing::{debug, error, info, warn, trace, instrument, Instrument, Span};
+use uuid::Uuid;
 
 use crate::models::{
     websocket::{ConnectionId, ConnectionInfo, SubscriptionTopic, WsConfig, WsMessage},
     ApiError,
 };
+use crate::services::{
+    message_bus::{BusEnvelope, BusEvent, LocalBus, MessageBus, NodeId, NodeSnapshot},
+    rate_limit::TokenBucket,
+    rpc_registry::{RpcContext, RpcRegistry},
+    ws_codec::WsCodec,
+};
 
 // ═══════════════════════════════════════════════════════════════════════════════════
 // WEBSOCKET SERVICE STRUCT
@@ -72,6 +80,14 @@ pub struct WebSocketService {
     connection_count: Arc<AtomicUsize>,
     /// Service configuration parameters
     config: WsConfig,
+    /// Request/response methods callable over the socket
+    rpc: RpcRegistry,
+    /// Identifier of this backend instance on the message bus
+    node_id: NodeId,
+    /// Cross-node fan-out for broadcasts and registry snapshots
+    bus: Arc<dyn MessageBus>,
+    /// Latest registry snapshot received from each peer node
+    cluster: Arc<RwLock<HashMap<NodeId, NodeSnapshot>>>,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════════
@@ -88,6 +104,9 @@ impl WebSocketService {
         
         info!(
             max_connections = config.max_connections,
+            max_connections_per_ip = config.max_connections_per_ip,
+            max_frame_size = config.max_frame_size,
+            rate_limit_per_second = config.rate_limit_per_second,
             ping_interval = ?config.ping_interval,
             connection_timeout = ?config.connection_timeout,
             "Initializing WebSocket service"
@@ -98,6 +117,10 @@ impl WebSocketService {
             broadcaster: tx,
             connection_count: Arc::new(AtomicUsize::new(0)),
             config,
+            rpc: RpcRegistry::new(),
+            node_id: Uuid::new_v4(),
+            bus: Arc::new(LocalBus::new()),
+            cluster: Arc::new(RwLock::new(HashMap::new())),
         };
 
         // Log service readiness
@@ -105,6 +128,29 @@ impl WebSocketService {
         service
     }
 
+    /// Replace the default in-process bus, e.g. with a `RedisBus` for multi-node setups
+    /// Must be called before `start_background_tasks`
+    pub fn with_message_bus(mut self, bus: Arc<dyn MessageBus>) -> Self {
+        info!(bus = bus.name(), node_id = %self.node_id, "Attached message bus");
+        self.bus = bus;
+        self
+    }
+
+    /// Identifier of this node on the message bus
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// Active service configuration
+    pub fn config(&self) -> &WsConfig {
+        &self.config
+    }
+
+    /// Registry of RPC methods exposed to WebSocket clients
+    pub fn rpc(&self) -> &RpcRegistry {
+        &self.rpc
+    }
+
     /// Get current connection count with detailed logging
     #[instrument(name = "get_connection_count", level = "trace")]
     pub fn connection_count(&self) -> usize {
@@ -129,6 +175,26 @@ impl WebSocketService {
         connections
     }
 
+    /// Snapshot of this node's connection registry
+    pub async fn local_snapshot(&self) -> NodeSnapshot {
+        NodeSnapshot {
+            node_id: self.node_id,
+            reported_at: chrono::Utc::now(),
+            connections: self.connections.read().await.values().cloned().collect(),
+        }
+    }
+
+    /// Connection registries of every known node, this one first
+    /// Peer data is as fresh as their last heartbeat
+    pub async fn get_cluster_registry(&self) -> Vec<NodeSnapshot> {
+        let mut nodes = vec![self.local_snapshot().await];
+        let cluster = self.cluster.read().await;
+        let mut peers: Vec<NodeSnapshot> = cluster.values().cloned().collect();
+        peers.sort_by_key(|snapshot| snapshot.node_id);
+        nodes.extend(peers);
+        nodes
+    }
+
     /// Get detailed service statistics for debugging
     #[instrument(name = "get_service_stats", level = "debug")]
     pub async fn get_service_stats(&self) -> ServiceStats {
@@ -164,70 +230,117 @@ impl WebSocketService {
 // and cleanup. Includes comprehensive error handling and logging.
 
 impl WebSocketService {
+    /// Check whether a new connection from `remote_ip` would be admitted
+    /// Called before the HTTP upgrade so rejected clients get a proper status code
+    pub async fn check_admission(&self, remote_ip: Option<IpAddr>) -> Result<(), ApiError> {
+        let connections = self.connections.read().await;
+        self.admission_check(&connections, remote_ip)
+    }
+
+    /// Enforce `max_connections` and `max_connections_per_ip` (unless 0) against the registry
+    fn admission_check(
+        &self,
+        connections: &HashMap<ConnectionId, ConnectionInfo>,
+        remote_ip: Option<IpAddr>,
+    ) -> Result<(), ApiError> {
+        if connections.len() >= self.config.max_connections {
+            warn!(
+                current_connections = connections.len(),
+                max_connections = self.config.max_connections,
+                "Connection rejected: Maximum connections reached"
+            );
+            return Err(ApiError::ServiceUnavailable(
+                "Maximum connections reached".to_string(),
+            ));
+        }
+
+        if let Some(ip) = remote_ip.filter(|_| self.config.max_connections_per_ip > 0) {
+            let from_ip = connections
+                .values()
+                .filter(|conn| conn.remote_ip == Some(ip))
+                .count();
+            if from_ip >= self.config.max_connections_per_ip {
+                warn!(
+                    remote_ip = %ip,
+                    connections_from_ip = from_ip,
+                    max_connections_per_ip = self.config.max_connections_per_ip,
+                    "Connection rejected: Per-IP connection cap reached"
+                );
+                return Err(ApiError::LimitExceeded(format!(
+                    "Too many connections from {}",
+                    ip
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Handle a new WebSocket connection with extensive debugging
-    #[instrument(name = "handle_connection", level = "info", fields(connection_id))]
-    pub async fn handle_connection(&self, socket: WebSocket) -> Result<(), ApiError> {
+    #[instrument(name = "handle_connection", level = "info", skip(socket), fields(connection_id))]
+    pub async fn handle_connection(
+        &self,
+        mut socket: WebSocket,
+        codec: WsCodec,
+        remote_ip: Option<IpAddr>,
+    ) -> Result<(), ApiError> {
         let start_time = Instant::now();
-        let current_count = self.connection_count.fetch_add(1, Ordering::Relaxed);
-        
-        // Prevent connection flooding
-        if current_count >= 50 { // Reasonable limit
-            error!("Connection rejected: Too many connections ({})", current_count);
-            return Err(ApiError::WebSocketError("Too many connections".to_string()));
-        }
 
         info!(
-            current_connections = current_count + 1,
+            current_connections = self.connection_count(),
             max_connections = self.config.max_connections,
             "Processing new WebSocket connection request"
         );
 
-        // Check connection limits
-        if current_count >= self.config.max_connections {
-            self.connection_count.fetch_sub(1, Ordering::Relaxed);
-            error!(
-                current_connections = current_count,
-                max_connections = self.config.max_connections,
-                "Connection rejected: Maximum connections reached"
-            );
-            return Err(ApiError::WebSocketError(
-                "Maximum connections reached".to_string()
-            ));
-        }
-
-        let connection_info = ConnectionInfo::new();
+        let mut connection_info = ConnectionInfo::new();
+        connection_info.metadata.insert("encoding".to_string(), codec.subprotocol());
+        connection_info.remote_ip = remote_ip;
         let connection_id = connection_info.id;
         
         // Update the span with the connection ID
-        Span::current().record("connection_id", &tracing::field::display(connection_id));
+        Span::current().record("connection_id", tracing::field::display(connection_id));
+
+        // Re-check limits and register under the same write lock so concurrent
+        // upgrades can't overshoot the caps
+        let admission = {
+            let mut connections = self.connections.write().await;
+            let admission = self.admission_check(&connections, remote_ip);
+            if admission.is_ok() {
+                connections.insert(connection_id, connection_info.clone());
+                self.connection_count.fetch_add(1, Ordering::Relaxed);
+                debug!(
+                    connection_id = %connection_id,
+                    total_connections = connections.len(),
+                    "Connection registered successfully"
+                );
+            }
+            admission
+        };
+
+        if let Err(e) = admission {
+            // Tell the client why before closing
+            if let Ok(frame) = codec.encode(&error_message(&e)) {
+                let _ = socket.send(frame).await;
+            }
+            let _ = socket.send(close_frame(close_code::AGAIN, "connection limit reached")).await;
+            return Err(e);
+        }
         
         info!(
             connection_id = %connection_id,
             connection_time = ?connection_info.connected_at,
+            remote_ip = ?remote_ip,
             setup_duration_ms = start_time.elapsed().as_millis(),
             "New WebSocket connection established"
         );
 
-        // Add connection to the registry with error handling
-        {
-            let mut connections = self.connections.write().await;
-            connections.insert(connection_id, connection_info);
-            debug!(
-                connection_id = %connection_id,
-                total_connections = connections.len(),
-                "Connection registered successfully"
-            );
-        }
-
         // Handle the connection in a separate task with comprehensive error logging
         let service = self.clone();
+        let span = tracing::info_span!("connection_handler", connection_id = %connection_id);
         tokio::spawn(async move {
-            let span = tracing::info_span!("connection_handler", connection_id = %connection_id);
-            let _enter = span.enter();
-            
             info!("Starting connection handler task");
             
-            match service.handle_socket(socket, connection_id).await {
+            match service.handle_socket(socket, connection_id, codec).await {
                 Ok(()) => {
                     info!("Connection handler completed successfully");
                 }
@@ -244,7 +357,7 @@ impl WebSocketService {
             info!("Beginning connection cleanup");
             service.cleanup_connection(connection_id).await;
             info!("Connection cleanup completed");
-        });
+        }.instrument(span));
 
         debug!(
             connection_id = %connection_id,
@@ -261,8 +374,9 @@ impl WebSocketService {
         &self,
         socket: WebSocket,
         connection_id: ConnectionId,
+        codec: WsCodec,
     ) -> Result<(), ApiError> {
-        info!("Starting socket handler for connection");
+        info!(encoding = ?codec.encoding, compression = ?codec.compression, "Starting socket handler for connection");
         
         let (mut sender, mut receiver) = socket.split();
         let mut message_count = 0u64;
@@ -272,22 +386,22 @@ impl WebSocketService {
 
         // Send connection established message with error handling
         let welcome_msg = WsMessage::ConnectionEstablished { connection_id };
-        let welcome_json = serde_json::to_string(&welcome_msg)
+        let welcome_frame = codec.encode(&welcome_msg)
             .map_err(|e| {
                 error!(
                     error = %e,
                     message_type = "ConnectionEstablished",
                     "Failed to serialize welcome message"
                 );
-                ApiError::SerializationError(e.to_string())
+                e
             })?;
 
         debug!(
-            message = %welcome_json,
+            message = ?welcome_msg,
             "Sending welcome message to client"
         );
 
-        if let Err(e) = sender.send(Message::Text(welcome_json)).await {
+        if let Err(e) = sender.send(welcome_frame).await {
             error!(
                 error = %e,
                 "Failed to send welcome message - connection may be broken"
@@ -297,6 +411,13 @@ impl WebSocketService {
 
         info!("Welcome message sent successfully");
 
+        // Per-connection input limits
+        let mut rate_limiter = TokenBucket::new(
+            self.config.rate_limit_burst,
+            self.config.rate_limit_per_second,
+        );
+        let mut violations = 0u32;
+
         // Subscribe to broadcast messages
         let mut broadcast_rx = self.broadcaster.subscribe();
         debug!("Subscribed to broadcast channel");
@@ -318,16 +439,13 @@ impl WebSocketService {
                             debug!(
                                 message_count,
                                 message_length = text.len(),
-                                message_preview = &text[..text.len().min(100)],
                                 "Processing text message from client"
                             );
-                            
-                            if let Err(e) = self.handle_incoming_message(&text, connection_id).await {
-                                warn!(
-                                    error = %e,
-                                    message_text = %text,
-                                    "Error handling incoming message"
-                                );
+
+                            if let Err(e) = self.handle_text_frame(&text, &codec, connection_id, &mut rate_limiter).await {
+                                if !self.reply_with_error(&mut sender, &codec, e, &mut violations).await {
+                                    break;
+                                }
                             } else {
                                 trace!("Successfully processed incoming message");
                             }
@@ -361,11 +479,33 @@ impl WebSocketService {
                             self.update_last_ping(connection_id).await;
                         }
                         Some(Ok(Message::Binary(data))) => {
+                            message_count += 1;
                             debug!(
+                                message_count,
                                 binary_length = data.len(),
-                                "Received binary message from client"
+                                "Processing binary message from client"
                             );
-                            // Handle binary messages if needed
+
+                            let result = match self.admit_frame(data.len(), &mut rate_limiter) {
+                                Ok(()) => match codec.decode_binary(&data, self.config.max_frame_size) {
+                                    Ok(message) => self.handle_incoming_message(message, connection_id).await,
+                                    Err(e) => Err(e),
+                                },
+                                Err(e) => Err(e),
+                            };
+
+                            if let Err(e) = result {
+                                warn!(
+                                    error = %e,
+                                    binary_length = data.len(),
+                                    "Error handling incoming binary message"
+                                );
+                                if !self.reply_with_error(&mut sender, &codec, e, &mut violations).await {
+                                    break;
+                                }
+                            } else {
+                                trace!("Successfully processed incoming binary message");
+                            }
                         }
                         Some(Err(e)) => {
                             error!(
@@ -401,23 +541,23 @@ impl WebSocketService {
                             );
                             
                             if self.should_send_to_connection(&topic, connection_id).await {
-                                let msg_text = serde_json::to_string(&message)
+                                let frame = codec.encode(&message)
                                     .map_err(|e| {
                                         error!(
                                             error = %e,
                                             topic = %topic.to_string(),
                                             "Failed to serialize broadcast message"
                                         );
-                                        ApiError::SerializationError(e.to_string())
+                                        e
                                     })?;
                                 
                                 debug!(
                                     topic = %topic.to_string(),
-                                    message_length = msg_text.len(),
+                                    message_length = frame_len(&frame),
                                     "Sending broadcast message to client"
                                 );
                                 
-                                if let Err(e) = sender.send(Message::Text(msg_text)).await {
+                                if let Err(e) = sender.send(frame).await {
                                     error!(
                                         error = %e,
                                         topic = %topic.to_string(),
@@ -458,6 +598,106 @@ impl WebSocketService {
         Ok(())
     }
 
+    /// Apply the rate limit and frame size limit to an inbound data frame
+    fn admit_frame(&self, frame_len: usize, rate_limiter: &mut TokenBucket) -> Result<(), ApiError> {
+        if !rate_limiter.try_acquire() {
+            return Err(ApiError::RateLimited(format!(
+                "More than {} messages per second",
+                self.config.rate_limit_per_second
+            )));
+        }
+
+        if frame_len > self.config.max_frame_size {
+            return Err(ApiError::PayloadTooLarge(format!(
+                "Message of {} bytes exceeds the {} byte limit",
+                frame_len, self.config.max_frame_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Admit, decode and handle one text frame
+    /// The frame itself is never logged: `credentials.*` requests carry passwords and keys
+    /// in their params, so failures only record the frame length and, once decoded, the
+    /// request's method and ID
+    async fn handle_text_frame(
+        &self,
+        text: &str,
+        codec: &WsCodec,
+        connection_id: ConnectionId,
+        rate_limiter: &mut TokenBucket,
+    ) -> Result<(), ApiError> {
+        if let Err(e) = self.admit_frame(text.len(), rate_limiter) {
+            warn!(error = %e, message_length = text.len(), "Rejected incoming message");
+            return Err(e);
+        }
+
+        let message = match codec.decode_text(text) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!(error = %e, message_length = text.len(), "Failed to decode incoming message");
+                return Err(e);
+            }
+        };
+
+        let (method, request_id) = match &message {
+            WsMessage::Request { id, method, .. } => (Some(method.clone()), Some(id.clone())),
+            _ => (None, None),
+        };
+        self.handle_incoming_message(message, connection_id).await.inspect_err(|e| {
+            warn!(
+                error = %e,
+                method = ?method,
+                request_id = ?request_id,
+                "Error handling incoming message"
+            );
+        })
+    }
+
+    /// Report a failed inbound message to the client as `WsMessage::Error`
+    /// Limit violations are counted; once `max_violations` is reached the socket is
+    /// closed and `false` is returned so the caller ends the message loop
+    async fn reply_with_error(
+        &self,
+        sender: &mut SplitSink<WebSocket, Message>,
+        codec: &WsCodec,
+        error: ApiError,
+        violations: &mut u32,
+    ) -> bool {
+        if let Ok(frame) = codec.encode(&error_message(&error)) {
+            if sender.send(frame).await.is_err() {
+                return false;
+            }
+        }
+
+        if !error.is_limit_violation() {
+            return true;
+        }
+
+        *violations += 1;
+        if *violations < self.config.max_violations {
+            debug!(
+                violations = *violations,
+                max_violations = self.config.max_violations,
+                "Recorded limit violation"
+            );
+            return true;
+        }
+
+        warn!(
+            violations = *violations,
+            error = %error,
+            "Too many limit violations - disconnecting client"
+        );
+        let code = match error {
+            ApiError::PayloadTooLarge(_) => close_code::SIZE,
+            _ => close_code::POLICY,
+        };
+        let _ = sender.send(close_frame(code, "too many limit violations")).await;
+        false
+    }
+
     /// Clean up a disconnected connection with detailed logging
     #[instrument(name = "cleanup_connection", level = "info", fields(connection_id = %connection_id))]
     async fn cleanup_connection(&self, connection_id: ConnectionId) {
@@ -478,8 +718,13 @@ impl WebSocketService {
             removed
         };
         
-        let previous_count = self.connection_count.fetch_sub(1, Ordering::Relaxed);
-        let new_count = previous_count.saturating_sub(1);
+        // Only decrement for connections that were actually registered, otherwise a
+        // double cleanup (stale sweep + socket exit) would underflow the counter
+        let previous_count = self.connection_count();
+        if connection_info.is_some() {
+            self.connection_count.fetch_sub(1, Ordering::Relaxed);
+        }
+        let new_count = self.connection_count();
         
         if let Some(info) = connection_info {
             let session_duration = chrono::Utc::now() - info.connected_at;
@@ -510,41 +755,21 @@ impl WebSocketService {
 
 impl WebSocketService {
     /// Handle incoming messages from clients with comprehensive logging
+    /// Messages arrive already decoded by the connection's `WsCodec`
     #[instrument(
         name = "handle_incoming_message", 
         level = "debug",
-        fields(
-            connection_id = %connection_id,
-            message_length = text.len()
-        )
+        skip(message),
+        fields(connection_id = %connection_id)
     )]
     async fn handle_incoming_message(
         &self,
-        text: &str,
+        message: WsMessage,
         connection_id: ConnectionId,
     ) -> Result<(), ApiError> {
-        let parse_start = Instant::now();
-        
-        debug!(
-            message_preview = &text[..text.len().min(200)],
-            "Parsing incoming message"
-        );
-
-        let message: WsMessage = serde_json::from_str(text)
-            .map_err(|e| {
-                error!(
-                    error = %e,
-                    raw_message = %text,
-                    parse_duration_ms = parse_start.elapsed().as_millis(),
-                    "Failed to deserialize incoming message"
-                );
-                ApiError::DeserializationError(e.to_string())
-            })?;
-
         debug!(
             message_type = ?std::mem::discriminant(&message),
-            parse_duration_ms = parse_start.elapsed().as_millis(),
-            "Message parsed successfully"
+            "Processing decoded message"
         );
 
         let process_start = Instant::now();
@@ -583,6 +808,10 @@ impl WebSocketService {
                 debug!("Custom event data: {:?}", data);
                 // Handle custom events here - add your business logic
             }
+            WsMessage::Request { id, method, params } => {
+                info!(request_id = %id, method = %method, "Processing RPC request");
+                self.spawn_rpc_request(connection_id, id, method, params);
+            }
             _ => {
                 debug!(message = ?message, "Received other message type");
             }
@@ -590,13 +819,57 @@ impl WebSocketService {
 
         debug!(
             processing_duration_ms = process_start.elapsed().as_millis(),
-            total_duration_ms = parse_start.elapsed().as_millis(),
             "Message processing completed"
         );
 
         Ok(())
     }
 
+    /// Run an RPC request in its own task so slow methods don't stall the socket loop
+    fn spawn_rpc_request(
+        &self,
+        connection_id: ConnectionId,
+        id: String,
+        method: String,
+        params: serde_json::Value,
+    ) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            let ctx = RpcContext { connection_id, request_id: id.clone() };
+
+            let reply = match service.rpc.dispatch(ctx, &method, params).await {
+                Ok(result) => {
+                    debug!(
+                        request_id = %id,
+                        method = %method,
+                        duration_ms = start.elapsed().as_millis(),
+                        "RPC request completed"
+                    );
+                    WsMessage::Response { id, result }
+                }
+                Err(e) => {
+                    warn!(
+                        request_id = %id,
+                        method = %method,
+                        error = %e,
+                        duration_ms = start.elapsed().as_millis(),
+                        "RPC request failed"
+                    );
+                    WsMessage::RequestFailed {
+                        id,
+                        code: e.status_code().as_u16(),
+                        message: e.public_message(),
+                    }
+                }
+            };
+
+            if let Err(e) = service.send_to_connection(connection_id, reply).await {
+                warn!(error = %e, "Failed to deliver RPC reply - client may have disconnected");
+            }
+        });
+    }
+
     /// Handle subscription requests with validation and logging
     #[instrument(name = "handle_subscription", level = "debug")]
     async fn handle_subscription(
@@ -608,6 +881,24 @@ impl WebSocketService {
         
         if let Some(connection) = connections.get_mut(&connection_id) {
             let before_count = connection.subscriptions.len();
+
+            let new_topics = topics
+                .iter()
+                .filter(|topic| !connection.subscriptions.contains(topic))
+                .collect::<std::collections::HashSet<_>>()
+                .len();
+            if before_count + new_topics > self.config.max_subscriptions {
+                warn!(
+                    subscriptions = before_count,
+                    requested = new_topics,
+                    max_subscriptions = self.config.max_subscriptions,
+                    "Subscription rejected: limit exceeded"
+                );
+                return Err(ApiError::LimitExceeded(format!(
+                    "Subscription limit of {} topics exceeded",
+                    self.config.max_subscriptions
+                )));
+            }
             
             for topic in &topics {
                 if !connection.subscriptions.contains(topic) {
@@ -726,10 +1017,14 @@ impl WebSocketService {
         }
     }
 
-    /// Broadcast a message to all connections subscribed to a topic
+    /// Broadcast a message to all connections subscribed to a topic, on every node
+    /// Local subscribers are served directly; other nodes receive it through the message bus.
+    /// A bus failure is logged and does not fail the broadcast, since local delivery already
+    /// happened
     #[instrument(
         name = "broadcast_to_topic", 
         level = "info",
+        skip(message),
         fields(topic = %topic.to_string())
     )]
     pub async fn broadcast_to_topic(
@@ -738,40 +1033,25 @@ impl WebSocketService {
         message: WsMessage,
     ) -> Result<(), ApiError> {
         let broadcast_start = Instant::now();
-        let subscriber_count = self.broadcaster.receiver_count();
-        
+
         info!(
-            subscriber_count,
             message_type = ?std::mem::discriminant(&message),
+            bus = self.bus.name(),
             "Broadcasting message to topic subscribers"
         );
 
-        // Count eligible connections for this topic
-        let eligible_connections = {
-            let connections = self.connections.read().await;
-            let count = connections.values()
-                .filter(|conn| {
-                    conn.subscriptions.contains(&"all".to_string()) ||
-                    conn.subscriptions.contains(&topic.to_string())
-                })
-                .count();
-            count
-        };
+        let receiver_count = self.deliver_local(&topic, message.clone()).await;
 
-        debug!(
-            eligible_connections,
-            total_connections = self.connection_count(),
-            "Calculated eligible connections for broadcast"
-        );
-
-        let send_result = self.broadcaster.send((topic.clone(), message));
+        let envelope = BusEnvelope {
+            origin: self.node_id,
+            event: BusEvent::Broadcast { topic: topic.to_string(), message },
+        };
 
-        match send_result {
-            Ok(receiver_count) => {
+        match self.bus.publish(envelope).await {
+            Ok(()) => {
                 info!(
                     topic = %topic.to_string(),
                     receiver_count,
-                    eligible_connections,
                     broadcast_duration_ms = broadcast_start.elapsed().as_millis(),
                     "Broadcast completed successfully"
                 );
@@ -780,11 +1060,42 @@ impl WebSocketService {
             Err(e) => {
                 error!(
                     topic = %topic.to_string(),
+                    receiver_count,
                     error = %e,
                     broadcast_duration_ms = broadcast_start.elapsed().as_millis(),
-                    "Failed to broadcast message"
+                    "Failed to publish broadcast to message bus; delivered locally only"
                 );
-                Err(ApiError::WebSocketError(format!("Failed to broadcast: {}", e)))
+                Ok(())
+            }
+        }
+    }
+
+    /// Deliver a message to this node's subscribers only
+    /// Returns the number of connection tasks that received it (0 when nobody is connected)
+    async fn deliver_local(&self, topic: &SubscriptionTopic, message: WsMessage) -> usize {
+        // Count eligible connections for this topic
+        let eligible_connections = {
+            let connections = self.connections.read().await;
+            connections.values()
+                .filter(|conn| {
+                    conn.subscriptions.contains(&"all".to_string()) ||
+                    conn.subscriptions.contains(&topic.to_string())
+                })
+                .count()
+        };
+
+        debug!(
+            topic = %topic.to_string(),
+            eligible_connections,
+            total_connections = self.connection_count(),
+            "Calculated eligible connections for local delivery"
+        );
+
+        match self.broadcaster.send((topic.clone(), message)) {
+            Ok(receiver_count) => receiver_count,
+            Err(_) => {
+                trace!(topic = %topic.to_string(), "No local receivers for broadcast");
+                0
             }
         }
     }
@@ -940,34 +1251,147 @@ impl WebSocketService {
 
         // Connection cleanup task
         let cleanup_service = self.clone();
-        tokio::spawn(async move {
-            let span = tracing::info_span!("cleanup_task");
-            let _enter = span.enter();
-            info!("Starting connection cleanup task");
-            cleanup_service.connection_cleanup_task().await;
-        });
+        tokio::spawn(
+            async move {
+                info!("Starting connection cleanup task");
+                cleanup_service.connection_cleanup_task().await;
+            }
+            .instrument(tracing::info_span!("cleanup_task")),
+        );
 
         // Ping task
         let ping_service = self.clone();
-        tokio::spawn(async move {
-            let span = tracing::info_span!("ping_task");
-            let _enter = span.enter();
-            info!("Starting ping task");
-            ping_service.ping_task().await;
-        });
+        tokio::spawn(
+            async move {
+                info!("Starting ping task");
+                ping_service.ping_task().await;
+            }
+            .instrument(tracing::info_span!("ping_task")),
+        );
 
         // Health monitoring task
         let health_service = self.clone();
-        tokio::spawn(async move {
-            let span = tracing::info_span!("health_monitor_task");
-            let _enter = span.enter();
-            info!("Starting health monitoring task");
-            health_service.health_monitoring_task().await;
-        });
+        tokio::spawn(
+            async move {
+                info!("Starting health monitoring task");
+                health_service.health_monitoring_task().await;
+            }
+            .instrument(tracing::info_span!("health_monitor_task")),
+        );
+
+        // Message bus listener: delivers broadcasts published by other nodes
+        let bus_service = self.clone();
+        tokio::spawn(
+            async move {
+                info!("Starting message bus listener task");
+                bus_service.bus_listener_task().await;
+            }
+            .instrument(tracing::info_span!("bus_listener_task")),
+        );
+
+        // Cluster heartbeat: publishes this node's registry snapshot
+        let heartbeat_service = self.clone();
+        tokio::spawn(
+            async move {
+                info!("Starting cluster heartbeat task");
+                heartbeat_service.cluster_heartbeat_task().await;
+            }
+            .instrument(tracing::info_span!("cluster_heartbeat_task")),
+        );
 
         info!("All background tasks started successfully");
     }
 
+    /// Background task consuming the message bus, re-subscribing if the stream drops
+    #[instrument(name = "bus_listener_task", level = "debug")]
+    async fn bus_listener_task(&self) {
+        let mut backoff = std::time::Duration::from_secs(1);
+
+        loop {
+            match self.bus.subscribe().await {
+                Ok(mut stream) => {
+                    info!(bus = self.bus.name(), "Subscribed to message bus");
+                    backoff = std::time::Duration::from_secs(1);
+                    while let Some(envelope) = stream.next().await {
+                        self.handle_bus_envelope(envelope).await;
+                    }
+                    warn!(bus = self.bus.name(), "Message bus stream ended");
+                }
+                Err(e) => {
+                    error!(error = %e, bus = self.bus.name(), "Failed to subscribe to message bus");
+                }
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(std::time::Duration::from_secs(30));
+        }
+    }
+
+    /// Apply an envelope published by another node
+    async fn handle_bus_envelope(&self, envelope: BusEnvelope) {
+        // Our own broadcasts were already delivered locally
+        if envelope.origin == self.node_id {
+            return;
+        }
+
+        match envelope.event {
+            BusEvent::Broadcast { topic, message } => {
+                let topic = SubscriptionTopic::from(topic.as_str());
+                let receivers = self.deliver_local(&topic, message).await;
+                debug!(
+                    origin = %envelope.origin,
+                    topic = %topic.to_string(),
+                    receivers,
+                    "Delivered broadcast from peer node"
+                );
+            }
+            BusEvent::Registry(snapshot) => {
+                trace!(origin = %envelope.origin, connections = snapshot.connections.len(), "Received registry snapshot");
+                self.cluster.write().await.insert(envelope.origin, snapshot);
+            }
+            BusEvent::NodeLeft => {
+                info!(origin = %envelope.origin, "Peer node left the cluster");
+                self.cluster.write().await.remove(&envelope.origin);
+            }
+        }
+    }
+
+    /// Background task publishing registry snapshots and pruning silent peers
+    #[instrument(name = "cluster_heartbeat_task", level = "debug")]
+    async fn cluster_heartbeat_task(&self) {
+        let mut interval = tokio::time::interval(self.config.cluster_heartbeat_interval);
+        let expiry = chrono::Duration::from_std(self.config.cluster_heartbeat_interval * 3)
+            .unwrap_or_else(|_| chrono::Duration::seconds(30));
+
+        loop {
+            interval.tick().await;
+
+            let envelope = BusEnvelope {
+                origin: self.node_id,
+                event: BusEvent::Registry(self.local_snapshot().await),
+            };
+            if let Err(e) = self.bus.publish(envelope).await {
+                warn!(error = %e, "Failed to publish registry snapshot");
+            }
+
+            let cutoff = chrono::Utc::now() - expiry;
+            let mut cluster = self.cluster.write().await;
+            let before = cluster.len();
+            cluster.retain(|_, snapshot| snapshot.reported_at >= cutoff);
+            if cluster.len() < before {
+                warn!(expired_nodes = before - cluster.len(), "Pruned silent peer nodes");
+            }
+        }
+    }
+
+    /// Announce that this node is going away so peers drop its registry
+    pub async fn leave_cluster(&self) {
+        let envelope = BusEnvelope { origin: self.node_id, event: BusEvent::NodeLeft };
+        if let Err(e) = self.bus.publish(envelope).await {
+            warn!(error = %e, "Failed to announce node departure");
+        }
+    }
+
     /// Background task to clean up stale connections with detailed logging
     #[instrument(name = "connection_cleanup_task", level = "debug")]
     async fn connection_cleanup_task(&self) {
@@ -1095,23 +1519,14 @@ impl WebSocketService {
                 "Sending periodic ping to all connections"
             );
 
-            match self.broadcast_to_all(WsMessage::Ping).await {
-                Ok(()) => {
-                    debug!(
-                        ping_cycle = ping_cycles,
-                        ping_duration_ms = ping_start.elapsed().as_millis(),
-                        "Ping broadcast completed successfully"
-                    );
-                }
-                Err(e) => {
-                    error!(
-                        ping_cycle = ping_cycles,
-                        error = %e,
-                        ping_duration_ms = ping_start.elapsed().as_millis(),
-                        "Failed to broadcast ping"
-                    );
-                }
-            }
+            // Pings are node-local; every node pings its own clients
+            let receivers = self.deliver_local(&SubscriptionTopic::All, WsMessage::Ping).await;
+            debug!(
+                ping_cycle = ping_cycles,
+                receivers,
+                ping_duration_ms = ping_start.elapsed().as_millis(),
+                "Ping broadcast completed"
+            );
         }
     }
     //===================================================================
@@ -1164,6 +1579,28 @@ impl WebSocketService {
     }
 }
 
+/// Build the `WsMessage::Error` sent to a client for a failed request
+fn error_message(error: &ApiError) -> WsMessage {
+    WsMessage::Error {
+        message: error.public_message(),
+        code: Some(error.status_code().as_u16()),
+    }
+}
+
+/// Build a close frame with the given code and reason
+fn close_frame(code: u16, reason: &'static str) -> Message {
+    Message::Close(Some(CloseFrame { code, reason: reason.into() }))
+}
+
+/// Payload length of an outgoing frame, for logging
+fn frame_len(frame: &Message) -> usize {
+    match frame {
+        Message::Text(text) => text.len(),
+        Message::Binary(data) => data.len(),
+        _ => 0,
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════════
 // DEBUGGING AND DIAGNOSTICS STRUCTURES
 // ═══════════════════════════════════════════════════════════════════════════════════
@@ -1274,7 +1711,7 @@ impl WebSocketService {
         info!("Simulating frontend connection for testing");
 
         // Create a test message that a frontend might send
-        let test_messages = vec![
+        let test_messages = [
             WsMessage::Subscribe { topics: vec!["test".to_string()] },
             WsMessage::Custom { 
                 event: "frontend_test".to_string(), 
@@ -1338,6 +1775,18 @@ impl WebSocketService {
             issues.push(issue);
         }
 
+        if self.config.rate_limit_per_second <= 0.0 {
+            let issue = "rate_limit_per_second is not positive - clients will be limited to the initial burst".to_string();
+            warn!("{}", issue);
+            issues.push(issue);
+        }
+
+        if self.config.max_violations == 0 {
+            let issue = "max_violations is 0 - the first limit violation disconnects the client".to_string();
+            warn!("{}", issue);
+            issues.push(issue);
+        }
+
         if self.config.ping_interval >= self.config.connection_timeout {
             let issue = "ping_interval is greater than or equal to connection_timeout - connections may timeout before ping".to_string();
             error!("{}", issue);
@@ -1410,3 +1859,273 @@ impl WebSocketService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::message_bus::{tests::RespStandin, RedisBus, DEFAULT_BUS_CHANNEL};
+    use async_trait::async_trait;
+    use futures_util::stream::BoxStream;
+    use std::time::Duration;
+
+    fn custom(text: &str) -> WsMessage {
+        WsMessage::Custom {
+            event: "test".to_string(),
+            data: serde_json::json!(text),
+        }
+    }
+
+    #[tokio::test]
+    async fn broadcast_reaches_subscribers_on_another_node() {
+        let server = RespStandin::start().await;
+        let node_a = WebSocketService::new(None).with_message_bus(Arc::new(
+            RedisBus::connect(&server.url, DEFAULT_BUS_CHANNEL).await.unwrap(),
+        ));
+        let node_b = WebSocketService::new(None).with_message_bus(Arc::new(
+            RedisBus::connect(&server.url, DEFAULT_BUS_CHANNEL).await.unwrap(),
+        ));
+
+        // Stands in for a client task on node B
+        let mut client_b = node_b.broadcaster.subscribe();
+        let listener = node_b.clone();
+        tokio::spawn(async move { listener.bus_listener_task().await });
+        server.wait_for_subscribers(DEFAULT_BUS_CHANNEL, 1).await;
+
+        let topic = SubscriptionTopic::DataUpdates("devices".to_string());
+        node_a.broadcast_to_topic(topic.clone(), custom("up")).await.unwrap();
+
+        let (received_topic, message) = tokio::time::timeout(Duration::from_secs(5), client_b.recv())
+            .await
+            .expect("node B received nothing")
+            .unwrap();
+        assert_eq!(received_topic, topic);
+        assert!(matches!(message, WsMessage::Custom { data, .. } if data == "up"));
+    }
+
+    /// Bus whose publishes always fail, as when Redis is unreachable
+    #[derive(Debug)]
+    struct UnreachableBus;
+
+    #[async_trait]
+    impl MessageBus for UnreachableBus {
+        fn name(&self) -> &'static str {
+            "unreachable"
+        }
+
+        async fn publish(&self, _envelope: BusEnvelope) -> Result<(), ApiError> {
+            Err(ApiError::WebSocketError("Failed to publish to bus: connection refused".to_string()))
+        }
+
+        async fn subscribe(&self) -> Result<BoxStream<'static, BusEnvelope>, ApiError> {
+            Err(ApiError::WebSocketError("Failed to open bus subscription".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn bus_failure_still_delivers_locally() {
+        let service = WebSocketService::new(None).with_message_bus(Arc::new(UnreachableBus));
+        let mut client = service.broadcaster.subscribe();
+
+        service.broadcast_to_topic(SubscriptionTopic::All, custom("local")).await.unwrap();
+
+        let (topic, message) = client.try_recv().unwrap();
+        assert_eq!(topic, SubscriptionTopic::All);
+        assert!(matches!(message, WsMessage::Custom { data, .. } if data == "local"));
+    }
+
+    /// Log sink shared with the test through `tracing_subscriber`'s writer
+    #[derive(Clone, Default)]
+    struct CapturedLog(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_credential_frames_do_not_log_secrets() {
+        let log = CapturedLog::default();
+        let writer = log.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let service = WebSocketService::new(Some(WsConfig { max_frame_size: 256, ..WsConfig::default() }));
+        let codec = WsCodec::default();
+        let connection_id = Uuid::new_v4();
+        let frame = serde_json::json!({
+            "type": "Request",
+            "payload": {
+                "id": "r1",
+                "method": "credentials.create",
+                "params": { "name": "lab", "username": "netops", "password": "hunter2-secret" }
+            }
+        })
+        .to_string();
+
+        // Rate limited
+        let mut empty = TokenBucket::new(1, 0.0);
+        assert!(empty.try_acquire());
+        let error = service.handle_text_frame(&frame, &codec, connection_id, &mut empty).await.unwrap_err();
+        assert!(matches!(error, ApiError::RateLimited(_)));
+
+        // Too large
+        let padded = frame.replace("hunter2-secret", &format!("hunter2-secret{}", "x".repeat(300)));
+        let mut bucket = TokenBucket::new(10, 10.0);
+        let error = service.handle_text_frame(&padded, &codec, connection_id, &mut bucket).await.unwrap_err();
+        assert!(matches!(error, ApiError::PayloadTooLarge(_)));
+
+        // Malformed
+        let truncated = &frame[..frame.len() - 3];
+        let error = service.handle_text_frame(truncated, &codec, connection_id, &mut bucket).await.unwrap_err();
+        assert!(matches!(error, ApiError::DeserializationError(_)));
+
+        let output = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Rejected incoming message"), "nothing captured: {}", output);
+        assert!(output.contains("Failed to decode incoming message"));
+        assert!(!output.contains("hunter2"), "secret logged: {}", output);
+    }
+
+    /// Replies the service routes to `connection_id`, waiting for `count` of them
+    async fn direct_replies(
+        receiver: &mut broadcast::Receiver<(SubscriptionTopic, WsMessage)>,
+        connection_id: ConnectionId,
+        count: usize,
+    ) -> Vec<WsMessage> {
+        let mut replies = Vec::new();
+        while replies.len() < count {
+            let (topic, message) = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+                .await
+                .expect("no reply")
+                .unwrap();
+            if topic == SubscriptionTopic::Direct(connection_id) {
+                replies.push(message);
+            }
+        }
+        replies
+    }
+
+    #[tokio::test]
+    async fn rpc_replies_echo_the_request_id() {
+        #[derive(serde::Deserialize)]
+        struct AddParams {
+            a: i64,
+            b: i64,
+        }
+
+        let service = WebSocketService::new(None);
+        service.rpc().register("math.add", |_ctx, params| async move {
+            let params: AddParams = crate::services::rpc_registry::parse_params(params)?;
+            Ok(serde_json::json!(params.a + params.b))
+        });
+        let connection_id = Uuid::new_v4();
+        service.connections.write().await.insert(
+            connection_id,
+            ConnectionInfo {
+                id: connection_id,
+                ..ConnectionInfo::new()
+            },
+        );
+        let mut receiver = service.broadcaster.subscribe();
+        let mut bucket = TokenBucket::new(10, 10.0);
+        let codec = WsCodec::default();
+
+        for (id, method, params) in [
+            ("ok-1", "math.add", serde_json::json!({ "a": 2, "b": 3 })),
+            ("missing-7", "math.sub", serde_json::json!({})),
+            ("bad-9", "math.add", serde_json::json!({ "a": "two" })),
+        ] {
+            let frame = serde_json::json!({ "type": "Request", "payload": { "id": id, "method": method, "params": params } });
+            service
+                .handle_text_frame(&frame.to_string(), &codec, connection_id, &mut bucket)
+                .await
+                .unwrap();
+        }
+
+        // Requests run in their own tasks, so replies may arrive in any order
+        // (id, failure code, result or message)
+        let mut replies: Vec<(String, Option<u16>, String)> = direct_replies(&mut receiver, connection_id, 3)
+            .await
+            .into_iter()
+            .map(|reply| match reply {
+                WsMessage::Response { id, result } => (id, None, result.to_string()),
+                WsMessage::RequestFailed { id, code, message } => (id, Some(code), message),
+                other => panic!("unexpected reply {:?}", other),
+            })
+            .collect();
+        replies.sort();
+
+        assert_eq!((replies[0].0.as_str(), replies[0].1), ("bad-9", Some(400)));
+        assert!(replies[0].2.contains("Invalid params"), "{}", replies[0].2);
+        assert_eq!((replies[1].0.as_str(), replies[1].1), ("missing-7", Some(404)));
+        assert!(replies[1].2.contains("math.sub"), "{}", replies[1].2);
+        assert_eq!(replies[2], ("ok-1".to_string(), None, "5".to_string()));
+    }
+
+    async fn service_with_peers(config: WsConfig, peers: &[&str]) -> WebSocketService {
+        let service = WebSocketService::new(Some(config));
+        let mut connections = service.connections.write().await;
+        for peer in peers {
+            let mut info = ConnectionInfo::new();
+            info.remote_ip = Some(peer.parse().unwrap());
+            connections.insert(info.id, info);
+        }
+        drop(connections);
+        service
+    }
+
+    #[tokio::test]
+    async fn admission_rejects_clients_over_the_per_ip_cap() {
+        let config = WsConfig { max_connections_per_ip: 2, ..WsConfig::default() };
+        let service = service_with_peers(config, &["10.0.0.1", "10.0.0.1", "10.0.0.2"]).await;
+
+        let result = service.check_admission(Some("10.0.0.1".parse().unwrap())).await;
+        assert!(matches!(result, Err(ApiError::LimitExceeded(_))), "{:?}", result);
+        assert!(service.check_admission(Some("10.0.0.2".parse().unwrap())).await.is_ok());
+        assert!(service.check_admission(None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn admission_rejects_everyone_over_the_global_cap() {
+        let config = WsConfig { max_connections: 3, ..WsConfig::default() };
+        let service = service_with_peers(config, &["10.0.0.1", "10.0.0.2", "10.0.0.3"]).await;
+
+        let result = service.check_admission(Some("10.0.0.4".parse().unwrap())).await;
+        assert!(matches!(result, Err(ApiError::ServiceUnavailable(_))), "{:?}", result);
+        let result = service.check_admission(None).await;
+        assert!(matches!(result, Err(ApiError::ServiceUnavailable(_))), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn zero_per_ip_cap_disables_the_check() {
+        let config = WsConfig { max_connections_per_ip: 0, ..WsConfig::default() };
+        let service = service_with_peers(config, &["10.0.0.1", "10.0.0.1"]).await;
+
+        assert!(service.check_admission(Some("10.0.0.1".parse().unwrap())).await.is_ok());
+    }
+
+    #[test]
+    fn forwarded_for_is_only_trusted_from_configured_proxies() {
+        let proxy: IpAddr = "10.0.0.254".parse().unwrap();
+        let config = WsConfig { trusted_proxies: vec![proxy], ..WsConfig::default() };
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+
+        // Untrusted peers can't spoof their address
+        assert_eq!(config.client_ip(ip("203.0.113.9"), Some("198.51.100.1")), ip("203.0.113.9"));
+        // The rightmost untrusted hop is the client; earlier entries are client-supplied
+        assert_eq!(config.client_ip(proxy, Some("198.51.100.1, 203.0.113.9")), ip("203.0.113.9"));
+        assert_eq!(config.client_ip(proxy, Some("203.0.113.9, 10.0.0.254")), ip("203.0.113.9"));
+        // Missing or malformed headers fall back to the peer
+        assert_eq!(config.client_ip(proxy, None), proxy);
+        assert_eq!(config.client_ip(proxy, Some("unknown")), proxy);
+    }
+}
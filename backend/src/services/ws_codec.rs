@@ -0,0 +1,295 @@
+// backend/src/services/ws_codec.rs
+
+//! # WebSocket Frame Codec
+//!
+//! ## Description
+//! Encodes and decodes `WsMessage` frames according to the encoding negotiated
+//! during the WebSocket upgrade. Two encodings are supported:
+//! - JSON (default) - text frames, compatible with every existing client
+//! - MessagePack - binary frames, considerably smaller for large `DataUpdate` payloads
+//!
+//! Either encoding can additionally be compressed message by message
+//! (`message-deflate`). Compressed frames are always sent as binary frames.
+//!
+//! ## Negotiation
+//! Clients request an encoding through the `Sec-WebSocket-Protocol` header:
+//! - `thalyx.json`, `thalyx.json+message-deflate`
+//! - `thalyx.msgpack`, `thalyx.msgpack+message-deflate`
+//!
+//! Clients that cannot set subprotocols may use the `encoding` (`json` | `msgpack`)
+//! and `compression` (`none` | `message-deflate`) query parameters instead.
+//!
+//! ## Compression
+//! This is NOT the RFC 7692 `permessage-deflate` extension: the WebSocket library in
+//! use cannot negotiate it, and `Sec-WebSocket-Extensions` offers are ignored.
+//! Compression is an application-level option instead: each frame's payload is a raw
+//! deflate stream of the encoded message, and only clients that select a
+//! `+message-deflate` subprotocol receive compressed frames.
+
+use axum::extract::ws::Message;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+use crate::models::{websocket::WsMessage, ApiError};
+
+/// Subprotocols offered to clients, in decreasing order of server preference
+pub const SUPPORTED_SUBPROTOCOLS: [&str; 4] = [
+    "thalyx.msgpack+message-deflate",
+    "thalyx.msgpack",
+    "thalyx.json+message-deflate",
+    "thalyx.json",
+];
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// ENCODING OPTIONS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Serialization format for WebSocket frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WsEncoding {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+}
+
+/// Application-level compression applied on top of the encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WsCompression {
+    #[default]
+    None,
+    /// Raw deflate of each whole message; unrelated to `permessage-deflate`
+    #[serde(rename = "message-deflate")]
+    MessageDeflate,
+}
+
+impl WsEncoding {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "msgpack" | "messagepack" => Some(Self::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+impl WsCompression {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "" => Some(Self::None),
+            "message-deflate" => Some(Self::MessageDeflate),
+            _ => None,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// CODEC
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Negotiated frame codec for a single connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct WsCodec {
+    pub encoding: WsEncoding,
+    pub compression: WsCompression,
+}
+
+impl WsCodec {
+    /// Build a codec from a negotiated subprotocol such as `thalyx.msgpack+message-deflate`
+    pub fn from_subprotocol(protocol: &str) -> Option<Self> {
+        let rest = protocol.trim().strip_prefix("thalyx.")?;
+        let (encoding, compression) = match rest.split_once('+') {
+            Some((encoding, compression)) => (encoding, compression),
+            None => (rest, "none"),
+        };
+
+        Some(Self {
+            encoding: WsEncoding::parse(encoding)?,
+            compression: WsCompression::parse(compression)?,
+        })
+    }
+
+    /// Subprotocol name describing this codec
+    pub fn subprotocol(&self) -> String {
+        let encoding = match self.encoding {
+            WsEncoding::Json => "json",
+            WsEncoding::MessagePack => "msgpack",
+        };
+        match self.compression {
+            WsCompression::None => format!("thalyx.{}", encoding),
+            WsCompression::MessageDeflate => format!("thalyx.{}+message-deflate", encoding),
+        }
+    }
+
+    /// Encode a message into a WebSocket frame
+    pub fn encode(&self, message: &WsMessage) -> Result<Message, ApiError> {
+        let payload = match self.encoding {
+            WsEncoding::Json => {
+                let text = serde_json::to_string(message)
+                    .map_err(|e| ApiError::SerializationError(e.to_string()))?;
+                if self.compression == WsCompression::None {
+                    return Ok(Message::Text(text));
+                }
+                text.into_bytes()
+            }
+            WsEncoding::MessagePack => rmp_serde::to_vec_named(message)
+                .map_err(|e| ApiError::SerializationError(e.to_string()))?,
+        };
+
+        let payload = match self.compression {
+            WsCompression::None => payload,
+            WsCompression::MessageDeflate => deflate(&payload)?,
+        };
+
+        Ok(Message::Binary(payload))
+    }
+
+    /// Decode a text frame; text frames are always uncompressed JSON
+    pub fn decode_text(&self, text: &str) -> Result<WsMessage, ApiError> {
+        serde_json::from_str(text).map_err(|e| ApiError::DeserializationError(e.to_string()))
+    }
+
+    /// Decode a binary frame using the negotiated encoding and compression
+    /// `max_size` bounds the inflated payload so small compressed frames can't expand unchecked
+    pub fn decode_binary(&self, data: &[u8], max_size: usize) -> Result<WsMessage, ApiError> {
+        let inflated;
+        let payload = match self.compression {
+            WsCompression::None => data,
+            WsCompression::MessageDeflate => {
+                inflated = inflate(data, max_size)?;
+                &inflated
+            }
+        };
+
+        match self.encoding {
+            WsEncoding::Json => serde_json::from_slice(payload)
+                .map_err(|e| ApiError::DeserializationError(e.to_string())),
+            WsEncoding::MessagePack => rmp_serde::from_slice(payload)
+                .map_err(|e| ApiError::DeserializationError(e.to_string())),
+        }
+    }
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>, ApiError> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(data.len() / 2), Compression::fast());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn inflate(data: &[u8], max_size: usize) -> Result<Vec<u8>, ApiError> {
+    let mut decoded = Vec::with_capacity(data.len().saturating_mul(4).min(max_size));
+    DeflateDecoder::new(data)
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| ApiError::DeserializationError(format!("Invalid deflate frame: {}", e)))?;
+
+    if decoded.len() > max_size {
+        return Err(ApiError::PayloadTooLarge(format!(
+            "Inflated message exceeds {} bytes",
+            max_size
+        )));
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MAX_SIZE: usize = 64 * 1024;
+
+    fn sample() -> WsMessage {
+        WsMessage::DataUpdate {
+            source: "reports.interfaces".to_string(),
+            data: json!({ "rows": [["ge-0/0/0", "up", 1500], ["ge-0/0/1", "down", 9000]] }),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn round_trip(codec: WsCodec, message: &WsMessage) -> WsMessage {
+        match codec.encode(message).unwrap() {
+            Message::Text(text) => codec.decode_text(&text).unwrap(),
+            Message::Binary(data) => codec.decode_binary(&data, MAX_SIZE).unwrap(),
+            other => panic!("unexpected frame {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_codec_round_trips_a_message() {
+        let message = sample();
+        let expected = serde_json::to_value(&message).unwrap();
+
+        for protocol in SUPPORTED_SUBPROTOCOLS {
+            let codec = WsCodec::from_subprotocol(protocol).unwrap();
+            let decoded = round_trip(codec, &message);
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), expected, "{}", protocol);
+        }
+    }
+
+    #[test]
+    fn only_uncompressed_json_uses_text_frames() {
+        let message = sample();
+        for protocol in SUPPORTED_SUBPROTOCOLS {
+            let codec = WsCodec::from_subprotocol(protocol).unwrap();
+            let is_text = matches!(codec.encode(&message).unwrap(), Message::Text(_));
+            assert_eq!(is_text, protocol == "thalyx.json", "{}", protocol);
+        }
+    }
+
+    #[test]
+    fn subprotocol_names_round_trip() {
+        for protocol in SUPPORTED_SUBPROTOCOLS {
+            assert_eq!(WsCodec::from_subprotocol(protocol).unwrap().subprotocol(), protocol);
+        }
+        assert_eq!(
+            WsCodec::from_subprotocol("thalyx.msgpack+message-deflate"),
+            Some(WsCodec {
+                encoding: WsEncoding::MessagePack,
+                compression: WsCompression::MessageDeflate,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_subprotocols_are_rejected() {
+        assert_eq!(WsCodec::from_subprotocol("thalyx.json+deflate"), None);
+        assert_eq!(WsCodec::from_subprotocol("thalyx.cbor"), None);
+        assert_eq!(WsCodec::from_subprotocol("permessage-deflate"), None);
+    }
+
+    #[test]
+    fn inflate_rejects_payloads_over_max_size() {
+        let payload = vec![b'a'; 4096];
+        let compressed = deflate(&payload).unwrap();
+        assert!(compressed.len() < 100);
+
+        assert_eq!(inflate(&compressed, 4096).unwrap(), payload);
+        assert!(matches!(inflate(&compressed, 4095), Err(ApiError::PayloadTooLarge(_))));
+    }
+
+    #[test]
+    fn decode_binary_bounds_the_inflated_message() {
+        let codec = WsCodec {
+            encoding: WsEncoding::Json,
+            compression: WsCompression::MessageDeflate,
+        };
+        let Message::Binary(frame) = codec.encode(&sample()).unwrap() else {
+            panic!("compressed frames are binary");
+        };
+
+        assert!(codec.decode_binary(&frame, MAX_SIZE).is_ok());
+        assert!(matches!(codec.decode_binary(&frame, 16), Err(ApiError::PayloadTooLarge(_))));
+    }
+
+    #[test]
+    fn invalid_deflate_streams_are_deserialization_errors() {
+        assert!(matches!(
+            inflate(b"not deflate at all", MAX_SIZE),
+            Err(ApiError::DeserializationError(_))
+        ));
+    }
+}
@@ -0,0 +1,392 @@
+// backend/src/services/xpath/eval.rs
+
+//! Expression evaluation
+//!
+//! Values, the conversions between them (`string()`, `number()`, `boolean()`) and
+//! comparison semantics follow XPath 1.0 sections 3.4 and 4.
+
+use super::{
+    functions,
+    node::{sort_document_order, XNode},
+    parser::{ArithmeticOp, Axis, CompareOp, Expr, NodeTest, PathStart, Step},
+    XPathError, XPathOptions,
+};
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// VALUES
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Result of evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a, 'input> {
+    /// Nodes in document order without duplicates
+    Nodes(Vec<XNode<'a, 'input>>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value<'_, '_> {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nodes(_) => "node-set",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+        }
+    }
+
+    pub fn to_boolean(&self) -> bool {
+        match self {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::Boolean(b) => *b,
+            Value::Number(n) => *n != 0.0 && !n.is_nan(),
+            Value::String(s) => !s.is_empty(),
+        }
+    }
+
+    pub fn to_number(&self) -> f64 {
+        match self {
+            Value::Nodes(_) | Value::String(_) => string_to_number(&self.to_xpath_string()),
+            Value::Boolean(b) => f64::from(u8::from(*b)),
+            Value::Number(n) => *n,
+        }
+    }
+
+    /// `string()` conversion; a node-set converts to its first node's string-value
+    pub fn to_xpath_string(&self) -> String {
+        match self {
+            Value::Nodes(nodes) => nodes.first().map(|n| n.string_value()).unwrap_or_default(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Number(n) => number_to_string(*n),
+            Value::String(s) => s.clone(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self {
+            Value::String(s) => s,
+            other => other.to_xpath_string(),
+        }
+    }
+}
+
+/// Number to string as in XPath 1.0 section 4.2 (no exponent notation)
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n == 0.0 {
+        "0".to_string()
+    } else {
+        format!("{}", n)
+    }
+}
+
+/// String to number as in XPath 1.0 section 4.4: optional '-', digits and one '.'
+pub fn string_to_number(s: &str) -> f64 {
+    let trimmed = s.trim_matches(|c| matches!(c, ' ' | '\t' | '\r' | '\n'));
+    let digits = trimmed.strip_prefix('-').unwrap_or(trimmed);
+    let valid = !digits.is_empty()
+        && digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if valid {
+        trimmed.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// EVALUATOR
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Context of one evaluation step
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a, 'input> {
+    pub node: XNode<'a, 'input>,
+    /// 1-based proximity position
+    pub position: usize,
+    pub size: usize,
+}
+
+pub struct Evaluator<'o> {
+    options: &'o XPathOptions,
+}
+
+impl<'o> Evaluator<'o> {
+    pub fn new(options: &'o XPathOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn evaluate<'a, 'input>(
+        &self,
+        expr: &Expr,
+        node: XNode<'a, 'input>,
+    ) -> Result<Value<'a, 'input>, XPathError> {
+        self.eval(expr, &Context { node, position: 1, size: 1 })
+    }
+
+    pub fn eval<'a, 'input>(
+        &self,
+        expr: &Expr,
+        context: &Context<'a, 'input>,
+    ) -> Result<Value<'a, 'input>, XPathError> {
+        match expr {
+            Expr::Or(left, right) => Ok(Value::Boolean(
+                self.eval(left, context)?.to_boolean() || self.eval(right, context)?.to_boolean(),
+            )),
+            Expr::And(left, right) => Ok(Value::Boolean(
+                self.eval(left, context)?.to_boolean() && self.eval(right, context)?.to_boolean(),
+            )),
+            Expr::Compare(op, left, right) => {
+                let left = self.eval(left, context)?;
+                let right = self.eval(right, context)?;
+                Ok(Value::Boolean(compare(*op, &left, &right)))
+            }
+            Expr::Arithmetic(op, left, right) => {
+                let left = self.eval(left, context)?.to_number();
+                let right = self.eval(right, context)?.to_number();
+                Ok(Value::Number(match op {
+                    ArithmeticOp::Add => left + right,
+                    ArithmeticOp::Subtract => left - right,
+                    ArithmeticOp::Multiply => left * right,
+                    ArithmeticOp::Divide => left / right,
+                    // Truncating remainder, like Java/ECMAScript '%'
+                    ArithmeticOp::Modulo => left % right,
+                }))
+            }
+            Expr::Negate(inner) => Ok(Value::Number(-self.eval(inner, context)?.to_number())),
+            Expr::Union(left, right) => {
+                let mut nodes = self.node_set(left, context, "|")?;
+                nodes.extend(self.node_set(right, context, "|")?);
+                sort_document_order(&mut nodes);
+                Ok(Value::Nodes(nodes))
+            }
+            Expr::Path(start, steps) => {
+                let initial = match start {
+                    PathStart::Context => vec![context.node],
+                    PathStart::Root => vec![XNode::Tree(context.node.tree_node().document().root())],
+                    PathStart::Expr(inner) => self.node_set(inner, context, "/")?,
+                };
+                Ok(Value::Nodes(self.steps(initial, steps)?))
+            }
+            Expr::Filter(primary, predicates) => {
+                let nodes = self.node_set(primary, context, "[]")?;
+                Ok(Value::Nodes(self.predicates(nodes, predicates)?))
+            }
+            Expr::Literal(value) => Ok(Value::String(value.clone())),
+            Expr::Number(value) => Ok(Value::Number(*value)),
+            Expr::Variable(name) => self.variable(name),
+            Expr::Function(name, args) => functions::call(self, name, args, context),
+        }
+    }
+
+    /// Evaluate an operand that must be a node-set
+    pub fn node_set<'a, 'input>(
+        &self,
+        expr: &Expr,
+        context: &Context<'a, 'input>,
+        operator: &str,
+    ) -> Result<Vec<XNode<'a, 'input>>, XPathError> {
+        match self.eval(expr, context)? {
+            Value::Nodes(nodes) => Ok(nodes),
+            other => Err(XPathError::runtime(format!(
+                "operand of '{}' must be a node-set, found a {}",
+                operator,
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn variable<'a, 'input>(&self, name: &str) -> Result<Value<'a, 'input>, XPathError> {
+        let value = self
+            .options
+            .variables
+            .get(name)
+            .ok_or_else(|| XPathError::runtime(format!("variable ${} is not bound", name)))?;
+        Ok(match value {
+            serde_json::Value::Null => Value::String(String::new()),
+            serde_json::Value::Bool(b) => Value::Boolean(*b),
+            serde_json::Value::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+            serde_json::Value::String(s) => Value::String(s.clone()),
+            other => Value::String(other.to_string()),
+        })
+    }
+
+    fn steps<'a, 'input>(
+        &self,
+        mut nodes: Vec<XNode<'a, 'input>>,
+        steps: &[Step],
+    ) -> Result<Vec<XNode<'a, 'input>>, XPathError> {
+        let mut index = 0;
+        while index < steps.len() {
+            let mut step = &steps[index];
+            let mut axis = step.axis;
+
+            // `//name` without predicates is `descendant::name`; avoids materialising
+            // every node of the document as an intermediate result
+            if is_plain_descendant_or_self(step) {
+                if let Some(next) = steps.get(index + 1) {
+                    if next.axis == Axis::Child && next.predicates.is_empty() {
+                        index += 1;
+                        step = next;
+                        axis = Axis::Descendant;
+                    }
+                }
+            }
+
+            let mut selected = Vec::new();
+            for node in &nodes {
+                let candidates: Vec<XNode> = node
+                    .axis(axis)
+                    .into_iter()
+                    .filter(|candidate| self.matches(&step.test, axis, candidate))
+                    .collect();
+                selected.extend(self.predicates(candidates, &step.predicates)?);
+            }
+            sort_document_order(&mut selected);
+            nodes = selected;
+            index += 1;
+        }
+        Ok(nodes)
+    }
+
+    /// Filter nodes (in proximity order) through predicates
+    fn predicates<'a, 'input>(
+        &self,
+        mut nodes: Vec<XNode<'a, 'input>>,
+        predicates: &[Expr],
+    ) -> Result<Vec<XNode<'a, 'input>>, XPathError> {
+        for predicate in predicates {
+            let size = nodes.len();
+            let mut kept = Vec::with_capacity(size);
+            for (index, node) in nodes.into_iter().enumerate() {
+                let context = Context {
+                    node,
+                    position: index + 1,
+                    size,
+                };
+                let keep = match self.eval(predicate, &context)? {
+                    Value::Number(n) => n == context.position as f64,
+                    other => other.to_boolean(),
+                };
+                if keep {
+                    kept.push(node);
+                }
+            }
+            nodes = kept;
+        }
+        Ok(nodes)
+    }
+
+    fn matches(&self, test: &NodeTest, axis: Axis, node: &XNode) -> bool {
+        match test {
+            NodeTest::Node => true,
+            NodeTest::Text => matches!(node, XNode::Tree(n) if n.is_text()),
+            NodeTest::Comment => matches!(node, XNode::Tree(n) if n.is_comment()),
+            NodeTest::ProcessingInstruction(target) => match node {
+                XNode::Tree(n) if n.is_pi() => {
+                    target.as_deref().is_none_or(|t| n.pi().is_some_and(|pi| pi.target == t))
+                }
+                _ => false,
+            },
+            NodeTest::Name { prefix, local } => {
+                // Principal node type: attributes on the attribute axis, elements elsewhere
+                let principal = match axis {
+                    Axis::Attribute => matches!(node, XNode::Attribute(..)),
+                    _ => node.is_element(),
+                };
+                principal
+                    && local.as_deref().is_none_or(|l| node.local_name() == l)
+                    && prefix.as_deref().is_none_or(|p| self.prefix_matches(p, node))
+            }
+        }
+    }
+
+    fn prefix_matches(&self, prefix: &str, node: &XNode) -> bool {
+        match self.options.namespaces.get(prefix) {
+            Some(uri) => node.namespace_uri() == Some(uri.as_str()),
+            None => node.namespace_uri().is_some() && node.document_prefix() == Some(prefix),
+        }
+    }
+}
+
+fn is_plain_descendant_or_self(step: &Step) -> bool {
+    step.axis == Axis::DescendantOrSelf && step.test == NodeTest::Node && step.predicates.is_empty()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// COMPARISONS
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Compare two values following XPath 1.0 section 3.4
+fn compare(op: CompareOp, left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Nodes(a), Value::Nodes(b)) => {
+            let b: Vec<String> = b.iter().map(|n| n.string_value()).collect();
+            a.iter().any(|n| {
+                let a = n.string_value();
+                b.iter().any(|b| compare_strings(op, &a, b))
+            })
+        }
+        (Value::Nodes(nodes), other) => compare_nodes(op, nodes, other, false),
+        (other, Value::Nodes(nodes)) => compare_nodes(op, nodes, other, true),
+        _ => compare_atomic(op, left, right),
+    }
+}
+
+/// Node-set against a non-node-set value; `swapped` when the node-set is on the right
+fn compare_nodes(op: CompareOp, nodes: &[XNode], other: &Value, swapped: bool) -> bool {
+    let ordered = |a: &Value, b: &Value| {
+        if swapped {
+            compare_atomic(op, b, a)
+        } else {
+            compare_atomic(op, a, b)
+        }
+    };
+    match other {
+        Value::Boolean(_) => ordered(&Value::Boolean(!nodes.is_empty()), other),
+        Value::Number(_) => nodes
+            .iter()
+            .any(|n| ordered(&Value::Number(string_to_number(&n.string_value())), other)),
+        _ => nodes.iter().any(|n| ordered(&Value::String(n.string_value()), other)),
+    }
+}
+
+fn compare_atomic(op: CompareOp, left: &Value, right: &Value) -> bool {
+    match op {
+        CompareOp::Eq | CompareOp::NotEq => {
+            let equal = if matches!(left, Value::Boolean(_)) || matches!(right, Value::Boolean(_)) {
+                left.to_boolean() == right.to_boolean()
+            } else if matches!(left, Value::Number(_)) || matches!(right, Value::Number(_)) {
+                left.to_number() == right.to_number()
+            } else {
+                left.to_xpath_string() == right.to_xpath_string()
+            };
+            equal == (op == CompareOp::Eq)
+        }
+        _ => compare_numbers(op, left.to_number(), right.to_number()),
+    }
+}
+
+fn compare_strings(op: CompareOp, a: &str, b: &str) -> bool {
+    match op {
+        CompareOp::Eq => a == b,
+        CompareOp::NotEq => a != b,
+        _ => compare_numbers(op, string_to_number(a), string_to_number(b)),
+    }
+}
+
+fn compare_numbers(op: CompareOp, a: f64, b: f64) -> bool {
+    match op {
+        CompareOp::Eq => a == b,
+        CompareOp::NotEq => a != b,
+        CompareOp::Lt => a < b,
+        CompareOp::Le => a <= b,
+        CompareOp::Gt => a > b,
+        CompareOp::Ge => a >= b,
+    }
+}
@@ -0,0 +1,189 @@
+// backend/src/services/xpath/functions.rs
+
+//! XPath 1.0 core function library (section 4)
+
+use super::{
+    eval::{string_to_number, Context, Evaluator, Value},
+    node::XNode,
+    parser::Expr,
+    XPathError,
+};
+
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Minimum and maximum argument count of each core function
+fn arity(name: &str) -> Option<(usize, usize)> {
+    Some(match name {
+        "last" | "position" | "true" | "false" => (0, 0),
+        "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => (1, 1),
+        "local-name" | "namespace-uri" | "name" | "string" | "string-length" | "normalize-space"
+        | "number" => (0, 1),
+        "concat" => (2, usize::MAX),
+        "starts-with" | "contains" | "substring-before" | "substring-after" => (2, 2),
+        "substring" => (2, 3),
+        "translate" => (3, 3),
+        _ => return None,
+    })
+}
+
+/// Validate a function call at compile time
+pub fn check_arity(name: &str, count: usize) -> Result<(), String> {
+    let (min, max) = arity(name).ok_or_else(|| format!("unknown function {}()", name))?;
+    if count < min || count > max {
+        let expected = match (min, max) {
+            (min, usize::MAX) => format!("at least {}", min),
+            (min, max) if min == max => min.to_string(),
+            (min, max) => format!("{} to {}", min, max),
+        };
+        return Err(format!("{}() takes {} argument(s), got {}", name, expected, count));
+    }
+    Ok(())
+}
+
+/// Call a core function
+pub fn call<'a, 'input>(
+    evaluator: &Evaluator,
+    name: &str,
+    args: &[Expr],
+    context: &Context<'a, 'input>,
+) -> Result<Value<'a, 'input>, XPathError> {
+    let arg = |index: usize| evaluator.eval(&args[index], context);
+    let string_arg = |index: usize| -> Result<String, XPathError> {
+        match args.get(index) {
+            Some(expr) => Ok(evaluator.eval(expr, context)?.into_string()),
+            None => Ok(context.node.string_value()),
+        }
+    };
+    // First node of an optional node-set argument, defaulting to the context node
+    let node_arg = |index: usize| -> Result<Option<XNode<'a, 'input>>, XPathError> {
+        match args.get(index) {
+            Some(expr) => Ok(evaluator.node_set(expr, context, name)?.first().copied()),
+            None => Ok(Some(context.node)),
+        }
+    };
+
+    Ok(match name {
+        // Node-set functions
+        "last" => Value::Number(context.size as f64),
+        "position" => Value::Number(context.position as f64),
+        "count" => Value::Number(evaluator.node_set(&args[0], context, name)?.len() as f64),
+        "id" => Value::Nodes(Vec::new()),
+        "local-name" => Value::String(node_arg(0)?.map(|n| n.local_name()).unwrap_or_default()),
+        "namespace-uri" => Value::String(
+            node_arg(0)?
+                .and_then(|n| n.namespace_uri())
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        "name" => Value::String(node_arg(0)?.map(|n| n.qualified_name()).unwrap_or_default()),
+
+        // String functions
+        "string" => Value::String(string_arg(0)?),
+        "concat" => Value::String(
+            (0..args.len())
+                .map(string_arg)
+                .collect::<Result<Vec<_>, _>>()?
+                .concat(),
+        ),
+        "starts-with" => Value::Boolean(string_arg(0)?.starts_with(&string_arg(1)?)),
+        "contains" => Value::Boolean(string_arg(0)?.contains(&string_arg(1)?)),
+        "substring-before" => {
+            let (haystack, needle) = (string_arg(0)?, string_arg(1)?);
+            Value::String(haystack.split_once(&needle).map(|(before, _)| before.to_string()).unwrap_or_default())
+        }
+        "substring-after" => {
+            let (haystack, needle) = (string_arg(0)?, string_arg(1)?);
+            Value::String(haystack.split_once(&needle).map(|(_, after)| after.to_string()).unwrap_or_default())
+        }
+        "substring" => {
+            let value = string_arg(0)?;
+            let start = round(arg(1)?.to_number());
+            let end = match args.get(2) {
+                Some(_) => start + round(arg(2)?.to_number()),
+                None => f64::INFINITY,
+            };
+            // Characters at 1-based positions p with start <= p < end (NaN excludes all)
+            Value::String(
+                value
+                    .chars()
+                    .enumerate()
+                    .filter(|(i, _)| {
+                        let p = (*i + 1) as f64;
+                        p >= start && p < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect(),
+            )
+        }
+        "string-length" => Value::Number(string_arg(0)?.chars().count() as f64),
+        "normalize-space" => Value::String(
+            string_arg(0)?
+                .split([' ', '\t', '\r', '\n'])
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        "translate" => {
+            let (value, from, to) = (string_arg(0)?, string_arg(1)?, string_arg(2)?);
+            let from: Vec<char> = from.chars().collect();
+            let to: Vec<char> = to.chars().collect();
+            Value::String(
+                value
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|f| *f == c) {
+                        Some(index) => to.get(index).copied(),
+                        None => Some(c),
+                    })
+                    .collect(),
+            )
+        }
+
+        // Boolean functions
+        "boolean" => Value::Boolean(arg(0)?.to_boolean()),
+        "not" => Value::Boolean(!arg(0)?.to_boolean()),
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "lang" => {
+            let wanted = string_arg(0)?.to_lowercase();
+            let lang = context
+                .node
+                .axis(super::parser::Axis::AncestorOrSelf)
+                .into_iter()
+                .find_map(|n| match n {
+                    XNode::Tree(node) if node.is_element() => node.attribute((XML_NS, "lang")),
+                    _ => None,
+                });
+            Value::Boolean(lang.is_some_and(|lang| {
+                let lang = lang.to_lowercase();
+                lang == wanted || lang.strip_prefix(&wanted).is_some_and(|rest| rest.starts_with('-'))
+            }))
+        }
+
+        // Number functions
+        "number" => Value::Number(match args.first() {
+            Some(_) => arg(0)?.to_number(),
+            None => string_to_number(&context.node.string_value()),
+        }),
+        "sum" => Value::Number(
+            evaluator
+                .node_set(&args[0], context, name)?
+                .iter()
+                .map(|n| string_to_number(&n.string_value()))
+                .sum(),
+        ),
+        "floor" => Value::Number(arg(0)?.to_number().floor()),
+        "ceiling" => Value::Number(arg(0)?.to_number().ceil()),
+        "round" => Value::Number(round(arg(0)?.to_number())),
+
+        _ => return Err(XPathError::runtime(format!("unknown function {}()", name))),
+    })
+}
+
+/// XPath `round()`: nearest integer, halves towards positive infinity
+fn round(n: f64) -> f64 {
+    if n.is_nan() || n.is_infinite() {
+        n
+    } else {
+        (n + 0.5).floor()
+    }
+}
@@ -0,0 +1,293 @@
+// backend/src/services/xpath/lexer.rs
+
+//! XPath 1.0 tokenizer
+//!
+//! Implements the lexical structure of XPath 1.0 section 3.7, including the
+//! disambiguation rules that decide whether `*` is a name test or the multiply
+//! operator and whether an NCName is an operator, axis, node type or function.
+
+use super::XPathError;
+
+/// A lexical token with its byte offset in the expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Eq,
+    NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal(String),
+    Number(f64),
+    /// `$name`, possibly prefixed
+    Variable(String),
+    /// `*`, `prefix:*`, `name` or `prefix:name`
+    NameTest { prefix: Option<String>, local: Option<String> },
+    /// `node`, `text`, `comment` or `processing-instruction` followed by `(`
+    NodeType(String),
+    FunctionName(String),
+    AxisName(String),
+}
+
+impl TokenKind {
+    /// Tokens after which `*` is a name test and NCNames are names (XPath 1.0, 3.7)
+    fn starts_operand(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::At
+                | TokenKind::ColonColon
+                | TokenKind::LParen
+                | TokenKind::LBracket
+                | TokenKind::Comma
+                | TokenKind::And
+                | TokenKind::Or
+                | TokenKind::Mod
+                | TokenKind::Div
+                | TokenKind::Multiply
+                | TokenKind::Slash
+                | TokenKind::DoubleSlash
+                | TokenKind::Pipe
+                | TokenKind::Plus
+                | TokenKind::Minus
+                | TokenKind::Eq
+                | TokenKind::NotEq
+                | TokenKind::Lt
+                | TokenKind::Le
+                | TokenKind::Gt
+                | TokenKind::Ge
+        )
+    }
+}
+
+/// Split an expression into tokens
+pub fn tokenize(source: &str) -> Result<Vec<Token>, XPathError> {
+    let mut lexer = Lexer {
+        source,
+        chars: source.char_indices().collect(),
+        index: 0,
+        tokens: Vec::new(),
+    };
+    lexer.run()?;
+    Ok(lexer.tokens)
+}
+
+struct Lexer<'s> {
+    source: &'s str,
+    chars: Vec<(usize, char)>,
+    index: usize,
+    tokens: Vec<Token>,
+}
+
+impl Lexer<'_> {
+    fn run(&mut self) -> Result<(), XPathError> {
+        while let Some(c) = self.peek(0) {
+            let position = self.offset();
+            if c.is_whitespace() {
+                self.index += 1;
+                continue;
+            }
+
+            let kind = match c {
+                '(' => self.single(TokenKind::LParen),
+                ')' => self.single(TokenKind::RParen),
+                '[' => self.single(TokenKind::LBracket),
+                ']' => self.single(TokenKind::RBracket),
+                '@' => self.single(TokenKind::At),
+                ',' => self.single(TokenKind::Comma),
+                '|' => self.single(TokenKind::Pipe),
+                '+' => self.single(TokenKind::Plus),
+                '-' => self.single(TokenKind::Minus),
+                '=' => self.single(TokenKind::Eq),
+                '!' if self.peek(1) == Some('=') => self.double(TokenKind::NotEq),
+                '<' if self.peek(1) == Some('=') => self.double(TokenKind::Le),
+                '<' => self.single(TokenKind::Lt),
+                '>' if self.peek(1) == Some('=') => self.double(TokenKind::Ge),
+                '>' => self.single(TokenKind::Gt),
+                ':' if self.peek(1) == Some(':') => self.double(TokenKind::ColonColon),
+                '/' if self.peek(1) == Some('/') => self.double(TokenKind::DoubleSlash),
+                '/' => self.single(TokenKind::Slash),
+                '.' if self.peek(1) == Some('.') => self.double(TokenKind::DotDot),
+                '.' if self.peek(1).is_some_and(|d| d.is_ascii_digit()) => self.number(),
+                '.' => self.single(TokenKind::Dot),
+                '"' | '\'' => self.literal(c)?,
+                '$' => {
+                    self.index += 1;
+                    let name = self
+                        .qname()
+                        .ok_or_else(|| XPathError::new("expected a variable name after '$'", position))?;
+                    TokenKind::Variable(name)
+                }
+                '*' => {
+                    self.index += 1;
+                    if self.operator_expected() {
+                        TokenKind::Multiply
+                    } else {
+                        TokenKind::NameTest { prefix: None, local: None }
+                    }
+                }
+                c if c.is_ascii_digit() => self.number(),
+                c if is_name_start(c) => self.name(position)?,
+                other => {
+                    return Err(XPathError::new(format!("unexpected character '{}'", other), position));
+                }
+            };
+            self.tokens.push(Token { kind, position });
+        }
+        Ok(())
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.index + ahead).map(|(_, c)| *c)
+    }
+
+    fn offset(&self) -> usize {
+        self.chars.get(self.index).map_or(self.source.len(), |(offset, _)| *offset)
+    }
+
+    fn single(&mut self, kind: TokenKind) -> TokenKind {
+        self.index += 1;
+        kind
+    }
+
+    fn double(&mut self, kind: TokenKind) -> TokenKind {
+        self.index += 2;
+        kind
+    }
+
+    /// True when the previous token ends an operand, so the next token is an operator
+    fn operator_expected(&self) -> bool {
+        self.tokens.last().is_some_and(|token| !token.kind.starts_operand())
+    }
+
+    fn literal(&mut self, quote: char) -> Result<TokenKind, XPathError> {
+        let position = self.offset();
+        self.index += 1;
+        let start = self.offset();
+        while let Some(c) = self.peek(0) {
+            if c == quote {
+                let value = self.source[start..self.offset()].to_string();
+                self.index += 1;
+                return Ok(TokenKind::Literal(value));
+            }
+            self.index += 1;
+        }
+        Err(XPathError::new("unterminated string literal", position))
+    }
+
+    fn number(&mut self) -> TokenKind {
+        let start = self.offset();
+        while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+            self.index += 1;
+        }
+        if self.peek(0) == Some('.') && self.peek(1) != Some('.') {
+            self.index += 1;
+            while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.index += 1;
+            }
+        }
+        // Digits with at most one '.' always parse
+        TokenKind::Number(self.source[start..self.offset()].parse().unwrap_or(f64::NAN))
+    }
+
+    fn ncname(&mut self) -> Option<String> {
+        if !self.peek(0).is_some_and(is_name_start) {
+            return None;
+        }
+        let start = self.offset();
+        while self.peek(0).is_some_and(is_name_char) {
+            self.index += 1;
+        }
+        Some(self.source[start..self.offset()].to_string())
+    }
+
+    /// `NCName` or `NCName:NCName`
+    fn qname(&mut self) -> Option<String> {
+        let first = self.ncname()?;
+        if self.peek(0) == Some(':') && self.peek(1).is_some_and(is_name_start) {
+            self.index += 1;
+            let local = self.ncname()?;
+            return Some(format!("{}:{}", first, local));
+        }
+        Some(first)
+    }
+
+    fn name(&mut self, position: usize) -> Result<TokenKind, XPathError> {
+        let first = self.ncname().unwrap_or_default();
+
+        if self.operator_expected() {
+            return match first.as_str() {
+                "and" => Ok(TokenKind::And),
+                "or" => Ok(TokenKind::Or),
+                "mod" => Ok(TokenKind::Mod),
+                "div" => Ok(TokenKind::Div),
+                _ => Err(XPathError::new(format!("expected an operator, found '{}'", first), position)),
+            };
+        }
+
+        // prefix:* and prefix:local
+        if self.peek(0) == Some(':') && self.peek(1) != Some(':') {
+            if self.peek(1) == Some('*') {
+                self.index += 2;
+                return Ok(TokenKind::NameTest { prefix: Some(first), local: None });
+            }
+            self.index += 1;
+            let local = self
+                .ncname()
+                .ok_or_else(|| XPathError::new(format!("incomplete qualified name '{}:'", first), position))?;
+            if self.next_non_space() == Some('(') {
+                return Ok(TokenKind::FunctionName(format!("{}:{}", first, local)));
+            }
+            return Ok(TokenKind::NameTest { prefix: Some(first), local: Some(local) });
+        }
+
+        match self.next_non_space() {
+            Some('(') if matches!(first.as_str(), "node" | "text" | "comment" | "processing-instruction") => {
+                Ok(TokenKind::NodeType(first))
+            }
+            Some('(') => Ok(TokenKind::FunctionName(first)),
+            Some(':') if self.rest_after_space().starts_with("::") => Ok(TokenKind::AxisName(first)),
+            _ => Ok(TokenKind::NameTest { prefix: None, local: Some(first) }),
+        }
+    }
+
+    fn rest_after_space(&self) -> &str {
+        self.source[self.offset()..].trim_start()
+    }
+
+    fn next_non_space(&self) -> Option<char> {
+        self.rest_after_space().chars().next()
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '\u{B7}')
+}
@@ -0,0 +1,147 @@
+// backend/src/services/xpath/mod.rs
+
+//! # XPath 1.0
+//!
+//! ## Description
+//! XPath 1.0 evaluator over `roxmltree` documents, used by the report engine to
+//! select rows (`Report.xpath`) and extract cells (`Report.fields`) from RPC replies.
+//! Expressions are compiled once and evaluated against any node of a document.
+//!
+//! ## Modules
+//! - `lexer` - tokenizer with the XPath 1.0 disambiguation rules
+//! - `parser` - recursive descent parser producing the expression tree
+//! - `node` - data model: attributes as nodes, document order, string-values, axes
+//! - `eval` - expression evaluation, values and comparisons
+//! - `functions` - the XPath 1.0 core function library
+//!
+//! ## Supported
+//! All axes except `namespace` (always empty), node tests (`*`, names,
+//! `text()`, `node()`, `comment()`, `processing-instruction()`), predicates,
+//! abbreviations (`//`, `.`, `..`, `@`), operators, variables and the full core
+//! function library. `id()` returns an empty node-set since replies have no DTD.
+//! Expressions deeper than `parser::MAX_DEPTH` (128) levels of nesting and chained
+//! operators are rejected at compile time.
+//!
+//! ## Namespaces
+//! Junos replies put elements in versioned default namespaces
+//! (`http://xml.juniper.net/junos/21.4R0/junos-routing`), so:
+//! - Unprefixed names match the local name in any namespace: `bgp-peer`, `@style`
+//! - Prefixed names match prefixes bound in `XPathOptions.namespaces` by URI, and
+//!   otherwise the prefix declared in the document: `@junos:seconds`
+//!
+//! ## How to Use
+//! 1. `let path = XPath::compile(".//bgp-peer[peer-state != 'Established']")?;`
+//! 2. `let rows = path.select(XNode::Tree(document.root()), &XPathOptions::default())?;`
+//! 3. `XPath::compile("peer-address")?.evaluate(rows[0], &options)?.into_string()`
+
+mod eval;
+mod functions;
+mod lexer;
+mod node;
+mod parser;
+
+use std::{collections::HashMap, fmt};
+
+pub use eval::Value;
+pub use node::XNode;
+
+/// Bindings available while evaluating an expression
+#[derive(Debug, Clone, Default)]
+pub struct XPathOptions {
+    /// Values of `$name` variable references
+    pub variables: HashMap<String, serde_json::Value>,
+    /// Prefix -> namespace URI bindings for prefixed name tests
+    pub namespaces: HashMap<String, String>,
+}
+
+/// Syntax or evaluation error
+#[derive(Debug, Clone, PartialEq)]
+pub struct XPathError {
+    pub message: String,
+    /// Byte offset in the expression for syntax errors
+    pub position: Option<usize>,
+}
+
+impl XPathError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position: Some(position),
+        }
+    }
+
+    fn runtime(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            position: None,
+        }
+    }
+}
+
+impl fmt::Display for XPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at offset {}", self.message, position),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for XPathError {}
+
+/// A compiled XPath expression
+#[derive(Debug, Clone)]
+pub struct XPath {
+    source: String,
+    expr: parser::Expr,
+}
+
+impl XPath {
+    /// Parse an expression, reporting the offset of the first syntax error
+    pub fn compile(source: &str) -> Result<Self, XPathError> {
+        let tokens = lexer::tokenize(source)?;
+        let expr = parser::parse(tokens, source.len())?;
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Expression text as compiled
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Names of the variables the expression references, without `$`, in order of appearance
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.expr.collect_variables(&mut names);
+        names
+    }
+
+    /// Evaluate with `context` as the context node
+    pub fn evaluate<'a, 'input>(
+        &self,
+        context: XNode<'a, 'input>,
+        options: &XPathOptions,
+    ) -> Result<Value<'a, 'input>, XPathError> {
+        eval::Evaluator::new(options).evaluate(&self.expr, context)
+    }
+
+    /// Evaluate an expression that must produce a node-set, in document order
+    pub fn select<'a, 'input>(
+        &self,
+        context: XNode<'a, 'input>,
+        options: &XPathOptions,
+    ) -> Result<Vec<XNode<'a, 'input>>, XPathError> {
+        match self.evaluate(context, options)? {
+            Value::Nodes(nodes) => Ok(nodes),
+            other => Err(XPathError::runtime(format!(
+                "'{}' evaluates to a {}, not a node-set",
+                self.source,
+                other.type_name()
+            ))),
+        }
+    }
+}
+
@@ -0,0 +1,187 @@
+// backend/src/services/xpath/node.rs
+
+//! XPath data model over `roxmltree`
+//!
+//! roxmltree stores attributes on their element instead of as tree nodes, so
+//! `XNode` wraps either a tree node or an (element, attribute index) pair and
+//! provides document order, string-values, names and the axes. Axes yield nodes
+//! in proximity order (reverse axes nearest first) so predicate positions are right.
+
+use super::parser::Axis;
+
+/// A node of the XPath data model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XNode<'a, 'input> {
+    /// Root, element, text, comment or processing-instruction node
+    Tree(roxmltree::Node<'a, 'input>),
+    /// Attribute `index` of an element
+    Attribute(roxmltree::Node<'a, 'input>, usize),
+}
+
+impl<'a, 'input> XNode<'a, 'input> {
+    /// Position in document order; attributes sort after their element and before its children
+    pub fn order_key(&self) -> (u32, usize) {
+        match self {
+            XNode::Tree(node) => (node.id().get(), 0),
+            XNode::Attribute(owner, index) => (owner.id().get(), index + 1),
+        }
+    }
+
+    fn attribute(&self) -> Option<roxmltree::Attribute<'a, 'input>> {
+        match self {
+            XNode::Attribute(owner, index) => owner.attributes().nth(*index),
+            XNode::Tree(_) => None,
+        }
+    }
+
+    /// The tree node itself, or the element owning an attribute
+    pub fn tree_node(&self) -> roxmltree::Node<'a, 'input> {
+        match self {
+            XNode::Tree(node) | XNode::Attribute(node, _) => *node,
+        }
+    }
+
+    pub fn is_element(&self) -> bool {
+        matches!(self, XNode::Tree(node) if node.is_element())
+    }
+
+    /// String-value as defined by XPath 1.0 section 5
+    pub fn string_value(&self) -> String {
+        match self {
+            XNode::Attribute(..) => self.attribute().map(|a| a.value().to_string()).unwrap_or_default(),
+            XNode::Tree(node) => match node.node_type() {
+                roxmltree::NodeType::Root | roxmltree::NodeType::Element => node
+                    .descendants()
+                    .filter(|n| n.is_text())
+                    .filter_map(|n| n.text())
+                    .collect(),
+                roxmltree::NodeType::Text | roxmltree::NodeType::Comment => {
+                    node.text().unwrap_or_default().to_string()
+                }
+                roxmltree::NodeType::PI => node
+                    .pi()
+                    .and_then(|pi| pi.value)
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+        }
+    }
+
+    /// Local part of the expanded name (empty for unnamed nodes)
+    pub fn local_name(&self) -> String {
+        match self {
+            XNode::Attribute(..) => self.attribute().map(|a| a.name().to_string()).unwrap_or_default(),
+            XNode::Tree(node) if node.is_element() => node.tag_name().name().to_string(),
+            XNode::Tree(node) => node.pi().map(|pi| pi.target.to_string()).unwrap_or_default(),
+        }
+    }
+
+    /// Namespace URI of the expanded name, if any
+    pub fn namespace_uri(&self) -> Option<&'a str> {
+        match self {
+            XNode::Attribute(..) => self.attribute().and_then(|a| a.namespace()),
+            XNode::Tree(node) if node.is_element() => node.tag_name().namespace(),
+            XNode::Tree(_) => None,
+        }
+    }
+
+    /// Prefix bound to the node's namespace in the source document
+    pub fn document_prefix(&self) -> Option<&'input str> {
+        let uri = self.namespace_uri()?;
+        self.tree_node().lookup_prefix(uri)
+    }
+
+    /// QName as written in the document, e.g. `junos:style`
+    pub fn qualified_name(&self) -> String {
+        let local = self.local_name();
+        match self.document_prefix() {
+            Some(prefix) if !prefix.is_empty() => format!("{}:{}", prefix, local),
+            _ => local,
+        }
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        match self {
+            XNode::Attribute(owner, _) => Some(XNode::Tree(*owner)),
+            XNode::Tree(node) => node.parent().map(XNode::Tree),
+        }
+    }
+
+    /// Nodes on an axis in proximity order (nearest first)
+    pub fn axis(&self, axis: Axis) -> Vec<Self> {
+        let tree = |nodes: &mut dyn Iterator<Item = roxmltree::Node<'a, 'input>>| -> Vec<Self> {
+            nodes.map(XNode::Tree).collect()
+        };
+
+        match (axis, self) {
+            (Axis::SelfNode, _) => vec![*self],
+            (Axis::Parent, _) => self.parent().into_iter().collect(),
+            (Axis::Ancestor | Axis::AncestorOrSelf, _) => {
+                let mut nodes = Vec::new();
+                if axis == Axis::AncestorOrSelf {
+                    nodes.push(*self);
+                }
+                let mut current = self.parent();
+                while let Some(node) = current {
+                    nodes.push(node);
+                    current = node.parent();
+                }
+                nodes
+            }
+            (Axis::Attribute, XNode::Tree(node)) if node.is_element() => {
+                (0..node.attributes().len()).map(|i| XNode::Attribute(*node, i)).collect()
+            }
+            (Axis::Namespace, _) => Vec::new(),
+            (Axis::Following, _) => self.following(),
+            (Axis::Preceding, _) => self.preceding(),
+            // Attributes have no children or siblings
+            (_, XNode::Attribute(..)) => match axis {
+                Axis::DescendantOrSelf => vec![*self],
+                _ => Vec::new(),
+            },
+            (Axis::Child, XNode::Tree(node)) => tree(&mut node.children()),
+            (Axis::Descendant, XNode::Tree(node)) => tree(&mut node.descendants().skip(1)),
+            (Axis::DescendantOrSelf, XNode::Tree(node)) => tree(&mut node.descendants()),
+            (Axis::FollowingSibling, XNode::Tree(node)) => tree(&mut node.next_siblings().skip(1)),
+            (Axis::PrecedingSibling, XNode::Tree(node)) => tree(&mut node.prev_siblings().skip(1)),
+            (Axis::Attribute, XNode::Tree(_)) => Vec::new(),
+        }
+    }
+
+    fn following(&self) -> Vec<Self> {
+        let mut nodes = Vec::new();
+        // Everything after an attribute starts with its element's children
+        if let XNode::Attribute(owner, _) = self {
+            nodes.extend(owner.descendants().skip(1).map(XNode::Tree));
+        }
+        let mut current = Some(self.tree_node());
+        while let Some(node) = current {
+            for sibling in node.next_siblings().skip(1) {
+                nodes.extend(sibling.descendants().map(XNode::Tree));
+            }
+            current = node.parent();
+        }
+        nodes
+    }
+
+    fn preceding(&self) -> Vec<Self> {
+        let target = self.tree_node();
+        let ancestors: Vec<_> = XNode::Tree(target).axis(Axis::AncestorOrSelf);
+        let mut nodes: Vec<Self> = target
+            .document()
+            .root()
+            .descendants()
+            .take_while(|n| *n != target)
+            .map(XNode::Tree)
+            .filter(|n| !ancestors.contains(n))
+            .collect();
+        nodes.reverse();
+        nodes
+    }
+}
+
+/// Sort a node list into document order and drop duplicates
+pub fn sort_document_order(nodes: &mut Vec<XNode>) {
+    nodes.sort_by_key(|node| node.order_key());
+    nodes.dedup();
+}
@@ -0,0 +1,667 @@
+// backend/src/services/xpath/parser.rs
+
+//! XPath 1.0 parser
+//!
+//! Recursive descent over the token stream following the grammar of the XPath 1.0
+//! recommendation. Abbreviations are expanded while parsing: `//` becomes
+//! `descendant-or-self::node()`, `.` becomes `self::node()`, `..` becomes
+//! `parent::node()` and `@` selects the attribute axis.
+
+use super::{
+    lexer::{Token, TokenKind},
+    XPathError,
+};
+
+/// Deepest accepted expression: parentheses, predicates, function arguments, unary minus
+/// and each chained binary operator add a level. Bounds the recursion of both the parser
+/// and the evaluator, so hostile expressions fail to compile instead of exhausting the stack
+pub const MAX_DEPTH: usize = 128;
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// SYNTAX TREE
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Arithmetic(ArithmeticOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Union(Box<Expr>, Box<Expr>),
+    /// Location path, optionally starting from a filter expression
+    Path(PathStart, Vec<Step>),
+    /// Primary expression with predicates, e.g. `(a | b)[1]`
+    Filter(Box<Expr>, Vec<Expr>),
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    Function(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Append every `$name` reference below this node, once each
+    pub fn collect_variables<'e>(&'e self, names: &mut Vec<&'e str>) {
+        match self {
+            Expr::Or(left, right)
+            | Expr::And(left, right)
+            | Expr::Compare(_, left, right)
+            | Expr::Arithmetic(_, left, right)
+            | Expr::Union(left, right) => {
+                left.collect_variables(names);
+                right.collect_variables(names);
+            }
+            Expr::Negate(inner) => inner.collect_variables(names),
+            Expr::Path(start, steps) => {
+                if let PathStart::Expr(inner) = start {
+                    inner.collect_variables(names);
+                }
+                for predicate in steps.iter().flat_map(|step| &step.predicates) {
+                    predicate.collect_variables(names);
+                }
+            }
+            Expr::Filter(inner, predicates) => {
+                inner.collect_variables(names);
+                for predicate in predicates {
+                    predicate.collect_variables(names);
+                }
+            }
+            Expr::Function(_, arguments) => {
+                for argument in arguments {
+                    argument.collect_variables(names);
+                }
+            }
+            Expr::Variable(name) => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            Expr::Literal(_) | Expr::Number(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStart {
+    /// Relative to the context node
+    Context,
+    /// Absolute, from the document root
+    Root,
+    /// From the node-set produced by an expression
+    Expr(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    SelfNode,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::SelfNode,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeTest {
+    /// Name test; `None` parts are wildcards (`*`, `prefix:*`)
+    Name { prefix: Option<String>, local: Option<String> },
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+impl Step {
+    fn abbreviated(axis: Axis) -> Self {
+        Self {
+            axis,
+            test: NodeTest::Node,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════════
+// PARSER
+// ═══════════════════════════════════════════════════════════════════════════════════
+
+/// Parse a token stream into an expression tree
+pub fn parse(tokens: Vec<Token>, source_len: usize) -> Result<Expr, XPathError> {
+    if tokens.is_empty() {
+        return Err(XPathError::new("empty expression", 0));
+    }
+    let mut parser = Parser {
+        tokens,
+        index: 0,
+        source_len,
+        depth: 0,
+    };
+    let expr = parser.or_expr()?;
+    if let Some(token) = parser.tokens.get(parser.index) {
+        return Err(XPathError::new(
+            format!("unexpected {}", describe(&token.kind)),
+            token.position,
+        ));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    source_len: usize,
+    /// Nested sub-expressions currently being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.index).map(|t| &t.kind)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.index).map_or(self.source_len, |t| t.position)
+    }
+
+    fn next(&mut self) -> Option<TokenKind> {
+        let token = self.tokens.get(self.index).map(|t| t.kind.clone());
+        self.index += 1;
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek() == Some(kind) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), XPathError> {
+        if self.eat(&kind) {
+            return Ok(());
+        }
+        let found = self.peek().map_or("end of expression".to_string(), describe);
+        Err(XPathError::new(
+            format!("expected {}, found {}", describe(&kind), found),
+            self.position(),
+        ))
+    }
+
+    /// Enter one level of the tree, refusing expressions deeper than `MAX_DEPTH`
+    /// A failed parse is abandoned, so only successful paths need to leave the level again
+    fn descend(&mut self) -> Result<(), XPathError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(XPathError::new(
+                format!("expression nested or chained deeper than {} levels", MAX_DEPTH),
+                self.position(),
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, XPathError> {
+        self.descend()?;
+        let expr = self.or_chain();
+        self.depth -= 1;
+        expr
+    }
+
+    fn or_chain(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.and_expr()?;
+        let mut links = 0;
+        while self.eat(&TokenKind::Or) {
+            self.descend()?;
+            links += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        self.depth -= links;
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.equality_expr()?;
+        let mut links = 0;
+        while self.eat(&TokenKind::And) {
+            self.descend()?;
+            links += 1;
+            left = Expr::And(Box::new(left), Box::new(self.equality_expr()?));
+        }
+        self.depth -= links;
+        Ok(left)
+    }
+
+    fn equality_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.relational_expr()?;
+        let mut links = 0;
+        loop {
+            let op = match self.peek() {
+                Some(TokenKind::Eq) => CompareOp::Eq,
+                Some(TokenKind::NotEq) => CompareOp::NotEq,
+                _ => {
+                    self.depth -= links;
+                    return Ok(left);
+                }
+            };
+            self.index += 1;
+            self.descend()?;
+            links += 1;
+            left = Expr::Compare(op, Box::new(left), Box::new(self.relational_expr()?));
+        }
+    }
+
+    fn relational_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.additive_expr()?;
+        let mut links = 0;
+        loop {
+            let op = match self.peek() {
+                Some(TokenKind::Lt) => CompareOp::Lt,
+                Some(TokenKind::Le) => CompareOp::Le,
+                Some(TokenKind::Gt) => CompareOp::Gt,
+                Some(TokenKind::Ge) => CompareOp::Ge,
+                _ => {
+                    self.depth -= links;
+                    return Ok(left);
+                }
+            };
+            self.index += 1;
+            self.descend()?;
+            links += 1;
+            left = Expr::Compare(op, Box::new(left), Box::new(self.additive_expr()?));
+        }
+    }
+
+    fn additive_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.multiplicative_expr()?;
+        let mut links = 0;
+        loop {
+            let op = match self.peek() {
+                Some(TokenKind::Plus) => ArithmeticOp::Add,
+                Some(TokenKind::Minus) => ArithmeticOp::Subtract,
+                _ => {
+                    self.depth -= links;
+                    return Ok(left);
+                }
+            };
+            self.index += 1;
+            self.descend()?;
+            links += 1;
+            left = Expr::Arithmetic(op, Box::new(left), Box::new(self.multiplicative_expr()?));
+        }
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.unary_expr()?;
+        let mut links = 0;
+        loop {
+            let op = match self.peek() {
+                Some(TokenKind::Multiply) => ArithmeticOp::Multiply,
+                Some(TokenKind::Div) => ArithmeticOp::Divide,
+                Some(TokenKind::Mod) => ArithmeticOp::Modulo,
+                _ => {
+                    self.depth -= links;
+                    return Ok(left);
+                }
+            };
+            self.index += 1;
+            self.descend()?;
+            links += 1;
+            left = Expr::Arithmetic(op, Box::new(left), Box::new(self.unary_expr()?));
+        }
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr, XPathError> {
+        if self.eat(&TokenKind::Minus) {
+            self.descend()?;
+            let inner = self.unary_expr();
+            self.depth -= 1;
+            return Ok(Expr::Negate(Box::new(inner?)));
+        }
+        self.union_expr()
+    }
+
+    fn union_expr(&mut self) -> Result<Expr, XPathError> {
+        let mut left = self.path_expr()?;
+        let mut links = 0;
+        while self.eat(&TokenKind::Pipe) {
+            self.descend()?;
+            links += 1;
+            left = Expr::Union(Box::new(left), Box::new(self.path_expr()?));
+        }
+        self.depth -= links;
+        Ok(left)
+    }
+
+    fn path_expr(&mut self) -> Result<Expr, XPathError> {
+        let starts_filter = matches!(
+            self.peek(),
+            Some(
+                TokenKind::Variable(_)
+                    | TokenKind::LParen
+                    | TokenKind::Literal(_)
+                    | TokenKind::Number(_)
+                    | TokenKind::FunctionName(_)
+            )
+        );
+        if !starts_filter {
+            return self.location_path();
+        }
+
+        let primary = self.primary_expr()?;
+        let mut predicates = Vec::new();
+        while self.peek() == Some(&TokenKind::LBracket) {
+            predicates.push(self.predicate()?);
+        }
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Expr::Filter(Box::new(primary), predicates)
+        };
+
+        let mut steps = Vec::new();
+        match self.peek() {
+            Some(TokenKind::Slash) => {
+                self.index += 1;
+            }
+            Some(TokenKind::DoubleSlash) => {
+                self.index += 1;
+                steps.push(Step::abbreviated(Axis::DescendantOrSelf));
+            }
+            _ => return Ok(filter),
+        }
+        self.relative_path(&mut steps)?;
+        Ok(Expr::Path(PathStart::Expr(Box::new(filter)), steps))
+    }
+
+    fn location_path(&mut self) -> Result<Expr, XPathError> {
+        let mut steps = Vec::new();
+        match self.peek() {
+            Some(TokenKind::Slash) => {
+                self.index += 1;
+                // A lone '/' selects the root node
+                if self.starts_step() {
+                    self.relative_path(&mut steps)?;
+                }
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            Some(TokenKind::DoubleSlash) => {
+                self.index += 1;
+                steps.push(Step::abbreviated(Axis::DescendantOrSelf));
+                self.relative_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::Root, steps))
+            }
+            _ => {
+                self.relative_path(&mut steps)?;
+                Ok(Expr::Path(PathStart::Context, steps))
+            }
+        }
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                TokenKind::Dot
+                    | TokenKind::DotDot
+                    | TokenKind::At
+                    | TokenKind::AxisName(_)
+                    | TokenKind::NameTest { .. }
+                    | TokenKind::NodeType(_)
+            )
+        )
+    }
+
+    fn relative_path(&mut self, steps: &mut Vec<Step>) -> Result<(), XPathError> {
+        steps.push(self.step()?);
+        loop {
+            match self.peek() {
+                Some(TokenKind::Slash) => {
+                    self.index += 1;
+                }
+                Some(TokenKind::DoubleSlash) => {
+                    self.index += 1;
+                    steps.push(Step::abbreviated(Axis::DescendantOrSelf));
+                }
+                _ => return Ok(()),
+            }
+            steps.push(self.step()?);
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, XPathError> {
+        if self.eat(&TokenKind::Dot) {
+            return Ok(Step::abbreviated(Axis::SelfNode));
+        }
+        if self.eat(&TokenKind::DotDot) {
+            return Ok(Step::abbreviated(Axis::Parent));
+        }
+
+        let position = self.position();
+        let axis = match self.peek() {
+            Some(TokenKind::At) => {
+                self.index += 1;
+                Axis::Attribute
+            }
+            Some(TokenKind::AxisName(name)) => {
+                let axis = Axis::from_name(name)
+                    .ok_or_else(|| XPathError::new(format!("unknown axis '{}'", name), position))?;
+                self.index += 1;
+                self.expect(TokenKind::ColonColon)?;
+                axis
+            }
+            _ => Axis::Child,
+        };
+
+        let position = self.position();
+        let test = match self.next() {
+            Some(TokenKind::NameTest { prefix, local }) => NodeTest::Name { prefix, local },
+            Some(TokenKind::NodeType(kind)) => {
+                self.expect(TokenKind::LParen)?;
+                let test = match kind.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    _ => match self.peek() {
+                        Some(TokenKind::Literal(target)) => {
+                            let target = target.clone();
+                            self.index += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        }
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                };
+                self.expect(TokenKind::RParen)?;
+                test
+            }
+            Some(other) => {
+                return Err(XPathError::new(
+                    format!("expected a node test, found {}", describe(&other)),
+                    position,
+                ));
+            }
+            None => return Err(XPathError::new("expected a node test", position)),
+        };
+
+        let mut predicates = Vec::new();
+        while self.peek() == Some(&TokenKind::LBracket) {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step { axis, test, predicates })
+    }
+
+    fn predicate(&mut self) -> Result<Expr, XPathError> {
+        self.expect(TokenKind::LBracket)?;
+        let expr = self.or_expr()?;
+        self.expect(TokenKind::RBracket)?;
+        Ok(expr)
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr, XPathError> {
+        let position = self.position();
+        match self.next() {
+            Some(TokenKind::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(TokenKind::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(TokenKind::Number(value)) => Ok(Expr::Number(value)),
+            Some(TokenKind::LParen) => {
+                let expr = self.or_expr()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(expr)
+            }
+            Some(TokenKind::FunctionName(name)) => {
+                self.expect(TokenKind::LParen)?;
+                let mut args = Vec::new();
+                if !self.eat(&TokenKind::RParen) {
+                    loop {
+                        args.push(self.or_expr()?);
+                        if self.eat(&TokenKind::RParen) {
+                            break;
+                        }
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+                super::functions::check_arity(&name, args.len())
+                    .map_err(|message| XPathError::new(message, position))?;
+                Ok(Expr::Function(name, args))
+            }
+            _ => Err(XPathError::new("expected an expression", position)),
+        }
+    }
+}
+
+fn describe(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::LParen => "'('".to_string(),
+        TokenKind::RParen => "')'".to_string(),
+        TokenKind::LBracket => "'['".to_string(),
+        TokenKind::RBracket => "']'".to_string(),
+        TokenKind::Dot => "'.'".to_string(),
+        TokenKind::DotDot => "'..'".to_string(),
+        TokenKind::At => "'@'".to_string(),
+        TokenKind::Comma => "','".to_string(),
+        TokenKind::ColonColon => "'::'".to_string(),
+        TokenKind::Slash => "'/'".to_string(),
+        TokenKind::DoubleSlash => "'//'".to_string(),
+        TokenKind::Pipe => "'|'".to_string(),
+        TokenKind::Plus => "'+'".to_string(),
+        TokenKind::Minus => "'-'".to_string(),
+        TokenKind::Eq => "'='".to_string(),
+        TokenKind::NotEq => "'!='".to_string(),
+        TokenKind::Lt => "'<'".to_string(),
+        TokenKind::Le => "'<='".to_string(),
+        TokenKind::Gt => "'>'".to_string(),
+        TokenKind::Ge => "'>='".to_string(),
+        TokenKind::Multiply => "'*'".to_string(),
+        TokenKind::And => "'and'".to_string(),
+        TokenKind::Or => "'or'".to_string(),
+        TokenKind::Mod => "'mod'".to_string(),
+        TokenKind::Div => "'div'".to_string(),
+        TokenKind::Literal(value) => format!("literal \"{}\"", value),
+        TokenKind::Number(value) => format!("number {}", value),
+        TokenKind::Variable(name) => format!("variable ${}", name),
+        TokenKind::NameTest { prefix, local } => format!(
+            "name '{}{}'",
+            prefix.as_ref().map(|p| format!("{}:", p)).unwrap_or_default(),
+            local.as_deref().unwrap_or("*")
+        ),
+        TokenKind::NodeType(name) => format!("node type {}()", name),
+        TokenKind::FunctionName(name) => format!("function {}()", name),
+        TokenKind::AxisName(name) => format!("axis '{}'", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::xpath::{lexer::tokenize, XNode, XPath, XPathOptions};
+
+    fn parse_source(source: &str) -> Result<Expr, XPathError> {
+        parse(tokenize(source)?, source.len())
+    }
+
+    fn assert_too_deep(source: &str) {
+        let error = parse_source(source).unwrap_err();
+        assert!(error.message.contains("deeper than 128 levels"), "{}", error.message);
+    }
+
+    #[test]
+    fn rejects_deeply_nested_expressions() {
+        assert_too_deep(&format!("{}1{}", "(".repeat(3000), ")".repeat(3000)));
+        assert_too_deep(&format!("{}1{}", "a[".repeat(3000), "]".repeat(3000)));
+        assert_too_deep(&format!("{}1{}", "count(".repeat(3000), ")".repeat(3000)));
+        assert_too_deep(&format!("{}1", "-".repeat(3000)));
+    }
+
+    #[test]
+    fn rejects_long_operator_chains() {
+        assert_too_deep(&vec!["a"; 20_000].join(" | "));
+        assert_too_deep(&vec!["1"; 20_000].join(" + "));
+        assert_too_deep(&vec!["a"; 20_000].join(" or "));
+    }
+
+    #[test]
+    fn accepts_and_evaluates_expressions_at_the_limit() {
+        let nested = format!("{}1{}", "(".repeat(MAX_DEPTH - 1), ")".repeat(MAX_DEPTH - 1));
+        assert_eq!(parse_source(&nested).unwrap(), Expr::Number(1.0));
+
+        let chain = XPath::compile(&vec!["a"; MAX_DEPTH].join(" | ")).unwrap();
+        let document = roxmltree::Document::parse("<r><a/></r>").unwrap();
+        let root = XNode::Tree(document.root().first_child().unwrap());
+        assert_eq!(chain.select(root, &XPathOptions::default()).unwrap().len(), 1);
+    }
+}
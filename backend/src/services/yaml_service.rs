@@ -42,6 +42,12 @@ impl YamlService {
         Ok(service)
     }
 
+    /// Read and write data files in `data_dir` instead of `../shared/data`
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
     async fn load_schemas(&mut self) -> ApiResult<()> {
         info!("Loading schemas from: {}", self.schema_dir.display());
         
@@ -145,6 +151,44 @@ impl YamlService {
         Ok(())
     }
 
+    /// Check a document against a loaded JSON schema, listing every violation
+    /// Used to enforce schemas on writes and on reads of `schedules.yaml`; other reads
+    /// still go through `basic_validation`
+    pub fn validate_against_schema(&self, schema_name: &str, data: &Value) -> ApiResult<()> {
+        let schema = self.schemas.get(schema_name).ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
+        })?;
+        let validator = jsonschema::validator_for(schema).map_err(|e| {
+            ApiError::InternalError(format!("Invalid JSON schema '{}': {}", schema_name, e))
+        })?;
+
+        let violations: Vec<String> = validator
+            .iter_errors(data)
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{}: {}", path, error)
+                }
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::ValidationError(format!(
+                "Does not match the {} schema: {}",
+                schema_name,
+                violations.join("; ")
+            )))
+        }
+    }
+
+    /// Path of a schema's default data file (`<data_dir>/<schema_name>.yaml`)
+    pub fn data_file(&self, schema_name: &str) -> PathBuf {
+        self.data_dir.join(format!("{}.yaml", schema_name))
+    }
+
     pub async fn list_available_schemas(&self) -> ApiResult<Vec<String>> {
         Ok(self.schemas.keys().cloned().collect())
     }
@@ -158,8 +202,7 @@ impl YamlService {
             }
             None => {
                 // Default to schema_name.yaml in the data directory
-                let default_file = format!("{}.yaml", schema_name);
-                Ok(self.data_dir.join(default_file))
+                Ok(self.data_file(schema_name))
             }
         }
     }
@@ -29,6 +29,8 @@
  * });
  * 
  * ws.connect();
+ *
+ * const reports = await ws.request('reports.list', { category: 'Routing' });
  * ```
  */
 
@@ -75,6 +77,8 @@ export class WebSocketService {
     this.eventHandlers = new Map();
     this.heartbeatTimer = null;
     this.reconnectTimer = null;
+    this.pendingRequests = new Map();
+    this.nextRequestId = 1;
     this.stats = this.initializeStats();
     this.instanceId = Math.random().toString(36).substr(2, 9); // For debugging
     
@@ -196,6 +200,45 @@ export class WebSocketService {
     }
   }
 
+  /**
+   * Call a server-side RPC method and resolve with its result
+   */
+  request(method, params = {}, timeout = 30000) {
+    const id = `${this.instanceId}-${this.nextRequestId++}`;
+
+    return new Promise((resolve, reject) => {
+      const timer = setTimeout(() => {
+        this.pendingRequests.delete(id);
+        reject(new Error(`Request '${method}' timed out`));
+      }, timeout);
+
+      this.pendingRequests.set(id, { resolve, reject, timer });
+      this.send({ type: 'Request', payload: { id, method, params } });
+    });
+  }
+
+  /**
+   * Settle a pending RPC request from a Response/RequestFailed message
+   */
+  settleRequest(payload, failed) {
+    const pending = this.pendingRequests.get(payload.id);
+    if (!pending) {
+      this.log('Reply for unknown request:', payload.id);
+      return;
+    }
+
+    clearTimeout(pending.timer);
+    this.pendingRequests.delete(payload.id);
+
+    if (failed) {
+      const error = new Error(payload.message);
+      error.code = payload.code;
+      pending.reject(error);
+    } else {
+      pending.resolve(payload.result);
+    }
+  }
+
   /**
    * Queue message for sending when connection is restored
    */
@@ -405,6 +448,14 @@ export class WebSocketService {
       case 'Custom':
         this.emit(message.payload.event, message.payload.data);
         break;
+
+      case 'Response':
+        this.settleRequest(message.payload, false);
+        break;
+
+      case 'RequestFailed':
+        this.settleRequest(message.payload, true);
+        break;
         
       default:
         this.log('Unknown message type:', message.type);
@@ -10,7 +10,8 @@ export type ConnectionId = string;
 
 export interface WsMessage {
   type: 'ConnectionEstablished' | 'Ping' | 'Pong' | 'NavigationUpdated' | 
-        'SchemaReloaded' | 'FileChanged' | 'DataUpdate' | 'Error' | 'Custom';
+        'SchemaReloaded' | 'FileChanged' | 'DataUpdate' | 'Error' | 'Custom' |
+        'Request' | 'Response' | 'RequestFailed';
   payload?: any;
 }
 
@@ -48,6 +49,23 @@ export interface CustomPayload {
   data: any;
 }
 
+export interface RequestPayload {
+  id: string;
+  method: string;
+  params?: any;
+}
+
+export interface ResponsePayload {
+  id: string;
+  result: any;
+}
+
+export interface RequestFailedPayload {
+  id: string;
+  code: number;
+  message: string;
+}
+
 // WebSocket connection states
 export enum WebSocketState {
   CONNECTING = 'connecting',